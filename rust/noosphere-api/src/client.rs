@@ -1,7 +1,10 @@
 use std::str::FromStr;
 
 use crate::{
-    data::{FetchParameters, FetchResponse, IdentifyResponse, PushBody, PushResponse},
+    data::{
+        FetchParameters, FetchResponse, IdentifyResponse, PairingApproval, PairingRequest,
+        PairingStatus, PublishBody, PublishResponse, PushBody, PushResponse,
+    },
     route::{Route, RouteUrl},
 };
 
@@ -10,14 +13,14 @@ use cid::Cid;
 use libipld_cbor::DagCborCodec;
 use noosphere_car::CarReader;
 
-use noosphere_core::authority::{Author, SphereAction, SphereReference};
+use noosphere_core::authority::{Author, SphereAction, SphereReference, SPHERE_SEMANTICS};
 use noosphere_storage::{block_deserialize, block_serialize};
 use reqwest::{header::HeaderMap, Body, StatusCode};
 use tokio_stream::{Stream, StreamExt};
 use tokio_util::io::StreamReader;
 use ucan::{
     builder::UcanBuilder,
-    capability::{Capability, Resource, With},
+    capability::{Capability, CapabilitySemantics, Resource, With},
     crypto::{did::DidParser, KeyMaterial},
     store::{UcanJwtStore, UcanStore},
     ucan::Ucan,
@@ -77,14 +80,12 @@ where
         let (jwt, ucan_headers) = Self::make_bearer_token(
             &gateway_identity,
             author,
-            &Capability {
+            &[Capability {
                 with: With::Resource {
-                    kind: Resource::Scoped(SphereReference {
-                        did: sphere_identity.to_string(),
-                    }),
+                    kind: Resource::Scoped(SphereReference::new(sphere_identity.to_string())),
                 },
                 can: SphereAction::Fetch,
-            },
+            }],
             &store,
         )
         .await?;
@@ -118,16 +119,19 @@ where
     async fn make_bearer_token(
         gateway_identity: &str,
         author: &Author<K>,
-        capability: &Capability<SphereReference, SphereAction>,
+        capabilities: &[Capability<SphereReference, SphereAction>],
         store: &S,
     ) -> Result<(String, HeaderMap)> {
-        let mut signable = UcanBuilder::default()
+        let mut builder = UcanBuilder::default()
             .issued_by(&author.key)
             .for_audience(gateway_identity)
-            .with_lifetime(120)
-            .claiming_capability(capability)
-            .with_nonce()
-            .build()?;
+            .with_lifetime(120);
+
+        for capability in capabilities {
+            builder = builder.claiming_capability(capability);
+        }
+
+        let mut signable = builder.with_nonce().build()?;
 
         let mut ucan_headers = HeaderMap::new();
 
@@ -176,6 +180,52 @@ where
         Ok((jwt, ucan_headers))
     }
 
+    /// The capabilities claimed when pushing: push access to the whole sphere,
+    /// plus any push access to a part of the sphere that the author's own
+    /// authorization grants. A key that has only been delegated access to
+    /// some content in the sphere can only prove the latter.
+    async fn push_capabilities(&self) -> Result<Vec<Capability<SphereReference, SphereAction>>> {
+        let mut capabilities = vec![Capability {
+            with: With::Resource {
+                kind: Resource::Scoped(SphereReference::new(self.sphere_identity.clone())),
+            },
+            can: SphereAction::Push,
+        }];
+
+        let authorization = self.author.require_authorization()?;
+
+        if let Ok(ucan) = authorization.resolve_ucan(&self.store).await {
+            for capability in ucan.attenuation() {
+                let capability = match SPHERE_SEMANTICS.parse(&capability.with, &capability.can) {
+                    Some(capability) => capability,
+                    None => continue,
+                };
+
+                if let With::Resource {
+                    kind:
+                        Resource::Scoped(SphereReference {
+                            did,
+                            content_prefix,
+                            ..
+                        }),
+                } = &capability.with
+                {
+                    if did == &self.sphere_identity
+                        && content_prefix.is_some()
+                        && capability.can >= SphereAction::Push
+                    {
+                        capabilities.push(Capability {
+                            with: capability.with.clone(),
+                            can: SphereAction::Push,
+                        });
+                    }
+                }
+            }
+        }
+
+        Ok(capabilities)
+    }
+
     /// Replicate content from Noosphere, streaming its blocks from the
     /// configured gateway. If the gateway doesn't have the desired content, it
     /// will look it up from other sources such as IPFS if they are available.
@@ -196,9 +246,7 @@ where
 
         let capability = Capability {
             with: With::Resource {
-                kind: Resource::Scoped(SphereReference {
-                    did: self.sphere_identity.clone(),
-                }),
+                kind: Resource::Scoped(SphereReference::new(self.sphere_identity.clone())),
             },
             can: SphereAction::Fetch,
         };
@@ -206,7 +254,7 @@ where
         let (token, ucan_headers) = Self::make_bearer_token(
             &self.session.gateway_identity,
             &self.author,
-            &capability,
+            &[capability],
             &self.store,
         )
         .await?;
@@ -243,9 +291,7 @@ where
         debug!("Client fetching blocks from {}", url);
         let capability = Capability {
             with: With::Resource {
-                kind: Resource::Scoped(SphereReference {
-                    did: self.sphere_identity.clone(),
-                }),
+                kind: Resource::Scoped(SphereReference::new(self.sphere_identity.clone())),
             },
             can: SphereAction::Fetch,
         };
@@ -253,7 +299,7 @@ where
         let (token, ucan_headers) = Self::make_bearer_token(
             &self.session.gateway_identity,
             &self.author,
-            &capability,
+            &[capability],
             &self.store,
         )
         .await?;
@@ -279,19 +325,12 @@ where
             push_body.sphere,
             url
         );
-        let capability = Capability {
-            with: With::Resource {
-                kind: Resource::Scoped(SphereReference {
-                    did: self.sphere_identity.clone(),
-                }),
-            },
-            can: SphereAction::Push,
-        };
+        let capabilities = self.push_capabilities().await?;
 
         let (token, ucan_headers) = Self::make_bearer_token(
             &self.session.gateway_identity,
             &self.author,
-            &capability,
+            &capabilities,
             &self.store,
         )
         .await?;
@@ -312,4 +351,221 @@ where
 
         block_deserialize::<DagCborCodec, _>(bytes.as_ref())
     }
+
+    /// Ask the gateway to publish a version of the sphere to the Noosphere Name
+    /// System using the given signed link record. The version may be older than
+    /// the latest one that has been pushed, which makes it possible to publish
+    /// a rollback without pushing new history.
+    pub async fn publish(&self, publish_body: &PublishBody) -> Result<PublishResponse> {
+        let url = Url::try_from(RouteUrl::<()>(&self.api_base, Route::Publish, None))?;
+        debug!(
+            "Client publishing a new name record for sphere {} to {}",
+            publish_body.sphere, url
+        );
+        let capability = Capability {
+            with: With::Resource {
+                kind: Resource::Scoped(SphereReference::new(self.sphere_identity.clone())),
+            },
+            can: SphereAction::Publish,
+        };
+
+        let (token, ucan_headers) = Self::make_bearer_token(
+            &self.session.gateway_identity,
+            &self.author,
+            &[capability],
+            &self.store,
+        )
+        .await?;
+
+        let (_, publish_body_bytes) = block_serialize::<DagCborCodec, _>(publish_body)?;
+
+        let response = self
+            .client
+            .put(url)
+            .bearer_auth(token)
+            .headers(ucan_headers)
+            .header("Content-Type", "application/octet-stream")
+            .body(Body::from(publish_body_bytes))
+            .send()
+            .await?;
+
+        match response.status() {
+            StatusCode::OK => (),
+            status => return Err(anyhow!("Gateway refused to publish ({status})")),
+        };
+
+        block_deserialize::<DagCborCodec, _>(response.bytes().await?.as_ref())
+    }
+
+    /// Look up a pending request from another device to be paired with this
+    /// sphere by its pairing code, so that the requesting key can be shown to
+    /// the user before the request is approved
+    pub async fn pairing_status(&self, code: &str) -> Result<PairingStatus> {
+        let url = Url::try_from(RouteUrl::<()>(
+            &self.api_base,
+            Route::Pairing(Some(code.to_string())),
+            None,
+        ))?;
+        debug!("Client looking up pairing request at {}", url);
+
+        let capability = Capability {
+            with: With::Resource {
+                kind: Resource::Scoped(SphereReference::new(self.sphere_identity.clone())),
+            },
+            can: SphereAction::Authorize,
+        };
+
+        let (token, ucan_headers) = Self::make_bearer_token(
+            &self.session.gateway_identity,
+            &self.author,
+            &[capability],
+            &self.store,
+        )
+        .await?;
+
+        let response = self
+            .client
+            .get(url)
+            .bearer_auth(token)
+            .headers(ucan_headers)
+            .send()
+            .await?;
+
+        match response.status() {
+            StatusCode::OK => (),
+            StatusCode::NOT_FOUND => {
+                return Err(anyhow!(
+                    "No pairing request with code {code:?} (it may have expired)"
+                ))
+            }
+            status => return Err(anyhow!("Unable to look up pairing request ({status})")),
+        };
+
+        block_deserialize::<DagCborCodec, _>(response.bytes().await?.as_ref())
+    }
+
+    /// Approve a pending request from another device to be paired with this
+    /// sphere, by telling the gateway which authorization was granted to the
+    /// requesting key. The authorization must have been synced to the gateway
+    /// before it is approved.
+    pub async fn approve_pairing(
+        &self,
+        code: &str,
+        approval: &PairingApproval,
+    ) -> Result<PairingStatus> {
+        let url = Url::try_from(RouteUrl::<()>(
+            &self.api_base,
+            Route::Pairing(Some(code.to_string())),
+            None,
+        ))?;
+        debug!("Client approving pairing request at {}", url);
+
+        let capability = Capability {
+            with: With::Resource {
+                kind: Resource::Scoped(SphereReference::new(self.sphere_identity.clone())),
+            },
+            can: SphereAction::Authorize,
+        };
+
+        let (token, ucan_headers) = Self::make_bearer_token(
+            &self.session.gateway_identity,
+            &self.author,
+            &[capability],
+            &self.store,
+        )
+        .await?;
+
+        let (_, approval_bytes) = block_serialize::<DagCborCodec, _>(approval)?;
+
+        let response = self
+            .client
+            .put(url)
+            .bearer_auth(token)
+            .headers(ucan_headers)
+            .header("Content-Type", "application/octet-stream")
+            .body(Body::from(approval_bytes))
+            .send()
+            .await?;
+
+        match response.status() {
+            StatusCode::OK => (),
+            status => return Err(anyhow!("Gateway refused to approve pairing ({status})")),
+        };
+
+        block_deserialize::<DagCborCodec, _>(response.bytes().await?.as_ref())
+    }
+}
+
+/// A [PairingClient] is used by a device that wishes to join a sphere, but
+/// that does not yet have an authorization to work on it. Since the device
+/// cannot prove any capabilities yet, none of its requests are UCAN-authorized.
+/// Instead, a request is identified by a short-lived, human-readable code that
+/// the owner of the sphere uses to approve it (see [Client::approve_pairing]),
+/// and it is signed by the key that the device wishes to have authorized.
+pub struct PairingClient {
+    pub api_base: Url,
+    client: reqwest::Client,
+}
+
+impl PairingClient {
+    pub fn new(api_base: &Url) -> Self {
+        PairingClient {
+            api_base: api_base.clone(),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Ask the gateway to hold a request to pair the given key with a sphere
+    /// until the owner of the sphere approves it (or until it expires)
+    pub async fn request(&self, request: &PairingRequest) -> Result<()> {
+        let url = Url::try_from(RouteUrl::<()>(&self.api_base, Route::Pair, None))?;
+        debug!(
+            "Client requesting to pair {} with sphere {} at {}",
+            request.did, request.sphere, url
+        );
+
+        let (_, request_bytes) = block_serialize::<DagCborCodec, _>(request)?;
+
+        let response = self
+            .client
+            .put(url)
+            .header("Content-Type", "application/octet-stream")
+            .body(Body::from(request_bytes))
+            .send()
+            .await?;
+
+        match response.status() {
+            StatusCode::OK => Ok(()),
+            StatusCode::CONFLICT => Err(anyhow!("The pairing code is already in use")),
+            StatusCode::TOO_MANY_REQUESTS => Err(anyhow!(
+                "Too many pairing requests are pending; try again in a few minutes"
+            )),
+            status => Err(anyhow!("Gateway refused the pairing request ({status})")),
+        }
+    }
+
+    /// Look up the status of a pending pairing request that was made with
+    /// [PairingClient::request]
+    pub async fn status(&self, request: &PairingRequest) -> Result<PairingStatus> {
+        let code = &request.code;
+        let url = Url::try_from(RouteUrl(
+            &self.api_base,
+            Route::Pairing(Some(code.to_string())),
+            Some(&request.status_parameters()),
+        ))?;
+
+        let response = self.client.get(url).send().await?;
+
+        match response.status() {
+            StatusCode::OK => (),
+            StatusCode::NOT_FOUND => {
+                return Err(anyhow!(
+                    "No pairing request with code {code:?} (it may have expired)"
+                ))
+            }
+            status => return Err(anyhow!("Unable to look up pairing request ({status})")),
+        };
+
+        block_deserialize::<DagCborCodec, _>(response.bytes().await?.as_ref())
+    }
 }
@@ -123,6 +123,8 @@ pub enum PushError {
     MissingHistory,
     #[error("Replica is up to date")]
     UpToDate,
+    #[error("Pushed history changes something outside of the authorized scope")]
+    OutOfScope,
     #[error("Internal error")]
     Internal(anyhow::Error),
 }
@@ -139,6 +141,7 @@ impl From<PushError> for StatusCode {
             PushError::Conflict => StatusCode::CONFLICT,
             PushError::MissingHistory => StatusCode::UNPROCESSABLE_ENTITY,
             PushError::UpToDate => StatusCode::BAD_REQUEST,
+            PushError::OutOfScope => StatusCode::FORBIDDEN,
             PushError::Internal(error) => {
                 error!("Internal: {:?}", error);
                 StatusCode::INTERNAL_SERVER_ERROR
@@ -147,6 +150,54 @@ impl From<PushError> for StatusCode {
     }
 }
 
+/// The body payload expected by the "publish" API route
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PublishBody {
+    /// The DID of the local sphere whose version is being published
+    pub sphere: Did,
+    /// A signed link record that points the sphere's identity to the version
+    /// to be published; this may be any version that the API host already
+    /// knows about, including versions older than the latest one
+    pub name_record: Jwt,
+}
+
+/// The response from the "publish" API route
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PublishResponse {
+    /// The version of the sphere that will be published to the Noosphere Name
+    /// System
+    pub version: Cid,
+}
+
+#[derive(Error, Debug)]
+pub enum PublishError {
+    #[error("Link record is not valid for this sphere")]
+    InvalidRecord,
+    #[error("Linked version is unknown")]
+    UnknownVersion,
+    #[error("Internal error")]
+    Internal(anyhow::Error),
+}
+
+impl From<anyhow::Error> for PublishError {
+    fn from(value: anyhow::Error) -> Self {
+        PublishError::Internal(value)
+    }
+}
+
+impl From<PublishError> for StatusCode {
+    fn from(error: PublishError) -> Self {
+        match error {
+            PublishError::InvalidRecord => StatusCode::BAD_REQUEST,
+            PublishError::UnknownVersion => StatusCode::UNPROCESSABLE_ENTITY,
+            PublishError::Internal(error) => {
+                error!("Internal: {:?}", error);
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        }
+    }
+}
+
 /// The response from the "identify" API route; this is a signed response that
 /// allows the client to verify the authority of the API host
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -216,9 +267,7 @@ impl IdentifyResponse {
 
         let capability = Capability {
             with: With::Resource {
-                kind: Resource::Scoped(SphereReference {
-                    did: self.sphere_identity.to_string(),
-                }),
+                kind: Resource::Scoped(SphereReference::new(self.sphere_identity.to_string())),
             },
             can: SphereAction::Push,
         };
@@ -248,3 +297,141 @@ impl Display for IdentifyResponse {
         )
     }
 }
+
+/// The body payload expected by the "pair" API route; it is submitted by a
+/// device that wishes to be authorized to work on a sphere, and that has no
+/// authorization yet
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PairingRequest {
+    /// A short, human-readable code that the owner of the sphere will use to
+    /// refer to this request when approving it
+    pub code: String,
+    /// The DID of the sphere that the device wishes to join
+    pub sphere: Did,
+    /// The DID of the key that the device wishes to have authorized
+    pub did: Did,
+    /// The signature of the key over the pairing code and the DID of the
+    /// sphere, as base64-encoded bytes
+    pub signature: String,
+}
+
+impl PairingRequest {
+    pub async fn sign<K>(code: &str, sphere: &Did, key: &K) -> Result<Self>
+    where
+        K: KeyMaterial,
+    {
+        let did = Did(key.get_did().await?);
+        let signature = base64_encode(&key.sign(&Self::payload(code, sphere)).await?)?;
+
+        Ok(PairingRequest {
+            code: code.into(),
+            sphere: sphere.clone(),
+            did,
+            signature,
+        })
+    }
+
+    /// Verifies that the request was signed by the key that it asks to have
+    /// authorized, so that a request cannot be made on behalf of a key that
+    /// the requester does not possess
+    pub async fn verify(&self, did_parser: &mut DidParser) -> Result<()> {
+        let key = did_parser.parse(&self.did)?;
+        let signature_bytes = base64_decode(&self.signature)?;
+
+        key.verify(&Self::payload(&self.code, &self.sphere), &signature_bytes)
+            .await
+    }
+
+    /// The parameters that the requesting device uses to look up the status
+    /// of this request
+    pub fn status_parameters(&self) -> PairingStatusParameters {
+        PairingStatusParameters {
+            signature: Some(self.signature.clone()),
+        }
+    }
+
+    fn payload(code: &str, sphere: &Did) -> Vec<u8> {
+        [code.as_bytes(), sphere.as_bytes()].concat()
+    }
+}
+
+/// The parameters expected for looking up the status of a pairing request;
+/// the requesting device identifies itself with the signature from its
+/// [PairingRequest], while the owner of the sphere presents a bearer UCAN
+/// instead
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PairingStatusParameters {
+    #[serde(default, deserialize_with = "empty_string_as_none")]
+    pub signature: Option<String>,
+}
+
+impl AsQuery for PairingStatusParameters {
+    fn as_query(&self) -> Result<Option<String>> {
+        Ok(self
+            .signature
+            .as_ref()
+            .map(|signature| format!("signature={signature}")))
+    }
+}
+
+/// The response from the "pairing" API route, describing a pending request
+/// to be paired with a sphere
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PairingStatus {
+    /// The DID of the sphere that the device wishes to join
+    pub sphere: Did,
+    /// The DID of the key that the device wishes to have authorized
+    pub did: Did,
+    /// The identity of the authorization that was granted to the key, once
+    /// the request has been approved
+    pub authorization: Option<Cid>,
+}
+
+/// The body payload expected when approving a pending pairing request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PairingApproval {
+    /// The identity of the authorization that was granted to the requesting
+    /// key; it must already have been synced to the gateway
+    pub authorization: Cid,
+}
+
+#[derive(Error, Debug)]
+pub enum PairingError {
+    #[error("Pairing code is already in use")]
+    CodeInUse,
+    #[error("Sphere is not managed by this gateway")]
+    UnknownSphere,
+    #[error("Pairing request is unknown or has expired")]
+    UnknownRequest,
+    #[error("Authorization does not grant access to the requesting key")]
+    InvalidAuthorization,
+    #[error("Pairing request is not signed by the requesting key")]
+    InvalidSignature,
+    #[error("Too many pairing requests are pending")]
+    TooManyRequests,
+    #[error("Internal error")]
+    Internal(anyhow::Error),
+}
+
+impl From<anyhow::Error> for PairingError {
+    fn from(value: anyhow::Error) -> Self {
+        PairingError::Internal(value)
+    }
+}
+
+impl From<PairingError> for StatusCode {
+    fn from(error: PairingError) -> Self {
+        match error {
+            PairingError::CodeInUse => StatusCode::CONFLICT,
+            PairingError::UnknownSphere => StatusCode::UNPROCESSABLE_ENTITY,
+            PairingError::UnknownRequest => StatusCode::NOT_FOUND,
+            PairingError::InvalidAuthorization => StatusCode::BAD_REQUEST,
+            PairingError::InvalidSignature => StatusCode::UNAUTHORIZED,
+            PairingError::TooManyRequests => StatusCode::TOO_MANY_REQUESTS,
+            PairingError::Internal(error) => {
+                error!("Internal: {:?}", error);
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        }
+    }
+}
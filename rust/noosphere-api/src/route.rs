@@ -14,6 +14,8 @@ pub enum Route {
     Did,
     Identify,
     Replicate(Option<Cid>),
+    Pair,
+    Pairing(Option<String>),
 }
 
 impl Display for Route {
@@ -28,6 +30,11 @@ impl Display for Route {
                 Some(cid) => format!("replicate/{cid}"),
                 None => "replicate/:memo".into(),
             },
+            Route::Pair => "pair".into(),
+            Route::Pairing(code) => match code {
+                Some(code) => format!("pair/{code}"),
+                None => "pair/:code".into(),
+            },
         };
 
         write!(f, "/api/{API_VERSION}/{fragment}")
@@ -1,14 +1,28 @@
 use cid::Cid;
-use libipld::codec::Codec;
+use libipld::{codec::Codec, Ipld};
 use libipld_cbor::DagCborCodec;
 
 use crate::error::Error;
 
+/// The bytes that every CARv2 file begins with; they are shaped like a CARv1
+/// header (so older readers fail gracefully), but only declare the version
+pub(crate) const CAR_V2_PRAGMA: [u8; 11] = [
+    0x0a, 0xa1, 0x67, 0x76, 0x65, 0x72, 0x73, 0x69, 0x6f, 0x6e, 0x02,
+];
+
+/// The length of the fixed-size CARv2 header that follows the pragma
+pub(crate) const CAR_V2_HEADER_LENGTH: usize = 40;
+
+/// The characteristics bit that signals that the index of a CARv2 file
+/// includes every block in its data payload
+const FULLY_INDEXED: u8 = 0b1000_0000;
+
 /// A car header.
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[non_exhaustive]
 pub enum CarHeader {
     V1(CarHeaderV1),
+    V2(CarHeaderV2),
 }
 
 impl CarHeader {
@@ -16,6 +30,12 @@ impl CarHeader {
         Self::V1(roots.into())
     }
 
+    pub fn new_v2(roots: Vec<Cid>) -> Self {
+        Self::V2(roots.into())
+    }
+
+    /// Decodes a CARv1 header (which is also the header of the data payload
+    /// of a CARv2 file)
     pub fn decode(buffer: &[u8]) -> Result<Self, Error> {
         let header: CarHeaderV1 = DagCborCodec
             .decode(buffer)
@@ -34,24 +54,48 @@ impl CarHeader {
         Ok(CarHeader::V1(header))
     }
 
+    /// Reads the version from the first section of a CAR file, which is either
+    /// a CARv1 header or the CARv2 pragma
+    pub(crate) fn decode_version(buffer: &[u8]) -> Result<u64, Error> {
+        let header: Ipld = DagCborCodec
+            .decode(buffer)
+            .map_err(|e| Error::Parsing(e.to_string()))?;
+
+        match header {
+            Ipld::Map(map) => match map.get("version") {
+                Some(Ipld::Integer(version)) if *version >= 0 => Ok(*version as u64),
+                _ => Err(Error::Parsing("CAR header has no version".to_owned())),
+            },
+            _ => Err(Error::Parsing("CAR header is not a map".to_owned())),
+        }
+    }
+
+    /// Encodes the header as it is written at the start of a CARv1 file; for
+    /// a CARv2 header, this is the header of its data payload
     pub fn encode(&self) -> Result<Vec<u8>, Error> {
         match self {
             CarHeader::V1(ref header) => {
                 let res = DagCborCodec.encode(header)?;
                 Ok(res)
             }
+            CarHeader::V2(ref header) => {
+                let res = DagCborCodec.encode(&CarHeaderV1::from(header.roots.clone()))?;
+                Ok(res)
+            }
         }
     }
 
     pub fn roots(&self) -> &[Cid] {
         match self {
             CarHeader::V1(header) => &header.roots,
+            CarHeader::V2(header) => &header.roots,
         }
     }
 
     pub fn version(&self) -> u64 {
         match self {
             CarHeader::V1(_) => 1,
+            CarHeader::V2(_) => 2,
         }
     }
 }
@@ -78,6 +122,80 @@ impl From<Vec<Cid>> for CarHeaderV1 {
     }
 }
 
+/// CAR file header version 2. This combines the fixed-size header that
+/// follows the CARv2 pragma with the roots that are declared by the header of
+/// the data payload. Offsets are in bytes from the start of the file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CarHeaderV2 {
+    pub characteristics: [u8; 16],
+    pub data_offset: u64,
+    pub data_size: u64,
+    pub index_offset: u64,
+    pub roots: Vec<Cid>,
+}
+
+impl CarHeaderV2 {
+    /// True if the index of the file includes every block in its data payload
+    pub fn is_fully_indexed(&self) -> bool {
+        self.characteristics[0] & FULLY_INDEXED != 0
+    }
+
+    pub fn set_fully_indexed(&mut self, fully_indexed: bool) {
+        if fully_indexed {
+            self.characteristics[0] |= FULLY_INDEXED;
+        } else {
+            self.characteristics[0] &= !FULLY_INDEXED;
+        }
+    }
+
+    /// True if the file has an index section
+    pub fn has_index(&self) -> bool {
+        self.index_offset != 0
+    }
+
+    /// Encodes the fixed-size part of the header (everything but the roots)
+    pub(crate) fn encode_fixed(&self) -> [u8; CAR_V2_HEADER_LENGTH] {
+        let mut bytes = [0u8; CAR_V2_HEADER_LENGTH];
+
+        bytes[0..16].copy_from_slice(&self.characteristics);
+        bytes[16..24].copy_from_slice(&self.data_offset.to_le_bytes());
+        bytes[24..32].copy_from_slice(&self.data_size.to_le_bytes());
+        bytes[32..40].copy_from_slice(&self.index_offset.to_le_bytes());
+
+        bytes
+    }
+
+    /// Decodes the fixed-size part of the header; the roots are left empty,
+    /// since they are found in the header of the data payload
+    pub(crate) fn decode_fixed(bytes: &[u8; CAR_V2_HEADER_LENGTH]) -> Self {
+        let read_u64 = |range: std::ops::Range<usize>| {
+            let mut word = [0u8; 8];
+            word.copy_from_slice(&bytes[range]);
+            u64::from_le_bytes(word)
+        };
+
+        let mut characteristics = [0u8; 16];
+        characteristics.copy_from_slice(&bytes[0..16]);
+
+        CarHeaderV2 {
+            characteristics,
+            data_offset: read_u64(16..24),
+            data_size: read_u64(24..32),
+            index_offset: read_u64(32..40),
+            roots: Vec::new(),
+        }
+    }
+}
+
+impl From<Vec<Cid>> for CarHeaderV2 {
+    fn from(roots: Vec<Cid>) -> Self {
+        Self {
+            roots,
+            ..Default::default()
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use libipld::codec::{Decode, Encode};
@@ -108,4 +226,28 @@ mod tests {
             header
         );
     }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[cfg_attr(not(target_arch = "wasm32"), test)]
+    fn symmetric_header_v2() {
+        let mut header = CarHeaderV2 {
+            data_offset: 51,
+            data_size: 1024,
+            index_offset: 1075,
+            ..Default::default()
+        };
+        header.set_fully_indexed(true);
+
+        let decoded = CarHeaderV2::decode_fixed(&header.encode_fixed());
+
+        assert_eq!(decoded, header);
+        assert!(decoded.is_fully_indexed());
+        assert!(decoded.has_index());
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[cfg_attr(not(target_arch = "wasm32"), test)]
+    fn pragma_declares_version_2() {
+        assert_eq!(CarHeader::decode_version(&CAR_V2_PRAGMA[1..]).unwrap(), 2);
+    }
 }
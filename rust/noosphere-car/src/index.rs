@@ -0,0 +1,306 @@
+use std::collections::BTreeMap;
+
+use cid::Cid;
+use integer_encoding::VarInt;
+
+use crate::error::Error;
+
+/// The multicodec code of an index that is keyed by multihash digests
+const INDEX_SORTED_CODE: u64 = 0x0400;
+
+/// The multicodec code of an index that is keyed by multihash digests, grouped
+/// by their multihash code
+const MULTIHASH_INDEX_SORTED_CODE: u64 = 0x0401;
+
+/// The length of the offset that follows each digest in an index bucket
+const OFFSET_LENGTH: usize = 8;
+
+/// The formats that the index of a CARv2 file may be encoded in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CarIndexFormat {
+    /// Entries are keyed only by the digest of each block's multihash
+    IndexSorted,
+    /// Entries are keyed by both the code and the digest of each block's
+    /// multihash
+    MultihashIndexSorted,
+}
+
+impl CarIndexFormat {
+    fn code(&self) -> u64 {
+        match self {
+            CarIndexFormat::IndexSorted => INDEX_SORTED_CODE,
+            CarIndexFormat::MultihashIndexSorted => MULTIHASH_INDEX_SORTED_CODE,
+        }
+    }
+}
+
+impl TryFrom<u64> for CarIndexFormat {
+    type Error = Error;
+
+    fn try_from(code: u64) -> Result<Self, Self::Error> {
+        match code {
+            INDEX_SORTED_CODE => Ok(CarIndexFormat::IndexSorted),
+            MULTIHASH_INDEX_SORTED_CODE => Ok(CarIndexFormat::MultihashIndexSorted),
+            _ => Err(Error::InvalidFile(format!(
+                "CAR index format 0x{code:x} is not supported"
+            ))),
+        }
+    }
+}
+
+/// The index of a CARv2 file, which maps the multihash of each block to the
+/// offset of its section, relative to the start of the data payload
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CarIndex {
+    format: CarIndexFormat,
+    /// Entries are keyed by multihash code (if known; an
+    /// [CarIndexFormat::IndexSorted] index does not record it) and digest
+    entries: BTreeMap<(Option<u64>, Vec<u8>), u64>,
+}
+
+impl CarIndex {
+    pub fn new(format: CarIndexFormat) -> Self {
+        CarIndex {
+            format,
+            entries: BTreeMap::new(),
+        }
+    }
+
+    pub fn format(&self) -> CarIndexFormat {
+        self.format
+    }
+
+    /// Record the offset of the section that holds the block with the given
+    /// [Cid]
+    pub fn insert(&mut self, cid: &Cid, offset: u64) {
+        let code = match self.format {
+            CarIndexFormat::IndexSorted => None,
+            CarIndexFormat::MultihashIndexSorted => Some(cid.hash().code()),
+        };
+
+        self.entries
+            .insert((code, cid.hash().digest().to_vec()), offset);
+    }
+
+    /// Look up the offset of the section that holds the block with the given
+    /// [Cid], if it is in the index
+    pub fn get(&self, cid: &Cid) -> Option<u64> {
+        let digest = cid.hash().digest().to_vec();
+
+        match self.format {
+            CarIndexFormat::IndexSorted => self.entries.get(&(None, digest)),
+            CarIndexFormat::MultihashIndexSorted => {
+                self.entries.get(&(Some(cid.hash().code()), digest))
+            }
+        }
+        .copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Encodes the index as it is written to the index section of a CARv2 file
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = self.format.code().encode_var_vec();
+
+        match self.format {
+            CarIndexFormat::IndexSorted => encode_buckets(
+                self.entries
+                    .iter()
+                    .map(|((_, digest), offset)| (digest.as_slice(), *offset)),
+                &mut bytes,
+            ),
+            CarIndexFormat::MultihashIndexSorted => {
+                let mut codes: BTreeMap<u64, Vec<(&[u8], u64)>> = BTreeMap::new();
+
+                for ((code, digest), offset) in self.entries.iter() {
+                    codes
+                        .entry(code.unwrap_or_default())
+                        .or_default()
+                        .push((digest.as_slice(), *offset));
+                }
+
+                bytes.extend_from_slice(&(codes.len() as i32).to_le_bytes());
+
+                for (code, entries) in codes {
+                    bytes.extend_from_slice(&code.to_le_bytes());
+                    encode_buckets(entries, &mut bytes);
+                }
+            }
+        }
+
+        bytes
+    }
+
+    /// Decodes an index from the bytes of the index section of a CARv2 file
+    pub fn decode(bytes: &[u8]) -> Result<Self, Error> {
+        let (code, length) = u64::decode_var(bytes)
+            .ok_or_else(|| Error::Parsing("failed to parse uvarint for index".to_string()))?;
+        let format = CarIndexFormat::try_from(code)?;
+        let mut index = CarIndex::new(format);
+        let mut reader = IndexReader {
+            bytes: &bytes[length..],
+        };
+
+        match format {
+            CarIndexFormat::IndexSorted => decode_buckets(&mut reader, None, &mut index)?,
+            CarIndexFormat::MultihashIndexSorted => {
+                let count = reader.read_count()?;
+
+                for _ in 0..count {
+                    let code = reader.read_u64()?;
+                    decode_buckets(&mut reader, Some(code), &mut index)?;
+                }
+            }
+        }
+
+        Ok(index)
+    }
+}
+
+/// Writes the entries as buckets of equal width (digest length plus offset
+/// length), in ascending order of width and then of digest
+fn encode_buckets<'a, I>(entries: I, bytes: &mut Vec<u8>)
+where
+    I: IntoIterator<Item = (&'a [u8], u64)>,
+{
+    let mut buckets: BTreeMap<usize, BTreeMap<&[u8], u64>> = BTreeMap::new();
+
+    for (digest, offset) in entries {
+        buckets
+            .entry(digest.len() + OFFSET_LENGTH)
+            .or_default()
+            .insert(digest, offset);
+    }
+
+    bytes.extend_from_slice(&(buckets.len() as i32).to_le_bytes());
+
+    for (width, entries) in buckets {
+        bytes.extend_from_slice(&(width as u32).to_le_bytes());
+        bytes.extend_from_slice(&((width * entries.len()) as u64).to_le_bytes());
+
+        for (digest, offset) in entries {
+            bytes.extend_from_slice(digest);
+            bytes.extend_from_slice(&offset.to_le_bytes());
+        }
+    }
+}
+
+fn decode_buckets(
+    reader: &mut IndexReader,
+    code: Option<u64>,
+    index: &mut CarIndex,
+) -> Result<(), Error> {
+    let count = reader.read_count()?;
+
+    for _ in 0..count {
+        let width = reader.read_u32()? as usize;
+        let length = reader.read_u64()? as usize;
+
+        if width <= OFFSET_LENGTH || length % width != 0 {
+            return Err(Error::InvalidFile(format!(
+                "CAR index bucket of width {width} cannot hold {length} bytes"
+            )));
+        }
+
+        for entry in reader.read_bytes(length)?.chunks_exact(width) {
+            let (digest, offset) = entry.split_at(width - OFFSET_LENGTH);
+            let mut offset_bytes = [0u8; OFFSET_LENGTH];
+
+            offset_bytes.copy_from_slice(offset);
+            index
+                .entries
+                .insert((code, digest.to_vec()), u64::from_le_bytes(offset_bytes));
+        }
+    }
+
+    Ok(())
+}
+
+/// Reads the little-endian integers and byte strings that an index is made of
+struct IndexReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> IndexReader<'a> {
+    fn read_bytes(&mut self, length: usize) -> Result<&'a [u8], Error> {
+        if self.bytes.len() < length {
+            return Err(Error::Parsing("CAR index is truncated".to_string()));
+        }
+
+        let (bytes, rest) = self.bytes.split_at(length);
+        self.bytes = rest;
+
+        Ok(bytes)
+    }
+
+    fn read_count(&mut self) -> Result<usize, Error> {
+        let mut word = [0u8; 4];
+        word.copy_from_slice(self.read_bytes(4)?);
+
+        usize::try_from(i32::from_le_bytes(word))
+            .map_err(|_| Error::InvalidFile("CAR index has a negative length".to_string()))
+    }
+
+    fn read_u32(&mut self) -> Result<u32, Error> {
+        let mut word = [0u8; 4];
+        word.copy_from_slice(self.read_bytes(4)?);
+
+        Ok(u32::from_le_bytes(word))
+    }
+
+    fn read_u64(&mut self) -> Result<u64, Error> {
+        let mut word = [0u8; 8];
+        word.copy_from_slice(self.read_bytes(8)?);
+
+        Ok(u64::from_le_bytes(word))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cid::Cid;
+    use libipld_cbor::DagCborCodec;
+    use multihash::MultihashDigest;
+
+    use super::*;
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    #[cfg(target_arch = "wasm32")]
+    wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_browser);
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[cfg_attr(not(target_arch = "wasm32"), test)]
+    fn symmetric_index() {
+        let cid_blake3 = Cid::new_v1(
+            DagCborCodec.into(),
+            multihash::Code::Blake3_256.digest(b"test"),
+        );
+        let cid_sha2 = Cid::new_v1(
+            DagCborCodec.into(),
+            multihash::Code::Sha2_512.digest(b"foo"),
+        );
+
+        for format in [
+            CarIndexFormat::IndexSorted,
+            CarIndexFormat::MultihashIndexSorted,
+        ] {
+            let mut index = CarIndex::new(format);
+            index.insert(&cid_blake3, 0);
+            index.insert(&cid_sha2, 42);
+
+            let decoded = CarIndex::decode(&index.encode()).unwrap();
+
+            assert_eq!(decoded, index);
+            assert_eq!(decoded.get(&cid_blake3), Some(0));
+            assert_eq!(decoded.get(&cid_sha2), Some(42));
+        }
+    }
+}
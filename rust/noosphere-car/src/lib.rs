@@ -2,11 +2,17 @@
 
 mod error;
 mod header;
+mod index;
 mod reader;
+mod reader_v2;
 mod util;
 mod varint;
 mod writer;
+mod writer_v2;
 
-pub use crate::header::CarHeader;
+pub use crate::header::{CarHeader, CarHeaderV1, CarHeaderV2};
+pub use crate::index::{CarIndex, CarIndexFormat};
 pub use crate::reader::CarReader;
+pub use crate::reader_v2::CarV2Reader;
 pub use crate::writer::CarWriter;
+pub use crate::writer_v2::CarV2Writer;
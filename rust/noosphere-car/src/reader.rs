@@ -1,11 +1,12 @@
 use cid::Cid;
 use futures::Stream;
-use tokio::io::AsyncRead;
+use integer_encoding::VarInt;
+use tokio::io::{AsyncRead, AsyncReadExt};
 
 use crate::{
     error::Error,
-    header::CarHeader,
-    util::{ld_read, read_node},
+    header::{CarHeader, CarHeaderV2, CAR_V2_HEADER_LENGTH, CAR_V2_PRAGMA},
+    util::{ld_read, read_node, section_length},
 };
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -20,12 +21,16 @@ pub trait CarReaderSend {}
 #[cfg(target_arch = "wasm32")]
 impl<S> CarReaderSend for S {}
 
-/// Reads CAR files that are in a BufReader
+/// Reads CAR files that are in a BufReader. Both CARv1 and CARv2 files can be
+/// read; the blocks of a CARv2 file are read from its data payload, and its
+/// index (if any) is ignored.
 #[derive(Debug)]
 pub struct CarReader<R> {
     reader: R,
     header: CarHeader,
     buffer: Vec<u8>,
+    /// For CARv2 files, the number of bytes left to read in the data payload
+    remaining: Option<u64>,
 }
 
 impl<R> CarReader<R>
@@ -36,20 +41,37 @@ where
     pub async fn new(mut reader: R) -> Result<Self, Error> {
         let mut buffer = Vec::new();
 
-        match ld_read(&mut reader, &mut buffer).await? {
-            Some(buf) => {
-                let header = CarHeader::decode(buf)?;
+        let header = match ld_read(&mut reader, &mut buffer).await? {
+            Some(buf) => match CarHeader::decode_version(buf)? {
+                1 => Some(CarHeader::decode(buf)?),
+                2 => None,
+                version => {
+                    return Err(Error::InvalidFile(format!(
+                        "CAR file version {version} is not supported"
+                    )))
+                }
+            },
+            None => {
+                return Err(Error::Parsing(
+                    "failed to parse uvarint for header".to_string(),
+                ))
+            }
+        };
 
-                Ok(CarReader {
-                    reader,
-                    header,
-                    buffer,
-                })
+        let (header, remaining) = match header {
+            Some(header) => (header, None),
+            None => {
+                let (header, remaining) = read_v2_header(&mut reader, &mut buffer).await?;
+                (CarHeader::V2(header), Some(remaining))
             }
-            None => Err(Error::Parsing(
-                "failed to parse uvarint for header".to_string(),
-            )),
-        }
+        };
+
+        Ok(CarReader {
+            reader,
+            header,
+            buffer,
+            remaining,
+        })
     }
 
     /// Returns the header of this car file.
@@ -59,17 +81,79 @@ where
 
     /// Returns the next IPLD Block in the buffer
     pub async fn next_block(&mut self) -> Result<Option<(Cid, Vec<u8>)>, Error> {
-        read_node(&mut self.reader, &mut self.buffer).await
+        if self.remaining == Some(0) {
+            return Ok(None);
+        }
+
+        let block = read_node(&mut self.reader, &mut self.buffer).await?;
+
+        if let (Some(remaining), Some((cid, data))) = (self.remaining.as_mut(), block.as_ref()) {
+            *remaining = remaining
+                .checked_sub(section_length(cid, data))
+                .ok_or_else(|| {
+                    Error::InvalidFile("block overruns the CARv2 data payload".to_string())
+                })?;
+        }
+
+        Ok(block)
     }
 
     pub fn stream(self) -> impl Stream<Item = Result<(Cid, Vec<u8>), Error>> {
         futures::stream::try_unfold(self, |mut this| async move {
-            let maybe_block = read_node(&mut this.reader, &mut this.buffer).await?;
+            let maybe_block = this.next_block().await?;
             Ok(maybe_block.map(|b| (b, this)))
         })
     }
 }
 
+/// Reads the fixed-size header of a CARv2 file (which is expected to follow
+/// the pragma that was just read), skips ahead to the data payload and reads
+/// the payload's own header. Returns the combined header, and the number of
+/// bytes in the data payload that follow the payload's header.
+pub(crate) async fn read_v2_header<R>(
+    reader: &mut R,
+    buffer: &mut Vec<u8>,
+) -> Result<(CarHeaderV2, u64), Error>
+where
+    R: AsyncRead + CarReaderSend + Unpin,
+{
+    let mut fixed_header = [0u8; CAR_V2_HEADER_LENGTH];
+
+    reader.read_exact(&mut fixed_header).await?;
+
+    let mut header = CarHeaderV2::decode_fixed(&fixed_header);
+    let padding = header
+        .data_offset
+        .checked_sub((CAR_V2_PRAGMA.len() + CAR_V2_HEADER_LENGTH) as u64)
+        .ok_or_else(|| Error::InvalidFile("data payload overlaps the CARv2 header".to_string()))?;
+
+    if tokio::io::copy(&mut (&mut *reader).take(padding), &mut tokio::io::sink()).await? != padding
+    {
+        return Err(Error::InvalidFile(
+            "data payload starts beyond the end of the file".to_string(),
+        ));
+    }
+
+    let payload_header_length = match ld_read(&mut *reader, buffer).await? {
+        Some(buf) => {
+            header.roots = CarHeader::decode(buf)?.roots().to_vec();
+            buf.len() + buf.len().required_space()
+        }
+        None => {
+            return Err(Error::Parsing(
+                "failed to parse uvarint for data payload header".to_string(),
+            ))
+        }
+    };
+
+    let remaining = header
+        .data_size
+        .checked_sub(payload_header_length as u64)
+        .ok_or_else(|| Error::InvalidFile("data payload header overruns its size".to_string()))?;
+
+    Ok((header, remaining))
+}
+
 #[cfg(test)]
 mod tests {
     use std::io::Cursor;
@@ -0,0 +1,287 @@
+use std::io::SeekFrom;
+
+use cid::Cid;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeek, AsyncSeekExt};
+
+use crate::{
+    error::Error,
+    header::CarHeader,
+    index::{CarIndex, CarIndexFormat},
+    reader::{read_v2_header, CarReaderSend},
+    util::{ld_read, read_node, section_length},
+};
+
+/// Reads blocks from a seekable CAR file in random order. The index of a
+/// CARv2 file is used to find blocks when it has one; otherwise (and for
+/// CARv1 files), an index is built by scanning the file once when it is
+/// opened.
+#[derive(Debug)]
+pub struct CarV2Reader<R> {
+    reader: R,
+    header: CarHeader,
+    index: CarIndex,
+    buffer: Vec<u8>,
+    /// The position of the data payload in the file
+    data_offset: u64,
+    /// The number of bytes in the data payload after its header, if known
+    data_size: Option<u64>,
+    /// The position of the first block in the file
+    blocks_offset: u64,
+}
+
+impl<R> CarV2Reader<R>
+where
+    R: AsyncRead + AsyncSeek + CarReaderSend + Unpin,
+{
+    /// Reads the header and index of a CAR file
+    pub async fn open(mut reader: R) -> Result<Self, Error> {
+        let mut buffer = Vec::new();
+
+        reader.seek(SeekFrom::Start(0)).await?;
+
+        let header = match ld_read(&mut reader, &mut buffer).await? {
+            Some(buf) => match CarHeader::decode_version(buf)? {
+                1 => Some(CarHeader::decode(buf)?),
+                2 => None,
+                version => {
+                    return Err(Error::InvalidFile(format!(
+                        "CAR file version {version} is not supported"
+                    )))
+                }
+            },
+            None => {
+                return Err(Error::Parsing(
+                    "failed to parse uvarint for header".to_string(),
+                ))
+            }
+        };
+
+        let (header, data_offset, data_size) = match header {
+            Some(header) => (header, 0, None),
+            None => {
+                let (header, remaining) = read_v2_header(&mut reader, &mut buffer).await?;
+                let data_offset = header.data_offset;
+
+                (CarHeader::V2(header), data_offset, Some(remaining))
+            }
+        };
+
+        let blocks_offset = reader.stream_position().await?;
+        let index_offset = match &header {
+            CarHeader::V2(header) if header.has_index() && header.is_fully_indexed() => {
+                Some(header.index_offset)
+            }
+            _ => None,
+        };
+
+        let mut car_reader = CarV2Reader {
+            reader,
+            header,
+            index: CarIndex::new(CarIndexFormat::MultihashIndexSorted),
+            buffer,
+            data_offset,
+            data_size,
+            blocks_offset,
+        };
+
+        car_reader.index = match index_offset {
+            Some(index_offset) => {
+                let mut index_bytes = Vec::new();
+
+                car_reader
+                    .reader
+                    .seek(SeekFrom::Start(index_offset))
+                    .await?;
+                car_reader.reader.read_to_end(&mut index_bytes).await?;
+
+                CarIndex::decode(&index_bytes)?
+            }
+            None => car_reader.scan_index().await?,
+        };
+
+        Ok(car_reader)
+    }
+
+    /// Returns the header of this car file.
+    pub fn header(&self) -> &CarHeader {
+        &self.header
+    }
+
+    /// Returns the index of this car file
+    pub fn index(&self) -> &CarIndex {
+        &self.index
+    }
+
+    /// Reads the block with the given [Cid], if it is in the file
+    pub async fn get_block(&mut self, cid: &Cid) -> Result<Option<Vec<u8>>, Error> {
+        let offset = match self.index.get(cid) {
+            Some(offset) => offset,
+            None => return Ok(None),
+        };
+
+        self.reader
+            .seek(SeekFrom::Start(self.data_offset + offset))
+            .await?;
+
+        match read_node(&mut self.reader, &mut self.buffer).await? {
+            Some((found_cid, data)) if found_cid.hash() == cid.hash() => Ok(Some(data)),
+            _ => Err(Error::InvalidFile(format!(
+                "CAR index entry for {cid} does not point to its block"
+            ))),
+        }
+    }
+
+    /// Reads the [Cid] of every block in the file, in the order that they
+    /// were written
+    pub async fn cids(&mut self) -> Result<Vec<Cid>, Error> {
+        let mut cids = Vec::new();
+
+        self.scan(|cid, _| cids.push(cid)).await?;
+
+        Ok(cids)
+    }
+
+    /// Builds an index by reading every block in the file
+    async fn scan_index(&mut self) -> Result<CarIndex, Error> {
+        let mut index = CarIndex::new(CarIndexFormat::MultihashIndexSorted);
+
+        self.scan(|cid, offset| index.insert(&cid, offset)).await?;
+
+        Ok(index)
+    }
+
+    /// Visits every block in the data payload along with the offset of its
+    /// section, relative to the start of the data payload
+    async fn scan<F>(&mut self, mut visit: F) -> Result<(), Error>
+    where
+        F: FnMut(Cid, u64),
+    {
+        let mut position = self.blocks_offset;
+        let end = self
+            .data_size
+            .map(|data_size| self.blocks_offset + data_size);
+
+        self.reader.seek(SeekFrom::Start(position)).await?;
+
+        while end.map(|end| position < end).unwrap_or(true) {
+            let (cid, data) = match read_node(&mut self.reader, &mut self.buffer).await? {
+                Some(block) => block,
+                None => break,
+            };
+
+            visit(cid, position - self.data_offset);
+            position += section_length(&cid, &data);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use cid::Cid;
+    use futures::TryStreamExt;
+    use libipld_cbor::DagCborCodec;
+    use multihash::MultihashDigest;
+
+    use crate::{CarReader, CarV2Writer, CarWriter};
+
+    use super::*;
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    #[cfg(target_arch = "wasm32")]
+    wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_browser);
+
+    fn make_block(bytes: &[u8]) -> (Cid, Vec<u8>) {
+        let digest = multihash::Code::Blake3_256.digest(bytes);
+        (Cid::new_v1(DagCborCodec.into(), digest), bytes.to_vec())
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[cfg_attr(not(target_arch = "wasm32"), tokio::test)]
+    async fn car_v2_write_read_in_random_order() {
+        let blocks: Vec<(Cid, Vec<u8>)> = [b"test".as_ref(), b"foo", b"bar", b"baz"]
+            .into_iter()
+            .map(make_block)
+            .collect();
+        let (missing_cid, _) = make_block(b"missing");
+
+        for format in [
+            CarIndexFormat::IndexSorted,
+            CarIndexFormat::MultihashIndexSorted,
+        ] {
+            let mut writer = CarV2Writer::new(vec![blocks[0].0], Cursor::new(Vec::new()))
+                .with_index_format(format);
+
+            for (cid, data) in blocks.iter() {
+                writer.write(*cid, data).await.unwrap();
+            }
+
+            let buffer = writer.finish().await.unwrap().into_inner();
+
+            let mut car_reader = CarV2Reader::open(Cursor::new(&buffer)).await.unwrap();
+
+            assert_eq!(car_reader.header().version(), 2);
+            assert_eq!(car_reader.header().roots(), &[blocks[0].0]);
+            assert_eq!(car_reader.index().format(), format);
+            assert_eq!(car_reader.index().len(), blocks.len());
+
+            for (cid, data) in blocks.iter().rev() {
+                assert_eq!(
+                    car_reader.get_block(cid).await.unwrap().as_ref(),
+                    Some(data)
+                );
+            }
+
+            assert_eq!(car_reader.get_block(&missing_cid).await.unwrap(), None);
+            assert_eq!(
+                car_reader.cids().await.unwrap(),
+                blocks.iter().map(|(cid, _)| *cid).collect::<Vec<Cid>>()
+            );
+
+            let streamed: Vec<_> = CarReader::new(Cursor::new(&buffer))
+                .await
+                .unwrap()
+                .stream()
+                .try_collect()
+                .await
+                .unwrap();
+
+            assert_eq!(streamed, blocks);
+        }
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[cfg_attr(not(target_arch = "wasm32"), tokio::test)]
+    async fn car_v1_is_indexed_when_opened() {
+        let blocks: Vec<(Cid, Vec<u8>)> = [b"test".as_ref(), b"foo"]
+            .into_iter()
+            .map(make_block)
+            .collect();
+
+        let mut buffer = Vec::new();
+        let mut writer = CarWriter::new(CarHeader::new_v1(vec![blocks[0].0]), &mut buffer);
+
+        for (cid, data) in blocks.iter() {
+            writer.write(*cid, data).await.unwrap();
+        }
+
+        writer.finish().await.unwrap();
+
+        let mut car_reader = CarV2Reader::open(Cursor::new(&buffer)).await.unwrap();
+
+        assert_eq!(car_reader.header().version(), 1);
+        assert_eq!(car_reader.index().len(), blocks.len());
+
+        for (cid, data) in blocks.iter().rev() {
+            assert_eq!(
+                car_reader.get_block(cid).await.unwrap().as_ref(),
+                Some(data)
+            );
+        }
+    }
+}
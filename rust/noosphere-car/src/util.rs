@@ -1,5 +1,6 @@
 use anyhow::Result;
 use cid::Cid;
+use integer_encoding::VarInt;
 use tokio::io::{AsyncRead, AsyncReadExt};
 
 use crate::{reader::CarReaderSend, varint::read_varint_async};
@@ -55,6 +56,14 @@ where
     Ok(None)
 }
 
+/// The number of bytes that a block occupies in the data section of a CAR
+/// file, including the varint that prefixes it
+pub(crate) fn section_length(cid: &Cid, data: &[u8]) -> u64 {
+    let length = cid.encoded_len() + data.len();
+
+    (length.required_space() + length) as u64
+}
+
 #[cfg(test)]
 mod tests {
     use integer_encoding::VarIntAsyncWriter;
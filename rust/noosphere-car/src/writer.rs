@@ -4,6 +4,8 @@ use tokio::io::{AsyncWrite, AsyncWriteExt};
 
 use crate::{error::Error, header::CarHeader};
 
+/// Writes CARv1 files; see [crate::CarV2Writer] for writing CARv2 files. A
+/// [CarWriter] that is given a CARv2 header fails to write.
 #[derive(Debug)]
 pub struct CarWriter<W> {
     header: CarHeader,
@@ -31,6 +33,15 @@ where
         T: AsRef<[u8]>,
     {
         if !self.is_header_written {
+            // A CARv2 file needs an index and a seekable writer, which this
+            // writer does not have
+            if let CarHeader::V2(_) = self.header {
+                return Err(Error::InvalidFile(
+                    "CarWriter only writes CARv1 files; use CarV2Writer to write a CARv2 file"
+                        .to_string(),
+                ));
+            }
+
             // Write header bytes
             let header_bytes = self.header.encode()?;
             self.writer.write_varint_async(header_bytes.len()).await?;
@@ -0,0 +1,116 @@
+use std::io::SeekFrom;
+
+use cid::Cid;
+use integer_encoding::VarIntAsyncWriter;
+use tokio::io::{AsyncSeek, AsyncSeekExt, AsyncWrite, AsyncWriteExt};
+
+use crate::{
+    error::Error,
+    header::{CarHeader, CarHeaderV2, CAR_V2_HEADER_LENGTH, CAR_V2_PRAGMA},
+    index::{CarIndex, CarIndexFormat},
+    util::section_length,
+};
+
+/// Writes CARv2 files, including an index of every block that is written so
+/// that the file can later be read in random order (see
+/// [crate::CarV2Reader]). The fixed-size header of a CARv2 file records where
+/// its index is, so it is written last; this requires a seekable writer.
+#[derive(Debug)]
+pub struct CarV2Writer<W> {
+    header: CarHeaderV2,
+    writer: W,
+    index: CarIndex,
+    cid_buffer: Vec<u8>,
+    /// The number of bytes written to the data payload so far
+    data_size: u64,
+    is_header_written: bool,
+}
+
+impl<W> CarV2Writer<W>
+where
+    W: AsyncWrite + AsyncSeek + Send + Unpin,
+{
+    pub fn new(roots: Vec<Cid>, writer: W) -> Self {
+        CarV2Writer {
+            header: roots.into(),
+            writer,
+            index: CarIndex::new(CarIndexFormat::MultihashIndexSorted),
+            cid_buffer: Vec::new(),
+            data_size: 0,
+            is_header_written: false,
+        }
+    }
+
+    /// Use the given format for the index of the file; by default, the index
+    /// is written as [CarIndexFormat::MultihashIndexSorted]
+    pub fn with_index_format(mut self, format: CarIndexFormat) -> Self {
+        self.index = CarIndex::new(format);
+        self
+    }
+
+    /// Writes the pragma, a placeholder for the fixed-size header and the
+    /// header of the data payload
+    async fn write_header(&mut self) -> Result<(), Error> {
+        if self.is_header_written {
+            return Ok(());
+        }
+
+        self.header.data_offset = (CAR_V2_PRAGMA.len() + CAR_V2_HEADER_LENGTH) as u64;
+
+        self.writer.write_all(&CAR_V2_PRAGMA).await?;
+        self.writer.write_all(&self.header.encode_fixed()).await?;
+
+        let header_bytes = CarHeader::V2(self.header.clone()).encode()?;
+        self.data_size += self.writer.write_varint_async(header_bytes.len()).await? as u64;
+        self.writer.write_all(&header_bytes).await?;
+        self.data_size += header_bytes.len() as u64;
+
+        self.is_header_written = true;
+
+        Ok(())
+    }
+
+    /// Writes a block to the data payload and records it in the index
+    pub async fn write<T>(&mut self, cid: Cid, data: T) -> Result<(), Error>
+    where
+        T: AsRef<[u8]>,
+    {
+        self.write_header().await?;
+
+        self.cid_buffer.clear();
+        cid.write_bytes(&mut self.cid_buffer).expect("vec write");
+
+        let data = data.as_ref();
+        let len = self.cid_buffer.len() + data.len();
+
+        self.writer.write_varint_async(len).await?;
+        self.writer.write_all(&self.cid_buffer).await?;
+        self.writer.write_all(data).await?;
+
+        self.index.insert(&cid, self.data_size);
+        self.data_size += section_length(&cid, data);
+
+        Ok(())
+    }
+
+    /// Writes the index, fills in the fixed-size header and flushes, returning
+    /// the writer (positioned at the end of the file)
+    pub async fn finish(mut self) -> Result<W, Error> {
+        self.write_header().await?;
+
+        self.header.data_size = self.data_size;
+        self.header.index_offset = self.header.data_offset + self.data_size;
+        self.header.set_fully_indexed(true);
+
+        self.writer.write_all(&self.index.encode()).await?;
+
+        self.writer
+            .seek(SeekFrom::Start(CAR_V2_PRAGMA.len() as u64))
+            .await?;
+        self.writer.write_all(&self.header.encode_fixed()).await?;
+        self.writer.seek(SeekFrom::End(0)).await?;
+        self.writer.flush().await?;
+
+        Ok(self.writer)
+    }
+}
@@ -78,3 +78,21 @@ async fn roundtrip_carv1_basic_fixtures_file() {
 
     assert_eq!(CAR_V1_BASIC, buffer.as_slice());
 }
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+#[cfg_attr(not(target_arch = "wasm32"), tokio::test)]
+async fn carv1_writer_rejects_a_carv2_header() {
+    let buf_reader = BufReader::new(TEST_V1_CAR);
+
+    let car_reader = CarReader::new(buf_reader).await.unwrap();
+    let roots = car_reader.header().roots().to_vec();
+    let files: Vec<_> = car_reader.stream().try_collect().await.unwrap();
+
+    let mut buffer = Vec::new();
+    let mut writer = CarWriter::new(CarHeader::new_v2(roots), &mut buffer);
+
+    let (cid, data) = &files[0];
+
+    assert!(writer.write(*cid, data).await.is_err());
+    assert!(buffer.is_empty());
+}
@@ -1,42 +1,210 @@
-use std::{convert::TryFrom, str::FromStr};
+use std::convert::TryFrom;
 
 use anyhow::{anyhow, Result};
 use cid::Cid;
+use noosphere::key::KeyStorage;
+use noosphere_api::data::PairingApproval;
 use noosphere_core::{
-    authority::{SphereAction, SphereReference},
-    data::{DelegationIpld, Link, RevocationIpld},
+    authority::{Author, SphereAction, SphereReference, SUPPORTED_KEYS},
+    data::{Did, Link, RevocationIpld},
     view::{Sphere, SphereMutation},
 };
+use noosphere_sphere::{
+    HasMutableSphereContext, HasSphereContext, SphereAuthorityRead, SphereAuthorityWrite,
+    SphereCursor, SpherePrivateContentWrite, SphereSync, AUTHORIZATION, USER_KEY_NAME,
+};
+use noosphere_storage::KeyValueStore;
 use serde_json::{json, Value};
 use ucan::{
-    builder::UcanBuilder,
     capability::{Capability, Resource, With},
-    crypto::KeyMaterial,
-    store::UcanJwtStore,
-    Ucan,
+    crypto::{did::DidParser, KeyMaterial},
 };
 
 use tokio_stream::StreamExt;
 
 use crate::native::workspace::Workspace;
 
-pub async fn auth_add(did: &str, name: Option<String>, workspace: &Workspace) -> Result<Cid> {
+/// Parse a duration such as `90s`, `12h`, `30d`, `2w` or `1y` into a number of
+/// seconds
+pub fn parse_duration(value: &str) -> Result<u64> {
+    let value = value.trim();
+    let split_at = value
+        .find(|character: char| !character.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (amount, unit) = value.split_at(split_at);
+
+    let amount: u64 = amount
+        .parse()
+        .map_err(|_| anyhow!("Expected a duration like 12h or 30d, but got {:?}", value))?;
+
+    let unit_seconds: u64 = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "" | "d" => 60 * 60 * 24,
+        "w" => 60 * 60 * 24 * 7,
+        "y" => 60 * 60 * 24 * 365,
+        _ => return Err(anyhow!("Unrecognized unit of time {:?}", unit)),
+    };
+
+    amount
+        .checked_mul(unit_seconds)
+        .ok_or_else(|| anyhow!("The duration {:?} is too long", value))
+}
+
+/// Describe when an authorization expires, relative to now
+fn describe_expiry(expires_at: u64, now: u64) -> String {
+    const HOUR: u64 = 60 * 60;
+    const DAY: u64 = HOUR * 24;
+    const CENTURY: u64 = DAY * 365 * 100;
+
+    if expires_at <= now {
+        "expired".into()
+    } else if expires_at - now > CENTURY {
+        "never".into()
+    } else if expires_at - now > DAY {
+        format!("in {} days", (expires_at - now) / DAY)
+    } else {
+        format!("in {} hours", (expires_at - now) / HOUR)
+    }
+}
+
+/// Authorize a key to work on the sphere. The key may take the given actions
+/// (or, if none are given, it is given full access to the sphere). If a
+/// content prefix is given, the key may only push changes to content whose
+/// slug starts with that prefix. If a lifetime is given (in seconds), the
+/// authorization expires after that long.
+pub async fn auth_add(
+    did: &str,
+    name: Option<String>,
+    can: &[SphereAction],
+    prefix: Option<String>,
+    expires: Option<u64>,
+    workspace: &Workspace,
+) -> Result<Cid> {
+    workspace.ensure_sphere_initialized()?;
+
+    let authorization = authorize_key(did, name, can, prefix, expires, workspace).await?;
+
+    info!(
+        r#"Successfully authorized {did} to access your sphere.
+
+IMPORTANT: You MUST sync to enable your gateway to recognize the authorization:
+
+  orb sync
+
+This is the authorization's identity:
+
+  {}
+  
+Use this identity when joining the sphere on the other client"#,
+        authorization
+    );
+
+    Ok(authorization)
+}
+
+/// Approve a request from another device to join the sphere; the request is
+/// identified by the pairing code that `orb sphere join` printed on that
+/// device. The device's key is authorized (as by [auth_add]), and the sphere
+/// is synced so that the gateway can hand the authorization to the device.
+/// Unless `confirmed` is true, the user is shown the device's key and asked to
+/// confirm that it should be authorized.
+pub async fn auth_approve(
+    code: &str,
+    name: Option<String>,
+    expires: Option<u64>,
+    confirmed: bool,
+    workspace: &Workspace,
+) -> Result<()> {
     workspace.ensure_sphere_initialized()?;
+
     let sphere_did = workspace.sphere_identity().await?;
-    let mut db = workspace.db().await?;
+    let mut sphere_context = workspace.sphere_context().await?;
+    let client = sphere_context.sphere_context().await?.client().await?;
 
-    let latest_sphere_cid = db.require_version(&sphere_did).await?;
-    let sphere = Sphere::at(&latest_sphere_cid, &db);
+    let status = client.pairing_status(code).await?;
 
-    let authority = sphere.get_authority().await?;
-    let delegations = authority.get_delegations().await?;
-    let mut delegations_stream = delegations.stream().await?;
+    if status.sphere != sphere_did {
+        return Err(anyhow!(
+            "The pairing request {:?} is for a different sphere ({})",
+            code,
+            status.sphere
+        ));
+    }
 
-    while let Some((Link { cid, .. }, delegation)) = delegations_stream.try_next().await? {
-        let ucan = delegation.resolve_ucan(&db).await?;
-        let authorized_did = ucan.audience();
+    if status.authorization.is_some() {
+        return Err(anyhow!(
+            "The pairing request {:?} has already been approved",
+            code
+        ));
+    }
+
+    if !confirmed {
+        info!(
+            r#"The pairing request {code:?} asks to authorize this key to work on your sphere:
+
+  {}
 
-        if authorized_did == did {
+The key will be able to do anything that you can do, including authorizing other keys.
+Make sure that it matches the key shown on the other device. Approve it? [y/N]"#,
+            status.did
+        );
+
+        let mut answer = String::new();
+
+        std::io::stdin().read_line(&mut answer)?;
+
+        if !matches!(answer.trim().to_lowercase().as_str(), "y" | "yes") {
+            return Err(anyhow!("The pairing request was not approved"));
+        }
+    }
+
+    let authorization = authorize_key(&status.did, name, &[], None, expires, workspace).await?;
+
+    sphere_context.sync().await?;
+
+    client
+        .approve_pairing(code, &PairingApproval { authorization })
+        .await?;
+
+    info!(
+        r#"Successfully authorized {} to access your sphere.
+
+The other device should finish joining the sphere in a moment."#,
+        status.did
+    );
+
+    Ok(())
+}
+
+/// Authorize a key to work on the sphere and save the authorization, yielding
+/// its identity
+async fn authorize_key(
+    did: &str,
+    name: Option<String>,
+    can: &[SphereAction],
+    prefix: Option<String>,
+    expires: Option<u64>,
+    workspace: &Workspace,
+) -> Result<Cid> {
+    let actions = match (can.is_empty(), &prefix) {
+        (true, Some(_)) => vec![SphereAction::Fetch, SphereAction::Push],
+        (true, None) => vec![SphereAction::Authorize],
+        (false, _) => can.to_vec(),
+    };
+
+    if prefix.is_some() && actions.iter().any(|action| action > &SphereAction::Push) {
+        return Err(anyhow!(
+            "A key that is limited to a content prefix may only be authorized to fetch and push"
+        ));
+    }
+
+    let sphere_did = workspace.sphere_identity().await?;
+    let mut sphere_context = workspace.sphere_context().await?;
+
+    for authorization in sphere_context.get_authorizations().await? {
+        if authorization.did.as_str() == did {
             return Err(anyhow!(
                 r#"{} is already authorized to access the sphere
 Here is the identity of the authorization:
@@ -49,8 +217,8 @@ If you want to change it to something new, revoke the current one first:
 
 You will be able to add a new one after the old one is revoked"#,
                 did,
-                cid,
-                delegation.name
+                authorization.jwt,
+                authorization.name
             ));
         }
     }
@@ -72,125 +240,129 @@ You will be able to add a new one after the old one is revoked"#,
         }
     };
 
-    let my_key = workspace.key().await?;
-    let my_did = my_key.get_did().await?;
-    let latest_sphere_cid = db.require_version(&sphere_did).await?;
-    let authorization = workspace.authorization().await?;
-    let authorization_expiry: u64 = {
-        let ucan = authorization.resolve_ucan(&db).await?;
-        *ucan.expires_at()
-    };
-
-    let mut signable = UcanBuilder::default()
-        .issued_by(&my_key)
-        .for_audience(did)
-        .claiming_capability(&Capability {
-            with: With::Resource {
-                kind: Resource::Scoped(SphereReference {
-                    did: sphere_did.to_string(),
-                }),
-            },
-            can: SphereAction::Authorize,
+    let capabilities: Vec<Capability<SphereReference, SphereAction>> = actions
+        .into_iter()
+        .map(|action| {
+            // Fetching is never limited to a content prefix, since a key needs
+            // the whole sphere in order to sync
+            let reference = match (&action, &prefix) {
+                (SphereAction::Fetch, _) | (_, None) => SphereReference::new(sphere_did.as_str()),
+                (_, Some(prefix)) => SphereReference::content(sphere_did.as_str(), prefix),
+            };
+
+            Capability {
+                with: With::Resource {
+                    kind: Resource::Scoped(reference),
+                },
+                can: action,
+            }
         })
-        .with_expiration(authorization_expiry)
-        .with_nonce()
-        // TODO(ucan-wg/rs-ucan#32): Clean this up when we can use a CID as an authorization
-        // .witnessed_by(&authorization)
-        .build()?;
-
-    signable
-        .proofs
-        .push(Cid::try_from(&authorization)?.to_string());
+        .collect();
 
-    let jwt = signable.sign().await?.encode()?;
+    let expires_at = expires.map(|lifetime| ucan::time::now().saturating_add(lifetime));
 
-    let delegation = DelegationIpld::register(&name, &jwt, &db).await?;
-
-    let sphere = Sphere::at(&latest_sphere_cid, &db);
+    let authorization = sphere_context
+        .authorize(&name, &Did(did.into()), &capabilities, expires_at)
+        .await?;
 
-    let mut mutation = SphereMutation::new(&my_did);
+    SphereCursor::latest(sphere_context).save(None).await?;
 
-    mutation
-        .delegations_mut()
-        .set(&Link::new(delegation.jwt), &delegation);
-
-    let mut revision = sphere.apply_mutation(&mutation).await?;
-    let version_cid = revision.sign(&my_key, Some(&authorization)).await?;
-
-    db.set_version(&sphere_did, &version_cid).await?;
-
-    info!(
-        r#"Successfully authorized {did} to access your sphere.
-
-IMPORTANT: You MUST sync to enable your gateway to recognize the authorization:
-
-  orb sync
-
-This is the authorization's identity:
-
-  {}
-  
-Use this identity when joining the sphere on the other client"#,
-        delegation.jwt
-    );
-
-    Ok(delegation.jwt)
+    Ok(authorization)
 }
 
 pub async fn auth_list(as_json: bool, workspace: &Workspace) -> Result<()> {
-    let sphere_did = workspace.sphere_identity().await?;
-    let db = workspace.db().await?;
-
-    let latest_sphere_cid = db
-        .get_version(&sphere_did)
-        .await?
-        .ok_or_else(|| anyhow!("Sphere version pointer is missing or corrupted"))?;
-
-    let sphere = Sphere::at(&latest_sphere_cid, &db);
-
-    let authorization = sphere.get_authority().await?;
-
-    let allowed_ucans = authorization.get_delegations().await?;
-
-    let mut authorizations: Vec<(String, String, Cid)> = Vec::new();
-    let mut delegation_stream = allowed_ucans.stream().await?;
-    let mut max_name_length: usize = 7;
-
-    while let Some(Ok((_, delegation))) = delegation_stream.next().await {
-        let jwt = db.require_token(&delegation.jwt).await?;
-        let ucan = Ucan::from_str(&jwt)?;
-        let name = delegation.name.clone();
-
-        max_name_length = max_name_length.max(name.len());
-        authorizations.push((
-            delegation.name.clone(),
-            ucan.audience().into(),
-            delegation.jwt,
-        ));
-    }
+    let sphere_context = workspace.sphere_context().await?;
+    let authorizations = sphere_context.get_authorizations().await?;
+    let now = ucan::time::now();
 
     if as_json {
         let authorizations: Vec<Value> = authorizations
             .into_iter()
-            .map(|(name, did, cid)| {
+            .map(|authorization| {
                 json!({
-                    "name": name,
-                    "did": did,
-                    "cid": cid.to_string()
+                    "name": authorization.name,
+                    "did": authorization.did.to_string(),
+                    "cid": authorization.jwt.to_string(),
+                    "expires_at": authorization.expires_at,
+                    "expired": authorization.is_expired_at(now)
                 })
             })
             .collect();
         info!("{}", serde_json::to_string_pretty(&json!(authorizations))?);
     } else {
-        info!("{:1$}  AUTHORIZED KEY", "NAME", max_name_length);
-        for (name, did, _) in authorizations {
-            info!("{name:max_name_length$}  {did}");
+        let max_name_length = authorizations
+            .iter()
+            .map(|authorization| authorization.name.len())
+            .fold(7, usize::max);
+        let max_did_length = authorizations
+            .iter()
+            .map(|authorization| authorization.did.len())
+            .fold(14, usize::max);
+
+        info!(
+            "{:2$}  {:3$}  EXPIRES",
+            "NAME", "AUTHORIZED KEY", max_name_length, max_did_length
+        );
+        for authorization in authorizations {
+            info!(
+                "{0:2$}  {1:3$}  {4}",
+                authorization.name,
+                authorization.did,
+                max_name_length,
+                max_did_length,
+                describe_expiry(authorization.expires_at, now)
+            );
         }
     }
 
     Ok(())
 }
 
+/// Renew the authorizations with the given names (or all of the
+/// authorizations that were issued to keys other than the local key, if no
+/// names are given), so that they expire after the given lifetime (in seconds).
+/// The replaced authorizations are not revoked, and remain valid until they
+/// expire.
+pub async fn auth_renew(
+    names: &[String],
+    expires: Option<u64>,
+    workspace: &Workspace,
+) -> Result<()> {
+    workspace.ensure_sphere_initialized()?;
+
+    let mut sphere_context = workspace.sphere_context().await?;
+    let expires_at = expires.map(|lifetime| ucan::time::now().saturating_add(lifetime));
+    let renewed = sphere_context
+        .renew_authorizations(names, expires_at)
+        .await?;
+
+    if renewed.is_empty() {
+        info!("There are no authorizations to renew");
+        return Ok(());
+    }
+
+    SphereCursor::latest(sphere_context).save(None).await?;
+
+    let now = ucan::time::now();
+
+    for authorization in renewed {
+        info!(
+            "Renewed {:?}; it now expires {}",
+            authorization.name,
+            describe_expiry(authorization.expires_at, now)
+        );
+    }
+
+    info!(
+        r#"
+IMPORTANT: You MUST sync to enable your gateway and the authorized keys to recognize the renewals:
+
+  orb sync"#
+    );
+
+    Ok(())
+}
+
 pub async fn auth_revoke(name: &str, workspace: &Workspace) -> Result<()> {
     workspace.ensure_sphere_initialized()?;
     let sphere_did = workspace.sphere_identity().await?;
@@ -238,3 +410,124 @@ pub async fn auth_revoke(name: &str, workspace: &Workspace) -> Result<()> {
 
     Err(anyhow!("There is no authorization named {:?}", name))
 }
+
+/// Transfer ownership of the sphere to a different local key. The sphere's
+/// mnemonic is required in order to sign a new owner authorization, and the
+/// authorization held by the current key is revoked in the process. This is
+/// the recovery path if the current key is lost or otherwise compromised.
+pub async fn auth_rotate(
+    key_name: &str,
+    mnemonic: Option<String>,
+    workspace: &Workspace,
+) -> Result<()> {
+    workspace.ensure_sphere_initialized()?;
+
+    let next_owner_key = workspace.key_storage().require_key(key_name).await?;
+    let next_owner_did = next_owner_key.get_did().await?;
+
+    let mnemonic = match mnemonic {
+        Some(mnemonic) => mnemonic,
+        None => {
+            info!(
+                r#"Rotating ownership of the sphere to the key {key_name:?}
+Type or paste the sequence of words that you were given when the sphere was created, and press enter:"#
+            );
+
+            let mut mnemonic = String::new();
+
+            std::io::stdin().read_line(&mut mnemonic)?;
+
+            mnemonic
+        }
+    };
+
+    let mut sphere_context = workspace.sphere_context().await?;
+
+    let (previous_key, previous_authorization) = {
+        let mut sphere_context = sphere_context.lock().await;
+        let sphere_did = sphere_context.identity().clone();
+        let current_key = sphere_context.author().key.clone();
+        let current_authorization = sphere_context.author().require_authorization()?.clone();
+
+        let (sphere, next_authorization) = {
+            let mut did_parser = DidParser::new(SUPPORTED_KEYS);
+
+            sphere_context
+                .sphere()
+                .await?
+                .change_owner(
+                    mnemonic.trim(),
+                    &next_owner_did,
+                    &current_authorization,
+                    &mut did_parser,
+                )
+                .await?
+        };
+
+        let db = sphere_context.db_mut();
+
+        db.set_version(&sphere_did, sphere.cid()).await?;
+        db.set_key(USER_KEY_NAME, key_name.to_string()).await?;
+        db.set_key(AUTHORIZATION, Cid::try_from(&next_authorization)?)
+            .await?;
+
+        sphere_context
+            .configure_author(Author {
+                key: next_owner_key,
+                authorization: Some(next_authorization),
+            })
+            .await?;
+
+        (current_key, current_authorization)
+    };
+
+    // Private content is only sealed for the keys that were authorized when
+    // it was written, so it must be re-sealed for the new owner
+    sphere_context.reseal_private(&previous_key).await?;
+
+    if sphere_context.has_unsaved_changes().await? {
+        sphere_context.save(None).await?;
+    }
+
+    let revoked_authorization = Cid::try_from(&previous_authorization)?;
+
+    info!(
+        r#"Ownership of the sphere has been transferred to the key {key_name:?}
+The previous authorization has been revoked:
+
+  {revoked_authorization}"#
+    );
+
+    match workspace.gateway_url().await {
+        Ok(_) => {
+            info!("Syncing the revocation with the gateway...");
+            sphere_context.sync().await?;
+            info!("Done!");
+        }
+        Err(_) => info!(
+            r#"IMPORTANT: You MUST sync to enable your gateway to recognize the revocation:
+
+  orb sync"#
+        ),
+    };
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_duration;
+
+    #[test]
+    fn it_parses_durations_in_various_units() {
+        assert_eq!(parse_duration("90s").unwrap(), 90);
+        assert_eq!(parse_duration("12h").unwrap(), 12 * 60 * 60);
+        assert_eq!(parse_duration("30").unwrap(), 30 * 60 * 60 * 24);
+        assert!(parse_duration("3 fortnights").is_err());
+    }
+
+    #[test]
+    fn it_rejects_durations_that_are_too_long() {
+        assert!(parse_duration(&format!("{}y", u64::MAX / 1000)).is_err());
+    }
+}
@@ -0,0 +1,283 @@
+use std::{
+    collections::BTreeSet,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, Result};
+use cid::Cid;
+use noosphere_core::data::{ContentType, Header};
+use noosphere_sphere::{BodyChunkDecoder, SphereContentRead, SphereCursor, SphereWalker};
+use noosphere_storage::{BlockStore, MemoryStore};
+use pathdiff::diff_paths;
+use similar::TextDiff;
+use tempfile::TempDir;
+use tokio::{fs, io::AsyncReadExt, process::Command};
+use tokio_stream::StreamExt;
+
+use crate::native::{
+    commands::config::Config,
+    workspace::{FileReference, Workspace},
+};
+
+/// The two versions of a file that are being compared, along with a file
+/// extension that may be used to hint the file type to a difftool
+struct FileVersions {
+    base: Option<Vec<u8>>,
+    working: Option<Vec<u8>>,
+    extension: Option<String>,
+}
+
+/// Show the differences between files in the workspace and the versions of
+/// those files that were saved in the sphere at a base revision; if no base
+/// revision is specified, the latest saved revision is used. If a difftool is
+/// configured, it will be invoked with both versions of each changed file.
+/// Otherwise, a unified diff is printed.
+pub async fn diff(paths: Vec<PathBuf>, base: Option<Cid>, workspace: &Workspace) -> Result<()> {
+    workspace.ensure_sphere_initialized()?;
+
+    let mut memory_store = MemoryStore::default();
+
+    let (content, changes) = match workspace
+        .get_file_content_changes(&mut memory_store)
+        .await?
+    {
+        Some(content_and_changes) => content_and_changes,
+        None => {
+            info!("No changes to show");
+            return Ok(());
+        }
+    };
+
+    let sphere_context = workspace.sphere_context().await?;
+    let cursor = match &base {
+        Some(version) => SphereCursor::mounted_at(sphere_context, version),
+        None => SphereCursor::latest(sphere_context),
+    };
+
+    let slugs: BTreeSet<String> = if !paths.is_empty() {
+        paths
+            .iter()
+            .map(|path| path_to_slug(path, workspace))
+            .collect::<Result<_>>()?
+    } else if base.is_some() {
+        // The change manifest is relative to the latest revision, so when
+        // diffing against an arbitrary base revision we have to consider
+        // every file in both the base revision and the working copy
+        let mut slugs = SphereWalker::from(&cursor).list_slugs().await?;
+        slugs.extend(content.matched.keys().cloned());
+        slugs
+    } else {
+        changes
+            .new
+            .keys()
+            .chain(changes.updated.keys())
+            .chain(changes.removed.keys())
+            .cloned()
+            .collect()
+    };
+
+    let difftool = Config::from(workspace).read().await?.difftool.clone();
+    let mut has_differences = false;
+
+    for slug in slugs {
+        let (base_bytes, base_extension) = match cursor.read(&slug).await? {
+            Some(mut file) => {
+                let mut bytes = Vec::new();
+                file.contents.read_to_end(&mut bytes).await?;
+                (
+                    Some(bytes),
+                    file.memo
+                        .get_first_header(&Header::FileExtension.to_string()),
+                )
+            }
+            None => (None, None),
+        };
+
+        let (working_bytes, working_extension) = match content.matched.get(&slug) {
+            Some(FileReference {
+                cid,
+                extension,
+                content_type,
+            }) => {
+                let bytes = match content_type {
+                    ContentType::DagCbor => memory_store.require_block(cid).await?,
+                    _ => {
+                        let mut bytes = Vec::new();
+                        let mut stream = BodyChunkDecoder(cid, &memory_store).stream();
+
+                        while let Some(chunk) = stream.try_next().await? {
+                            bytes.extend_from_slice(&chunk);
+                        }
+
+                        bytes
+                    }
+                };
+
+                (Some(bytes), extension.clone())
+            }
+            None => (None, None),
+        };
+
+        if base_bytes == working_bytes {
+            continue;
+        }
+
+        has_differences = true;
+
+        let versions = FileVersions {
+            base: base_bytes,
+            working: working_bytes,
+            extension: working_extension.or(base_extension),
+        };
+
+        match &difftool {
+            Some(tool) => run_difftool(tool, &slug, &versions).await?,
+            None => info!("{}", unified_diff(&slug, &versions)),
+        }
+    }
+
+    if !has_differences {
+        info!("No changes to show");
+    }
+
+    Ok(())
+}
+
+/// Converts a path to a file in the workspace to the slug that the file's
+/// content is saved under in the sphere
+fn path_to_slug(path: &Path, workspace: &Workspace) -> Result<String> {
+    let path = std::env::current_dir()?.join(path);
+    let relative_path = diff_paths(&path, workspace.root_directory())
+        .ok_or_else(|| anyhow!("Could not determine relative path to {:?}", path))?;
+
+    let name = relative_path
+        .file_stem()
+        .ok_or_else(|| anyhow!("Could not determine a slug for {:?}", path))?
+        .to_string_lossy();
+
+    Ok(match relative_path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => {
+            format!("{}/{name}", parent.to_string_lossy())
+        }
+        _ => name.to_string(),
+    })
+}
+
+/// Renders a unified diff of the two versions of the file at the given slug;
+/// if either version is not valid UTF-8, a short notice is rendered instead
+fn unified_diff(slug: &str, versions: &FileVersions) -> String {
+    let base_label = match versions.base {
+        Some(_) => format!("a/{slug}"),
+        None => "/dev/null".into(),
+    };
+    let working_label = match versions.working {
+        Some(_) => format!("b/{slug}"),
+        None => "/dev/null".into(),
+    };
+
+    let base = std::str::from_utf8(versions.base.as_deref().unwrap_or_default());
+    let working = std::str::from_utf8(versions.working.as_deref().unwrap_or_default());
+
+    match (base, working) {
+        (Ok(base), Ok(working)) => TextDiff::from_lines(base, working)
+            .unified_diff()
+            .header(&base_label, &working_label)
+            .to_string(),
+        _ => format!("Binary files {base_label} and {working_label} differ\n"),
+    }
+}
+
+/// Writes both versions of a file to a temporary directory and hands their
+/// paths to the configured difftool
+async fn run_difftool(tool: &str, slug: &str, versions: &FileVersions) -> Result<()> {
+    let mut arguments = tool.split_whitespace();
+    let program = arguments
+        .next()
+        .ok_or_else(|| anyhow!("The configured difftool is empty"))?;
+
+    let temporary_directory = TempDir::new()?;
+    let file_name = match &versions.extension {
+        Some(extension) => format!("{slug}.{extension}"),
+        None => slug.to_string(),
+    };
+
+    let base_path = temporary_directory.path().join("base").join(&file_name);
+    let working_path = temporary_directory.path().join("working").join(&file_name);
+
+    for (path, bytes) in [
+        (&base_path, &versions.base),
+        (&working_path, &versions.working),
+    ] {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+        fs::write(path, bytes.as_deref().unwrap_or_default()).await?;
+    }
+
+    let status = Command::new(program)
+        .args(arguments)
+        .arg(&base_path)
+        .arg(&working_path)
+        .status()
+        .await?;
+
+    // Many difftools exit with a non-zero status when files differ, so we only
+    // treat abnormal termination as an error
+    if status.code().is_none() {
+        return Err(anyhow!("Difftool {:?} was terminated unexpectedly", tool));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{unified_diff, FileVersions};
+
+    #[test]
+    fn it_renders_a_unified_diff_of_two_text_files() {
+        let versions = FileVersions {
+            base: Some(b"foo\nbar\n".to_vec()),
+            working: Some(b"foo\nbaz\n".to_vec()),
+            extension: None,
+        };
+
+        let diff = unified_diff("cats", &versions);
+
+        assert!(diff.contains("--- a/cats"));
+        assert!(diff.contains("+++ b/cats"));
+        assert!(diff.contains("-bar"));
+        assert!(diff.contains("+baz"));
+    }
+
+    #[test]
+    fn it_renders_new_and_removed_files_against_dev_null() {
+        let new_file = FileVersions {
+            base: None,
+            working: Some(b"meow\n".to_vec()),
+            extension: None,
+        };
+        let removed_file = FileVersions {
+            base: Some(b"woof\n".to_vec()),
+            working: None,
+            extension: None,
+        };
+
+        assert!(unified_diff("cats", &new_file).contains("--- /dev/null"));
+        assert!(unified_diff("dogs", &removed_file).contains("+++ /dev/null"));
+    }
+
+    #[test]
+    fn it_does_not_render_a_diff_of_binary_files() {
+        let versions = FileVersions {
+            base: Some(vec![0xff, 0xfe]),
+            working: Some(vec![0xfe, 0xff]),
+            extension: None,
+        };
+
+        assert_eq!(
+            unified_diff("blob", &versions),
+            "Binary files a/blob and b/blob differ\n"
+        );
+    }
+}
@@ -0,0 +1,201 @@
+use std::collections::BTreeSet;
+
+use anyhow::{anyhow, Result};
+use cid::Cid;
+use noosphere_ipfs::{IpfsStore, KuboClient};
+use noosphere_storage::{
+    verify_cid, BlockStore, IntegrityReport, MemoryStore, NativeStorage, SphereDb,
+};
+use tokio_stream::StreamExt;
+use url::Url;
+
+use crate::native::workspace::Workspace;
+
+/// Verify that every block that is referenced by the sphere (or by any other
+/// sphere that is tracked locally) is present in local storage, that it
+/// matches its CID and that its recorded links are correct. Orphaned blocks,
+/// and blocks whose CID uses a hash function that is not supported, are
+/// reported but left alone (see `orb gc`). With `repair`, corrupt blocks
+/// are discarded, link records are rebuilt and missing blocks are fetched
+/// again from the configured gateway and, if `ipfs_api` is specified, from
+/// IPFS. An error is returned if any missing, corrupt or mismatched blocks
+/// remain afterwards.
+pub async fn fsck(repair: bool, ipfs_api: Option<Url>, workspace: &Workspace) -> Result<()> {
+    workspace.ensure_sphere_initialized()?;
+
+    let mut db = workspace.db().await?;
+    let mut report = db.verify_integrity().await?;
+
+    print_report(&report);
+
+    if repair && !report.is_ok() {
+        db.repair_integrity(&report).await?;
+
+        let mut missing_blocks = db.verify_integrity().await?.missing_blocks;
+
+        if !missing_blocks.is_empty() && workspace.gateway_url().await.is_ok() {
+            info!(
+                "Fetching {} missing blocks from the gateway...",
+                missing_blocks.len()
+            );
+
+            let recovered_count = fetch_from_gateway(&mut db, &missing_blocks, workspace).await?;
+
+            info!("Recovered {recovered_count} blocks from the gateway");
+            missing_blocks = db.verify_integrity().await?.missing_blocks;
+        }
+
+        if let Some(ipfs_api) = ipfs_api {
+            let ipfs_store =
+                IpfsStore::new(MemoryStore::default(), Some(KuboClient::new(&ipfs_api)?));
+
+            // Fetching a block may reveal more missing blocks that it refers
+            // to, so keep going for as long as progress is being made
+            while !missing_blocks.is_empty() {
+                info!(
+                    "Fetching {} missing blocks from IPFS...",
+                    missing_blocks.len()
+                );
+
+                let recovered_count =
+                    fetch_from_ipfs(&mut db, &missing_blocks, &ipfs_store).await?;
+
+                info!("Recovered {recovered_count} blocks from IPFS");
+
+                if recovered_count == 0 {
+                    break;
+                }
+
+                missing_blocks = db.verify_integrity().await?.missing_blocks;
+            }
+        }
+
+        report = db.verify_integrity().await?;
+
+        info!("\nAfter repairing:");
+        print_report(&report);
+    }
+
+    if !report.orphaned_blocks.is_empty() {
+        info!("\nOrphaned blocks can be removed with `orb gc`");
+    }
+
+    if !report.is_ok() {
+        return Err(anyhow!(
+            "Some blocks could not be verified{}",
+            if repair {
+                "; they may still be available from another replica of the sphere"
+            } else {
+                "; run again with --repair to try to fix them"
+            }
+        ));
+    }
+
+    Ok(())
+}
+
+fn print_report(report: &IntegrityReport) {
+    info!("Verified {} blocks", report.blocks_verified);
+
+    for (label, cids) in [
+        ("Missing blocks", &report.missing_blocks),
+        ("Corrupt blocks", &report.corrupt_blocks),
+        (
+            "Blocks with an unsupported hash function",
+            &report.unverifiable_blocks,
+        ),
+        ("Blocks with mismatched links", &report.mismatched_links),
+        ("Orphaned blocks", &report.orphaned_blocks),
+    ] {
+        if cids.is_empty() {
+            continue;
+        }
+
+        info!("\n{label} ({}):", cids.len());
+
+        for cid in cids {
+            info!("  {cid}");
+        }
+    }
+}
+
+/// Replicate each locally recorded sphere version from the gateway, keeping
+/// any blocks that are missing locally and that match their CID; returns the
+/// number of blocks that were recovered
+async fn fetch_from_gateway(
+    db: &mut SphereDb<NativeStorage>,
+    missing_blocks: &BTreeSet<Cid>,
+    workspace: &Workspace,
+) -> Result<usize> {
+    let client = {
+        let sphere_context = workspace.sphere_context().await?;
+        let sphere_context = sphere_context.lock().await;
+
+        sphere_context.client().await?
+    };
+
+    let mut recovered = MemoryStore::default();
+    let mut recovered_count = 0;
+
+    for (identity, version) in db.get_all_versions().await? {
+        let stream = match client.replicate(&version).await {
+            Ok(stream) => stream,
+            Err(error) => {
+                warn!("Could not replicate {identity} at version {version}: {error}");
+                continue;
+            }
+        };
+
+        tokio::pin!(stream);
+
+        while let Some(result) = stream.next().await {
+            let (cid, block) = match result {
+                Ok(block) => block,
+                Err(error) => {
+                    warn!("Stopped replicating {identity} at version {version}: {error}");
+                    break;
+                }
+            };
+
+            // Blocks below a missing block may be missing as well, even
+            // though they are not reported yet
+            let is_wanted = missing_blocks.contains(&cid) || db.get_block(&cid).await?.is_none();
+
+            if is_wanted && matches!(verify_cid(&cid, &block), Ok(true)) {
+                recovered.put_block(&cid, &block).await?;
+                recovered_count += 1;
+            }
+        }
+    }
+
+    db.persist(&recovered).await?;
+
+    Ok(recovered_count)
+}
+
+/// Fetch the missing blocks from IPFS, keeping any that match their CID;
+/// returns the number of blocks that were recovered
+async fn fetch_from_ipfs(
+    db: &mut SphereDb<NativeStorage>,
+    missing_blocks: &BTreeSet<Cid>,
+    ipfs_store: &IpfsStore<MemoryStore, KuboClient>,
+) -> Result<usize> {
+    let mut recovered = MemoryStore::default();
+    let mut recovered_count = 0;
+
+    for cid in missing_blocks {
+        match ipfs_store.get_block(cid).await {
+            Ok(Some(block)) if matches!(verify_cid(cid, &block), Ok(true)) => {
+                recovered.put_block(cid, &block).await?;
+                recovered_count += 1;
+            }
+            Ok(Some(_)) => warn!("Block fetched from IPFS does not match its CID {cid}"),
+            Ok(None) => (),
+            Err(error) => warn!("Could not fetch {cid} from IPFS: {error}"),
+        }
+    }
+
+    db.persist(&recovered).await?;
+
+    Ok(recovered_count)
+}
@@ -0,0 +1,32 @@
+use crate::native::workspace::Workspace;
+use anyhow::Result;
+use noosphere_sphere::{collect_garbage, HistoryRetention};
+
+/// Remove blocks from local storage that are no longer referenced by the
+/// sphere (or by any other sphere that is tracked locally, such as the
+/// gateway's counterpart sphere). If `keep_revisions` is specified, only that
+/// many of the most recent revisions of each sphere are kept, and any blocks
+/// that are only referenced by older revisions are removed as well.
+pub async fn gc(keep_revisions: Option<usize>, workspace: &Workspace) -> Result<()> {
+    workspace.ensure_sphere_initialized()?;
+
+    let retention = match keep_revisions {
+        Some(count) => HistoryRetention::Revisions(count),
+        None => HistoryRetention::All,
+    };
+
+    let report = {
+        let sphere_context = workspace.sphere_context().await?;
+        let mut sphere_context = sphere_context.lock().await;
+
+        collect_garbage(sphere_context.db_mut(), retention).await?
+    };
+
+    info!(
+        r#"Removed {} unreferenced blocks and {} link records
+Reclaimed {} bytes ({} blocks are still in use)"#,
+        report.blocks_removed, report.links_removed, report.bytes_reclaimed, report.blocks_kept
+    );
+
+    Ok(())
+}
@@ -1,7 +1,12 @@
 pub mod auth;
 pub mod config;
+pub mod diff;
+pub mod fsck;
+pub mod gc;
 pub mod key;
+pub mod publish;
 pub mod save;
+pub mod search;
 pub mod serve;
 pub mod sphere;
 pub mod status;
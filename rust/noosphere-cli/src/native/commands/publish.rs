@@ -0,0 +1,84 @@
+use anyhow::{anyhow, Result};
+use cid::Cid;
+use noosphere_api::data::PublishBody;
+use noosphere_core::{
+    authority::{generate_capability, SphereAction},
+    data::Jwt,
+    view::Sphere,
+};
+use serde_json::json;
+use ucan::builder::UcanBuilder;
+
+use crate::native::workspace::Workspace;
+
+/// Ask the configured gateway to publish a version of the local sphere to the
+/// Noosphere Name System. If no version is specified, the latest saved version
+/// is published. The version must already be known to the gateway, so an
+/// older version can be published (e.g., to roll back) without syncing. The
+/// version that the gateway will publish is returned.
+pub async fn publish(version: Option<Cid>, workspace: &Workspace) -> Result<Cid> {
+    workspace.ensure_sphere_initialized()?;
+
+    let sphere_context = workspace.sphere_context().await?;
+    let sphere_context = sphere_context.lock().await;
+
+    let sphere_identity = sphere_context.identity().clone();
+    let version = match version {
+        Some(version) => version,
+        None => sphere_context.head().await?,
+    };
+
+    if Sphere::at(&version, sphere_context.db())
+        .get_identity()
+        .await?
+        != sphere_identity
+    {
+        return Err(anyhow!(
+            "Version {version} is not a revision of sphere {sphere_identity}"
+        ));
+    }
+
+    let authorization = sphere_context
+        .author()
+        .require_authorization()?
+        .resolve_ucan(sphere_context.db())
+        .await?;
+
+    let name_record = Jwt(UcanBuilder::default()
+        .issued_by(&sphere_context.author().key)
+        .for_audience(&sphere_identity)
+        .witnessed_by(&authorization)
+        .claiming_capability(&generate_capability(
+            &sphere_identity,
+            SphereAction::Publish,
+        ))
+        .with_lifetime(120)
+        .with_fact(json!({
+          "link": version.to_string()
+        }))
+        .build()?
+        .sign()
+        .await?
+        .encode()?);
+
+    let client = sphere_context.client().await?;
+
+    info!(
+        "Asking gateway {} to publish version {version}...",
+        client.session.gateway_identity
+    );
+
+    let response = client
+        .publish(&PublishBody {
+            sphere: sphere_identity,
+            name_record,
+        })
+        .await?;
+
+    info!(
+        "The gateway will publish version {} to the name system",
+        response.version
+    );
+
+    Ok(response.version)
+}
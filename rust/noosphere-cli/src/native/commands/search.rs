@@ -0,0 +1,26 @@
+use crate::native::workspace::Workspace;
+use anyhow::Result;
+use noosphere_sphere::SphereSearch;
+
+/// Search the saved content of the sphere and list the slugs that best match
+/// the query, most relevant first
+pub async fn search(query: &str, limit: usize, workspace: &Workspace) -> Result<()> {
+    workspace.ensure_sphere_initialized()?;
+
+    let results = workspace
+        .sphere_context()
+        .await?
+        .search(query, limit)
+        .await?;
+
+    if results.is_empty() {
+        info!("No matches for \"{}\"", query);
+        return Ok(());
+    }
+
+    for result in results {
+        info!("{:>6.2}  {}", result.score, result.slug);
+    }
+
+    Ok(())
+}
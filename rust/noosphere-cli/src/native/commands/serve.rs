@@ -4,9 +4,12 @@ use std::net::{IpAddr, TcpListener};
 
 use url::Url;
 
-use crate::native::workspace::Workspace;
+use crate::native::workspace::{CliSphereContext, Workspace};
 
-use noosphere_gateway::{start_gateway, GatewayScope};
+use noosphere_gateway::{start_multi_tenant_gateway, GatewayScope};
+
+use std::sync::Arc;
+use tokio::sync::Mutex;
 
 pub async fn serve(
     interface: IpAddr,
@@ -14,12 +17,33 @@ pub async fn serve(
     ipfs_api: Url,
     name_resolver_api: Url,
     cors_origin: Option<Url>,
+    tenants: &[Workspace],
     workspace: &Workspace,
 ) -> Result<()> {
-    workspace.ensure_sphere_initialized()?;
+    let mut gateway_tenants = vec![gateway_tenant(workspace).await?];
+
+    for tenant in tenants {
+        gateway_tenants.push(gateway_tenant(tenant).await?);
+    }
 
     let listener = TcpListener::bind((interface, port))?;
 
+    start_multi_tenant_gateway(
+        listener,
+        gateway_tenants,
+        ipfs_api,
+        name_resolver_api,
+        cors_origin,
+    )
+    .await
+}
+
+/// Pair the gateway sphere in a workspace with its configured counterpart
+async fn gateway_tenant(
+    workspace: &Workspace,
+) -> Result<(GatewayScope, Arc<Mutex<CliSphereContext>>)> {
+    workspace.ensure_sphere_initialized()?;
+
     let counterpart = workspace.counterpart_identity().await?;
 
     let identity = workspace.sphere_identity().await?;
@@ -31,13 +55,5 @@ pub async fn serve(
 
     let sphere_context = workspace.sphere_context().await?;
 
-    start_gateway(
-        listener,
-        gateway_scope,
-        sphere_context,
-        ipfs_api,
-        name_resolver_api,
-        cors_origin,
-    )
-    .await
+    Ok((gateway_scope, sphere_context))
 }
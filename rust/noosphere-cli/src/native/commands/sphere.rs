@@ -1,13 +1,20 @@
-use std::str::FromStr;
+use std::{path::Path, str::FromStr, time::Duration};
 
 use crate::native::workspace::Workspace;
 use anyhow::{anyhow, Result};
 use cid::Cid;
 use noosphere::{key::KeyStorage, sphere::SphereContextBuilder};
+use noosphere_api::{client::PairingClient, data::PairingRequest};
 use noosphere_core::{authority::Authorization, data::Did};
-use noosphere_sphere::SphereContext;
+use noosphere_sphere::{ArchiveHistory, SphereContext};
+use noosphere_storage::MemoryStore;
+use tokio::fs::File;
 
 use ucan::crypto::KeyMaterial;
+use url::Url;
+
+/// How often to check whether a pairing request has been approved
+const PAIRING_POLL_INTERVAL: Duration = Duration::from_secs(2);
 
 pub async fn sphere_create(owner_key: &str, workspace: &Workspace) -> Result<()> {
     workspace.ensure_sphere_uninitialized()?;
@@ -45,22 +52,27 @@ You will be asked to enter them if you ever need to transfer ownership of the sp
     Ok(())
 }
 
+/// Join an existing sphere with the given local key. If the identity of an
+/// authorization for the key is not given, it is obtained either by pairing
+/// with an authorized device through the gateway at `gateway_url` (if one is
+/// given), or by prompting for it.
 pub async fn sphere_join(
     local_key: &str,
     authorization: Option<String>,
     sphere_identity: &Did,
+    gateway_url: Option<&Url>,
     workspace: &Workspace,
 ) -> Result<()> {
     workspace.ensure_sphere_uninitialized()?;
     info!("Joining sphere {sphere_identity}...");
 
-    let did = {
-        let local_key = workspace.key_storage().require_key(local_key).await?;
-        local_key.get_did().await?
-    };
+    let key = workspace.key_storage().require_key(local_key).await?;
+    let did = key.get_did().await?;
 
-    let cid_string = match authorization {
-        None => {
+    let cid = match (authorization, gateway_url) {
+        (Some(cid_string), _) => parse_authorization(&cid_string)?,
+        (None, Some(gateway_url)) => request_pairing(&key, sphere_identity, gateway_url).await?,
+        (None, None) => {
             info!(
                 r#"In order to join the sphere, another client must authorize your local key
 This is the local key's ID; share it with an authorized client:
@@ -79,27 +91,39 @@ Type or paste the code here and press enter:"#
 
             std::io::stdin().read_line(&mut cid_string)?;
 
-            cid_string
+            parse_authorization(&cid_string)?
         }
-        Some(cid_string) => cid_string,
     };
 
-    let cid = Cid::from_str(cid_string.trim())
-        .map_err(|_| anyhow!("Could not parse the authorization identity as a CID"))?;
-
-    SphereContextBuilder::default()
+    let mut sphere_context: SphereContext<_, _> = SphereContextBuilder::default()
         .join_sphere(sphere_identity)
         .at_storage_path(workspace.root_directory())
         .reading_keys_from(workspace.key_storage().clone())
         .using_key(local_key)
         .authorized_by(Some(&Authorization::Cid(cid)))
         .build()
-        .await?;
+        .await?
+        .into();
 
     // TODO(#103): Recovery path if the auth needs to change for some reason
 
-    info!(
-        r#"The authorization has been saved.
+    match gateway_url {
+        Some(gateway_url) => {
+            sphere_context
+                .configure_gateway_url(Some(gateway_url))
+                .await?;
+
+            info!(
+                r#"The authorization has been saved.
+You should now be able to sync:
+
+  orb sync
+  
+Happy pondering!"#
+            );
+        }
+        None => info!(
+            r#"The authorization has been saved.
 Make sure that you have configured the gateway's URL:
 
   orb config set gateway-url <URL>
@@ -109,7 +133,113 @@ And then you should be able to sync:
   orb sync
   
 Happy pondering!"#
+        ),
+    };
+
+    Ok(())
+}
+
+fn parse_authorization(cid_string: &str) -> Result<Cid> {
+    Cid::from_str(cid_string.trim())
+        .map_err(|_| anyhow!("Could not parse the authorization identity as a CID"))
+}
+
+/// Ask the gateway to pair the local key with the sphere, and wait until an
+/// authorized device approves the request, yielding the identity of the
+/// authorization that it granted
+async fn request_pairing<K: KeyMaterial>(
+    key: &K,
+    sphere_identity: &Did,
+    gateway_url: &Url,
+) -> Result<Cid> {
+    let client = PairingClient::new(gateway_url);
+    let code = witty_phrase_generator::WPGen::new()
+        .with_words(3)
+        .ok_or_else(|| anyhow!("Unable to generate a pairing code"))?
+        .join("-");
+
+    let request = PairingRequest::sign(&code, sphere_identity, key).await?;
+
+    client.request(&request).await?;
+
+    info!(
+        r#"In order to join the sphere, an authorized client must approve this device
+If the authorized client is using the "orb" CLI, use this command from the existing workspace:
+
+  orb auth approve {code}
+
+When asked, confirm that this is the key to authorize:
+
+  {}
+
+The request expires in a few minutes. Waiting for approval..."#,
+        request.did
     );
 
+    loop {
+        tokio::time::sleep(PAIRING_POLL_INTERVAL).await;
+
+        if let Some(authorization) = client.status(&request).await?.authorization {
+            return Ok(authorization);
+        }
+    }
+}
+
+/// Export the sphere in the workspace as a CAR archive, written to the given
+/// path
+pub async fn sphere_export(
+    history: ArchiveHistory,
+    output: &Path,
+    workspace: &Workspace,
+) -> Result<()> {
+    workspace.ensure_sphere_initialized()?;
+
+    let sphere_context = workspace.sphere_context().await?;
+    let version = sphere_context
+        .lock()
+        .await
+        .export_archive(history, File::create(output).await?)
+        .await?;
+
+    info!("Exported version {version} of the sphere to {output:?}");
+
+    Ok(())
+}
+
+/// Import a CAR archive of the sphere in the workspace (such as one made with
+/// `orb sphere export`), verifying its history and fast-forwarding the local
+/// version of the sphere to the archived version
+pub async fn sphere_import(path: &Path, workspace: &Workspace) -> Result<()> {
+    workspace.ensure_sphere_initialized()?;
+
+    let mut memory_store = MemoryStore::default();
+
+    match workspace
+        .get_file_content_changes(&mut memory_store)
+        .await?
+    {
+        Some((_, content_changes)) if !content_changes.is_empty() => {
+            return Err(anyhow!(
+                "You have unsaved local changes; save or revert them before importing!"
+            ));
+        }
+        _ => (),
+    };
+
+    let version = {
+        let sphere_context = workspace.sphere_context().await?;
+        let mut sphere_context = sphere_context.lock().await;
+
+        sphere_context
+            .import_archive(File::open(path).await?)
+            .await?
+    };
+
+    info!("Imported version {version} of the sphere from {path:?}, rendering updated workspace...");
+
+    workspace.render().await?;
+
+    info!("Done!");
+
     Ok(())
 }
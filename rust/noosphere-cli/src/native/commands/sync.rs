@@ -1,9 +1,9 @@
-use crate::native::workspace::Workspace;
+use crate::native::{workspace::Workspace, OnConflict};
 use anyhow::{anyhow, Result};
-use noosphere_sphere::SphereSync;
+use noosphere_sphere::{ConflictResolution, MergeSyncStrategy, SphereSync, SyncReport};
 use noosphere_storage::MemoryStore;
 
-pub async fn sync(workspace: &Workspace) -> Result<()> {
+pub async fn sync(on_conflict: Option<OnConflict>, workspace: &Workspace) -> Result<()> {
     workspace.ensure_sphere_initialized()?;
 
     let mut memory_store = MemoryStore::default();
@@ -20,9 +20,80 @@ pub async fn sync(workspace: &Workspace) -> Result<()> {
         _ => (),
     };
 
-    {
+    let report = {
         let mut context = workspace.sphere_context().await?;
-        context.sync().await?;
+
+        match on_conflict {
+            None => {
+                context.sync().await?;
+                SyncReport::default()
+            }
+            Some(OnConflict::Report) => context.sync_with(&MergeSyncStrategy::default()).await?,
+            Some(OnConflict::KeepLocal) => {
+                context
+                    .sync_with(&MergeSyncStrategy::resolving_with(
+                        ConflictResolution::KeepLocal,
+                    ))
+                    .await?
+            }
+            Some(OnConflict::KeepCounterpart) => {
+                context
+                    .sync_with(&MergeSyncStrategy::resolving_with(
+                        ConflictResolution::KeepCounterpart,
+                    ))
+                    .await?
+            }
+            Some(OnConflict::KeepBoth) => {
+                context
+                    .sync_with(&MergeSyncStrategy::resolving_with(
+                        ConflictResolution::KeepBoth,
+                    ))
+                    .await?
+            }
+        }
+    };
+
+    for (conflict, resolution) in report.resolved.iter() {
+        match resolution {
+            ConflictResolution::KeepLocal => {
+                info!("Conflict in {}: kept the local version", conflict.slug)
+            }
+            ConflictResolution::KeepCounterpart => {
+                info!("Conflict in {}: kept the gateway's version", conflict.slug)
+            }
+            ConflictResolution::KeepBoth => match conflict.conflict_slug() {
+                Some(conflict_slug) => info!(
+                    "Conflict in {}: kept the gateway's version; the local version was moved to {}",
+                    conflict.slug, conflict_slug
+                ),
+                None => info!(
+                    "Conflict in {}: kept the gateway's version (it was removed locally)",
+                    conflict.slug
+                ),
+            },
+        }
+    }
+
+    if !report.is_complete() {
+        info!("Sync could not be completed because of conflicting changes:");
+
+        for conflict in report.unresolved.iter() {
+            let describe = |version: &Option<cid::Cid>| match version {
+                Some(cid) => format!("changed ({cid})"),
+                None => "removed".to_string(),
+            };
+
+            info!(
+                "  {}: locally {}, on the gateway {}",
+                conflict.slug,
+                describe(&conflict.local),
+                describe(&conflict.counterpart)
+            );
+        }
+
+        return Err(anyhow!(
+            "Run `orb sync --on-conflict <keep-local|keep-counterpart|keep-both>` to resolve the conflicts"
+        ));
     }
 
     info!("Sync complete, rendering updated workspace...");
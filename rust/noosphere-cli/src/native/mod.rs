@@ -3,7 +3,8 @@ pub mod workspace;
 
 use anyhow::Result;
 
-use noosphere_core::{data::Did, tracing::initialize_tracing};
+use noosphere_core::{authority::SphereAction, data::Did, tracing::initialize_tracing};
+use noosphere_sphere::ArchiveHistory;
 use std::ffi::OsString;
 
 use std::net::IpAddr;
@@ -13,20 +14,32 @@ use cid::Cid;
 
 use clap::Parser;
 use clap::Subcommand;
+use clap::ValueEnum;
 use url::Url;
 
 use commands::key::key_create;
 use commands::key::key_list;
 use commands::sphere::sphere_create;
+use commands::sphere::sphere_export;
+use commands::sphere::sphere_import;
 use commands::sphere::sphere_join;
 use workspace::Workspace;
 
 use self::commands::auth::auth_add;
+use self::commands::auth::auth_approve;
 use self::commands::auth::auth_list;
+use self::commands::auth::auth_renew;
 use self::commands::auth::auth_revoke;
+use self::commands::auth::auth_rotate;
+use self::commands::auth::parse_duration;
 use self::commands::config::config_get;
 use self::commands::config::config_set;
+use self::commands::diff::diff;
+use self::commands::fsck::fsck;
+use self::commands::gc::gc;
+use self::commands::publish::publish;
 use self::commands::save::save;
+use self::commands::search::search;
 use self::commands::serve::serve;
 use self::commands::status::status;
 use self::commands::sync::sync;
@@ -92,13 +105,20 @@ pub enum OrbCommand {
         /// The port that the gateway should listen on
         #[clap(short, long, default_value = "4433")]
         port: u16,
+
+        /// The path to another gateway sphere (configured with its own
+        /// counterpart) that should be served alongside this one; may be
+        /// given more than once
+        #[clap(long = "tenant")]
+        tenants: Vec<PathBuf>,
     },
 
     /// Show details about files in the sphere directory that have changed since
     /// the last time the sphere was saved
     Status,
 
-    /// If a difftool is configured, show a diff between files on disk and saved versions in the sphere
+    /// Show a diff between files on disk and saved versions in the sphere; if
+    /// a difftool is configured, it will be used to show the diff
     Diff {
         /// The specific file or files to show a diff of
         paths: Vec<PathBuf>,
@@ -114,10 +134,16 @@ pub enum OrbCommand {
     Save,
 
     /// Synchronizes the local sphere with the copy in a configured gateway;
-    /// note that this is a "conflict-free" sync that may cause local changes
+    /// by default this is a "conflict-free" sync that may cause local changes
     /// to be overwritten in cases where two or more clients have made changes
-    /// to the same files
-    Sync,
+    /// to the same files; use --on-conflict to detect such conflicts instead
+    Sync {
+        /// Merge local changes with changes on the gateway, and decide what to
+        /// do with files that were changed in both places; "report" lists the
+        /// conflicts and leaves the sphere as it was
+        #[clap(short, long, value_enum, value_name = "RESOLUTION")]
+        on_conflict: Option<OnConflict>,
+    },
 
     /// Tell a configured gateway to update the published version of the sphere
     /// in the Noosphere name system
@@ -127,6 +153,76 @@ pub enum OrbCommand {
         #[clap(value_name = "CID")]
         version: Option<Cid>,
     },
+
+    /// Remove blocks from local storage that are no longer referenced by the
+    /// sphere, and report how much space was reclaimed
+    Gc {
+        /// Only keep this many of the most recent revisions of each sphere;
+        /// by default, all history is kept
+        #[clap(short, long, value_name = "COUNT")]
+        keep_revisions: Option<usize>,
+    },
+
+    /// Verify that the blocks in local storage are intact, reporting any that
+    /// are missing, corrupt or no longer referenced by the sphere; exits with
+    /// an error if any blocks are missing or corrupt (after repairing them,
+    /// with --repair)
+    Fsck {
+        /// Try to fix any problems that are found, fetching missing blocks
+        /// again from the gateway (and from IPFS, if --ipfs-api is specified)
+        #[clap(short, long)]
+        repair: bool,
+
+        /// The URL of an IPFS Kubo RPC API to fetch missing blocks from when
+        /// repairing
+        #[clap(short = 'I', long, value_name = "URL")]
+        ipfs_api: Option<Url>,
+    },
+
+    /// Search the saved text and Subtext content of the sphere, listing the
+    /// slugs that best match the query; the search index is built the first
+    /// time this is run
+    Search {
+        /// The words to search for
+        query: String,
+
+        /// The maximum number of results to show
+        #[clap(short, long, default_value = "10")]
+        limit: usize,
+    },
+}
+
+/// The ways that `orb sync` may handle files that were changed both locally
+/// and on the gateway
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum OnConflict {
+    /// List the conflicting files and abandon the sync
+    Report,
+    /// Keep the local version of each conflicting file
+    KeepLocal,
+    /// Keep the gateway's version of each conflicting file
+    KeepCounterpart,
+    /// Keep the gateway's version of each conflicting file, and move the local
+    /// version to a separate file alongside it
+    KeepBoth,
+}
+
+/// How much of the history of a sphere `orb sphere export` includes
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum ExportHistory {
+    /// Every revision of the sphere
+    Full,
+    /// Only the latest revision of the sphere
+    Latest,
+}
+
+impl From<ExportHistory> for ArchiveHistory {
+    fn from(history: ExportHistory) -> Self {
+        match history {
+            ExportHistory::Full => ArchiveHistory::Full,
+            ExportHistory::Latest => ArchiveHistory::Latest,
+        }
+    }
 }
 
 /// Read and manage configuration values for a local sphere
@@ -226,6 +322,12 @@ pub enum SphereCommand {
         #[clap(short = 'a', long)]
         authorization: Option<String>,
 
+        /// The URL of the sphere's gateway; if it is given (and no
+        /// authorization is), an authorized client is asked to approve this
+        /// device through the gateway
+        #[clap(short = 'g', long)]
+        gateway_url: Option<Url>,
+
         /// The identity of an existing sphere to join
         id: Did,
 
@@ -234,6 +336,26 @@ pub enum SphereCommand {
         /// be used
         path: Option<OsString>,
     },
+
+    /// Export the sphere as a CAR archive, so that it can be backed up or
+    /// moved to another device
+    Export {
+        /// How much of the sphere's history to include
+        #[clap(long, value_enum, default_value = "full")]
+        history: ExportHistory,
+
+        /// The path to write the archive to (it is not written to stdout,
+        /// since logs are written there)
+        #[clap(short = 'o', long)]
+        output: PathBuf,
+    },
+
+    /// Import a CAR archive of the sphere (such as one made with `orb sphere
+    /// export`) and update the local working copy to the archived version
+    Import {
+        /// The path of the archive to import
+        path: PathBuf,
+    },
 }
 
 /// Manage access to a sphere by holders of other keys
@@ -248,6 +370,44 @@ pub enum AuthCommand {
         /// one will be assigned
         #[clap(short = 'n', long)]
         name: Option<String>,
+
+        /// The actions that the key will be authorized to take; by default,
+        /// the key is given full access to the sphere (or, if a prefix is
+        /// specified, it may fetch and push)
+        #[clap(long, value_delimiter = ',')]
+        can: Vec<AuthAction>,
+
+        /// Only allow the key to push changes to content whose slug starts
+        /// with this prefix (the key may still fetch the whole sphere)
+        #[clap(long)]
+        prefix: Option<String>,
+
+        /// How long the authorization lasts (e.g., 12h, 30d or 1y); by default,
+        /// it lasts as long as your own authorization
+        #[clap(long, value_parser = parse_duration)]
+        expires: Option<u64>,
+    },
+
+    /// Approve a request from another device to join the sphere in the
+    /// current directory (made with `orb sphere join --gateway-url`)
+    Approve {
+        /// The pairing code that is shown on the other device
+        code: String,
+
+        /// An optional name to give the other device's key; if one is not
+        /// specified, a random one will be assigned
+        #[clap(short = 'n', long)]
+        name: Option<String>,
+
+        /// How long the authorization lasts (e.g., 12h, 30d or 1y); by default,
+        /// it lasts as long as your own authorization
+        #[clap(long, value_parser = parse_duration)]
+        expires: Option<u64>,
+
+        /// Approve the request without being asked to confirm the key that
+        /// it is for
+        #[clap(short = 'y', long)]
+        yes: bool,
     },
 
     /// Print the name and DID for all keys that the owner has authorized
@@ -258,14 +418,58 @@ pub enum AuthCommand {
         as_json: bool,
     },
 
+    /// Replace authorizations that have expired or are about to expire with
+    /// new ones that grant the same access to the same keys. The replaced
+    /// authorizations are not revoked, so they remain valid until they expire
+    /// (use `orb auth revoke` to take access away sooner)
+    Renew {
+        /// The names of the authorizations to renew; if none are specified,
+        /// every authorization for a key other than your own is renewed
+        names: Vec<String>,
+
+        /// How long the renewed authorizations last (e.g., 12h, 30d or 1y); by
+        /// default, they last as long as your own authorization
+        #[clap(long, value_parser = parse_duration)]
+        expires: Option<u64>,
+    },
+
     /// Revoke authorization to work on the sphere from a specified key
     Revoke {
         /// The name of a key to revoke authorization for
         name: String,
     },
 
-    /// Rotate key authority from one key to another
-    Rotate {},
+    /// Transfer ownership of the sphere to a different local key, revoking
+    /// the authorization of the current key; you will be prompted for the
+    /// sphere's mnemonic
+    Rotate {
+        /// The name of the local key that will become the owner of the sphere
+        key: String,
+    },
+}
+
+/// The actions that `orb auth add` may authorize a key to take
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum AuthAction {
+    /// Fetch the latest changes to the sphere
+    Fetch,
+    /// Push new changes to the sphere
+    Push,
+    /// Publish versions of the sphere to the name system
+    Publish,
+    /// Authorize other keys to work on the sphere
+    Authorize,
+}
+
+impl From<AuthAction> for SphereAction {
+    fn from(action: AuthAction) -> Self {
+        match action {
+            AuthAction::Fetch => SphereAction::Fetch,
+            AuthAction::Push => SphereAction::Push,
+            AuthAction::Publish => SphereAction::Publish,
+            AuthAction::Authorize => SphereAction::Authorize,
+        }
+    }
 }
 
 pub async fn main() -> Result<()> {
@@ -295,6 +499,7 @@ pub async fn main() -> Result<()> {
             SphereCommand::Join {
                 local_key,
                 authorization,
+                gateway_url,
                 id,
                 path,
             } => {
@@ -302,21 +507,53 @@ pub async fn main() -> Result<()> {
                     workspace = Workspace::new(&current_working_directory.join(path), None)?;
                 }
 
-                sphere_join(&local_key, authorization, &id, &workspace).await?;
+                sphere_join(
+                    &local_key,
+                    authorization,
+                    &id,
+                    gateway_url.as_ref(),
+                    &workspace,
+                )
+                .await?;
             }
+            SphereCommand::Export { history, output } => {
+                sphere_export(history.into(), &output, &workspace).await?
+            }
+            SphereCommand::Import { path } => sphere_import(&path, &workspace).await?,
         },
         OrbCommand::Status => status(&workspace).await?,
-        OrbCommand::Diff { paths: _, base: _ } => todo!(),
+        OrbCommand::Diff { paths, base } => diff(paths, base, &workspace).await?,
         OrbCommand::Save => save(&workspace).await?,
-        OrbCommand::Sync => sync(&workspace).await?,
-        OrbCommand::Publish { version: _ } => todo!(),
+        OrbCommand::Sync { on_conflict } => sync(on_conflict, &workspace).await?,
+        OrbCommand::Publish { version } => {
+            publish(version, &workspace).await?;
+        }
+        OrbCommand::Gc { keep_revisions } => gc(keep_revisions, &workspace).await?,
+        OrbCommand::Fsck { repair, ipfs_api } => fsck(repair, ipfs_api, &workspace).await?,
+        OrbCommand::Search { query, limit } => search(&query, limit, &workspace).await?,
         OrbCommand::Auth { command } => match command {
-            AuthCommand::Add { did, name } => {
-                auth_add(&did, name, &workspace).await?;
+            AuthCommand::Add {
+                did,
+                name,
+                can,
+                prefix,
+                expires,
+            } => {
+                let can: Vec<SphereAction> = can.into_iter().map(SphereAction::from).collect();
+                auth_add(&did, name, &can, prefix, expires, &workspace).await?;
             }
+            AuthCommand::Approve {
+                code,
+                name,
+                expires,
+                yes,
+            } => auth_approve(&code, name, expires, yes, &workspace).await?,
             AuthCommand::List { as_json } => auth_list(as_json, &workspace).await?,
+            AuthCommand::Renew { names, expires } => {
+                auth_renew(&names, expires, &workspace).await?
+            }
             AuthCommand::Revoke { name } => auth_revoke(&name, &workspace).await?,
-            AuthCommand::Rotate {} => todo!(),
+            AuthCommand::Rotate { key } => auth_rotate(&key, None, &workspace).await?,
         },
         OrbCommand::Serve {
             cors_origin,
@@ -324,13 +561,20 @@ pub async fn main() -> Result<()> {
             name_resolver_api,
             interface,
             port,
+            tenants,
         } => {
+            let tenants = tenants
+                .iter()
+                .map(|path| Workspace::new(&current_working_directory.join(path), None))
+                .collect::<Result<Vec<Workspace>>>()?;
+
             serve(
                 interface,
                 port,
                 ipfs_api,
                 name_resolver_api,
                 cors_origin,
+                &tenants,
                 &workspace,
             )
             .await?
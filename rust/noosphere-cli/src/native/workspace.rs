@@ -1,12 +1,13 @@
 use anyhow::{anyhow, Result};
 use cid::Cid;
 use globset::{Glob, GlobSet, GlobSetBuilder};
+use libipld_cbor::DagCborCodec;
 use noosphere_core::{
     authority::Authorization,
-    data::{BodyChunkIpld, ContentType, Did, Header},
+    data::{BodyTreeIpld, ContentType, Did, Header},
     view::Sphere,
 };
-use noosphere_storage::{BlockStore, KeyValueStore, NativeStorage, SphereDb, Store};
+use noosphere_storage::{derive_cid, BlockStore, KeyValueStore, NativeStorage, SphereDb, Store};
 use pathdiff::diff_paths;
 use std::{
     collections::{BTreeMap, BTreeSet},
@@ -325,7 +326,19 @@ impl Workspace {
                 };
 
                 let file_bytes = fs::read(path).await?;
-                let body_cid = BodyChunkIpld::store_bytes(&file_bytes, store).await?;
+                let body_cid = match &content_type {
+                    // DAG-CBOR documents are stored as they are, rather than
+                    // as chunks of bytes (see `SphereContentWrite::write_ipld`)
+                    ContentType::DagCbor => {
+                        let cid = derive_cid::<DagCborCodec>(&file_bytes);
+
+                        store.put_block(&cid, &file_bytes).await?;
+                        store.put_links::<DagCborCodec>(&cid, &file_bytes).await?;
+
+                        cid
+                    }
+                    _ => BodyTreeIpld::store_bytes(&file_bytes, store).await?,
+                };
 
                 content.matched.insert(
                     slug,
@@ -420,6 +433,7 @@ impl Workspace {
         Ok(match extension {
             "subtext" => ContentType::Subtext,
             "sphere" => ContentType::Sphere,
+            "dagcbor" => ContentType::DagCbor,
             _ => ContentType::from_str(
                 mime_guess::from_ext(extension)
                     .first_raw()
@@ -443,6 +457,7 @@ impl Workspace {
             }
             ContentType::Cbor => Some("json".into()),
             ContentType::Json => Some("cbor".into()),
+            ContentType::DagCbor => Some("dagcbor".into()),
         }
     }
 
@@ -1,10 +1,12 @@
 #![cfg(not(target_arch = "wasm32"))]
 
 use anyhow::anyhow;
+use cid::Cid;
 use libipld_cbor::DagCborCodec;
-use noosphere::key::KeyStorage;
+use noosphere::{key::KeyStorage, sphere::SphereContextBuilder};
 use noosphere_sphere::{
-    HasMutableSphereContext, SphereContentRead, SphereContentWrite, SphereCursor, SphereSync,
+    ConflictResolution, HasMutableSphereContext, HasSphereContext, MergeSyncStrategy,
+    SphereContentRead, SphereContentWrite, SphereCursor, SphereSync,
 };
 use noosphere_storage::BlockStore;
 use std::net::TcpListener;
@@ -13,12 +15,13 @@ use tokio_stream::StreamExt;
 use url::Url;
 
 use noosphere_api::{
-    data::{FetchParameters, FetchResponse, PushBody, PushResponse},
+    client::PairingClient,
+    data::{FetchParameters, FetchResponse, PairingRequest, PushBody, PushResponse},
     route::Route,
 };
 use noosphere_core::{
     authority::Authorization,
-    data::{ContentType, MemoIpld},
+    data::{ContentType, Link, MemoIpld},
     view::{Sphere, SphereMutation},
 };
 
@@ -26,14 +29,15 @@ use ucan::crypto::KeyMaterial;
 
 use noosphere_cli::native::{
     commands::{
-        auth::auth_add,
+        auth::{auth_add, auth_approve, auth_rotate},
         key::key_create,
+        publish::publish,
         sphere::{sphere_create, sphere_join},
     },
     workspace::Workspace,
 };
 use noosphere_core::tracing::initialize_tracing;
-use noosphere_gateway::{start_gateway, GatewayScope};
+use noosphere_gateway::{start_gateway, start_multi_tenant_gateway, GatewayScope};
 
 #[tokio::test]
 async fn gateway_tells_you_its_identity() {
@@ -691,6 +695,9 @@ async fn gateway_can_sync_an_authorized_sphere_across_multiple_replicas() {
         auth_add(
             &client_replica_key.get_did().await.unwrap(),
             None,
+            &[],
+            None,
+            None,
             &client_workspace,
         )
         .await
@@ -701,6 +708,7 @@ async fn gateway_can_sync_an_authorized_sphere_across_multiple_replicas() {
         client_replica_key_name,
         Some(client_replica_authorization.to_string()),
         &client_sphere_identity,
+        None,
         &client_replica_workspace,
     )
     .await
@@ -769,3 +777,832 @@ async fn gateway_can_sync_an_authorized_sphere_across_multiple_replicas() {
 
     client_task.await.unwrap();
 }
+
+#[tokio::test]
+async fn gateway_reports_and_resolves_conflicting_changes_from_multiple_replicas() {
+    initialize_tracing(None);
+
+    let (gateway_workspace, _gateway_temporary_directories) = Workspace::temporary().unwrap();
+    let (client_workspace, _client_temporary_directories) = Workspace::temporary().unwrap();
+    let (client_replica_workspace, _client_replica_temporary_directories) =
+        Workspace::temporary().unwrap();
+
+    let gateway_key_name = "GATEWAY_KEY";
+    let client_key_name = "CLIENT_KEY";
+    let client_replica_key_name = "CLIENT_REPLICA_KEY";
+
+    key_create(client_key_name, &client_workspace)
+        .await
+        .unwrap();
+    key_create(gateway_key_name, &gateway_workspace)
+        .await
+        .unwrap();
+    key_create(client_replica_key_name, &client_replica_workspace)
+        .await
+        .unwrap();
+
+    sphere_create(client_key_name, &client_workspace)
+        .await
+        .unwrap();
+    sphere_create(gateway_key_name, &gateway_workspace)
+        .await
+        .unwrap();
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let gateway_address = listener.local_addr().unwrap();
+
+    let gateway_sphere_identity = gateway_workspace.sphere_identity().await.unwrap();
+    let client_sphere_identity = client_workspace.sphere_identity().await.unwrap();
+
+    let gateway_sphere_context = gateway_workspace.sphere_context().await.unwrap();
+
+    let server_task = {
+        let gateway_sphere_context = gateway_sphere_context.clone();
+        let client_sphere_identity = client_sphere_identity.clone();
+        tokio::spawn(async move {
+            start_gateway(
+                listener,
+                GatewayScope {
+                    identity: gateway_sphere_identity,
+                    counterpart: client_sphere_identity,
+                },
+                gateway_sphere_context,
+                Url::parse("http://127.0.0.1:5001").unwrap(),
+                Url::parse("http://127.0.0.1:6667").unwrap(),
+                None,
+            )
+            .await
+            .unwrap()
+        })
+    };
+
+    let client_replica_key_storage = client_replica_workspace.key_storage();
+    let client_replica_key = client_replica_key_storage
+        .require_key(client_replica_key_name)
+        .await
+        .unwrap();
+
+    let client_replica_authorization = Authorization::Cid(
+        auth_add(
+            &client_replica_key.get_did().await.unwrap(),
+            None,
+            &[],
+            None,
+            None,
+            &client_workspace,
+        )
+        .await
+        .unwrap(),
+    );
+
+    sphere_join(
+        client_replica_key_name,
+        Some(client_replica_authorization.to_string()),
+        &client_sphere_identity,
+        None,
+        &client_replica_workspace,
+    )
+    .await
+    .unwrap();
+
+    let mut client_sphere_context = client_workspace.sphere_context().await.unwrap();
+    let mut client_replica_sphere_context =
+        client_replica_workspace.sphere_context().await.unwrap();
+
+    let client_task = tokio::spawn(async move {
+        let gateway_url: Url =
+            format!("http://{}:{}", gateway_address.ip(), gateway_address.port())
+                .parse()
+                .unwrap();
+
+        {
+            client_sphere_context
+                .lock()
+                .await
+                .configure_gateway_url(Some(&gateway_url))
+                .await
+                .unwrap();
+        }
+
+        client_sphere_context
+            .write(
+                "shared",
+                &ContentType::Subtext.to_string(),
+                b"original".as_ref(),
+                None,
+            )
+            .await
+            .unwrap();
+        client_sphere_context.save(None).await.unwrap();
+        client_sphere_context.sync().await.unwrap();
+
+        {
+            let mut client_replica_sphere_context = client_replica_sphere_context.lock().await;
+            client_replica_sphere_context
+                .configure_gateway_url(Some(&gateway_url))
+                .await
+                .unwrap();
+        }
+        client_replica_sphere_context.sync().await.unwrap();
+
+        client_sphere_context
+            .write(
+                "shared",
+                &ContentType::Subtext.to_string(),
+                b"client".as_ref(),
+                None,
+            )
+            .await
+            .unwrap();
+        client_sphere_context.save(None).await.unwrap();
+        client_sphere_context.sync().await.unwrap();
+
+        client_replica_sphere_context
+            .write(
+                "shared",
+                &ContentType::Subtext.to_string(),
+                b"replica".as_ref(),
+                None,
+            )
+            .await
+            .unwrap();
+        client_replica_sphere_context
+            .write(
+                "unrelated",
+                &ContentType::Subtext.to_string(),
+                b"unrelated".as_ref(),
+                None,
+            )
+            .await
+            .unwrap();
+        let replica_version = client_replica_sphere_context.save(None).await.unwrap();
+
+        let report = client_replica_sphere_context
+            .sync_with(&MergeSyncStrategy::default())
+            .await
+            .unwrap();
+
+        assert!(!report.is_complete());
+        assert_eq!(report.unresolved.len(), 1);
+        assert_eq!(report.unresolved[0].slug, "shared");
+        assert!(report.unresolved[0].ancestor.is_some());
+
+        let replica_memo = report.unresolved[0].local.unwrap();
+
+        assert_eq!(
+            client_replica_sphere_context
+                .sphere_context()
+                .await
+                .unwrap()
+                .head()
+                .await
+                .unwrap(),
+            replica_version
+        );
+
+        let mut contents = String::new();
+        client_replica_sphere_context
+            .read("shared")
+            .await
+            .unwrap()
+            .unwrap()
+            .contents
+            .read_to_string(&mut contents)
+            .await
+            .unwrap();
+        assert_eq!(contents, "replica");
+
+        let report = client_replica_sphere_context
+            .sync_with(&MergeSyncStrategy::resolving_with(
+                ConflictResolution::KeepBoth,
+            ))
+            .await
+            .unwrap();
+
+        assert!(report.is_complete());
+        assert_eq!(report.resolved.len(), 1);
+
+        let (conflict, resolution) = &report.resolved[0];
+        let conflict_slug = conflict.conflict_slug().unwrap();
+
+        assert_eq!(resolution, &ConflictResolution::KeepBoth);
+        assert_eq!(conflict_slug, format!("shared.conflict-{replica_memo}"));
+
+        client_sphere_context.sync().await.unwrap();
+
+        for context in [&client_sphere_context, &client_replica_sphere_context] {
+            for (slug, expected) in [
+                ("shared", "client"),
+                ("unrelated", "unrelated"),
+                (conflict_slug.as_str(), "replica"),
+            ] {
+                let mut contents = String::new();
+                context
+                    .read(slug)
+                    .await
+                    .unwrap()
+                    .unwrap()
+                    .contents
+                    .read_to_string(&mut contents)
+                    .await
+                    .unwrap();
+                assert_eq!(contents, expected);
+            }
+        }
+
+        server_task.abort();
+        let _ = server_task.await;
+    });
+
+    client_task.await.unwrap();
+}
+
+#[tokio::test]
+async fn gateway_publishes_an_older_version_of_a_sphere() {
+    initialize_tracing(None);
+
+    let (gateway_workspace, _gateway_temporary_directories) = Workspace::temporary().unwrap();
+    let (client_workspace, _client_temporary_directories) = Workspace::temporary().unwrap();
+
+    let gateway_key_name = "GATEWAY_KEY";
+    let client_key_name = "CLIENT_KEY";
+
+    key_create(client_key_name, &client_workspace)
+        .await
+        .unwrap();
+    key_create(gateway_key_name, &gateway_workspace)
+        .await
+        .unwrap();
+
+    sphere_create(client_key_name, &client_workspace)
+        .await
+        .unwrap();
+    sphere_create(gateway_key_name, &gateway_workspace)
+        .await
+        .unwrap();
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let gateway_address = listener.local_addr().unwrap();
+
+    let gateway_sphere_identity = gateway_workspace.sphere_identity().await.unwrap();
+    let client_sphere_identity = client_workspace.sphere_identity().await.unwrap();
+
+    let gateway_sphere_context = gateway_workspace.sphere_context().await.unwrap();
+
+    let server_task = {
+        let gateway_sphere_context = gateway_sphere_context.clone();
+        let client_sphere_identity = client_sphere_identity.clone();
+        tokio::spawn(async move {
+            start_gateway(
+                listener,
+                GatewayScope {
+                    identity: gateway_sphere_identity,
+                    counterpart: client_sphere_identity,
+                },
+                gateway_sphere_context,
+                Url::parse("http://127.0.0.1:5001").unwrap(),
+                Url::parse("http://127.0.0.1:6667").unwrap(),
+                None,
+            )
+            .await
+            .unwrap()
+        })
+    };
+
+    let mut client_sphere_context = client_workspace.sphere_context().await.unwrap();
+
+    let client_task = tokio::spawn(async move {
+        let gateway_url: Url =
+            format!("http://{}:{}", gateway_address.ip(), gateway_address.port())
+                .parse()
+                .unwrap();
+
+        {
+            client_sphere_context
+                .lock()
+                .await
+                .configure_gateway_url(Some(&gateway_url))
+                .await
+                .unwrap();
+        }
+
+        let mut versions = Vec::new();
+
+        for value in ["one", "two", "three"] {
+            client_sphere_context
+                .write(
+                    value,
+                    &ContentType::Subtext.to_string(),
+                    value.as_ref(),
+                    None,
+                )
+                .await
+                .unwrap();
+            versions.push(
+                SphereCursor::latest(client_sphere_context.clone())
+                    .save(None)
+                    .await
+                    .unwrap(),
+            );
+        }
+
+        client_sphere_context.sync().await.unwrap();
+
+        let older_version = versions[0];
+
+        assert_eq!(
+            publish(Some(older_version), &client_workspace)
+                .await
+                .unwrap(),
+            older_version
+        );
+
+        // Versions that have never been pushed to the gateway are refused
+        client_sphere_context
+            .write(
+                "four",
+                &ContentType::Subtext.to_string(),
+                "four".as_ref(),
+                None,
+            )
+            .await
+            .unwrap();
+        let unknown_version = SphereCursor::latest(client_sphere_context.clone())
+            .save(None)
+            .await
+            .unwrap();
+
+        assert!(publish(Some(unknown_version), &client_workspace)
+            .await
+            .is_err());
+
+        server_task.abort();
+        let _ = server_task.await;
+    });
+
+    client_task.await.unwrap();
+}
+
+#[tokio::test]
+async fn gateway_receives_a_revocation_when_sphere_ownership_is_rotated() {
+    initialize_tracing(None);
+
+    let (gateway_workspace, _gateway_temporary_directories) = Workspace::temporary().unwrap();
+    let (client_workspace, _client_temporary_directories) = Workspace::temporary().unwrap();
+
+    let gateway_key_name = "GATEWAY_KEY";
+    let client_key_name = "CLIENT_KEY";
+    let next_client_key_name = "NEXT_CLIENT_KEY";
+
+    key_create(client_key_name, &client_workspace)
+        .await
+        .unwrap();
+    key_create(next_client_key_name, &client_workspace)
+        .await
+        .unwrap();
+    key_create(gateway_key_name, &gateway_workspace)
+        .await
+        .unwrap();
+
+    let mnemonic = {
+        let artifacts = SphereContextBuilder::default()
+            .create_sphere()
+            .at_storage_path(client_workspace.root_directory())
+            .reading_keys_from(client_workspace.key_storage().clone())
+            .using_key(client_key_name)
+            .build()
+            .await
+            .unwrap();
+        artifacts.require_mnemonic().unwrap().to_string()
+    };
+
+    sphere_create(gateway_key_name, &gateway_workspace)
+        .await
+        .unwrap();
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let gateway_address = listener.local_addr().unwrap();
+
+    let gateway_sphere_identity = gateway_workspace.sphere_identity().await.unwrap();
+    let client_sphere_identity = client_workspace.sphere_identity().await.unwrap();
+
+    let gateway_sphere_context = gateway_workspace.sphere_context().await.unwrap();
+
+    let server_task = {
+        let gateway_sphere_context = gateway_sphere_context.clone();
+        let client_sphere_identity = client_sphere_identity.clone();
+        tokio::spawn(async move {
+            start_gateway(
+                listener,
+                GatewayScope {
+                    identity: gateway_sphere_identity,
+                    counterpart: client_sphere_identity,
+                },
+                gateway_sphere_context,
+                Url::parse("http://127.0.0.1:5001").unwrap(),
+                Url::parse("http://127.0.0.1:6667").unwrap(),
+                None,
+            )
+            .await
+            .unwrap()
+        })
+    };
+
+    let mut client_sphere_context = client_workspace.sphere_context().await.unwrap();
+
+    let client_task = tokio::spawn(async move {
+        let gateway_url: Url =
+            format!("http://{}:{}", gateway_address.ip(), gateway_address.port())
+                .parse()
+                .unwrap();
+
+        {
+            client_sphere_context
+                .lock()
+                .await
+                .configure_gateway_url(Some(&gateway_url))
+                .await
+                .unwrap();
+        }
+
+        client_sphere_context.sync().await.unwrap();
+
+        let original_authorization =
+            Cid::try_from(&client_workspace.authorization().await.unwrap()).unwrap();
+
+        auth_rotate(next_client_key_name, Some(mnemonic), &client_workspace)
+            .await
+            .unwrap();
+
+        let next_client_did = client_workspace
+            .key()
+            .await
+            .unwrap()
+            .get_did()
+            .await
+            .unwrap();
+
+        assert_ne!(
+            Cid::try_from(&client_workspace.authorization().await.unwrap()).unwrap(),
+            original_authorization
+        );
+
+        let gateway_db = gateway_sphere_context.lock().await.db().clone();
+        let client_sphere_version = gateway_db
+            .require_version(&client_sphere_identity)
+            .await
+            .unwrap();
+        let authority = Sphere::at(&client_sphere_version, &gateway_db)
+            .get_authority()
+            .await
+            .unwrap();
+
+        let revocation = authority
+            .get_revocations()
+            .await
+            .unwrap()
+            .get(&Link::new(original_authorization))
+            .await
+            .unwrap()
+            .cloned();
+
+        assert!(revocation.is_some());
+
+        let delegations = authority.get_delegations().await.unwrap();
+        let delegations = delegations.into_stream().await.unwrap();
+        let mut delegated_dids = Vec::new();
+
+        tokio::pin!(delegations);
+
+        while let Some((_, delegation)) = delegations.try_next().await.unwrap() {
+            let ucan = delegation.resolve_ucan(&gateway_db).await.unwrap();
+            delegated_dids.push(ucan.audience().to_string());
+        }
+
+        assert_eq!(delegated_dids, vec![next_client_did]);
+
+        server_task.abort();
+        let _ = server_task.await;
+    });
+
+    client_task.await.unwrap();
+}
+
+#[tokio::test]
+async fn gateway_serves_many_counterpart_spheres_from_one_process() {
+    initialize_tracing(None);
+
+    let (gateway_workspace, gateway_temporary_directories) = Workspace::temporary().unwrap();
+    let other_gateway_root = tempfile::TempDir::new().unwrap();
+    let other_gateway_workspace = Workspace::new(
+        other_gateway_root.path(),
+        Some(gateway_temporary_directories.1.path()),
+    )
+    .unwrap();
+
+    let (client_workspace, _client_temporary_directories) = Workspace::temporary().unwrap();
+    let (other_client_workspace, _other_client_temporary_directories) =
+        Workspace::temporary().unwrap();
+
+    let gateway_key_name = "GATEWAY_KEY";
+    let client_key_name = "CLIENT_KEY";
+
+    key_create(gateway_key_name, &gateway_workspace)
+        .await
+        .unwrap();
+    key_create(client_key_name, &client_workspace)
+        .await
+        .unwrap();
+    key_create(client_key_name, &other_client_workspace)
+        .await
+        .unwrap();
+
+    sphere_create(gateway_key_name, &gateway_workspace)
+        .await
+        .unwrap();
+    sphere_create(gateway_key_name, &other_gateway_workspace)
+        .await
+        .unwrap();
+    sphere_create(client_key_name, &client_workspace)
+        .await
+        .unwrap();
+    sphere_create(client_key_name, &other_client_workspace)
+        .await
+        .unwrap();
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let gateway_address = listener.local_addr().unwrap();
+
+    let gateway_sphere_identity = gateway_workspace.sphere_identity().await.unwrap();
+    let other_gateway_sphere_identity = other_gateway_workspace.sphere_identity().await.unwrap();
+    let client_sphere_identity = client_workspace.sphere_identity().await.unwrap();
+    let other_client_sphere_identity = other_client_workspace.sphere_identity().await.unwrap();
+
+    let tenants = vec![
+        (
+            GatewayScope {
+                identity: gateway_sphere_identity.clone(),
+                counterpart: client_sphere_identity.clone(),
+            },
+            gateway_workspace.sphere_context().await.unwrap(),
+        ),
+        (
+            GatewayScope {
+                identity: other_gateway_sphere_identity.clone(),
+                counterpart: other_client_sphere_identity.clone(),
+            },
+            other_gateway_workspace.sphere_context().await.unwrap(),
+        ),
+    ];
+
+    let server_task = tokio::spawn(async move {
+        start_multi_tenant_gateway(
+            listener,
+            tenants,
+            Url::parse("http://127.0.0.1:5001").unwrap(),
+            Url::parse("http://127.0.0.1:6667").unwrap(),
+            None,
+        )
+        .await
+        .unwrap()
+    });
+
+    let client_sphere_context = client_workspace.sphere_context().await.unwrap();
+    let other_client_sphere_context = other_client_workspace.sphere_context().await.unwrap();
+
+    let client_task = tokio::spawn(async move {
+        let gateway_url: Url =
+            format!("http://{}:{}", gateway_address.ip(), gateway_address.port())
+                .parse()
+                .unwrap();
+
+        for (mut sphere_context, expected_gateway_sphere_identity) in [
+            (client_sphere_context, gateway_sphere_identity),
+            (other_client_sphere_context, other_gateway_sphere_identity),
+        ] {
+            {
+                sphere_context
+                    .lock()
+                    .await
+                    .configure_gateway_url(Some(&gateway_url))
+                    .await
+                    .unwrap();
+            }
+
+            sphere_context
+                .write(
+                    "hello",
+                    &ContentType::Subtext.to_string(),
+                    "world".as_ref(),
+                    None,
+                )
+                .await
+                .unwrap();
+            sphere_context.save(None).await.unwrap();
+
+            sphere_context.sync().await.unwrap();
+
+            let sphere_context = sphere_context.lock().await;
+            let client = sphere_context.client().await.unwrap();
+
+            assert_eq!(
+                client.session.sphere_identity,
+                expected_gateway_sphere_identity
+            );
+        }
+
+        // Requests that do not name a sphere can't be routed to a tenant, but
+        // the routes that don't need one still work
+        let client = reqwest::Client::new();
+
+        let mut url = gateway_url.clone();
+        url.set_path(&Route::Identify.to_string());
+
+        let identify_response = client.get(url).send().await.unwrap();
+
+        assert_eq!(
+            identify_response.status(),
+            reqwest::StatusCode::UNAUTHORIZED
+        );
+
+        let mut url = gateway_url.clone();
+        url.set_path(&Route::Did.to_string());
+
+        let did_response = client.get(url).send().await.unwrap();
+
+        assert_eq!(did_response.status(), reqwest::StatusCode::OK);
+
+        server_task.abort();
+        let _ = server_task.await;
+    });
+
+    client_task.await.unwrap();
+}
+
+#[tokio::test]
+async fn gateway_pairs_a_new_device_with_a_sphere() {
+    initialize_tracing(None);
+
+    let (gateway_workspace, _gateway_temporary_directories) = Workspace::temporary().unwrap();
+    let (client_workspace, _client_temporary_directories) = Workspace::temporary().unwrap();
+    let (client_replica_workspace, _client_replica_temporary_directories) =
+        Workspace::temporary().unwrap();
+
+    let gateway_key_name = "GATEWAY_KEY";
+    let client_key_name = "CLIENT_KEY";
+    let client_replica_key_name = "CLIENT_REPLICA_KEY";
+
+    key_create(client_key_name, &client_workspace)
+        .await
+        .unwrap();
+    key_create(gateway_key_name, &gateway_workspace)
+        .await
+        .unwrap();
+    key_create(client_replica_key_name, &client_replica_workspace)
+        .await
+        .unwrap();
+
+    sphere_create(client_key_name, &client_workspace)
+        .await
+        .unwrap();
+    sphere_create(gateway_key_name, &gateway_workspace)
+        .await
+        .unwrap();
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let gateway_address = listener.local_addr().unwrap();
+
+    let gateway_sphere_identity = gateway_workspace.sphere_identity().await.unwrap();
+    let client_sphere_identity = client_workspace.sphere_identity().await.unwrap();
+
+    let gateway_sphere_context = gateway_workspace.sphere_context().await.unwrap();
+
+    let server_task = {
+        let gateway_sphere_context = gateway_sphere_context.clone();
+        let client_sphere_identity = client_sphere_identity.clone();
+        tokio::spawn(async move {
+            start_gateway(
+                listener,
+                GatewayScope {
+                    identity: gateway_sphere_identity,
+                    counterpart: client_sphere_identity,
+                },
+                gateway_sphere_context,
+                Url::parse("http://127.0.0.1:5001").unwrap(),
+                Url::parse("http://127.0.0.1:6667").unwrap(),
+                None,
+            )
+            .await
+            .unwrap()
+        })
+    };
+
+    let client_replica_key = client_replica_workspace
+        .key_storage()
+        .require_key(client_replica_key_name)
+        .await
+        .unwrap();
+    let client_replica_did = client_replica_key.get_did().await.unwrap();
+
+    let mut client_sphere_context = client_workspace.sphere_context().await.unwrap();
+
+    let client_task = tokio::spawn(async move {
+        let gateway_url: Url =
+            format!("http://{}:{}", gateway_address.ip(), gateway_address.port())
+                .parse()
+                .unwrap();
+
+        {
+            client_sphere_context
+                .lock()
+                .await
+                .configure_gateway_url(Some(&gateway_url))
+                .await
+                .unwrap();
+        }
+
+        client_sphere_context
+            .write(
+                "hello",
+                &ContentType::Subtext.to_string(),
+                "world".as_ref(),
+                None,
+            )
+            .await
+            .unwrap();
+        client_sphere_context.save(None).await.unwrap();
+        client_sphere_context.sync().await.unwrap();
+
+        let pairing_client = PairingClient::new(&gateway_url);
+        let code = "curious-otter-hums";
+
+        // A request must be signed by the key that it asks to have authorized
+        let mut forged_request =
+            PairingRequest::sign(code, &client_sphere_identity, &client_replica_key)
+                .await
+                .unwrap();
+        forged_request.did = client_sphere_identity.clone();
+
+        assert!(pairing_client.request(&forged_request).await.is_err());
+
+        let request = PairingRequest::sign(code, &client_sphere_identity, &client_replica_key)
+            .await
+            .unwrap();
+
+        pairing_client.request(&request).await.unwrap();
+
+        // A code may only be used by one pending request at a time
+        assert!(pairing_client.request(&request).await.is_err());
+
+        let status = pairing_client.status(&request).await.unwrap();
+
+        assert_eq!(status.did.as_str(), client_replica_did);
+        assert!(status.authorization.is_none());
+
+        // The status is not revealed to anyone who merely knows the code
+        let mut unsigned_request = request.clone();
+        unsigned_request.signature = String::new();
+
+        assert!(pairing_client.status(&unsigned_request).await.is_err());
+
+        auth_approve(code, None, None, true, &client_workspace)
+            .await
+            .unwrap();
+
+        let authorization = pairing_client
+            .status(&request)
+            .await
+            .unwrap()
+            .authorization
+            .unwrap();
+
+        sphere_join(
+            client_replica_key_name,
+            Some(authorization.to_string()),
+            &client_sphere_identity,
+            Some(&gateway_url),
+            &client_replica_workspace,
+        )
+        .await
+        .unwrap();
+
+        let mut client_replica_sphere_context =
+            client_replica_workspace.sphere_context().await.unwrap();
+
+        client_replica_sphere_context.sync().await.unwrap();
+
+        let mut file = client_replica_sphere_context
+            .read("hello")
+            .await
+            .unwrap()
+            .unwrap();
+        let mut contents = String::new();
+        file.contents.read_to_string(&mut contents).await.unwrap();
+        assert_eq!("world", &contents);
+
+        server_task.abort();
+        let _ = server_task.await;
+    });
+
+    client_task.await.unwrap();
+}
@@ -1,14 +1,13 @@
 use crate::{
     authority::{
-        generate_ed25519_key, Authorization, SphereAction, SphereReference, SPHERE_SEMANTICS,
-        SUPPORTED_KEYS,
+        content_prefix_of, enables_sphere_content, generate_ed25519_key, Authorization,
+        SphereAction, SPHERE_SEMANTICS, SUPPORTED_KEYS,
     },
     data::Did,
 };
 use anyhow::{anyhow, Result};
 use noosphere_storage::{SphereDb, Storage};
 use ucan::{
-    capability::{Capability, Resource, With},
     chain::ProofChain,
     crypto::{did::DidParser, KeyMaterial},
 };
@@ -16,13 +15,29 @@ use ucan_key_support::ed25519::Ed25519KeyMaterial;
 
 /// The level of access that a given user has to a related resource. Broadly,
 /// a user will always have either read/write access (to their own sphere) or
-/// else read-only access (to all other spheres).
+/// else read-only access (to all other spheres). A user who was only delegated
+/// some of the content of a sphere may only write content whose slugs start
+/// with one of the given prefixes.
 #[derive(PartialEq, Eq, Debug, Clone)]
 pub enum Access {
     ReadWrite,
+    ReadWriteContent(Vec<String>),
     ReadOnly,
 }
 
+impl Access {
+    /// Returns true if the content at the given slug may be written
+    pub fn can_write_slug(&self, slug: &str) -> bool {
+        match self {
+            Access::ReadWrite => true,
+            Access::ReadWriteContent(content_prefixes) => content_prefixes
+                .iter()
+                .any(|prefix| slug.starts_with(prefix.as_str())),
+            Access::ReadOnly => false,
+        }
+    }
+}
+
 /// An author is a user or program who is reading content from and/or writing
 /// content to a sphere. This construct collects the identity and the
 /// authorization of that entity to make it easier to determine their level of
@@ -79,26 +94,26 @@ where
                 return Ok(Access::ReadOnly);
             }
 
-            let read_write_capability = Capability {
-                with: With::Resource {
-                    kind: Resource::Scoped(SphereReference {
-                        did: sphere_identity.to_string(),
-                    }),
-                },
-                can: SphereAction::Push,
-            };
             let mut did_parser = DidParser::new(SUPPORTED_KEYS);
             let proof_chain = ProofChain::from_ucan(ucan, None, &mut did_parser, db).await?;
 
             let capability_infos = proof_chain.reduce_capabilities(&SPHERE_SEMANTICS);
+            let mut content_prefixes = Vec::new();
 
             for info in capability_infos {
                 if info.originators.contains(sphere_identity.as_str())
-                    && info.capability.enables(&read_write_capability)
+                    && enables_sphere_content(&info.capability, sphere_identity, SphereAction::Push)
                 {
-                    return Ok(Access::ReadWrite);
+                    match content_prefix_of(&info.capability) {
+                        Some(content_prefix) => content_prefixes.push(content_prefix.to_owned()),
+                        None => return Ok(Access::ReadWrite),
+                    }
                 }
             }
+
+            if !content_prefixes.is_empty() {
+                return Ok(Access::ReadWriteContent(content_prefixes));
+            }
         }
 
         Ok(Access::ReadOnly)
@@ -108,9 +123,18 @@ where
 #[cfg(test)]
 mod tests {
     use noosphere_storage::{MemoryStorage, SphereDb};
-    use ucan::crypto::KeyMaterial;
-
-    use crate::{authority::generate_ed25519_key, data::Did, view::Sphere};
+    use ucan::{
+        builder::UcanBuilder,
+        capability::{Capability, Resource, With},
+        crypto::KeyMaterial,
+        store::UcanJwtStore,
+    };
+
+    use crate::{
+        authority::{generate_ed25519_key, Authorization, SphereAction, SphereReference},
+        data::Did,
+        view::Sphere,
+    };
 
     use super::{Access, Author};
 
@@ -156,4 +180,52 @@ mod tests {
 
         assert_eq!(access, Access::ReadWrite);
     }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[cfg_attr(not(target_arch = "wasm32"), tokio::test)]
+    async fn it_gives_write_access_to_only_the_content_that_the_key_is_authorized_for() {
+        let owner_key = generate_ed25519_key();
+        let owner_did = Did(owner_key.get_did().await.unwrap());
+        let mut db = SphereDb::new(&MemoryStorage::default()).await.unwrap();
+
+        let (sphere, authorization, _) = Sphere::generate(&owner_did, &mut db).await.unwrap();
+        let sphere_identity = sphere.get_identity().await.unwrap();
+        let owner_ucan = authorization.resolve_ucan(&db).await.unwrap();
+
+        let device_key = generate_ed25519_key();
+        let device_ucan = UcanBuilder::default()
+            .issued_by(&owner_key)
+            .for_audience(&device_key.get_did().await.unwrap())
+            .claiming_capability(&Capability {
+                with: With::Resource {
+                    kind: Resource::Scoped(SphereReference::content(
+                        sphere_identity.as_str(),
+                        "blog/",
+                    )),
+                },
+                can: SphereAction::Push,
+            })
+            .witnessed_by(&owner_ucan)
+            .with_expiration(*owner_ucan.expires_at())
+            .build()
+            .unwrap()
+            .sign()
+            .await
+            .unwrap();
+
+        let author = Author {
+            key: device_key,
+            authorization: Some(Authorization::Cid(
+                db.write_token(&device_ucan.encode().unwrap())
+                    .await
+                    .unwrap(),
+            )),
+        };
+
+        let access = author.access_to(&sphere_identity, &db).await.unwrap();
+
+        assert_eq!(access, Access::ReadWriteContent(vec!["blog/".into()]));
+        assert!(access.can_write_slug("blog/hello"));
+        assert!(!access.can_write_slug("notes/hello"));
+    }
 }
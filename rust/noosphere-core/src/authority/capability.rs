@@ -42,20 +42,69 @@ impl TryFrom<String> for SphereAction {
     }
 }
 
+/// The path segment that separates a sphere's DID from a slug prefix in a
+/// content-scoped [SphereReference] (e.g., `sphere:<did>/content/blog/`)
+const CONTENT_PATH_SEGMENT: &str = "/content/";
+
+/// A [SphereReference] is the resource that sphere capabilities refer to. It
+/// may refer to a whole sphere (`sphere:<did>`) or only to the content of the
+/// sphere whose slugs start with a given prefix
+/// (`sphere:<did>/content/<slug-prefix>`).
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
 pub struct SphereReference {
     pub did: String,
+    /// If set, only content whose slugs start with this prefix is referred to
+    pub content_prefix: Option<String>,
+}
+
+impl SphereReference {
+    /// A reference to the whole sphere with the given DID
+    pub fn new<D: Into<String>>(did: D) -> Self {
+        SphereReference {
+            did: did.into(),
+            content_prefix: None,
+        }
+    }
+
+    /// A reference to the content of the sphere with the given DID whose slugs
+    /// start with the given prefix
+    pub fn content<D: Into<String>, P: Into<String>>(did: D, content_prefix: P) -> Self {
+        SphereReference {
+            did: did.into(),
+            content_prefix: Some(content_prefix.into()),
+        }
+    }
+
+    /// Returns true if this reference includes the content at the given slug
+    pub fn includes_slug(&self, slug: &str) -> bool {
+        match &self.content_prefix {
+            Some(prefix) => slug.starts_with(prefix.as_str()),
+            None => true,
+        }
+    }
 }
 
 impl Scope for SphereReference {
     fn contains(&self, other: &Self) -> bool {
-        other.did == self.did
+        if other.did != self.did {
+            return false;
+        }
+
+        match (&self.content_prefix, &other.content_prefix) {
+            (None, _) => true,
+            (Some(_), None) => false,
+            (Some(prefix), Some(other_prefix)) => other_prefix.starts_with(prefix.as_str()),
+        }
     }
 }
 
 impl ToString for SphereReference {
     fn to_string(&self) -> String {
-        format!("sphere:{}", self.did)
+        match &self.content_prefix {
+            Some(prefix) => format!("sphere:{}{CONTENT_PATH_SEGMENT}{prefix}", self.did),
+            None => format!("sphere:{}", self.did),
+        }
     }
 }
 
@@ -64,9 +113,14 @@ impl TryFrom<Url> for SphereReference {
 
     fn try_from(value: Url) -> Result<Self> {
         match value.scheme() {
-            "sphere" => Ok(SphereReference {
-                did: String::from(value.path()),
-            }),
+            "sphere" => {
+                let path = value.path();
+
+                Ok(match path.split_once(CONTENT_PATH_SEGMENT) {
+                    Some((did, prefix)) => SphereReference::content(did, prefix),
+                    None => SphereReference::new(path),
+                })
+            }
             _ => Err(anyhow!(
                 "Could not interpret URI as a sphere reference: {:?}",
                 value
@@ -90,9 +144,7 @@ pub const SPHERE_SEMANTICS: SphereSemantics = SphereSemantics {};
 /// let identity = "did:key:z6MkoE19WHXJzpLqkxbGP7uXdJX38sWZNUWwyjcuCmjhPpUP";
 /// let expected_capability = Capability {
 ///     with: With::Resource {
-///         kind: Resource::Scoped(SphereReference {
-///            did: identity.to_owned(),
-///         }),
+///         kind: Resource::Scoped(SphereReference::new(identity)),
 ///     },
 ///     can: SphereAction::Publish,
 /// };
@@ -104,10 +156,90 @@ pub fn generate_capability(
 ) -> Capability<SphereReference, SphereAction> {
     Capability {
         with: With::Resource {
-            kind: Resource::Scoped(SphereReference {
-                did: identity.to_owned(),
-            }),
+            kind: Resource::Scoped(SphereReference::new(identity)),
         },
         can: action,
     }
 }
+
+/// Returns the slug prefix that the [Capability] is limited to, if it only
+/// refers to some of the content of a sphere
+pub fn content_prefix_of(capability: &Capability<SphereReference, SphereAction>) -> Option<&str> {
+    match &capability.with {
+        With::Resource {
+            kind: Resource::Scoped(reference),
+        } => reference.content_prefix.as_deref(),
+        _ => None,
+    }
+}
+
+/// Returns true if the [Capability] enables the given action on at least some
+/// of the content of the sphere with the given identity (that is, on the whole
+/// sphere, or on the content under some slug prefix).
+pub fn enables_sphere_content(
+    capability: &Capability<SphereReference, SphereAction>,
+    identity: &str,
+    action: SphereAction,
+) -> bool {
+    let reference = match content_prefix_of(capability) {
+        Some(content_prefix) => SphereReference::content(identity, content_prefix),
+        None => SphereReference::new(identity),
+    };
+
+    capability.enables(&Capability {
+        with: With::Resource {
+            kind: Resource::Scoped(reference),
+        },
+        can: action,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use ucan::capability::Scope;
+    use url::Url;
+
+    use super::SphereReference;
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    #[cfg(target_arch = "wasm32")]
+    wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_browser);
+
+    const DID: &str = "did:key:z6MkoE19WHXJzpLqkxbGP7uXdJX38sWZNUWwyjcuCmjhPpUP";
+
+    fn reference(content_prefix: Option<&str>) -> SphereReference {
+        match content_prefix {
+            Some(content_prefix) => SphereReference::content(DID, content_prefix),
+            None => SphereReference::new(DID),
+        }
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[cfg_attr(not(target_arch = "wasm32"), test)]
+    fn it_round_trips_sphere_references_through_urls() {
+        for expected in [reference(None), reference(Some("build-reports/"))] {
+            let url = Url::parse(&expected.to_string()).unwrap();
+            assert_eq!(SphereReference::try_from(url).unwrap(), expected);
+        }
+
+        assert_eq!(
+            reference(Some("blog/")).to_string(),
+            format!("sphere:{DID}/content/blog/")
+        );
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[cfg_attr(not(target_arch = "wasm32"), test)]
+    fn it_only_lets_a_content_scope_contain_narrower_scopes() {
+        assert!(reference(None).contains(&reference(Some("blog/"))));
+        assert!(reference(Some("blog/")).contains(&reference(Some("blog/2023/"))));
+        assert!(!reference(Some("blog/")).contains(&reference(None)));
+        assert!(!reference(Some("blog/")).contains(&reference(Some("notes/"))));
+        assert!(!reference(None).contains(&SphereReference::new("did:key:other")));
+
+        assert!(reference(Some("blog/")).includes_slug("blog/hello"));
+        assert!(!reference(Some("blog/")).includes_slug("notes"));
+    }
+}
@@ -1,25 +1,27 @@
-use std::str::FromStr;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    str::FromStr,
+};
 
 use anyhow::{anyhow, Result};
 use cid::Cid;
 use libipld_cbor::DagCborCodec;
-use ucan::{
-    capability::{Capability, Resource, With},
-    chain::ProofChain,
-    crypto::did::DidParser,
-    store::UcanJwtStore,
-    ucan::Ucan,
-};
+use libipld_core::{ipld::Ipld, raw::RawCodec};
+use ucan::{chain::ProofChain, crypto::did::DidParser, store::UcanJwtStore, ucan::Ucan};
 
-use crate::data::{ContentType, Header, MemoIpld, SphereIpld};
+use crate::{
+    data::{ContentType, Header, Link, MemoIpld, SphereIpld},
+    view::Sphere,
+};
 
-use noosphere_storage::{base64_decode, BlockStore, SphereDb, Storage, UcanStore};
+use noosphere_storage::{base64_decode, block_encode, BlockStore, SphereDb, Storage, UcanStore};
+use tokio_stream::StreamExt;
 
 use crate::authority::SPHERE_SEMANTICS;
 
-use super::{SphereAction, SphereReference};
+use super::{content_prefix_of, enables_sphere_content, SphereAction};
 
-pub async fn verify_sphere_cid<S: Storage>(
+pub async fn verify_sphere_cid<S: Storage + 'static>(
     cid: &Cid,
     store: &SphereDb<S>,
     did_parser: &mut DidParser,
@@ -62,27 +64,32 @@ pub async fn verify_sphere_cid<S: Storage>(
         // Check the proof's provenance and that it enables the signer to sign
         let proof = ProofChain::from_ucan(ucan, None, did_parser, &ucan_store).await?;
 
-        let desired_capability = Capability {
-            with: With::Resource {
-                kind: Resource::Scoped(SphereReference {
-                    did: sphere.identity.to_string(),
-                }),
-            },
-            can: SphereAction::Push,
-        };
+        // A signer that may only push some of the content of the sphere may
+        // still sign it, but only if the revision changes nothing else
+        let mut content_prefixes = Vec::new();
 
         for capability_info in proof.reduce_capabilities(&SPHERE_SEMANTICS) {
-            let capability = capability_info.capability;
             if capability_info
                 .originators
                 .contains(sphere.identity.as_str())
-                && capability.enables(&desired_capability)
+                && enables_sphere_content(
+                    &capability_info.capability,
+                    &sphere.identity,
+                    SphereAction::Push,
+                )
             {
-                return Ok(());
+                match content_prefix_of(&capability_info.capability) {
+                    Some(content_prefix) => content_prefixes.push(content_prefix.to_owned()),
+                    None => return Ok(()),
+                }
             }
         }
 
-        Err(anyhow!("Proof did not enable signer to sign this sphere"))
+        if content_prefixes.is_empty() {
+            return Err(anyhow!("Proof did not enable signer to sign this sphere"));
+        }
+
+        verify_sphere_scope(cid, store, &content_prefixes).await
     } else {
         // Assume the identity is the signer
         let credential = did_parser.parse(&sphere.identity)?;
@@ -93,3 +100,373 @@ pub async fn verify_sphere_cid<S: Storage>(
         Ok(())
     }
 }
+
+/// Ensure that the revision of a sphere at the given [Cid] only changes content
+/// whose slug starts with one of the given prefixes, compared to its parent
+/// revision. The changed slugs are found by comparing the content of the
+/// revision with the content of its parent. Changes to anything other than the
+/// content of the sphere (such as its names or its authority) are never in
+/// scope, and neither is a revision that has no parent.
+pub async fn verify_sphere_scope<S: BlockStore + 'static>(
+    cid: &Cid,
+    store: &S,
+    content_prefixes: &[String],
+) -> Result<()> {
+    let sphere = Sphere::at(cid, store);
+    let parent = sphere
+        .get_parent()
+        .await?
+        .ok_or_else(|| anyhow!("Revision {} has no parent to compare against", cid))?;
+
+    let body = sphere.to_body().await?;
+    let parent_body = parent.to_body().await?;
+
+    if body.identity != parent_body.identity
+        || body.address_book != parent_body.address_book
+        || body.authority != parent_body.authority
+        || body.private != parent_body.private
+    {
+        return Err(anyhow!("Revision {} changes more than sphere content", cid));
+    }
+
+    if body.content == parent_body.content {
+        return Ok(());
+    }
+
+    // The changelog of a revision is written by whoever produced it, so it
+    // can't be trusted to describe the change; instead, the content of the
+    // revision is compared with the content of its parent
+    let mut parent_entries = BTreeMap::new();
+    let mut changed_slugs = BTreeSet::new();
+
+    let parent_content = parent.get_content().await?.into_stream().await?;
+
+    tokio::pin!(parent_content);
+
+    while let Some((slug, memo)) = parent_content.try_next().await? {
+        parent_entries.insert(slug, memo);
+    }
+
+    let content = sphere.get_content().await?.into_stream().await?;
+
+    tokio::pin!(content);
+
+    while let Some((slug, memo)) = content.try_next().await? {
+        if parent_entries.remove(&slug) != Some(memo) {
+            changed_slugs.insert(slug);
+        }
+    }
+
+    changed_slugs.extend(parent_entries.into_keys());
+
+    for slug in changed_slugs {
+        if !content_prefixes
+            .iter()
+            .any(|prefix| slug.starts_with(prefix.as_str()))
+        {
+            return Err(anyhow!(
+                "Revision {} changes out-of-scope slug {:?}",
+                cid,
+                slug
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Walk every UCAN in a [ProofChain] and look it up in the revocations of the
+/// given sphere, returning an error if any of them has been revoked. A
+/// revocation is only honored if it refers to the UCAN it is recorded for, it
+/// is issued by the issuer of that UCAN (or of any UCAN in its proof chain)
+/// and its signature can be verified against the key of its issuer; any other
+/// revocation is ignored.
+pub async fn verify_proof_chain_unrevoked<S: BlockStore>(
+    proof_chain: &ProofChain,
+    sphere: &Sphere<S>,
+    did_parser: &mut DidParser,
+) -> Result<()> {
+    let revocations = sphere.get_authority().await?.get_revocations().await?;
+    let mut remaining_chains = vec![proof_chain];
+
+    while let Some(proof_chain) = remaining_chains.pop() {
+        let (jwt_cid, _) = block_encode::<RawCodec, _>(&Ipld::Bytes(
+            proof_chain.ucan().encode()?.as_bytes().to_vec(),
+        ))?;
+
+        if let Some(revocation) = revocations.get(&Link::new(jwt_cid)).await? {
+            if Cid::try_from(revocation.revoke.as_str()).ok() != Some(jwt_cid) {
+                warn!("Ignoring revocation recorded for {jwt_cid} that refers to a different UCAN");
+            } else if !proof_chain_issuers(proof_chain).contains(revocation.iss.as_str()) {
+                warn!(
+                    "Ignoring revocation of {jwt_cid} by {}, who has no authority over it",
+                    revocation.iss
+                );
+            } else {
+                let issuer = did_parser.parse(&revocation.iss)?;
+
+                match revocation.verify(&*issuer).await {
+                    Ok(_) => return Err(anyhow!("UCAN {jwt_cid} has been revoked")),
+                    Err(error) => {
+                        warn!(
+                            "Ignoring revocation of {jwt_cid} that could not be verified: {error}"
+                        )
+                    }
+                }
+            }
+        }
+
+        remaining_chains.extend(proof_chain.proofs().iter());
+    }
+
+    Ok(())
+}
+
+/// The issuers of the UCAN at the root of a [ProofChain] and of every UCAN in
+/// its proofs; these are the only parties who may revoke that UCAN
+fn proof_chain_issuers(proof_chain: &ProofChain) -> BTreeSet<&str> {
+    let mut issuers = BTreeSet::new();
+    let mut remaining_chains = vec![proof_chain];
+
+    while let Some(proof_chain) = remaining_chains.pop() {
+        issuers.insert(proof_chain.ucan().issuer());
+        remaining_chains.extend(proof_chain.proofs().iter());
+    }
+
+    issuers
+}
+
+#[cfg(test)]
+mod tests {
+    use cid::Cid;
+    use libipld_cbor::DagCborCodec;
+    use noosphere_storage::{BlockStore, MemoryStorage, MemoryStore, SphereDb, UcanStore};
+    use ucan::{
+        builder::UcanBuilder,
+        capability::{Capability, Resource, With},
+        chain::ProofChain,
+        crypto::{did::DidParser, KeyMaterial},
+        store::UcanJwtStore,
+    };
+
+    use crate::{
+        authority::{
+            generate_ed25519_key, Authorization, SphereAction, SphereReference, SUPPORTED_KEYS,
+        },
+        data::{DelegationIpld, Did, IdentityIpld, Link, MemoIpld, RevocationIpld},
+        view::{Sphere, SphereMutation},
+    };
+
+    use super::{verify_proof_chain_unrevoked, verify_sphere_cid};
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    #[cfg(target_arch = "wasm32")]
+    wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_browser);
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[cfg_attr(not(target_arch = "wasm32"), tokio::test)]
+    async fn it_rejects_a_proof_chain_once_any_of_its_ucans_is_revoked() {
+        let mut store = MemoryStore::default();
+        let owner_key = generate_ed25519_key();
+        let owner_did = owner_key.get_did().await.unwrap();
+        let mut did_parser = DidParser::new(SUPPORTED_KEYS);
+
+        let (mut sphere, authorization, _) =
+            Sphere::generate(&owner_did, &mut store).await.unwrap();
+        let sphere_identity = sphere.get_identity().await.unwrap();
+
+        let owner_ucan = authorization
+            .resolve_ucan(&UcanStore(store.clone()))
+            .await
+            .unwrap();
+
+        let device_key = generate_ed25519_key();
+        let device_ucan = UcanBuilder::default()
+            .issued_by(&owner_key)
+            .for_audience(&device_key.get_did().await.unwrap())
+            .claiming_capability(&Capability {
+                with: With::Resource {
+                    kind: Resource::Scoped(SphereReference::new(sphere_identity.to_string())),
+                },
+                can: SphereAction::Push,
+            })
+            .witnessed_by(&owner_ucan)
+            .with_expiration(*owner_ucan.expires_at())
+            .build()
+            .unwrap()
+            .sign()
+            .await
+            .unwrap();
+
+        let delegation = DelegationIpld::register("device", &device_ucan.encode().unwrap(), &store)
+            .await
+            .unwrap();
+
+        let proof_chain = ProofChain::from_ucan(
+            device_ucan,
+            None,
+            &mut did_parser,
+            &UcanStore(store.clone()),
+        )
+        .await
+        .unwrap();
+
+        assert!(
+            verify_proof_chain_unrevoked(&proof_chain, &sphere, &mut did_parser)
+                .await
+                .is_ok()
+        );
+
+        // A revocation with a signature from the wrong key is ignored
+        let mut mutation = SphereMutation::new(&owner_did);
+        let mut forged_revocation = RevocationIpld::revoke(&delegation.jwt, &device_key)
+            .await
+            .unwrap();
+        forged_revocation.iss = owner_did.to_string();
+
+        mutation
+            .revocations_mut()
+            .set(&Link::new(delegation.jwt), &forged_revocation);
+
+        let mut revision = sphere.apply_mutation(&mutation).await.unwrap();
+        let next_cid = revision
+            .sign(&owner_key, Some(&authorization))
+            .await
+            .unwrap();
+        sphere = Sphere::at(&next_cid, &store);
+
+        assert!(
+            verify_proof_chain_unrevoked(&proof_chain, &sphere, &mut did_parser)
+                .await
+                .is_ok()
+        );
+
+        // A revocation by a key that has no authority over the revoked UCAN
+        // is ignored, even if its signature is valid
+        let owner_jwt = Cid::try_from(&authorization).unwrap();
+        let mut mutation = SphereMutation::new(&owner_did);
+
+        mutation.revocations_mut().set(
+            &Link::new(owner_jwt),
+            &RevocationIpld::revoke(&owner_jwt, &device_key)
+                .await
+                .unwrap(),
+        );
+
+        let mut revision = sphere.apply_mutation(&mutation).await.unwrap();
+        let next_cid = revision
+            .sign(&owner_key, Some(&authorization))
+            .await
+            .unwrap();
+        sphere = Sphere::at(&next_cid, &store);
+
+        assert!(
+            verify_proof_chain_unrevoked(&proof_chain, &sphere, &mut did_parser)
+                .await
+                .is_ok()
+        );
+
+        // A valid revocation of any UCAN in the chain rejects the chain
+        let mut mutation = SphereMutation::new(&owner_did);
+
+        mutation.revocations_mut().set(
+            &Link::new(delegation.jwt),
+            &RevocationIpld::revoke(&delegation.jwt, &owner_key)
+                .await
+                .unwrap(),
+        );
+
+        let mut revision = sphere.apply_mutation(&mutation).await.unwrap();
+        let next_cid = revision
+            .sign(&owner_key, Some(&authorization))
+            .await
+            .unwrap();
+        sphere = Sphere::at(&next_cid, &store);
+
+        assert!(
+            verify_proof_chain_unrevoked(&proof_chain, &sphere, &mut did_parser)
+                .await
+                .is_err()
+        );
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[cfg_attr(not(target_arch = "wasm32"), tokio::test)]
+    async fn it_only_verifies_revisions_within_the_content_scope_of_the_signer() {
+        let mut db = SphereDb::new(&MemoryStorage::default()).await.unwrap();
+        let owner_key = generate_ed25519_key();
+        let owner_did = owner_key.get_did().await.unwrap();
+        let mut did_parser = DidParser::new(SUPPORTED_KEYS);
+
+        let (sphere, authorization, _) = Sphere::generate(&owner_did, &mut db).await.unwrap();
+        let sphere_identity = sphere.get_identity().await.unwrap();
+
+        let owner_ucan = authorization.resolve_ucan(&db).await.unwrap();
+
+        let device_key = generate_ed25519_key();
+        let device_did = device_key.get_did().await.unwrap();
+        let device_ucan = UcanBuilder::default()
+            .issued_by(&owner_key)
+            .for_audience(&device_did)
+            .claiming_capability(&Capability {
+                with: With::Resource {
+                    kind: Resource::Scoped(SphereReference::content(
+                        sphere_identity.as_str(),
+                        "blog/",
+                    )),
+                },
+                can: SphereAction::Push,
+            })
+            .witnessed_by(&owner_ucan)
+            .with_expiration(*owner_ucan.expires_at())
+            .build()
+            .unwrap()
+            .sign()
+            .await
+            .unwrap();
+        let device_authorization = Authorization::Cid(
+            db.write_token(&device_ucan.encode().unwrap())
+                .await
+                .unwrap(),
+        );
+
+        let memo = MemoIpld::for_body(&mut db, b"Hello").await.unwrap();
+        let memo_link: Link<MemoIpld> = db.save::<DagCborCodec, _>(&memo).await.unwrap().into();
+
+        for (slug, in_scope) in [("blog/hello", true), ("notes/hello", false)] {
+            let mut mutation = SphereMutation::new(&device_did);
+            mutation.content_mut().set(&slug.to_string(), &memo_link);
+
+            let mut revision = sphere.apply_mutation(&mutation).await.unwrap();
+            let cid = revision
+                .sign(&device_key, Some(&device_authorization))
+                .await
+                .unwrap();
+
+            assert_eq!(
+                verify_sphere_cid(&cid, &db, &mut did_parser).await.is_ok(),
+                in_scope
+            );
+        }
+
+        // A revision that changes anything other than content is out of scope
+        let mut mutation = SphereMutation::new(&device_did);
+        mutation.identities_mut().set(
+            &String::from("bob"),
+            &IdentityIpld {
+                did: Did(device_did.clone()),
+                link_record: None,
+            },
+        );
+
+        let mut revision = sphere.apply_mutation(&mutation).await.unwrap();
+        let cid = revision
+            .sign(&device_key, Some(&device_authorization))
+            .await
+            .unwrap();
+
+        assert!(verify_sphere_cid(&cid, &db, &mut did_parser).await.is_err());
+    }
+}
@@ -10,8 +10,11 @@ pub const BODY_CHUNK_MAX_SIZE: usize = 1024 * 64 * 8; // ~.5mb/chunk worst case
 
 /// A body chunk is a simplified flexible byte layout used for linking
 /// chunks of bytes. This is necessary to support cases when body contents
-/// byte size exceeds the IPFS block size (~1MB). This may be replaced with
-/// a more sophisticated layout structure in the future.
+/// byte size exceeds the IPFS block size (~1MB). Bodies were originally
+/// stored as a linked list of chunks; they are now stored as a tree (see
+/// [BodyTreeIpld]), where each leaf is a chunk with no `next` chunk.
+///
+/// [BodyTreeIpld]: crate::data::BodyTreeIpld
 #[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
 pub struct BodyChunkIpld {
     /// A chunk of bytes
@@ -21,16 +24,12 @@ pub struct BodyChunkIpld {
 }
 
 impl BodyChunkIpld {
-    pub async fn store_bytes<S: BlockStore>(bytes: &[u8], store: &mut S) -> Result<Cid> {
-        let chunks = FastCDC::new(
-            bytes,
-            fastcdc::ronomon::MINIMUM_MIN,
-            BODY_CHUNK_MAX_SIZE / 2,
-            BODY_CHUNK_MAX_SIZE,
-        );
+    /// Split bytes into content-defined chunks of at most
+    /// [BODY_CHUNK_MAX_SIZE] bytes each
+    pub(crate) fn chunk_bytes(bytes: &[u8]) -> Vec<&[u8]> {
         let mut byte_chunks = Vec::new();
 
-        for chunk in chunks {
+        for chunk in BodyChunkIpld::chunker(bytes) {
             let length = chunk.length;
             let offset = chunk.offset;
             let end = offset + length;
@@ -39,6 +38,35 @@ impl BodyChunkIpld {
             byte_chunks.push(bytes);
         }
 
+        byte_chunks
+    }
+
+    /// The length of the first content-defined chunk of some bytes. Only the
+    /// first [BODY_CHUNK_MAX_SIZE] bytes are considered, so bytes can be
+    /// chunked incrementally as they arrive.
+    pub(crate) fn first_chunk_length(bytes: &[u8]) -> usize {
+        BodyChunkIpld::chunker(bytes)
+            .next()
+            .map(|chunk| chunk.length)
+            .unwrap_or_default()
+    }
+
+    fn chunker(bytes: &[u8]) -> FastCDC {
+        FastCDC::new(
+            bytes,
+            fastcdc::ronomon::MINIMUM_MIN,
+            BODY_CHUNK_MAX_SIZE / 2,
+            BODY_CHUNK_MAX_SIZE,
+        )
+    }
+
+    /// Store bytes as a linked list of chunks. New bodies should be stored
+    /// with [BodyTreeIpld::store_bytes] instead, which allows them to be read
+    /// in random order.
+    ///
+    /// [BodyTreeIpld::store_bytes]: crate::data::BodyTreeIpld::store_bytes
+    pub async fn store_bytes<S: BlockStore>(bytes: &[u8], store: &mut S) -> Result<Cid> {
+        let byte_chunks = BodyChunkIpld::chunk_bytes(bytes);
         let mut next_chunk_cid = None;
 
         for byte_chunk in byte_chunks.into_iter().rev() {
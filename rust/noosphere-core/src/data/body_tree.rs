@@ -0,0 +1,488 @@
+use std::ops::Range;
+
+use anyhow::{anyhow, Result};
+use cid::Cid;
+use libipld_cbor::DagCborCodec;
+use libipld_core::{ipld::Ipld, serde::from_ipld};
+use serde::{Deserialize, Serialize};
+
+use noosphere_storage::{block_decode, BlockStore};
+
+use crate::data::{BodyChunkIpld, BODY_CHUNK_MAX_SIZE};
+
+/// The maximum number of children of each node in a body tree (the same as
+/// the width of a balanced UnixFS DAG)
+pub const BODY_TREE_MAX_LINKS: usize = 174;
+
+/// A node in a balanced tree of body chunks. The leaves of the tree are
+/// [BodyChunkIpld]s (with no `next` chunk), and every other node is a
+/// [BodyTreeIpld]. Each node records the cumulative size of its children, so
+/// the chunk that holds any given byte of a body can be found by loading only
+/// one node at each level of the tree.
+#[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
+pub struct BodyTreeIpld {
+    /// The children of this node, in order
+    pub links: Vec<Cid>,
+    /// The number of bytes in all of the children of this node up to and
+    /// including the child at the same index in `links`
+    pub cumulative_sizes: Vec<u64>,
+}
+
+impl BodyTreeIpld {
+    /// The total number of bytes in the chunks below this node
+    pub fn size(&self) -> u64 {
+        self.cumulative_sizes.last().copied().unwrap_or_default()
+    }
+
+    /// Store bytes as a balanced tree of chunks, returning the [Cid] of the
+    /// root of the tree. Bytes that fit in a single chunk are stored as a lone
+    /// [BodyChunkIpld].
+    pub async fn store_bytes<S: BlockStore>(bytes: &[u8], store: &mut S) -> Result<Cid> {
+        BodyTreeIpld::store_bytes_with_width(bytes, BODY_TREE_MAX_LINKS, store).await
+    }
+
+    async fn store_bytes_with_width<S: BlockStore>(
+        bytes: &[u8],
+        max_links: usize,
+        store: &mut S,
+    ) -> Result<Cid> {
+        let mut writer = BodyTreeWriter::with_width(store, max_links);
+
+        writer.write(bytes).await?;
+        writer.finish().await
+    }
+}
+
+/// Stores a body as a balanced tree of chunks (see [BodyTreeIpld]) as its
+/// bytes are written, so that the whole body never has to be held in memory.
+/// The resulting tree is identical to the one that [BodyTreeIpld::store_bytes]
+/// produces for the same bytes.
+pub struct BodyTreeWriter<'a, S: BlockStore> {
+    store: &'a mut S,
+    max_links: usize,
+    /// Bytes that have been written but not yet stored as a chunk; never
+    /// more than [BODY_CHUNK_MAX_SIZE] bytes
+    buffer: Vec<u8>,
+    /// The chunks that have been stored so far, along with their sizes
+    leaves: Vec<(Cid, u64)>,
+}
+
+impl<'a, S: BlockStore> BodyTreeWriter<'a, S> {
+    pub fn new(store: &'a mut S) -> Self {
+        BodyTreeWriter::with_width(store, BODY_TREE_MAX_LINKS)
+    }
+
+    fn with_width(store: &'a mut S, max_links: usize) -> Self {
+        BodyTreeWriter {
+            store,
+            max_links,
+            buffer: Vec::with_capacity(BODY_CHUNK_MAX_SIZE),
+            leaves: Vec::new(),
+        }
+    }
+
+    /// Append bytes to the body, storing each chunk as soon as its end is
+    /// known
+    pub async fn write(&mut self, mut bytes: &[u8]) -> Result<()> {
+        while !bytes.is_empty() {
+            let length = (BODY_CHUNK_MAX_SIZE - self.buffer.len()).min(bytes.len());
+
+            self.buffer.extend_from_slice(&bytes[..length]);
+            bytes = &bytes[length..];
+
+            // The end of the next chunk only depends on the bytes that
+            // follow its start, up to the maximum size of a chunk
+            if self.buffer.len() == BODY_CHUNK_MAX_SIZE {
+                self.store_next_chunk().await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Store any remaining bytes along with the rest of the tree, returning
+    /// the [Cid] of its root
+    pub async fn finish(mut self) -> Result<Cid> {
+        while !self.buffer.is_empty() {
+            self.store_next_chunk().await?;
+        }
+
+        let mut nodes = self.leaves;
+
+        while nodes.len() > 1 {
+            let mut parents = Vec::new();
+
+            for children in nodes.chunks(self.max_links) {
+                let mut tree = BodyTreeIpld {
+                    links: Vec::with_capacity(children.len()),
+                    cumulative_sizes: Vec::with_capacity(children.len()),
+                };
+
+                for (cid, size) in children {
+                    tree.links.push(*cid);
+                    tree.cumulative_sizes.push(tree.size() + size);
+                }
+
+                let size = tree.size();
+
+                parents.push((self.store.save::<DagCborCodec, _>(&tree).await?, size));
+            }
+
+            nodes = parents;
+        }
+
+        nodes
+            .pop()
+            .map(|(cid, _)| cid)
+            .ok_or_else(|| anyhow!("No CID; did you try to store zero bytes?"))
+    }
+
+    async fn store_next_chunk(&mut self) -> Result<()> {
+        let length = BodyChunkIpld::first_chunk_length(&self.buffer);
+        let cid = self
+            .store
+            .save::<DagCborCodec, _>(&BodyChunkIpld {
+                bytes: self.buffer[..length].to_vec(),
+                next: None,
+            })
+            .await?;
+
+        self.leaves.push((cid, length as u64));
+        self.buffer.drain(..length);
+
+        Ok(())
+    }
+}
+
+/// Any node of a memo body: either a [BodyTreeIpld], or a [BodyChunkIpld]
+/// (which may be a leaf of a tree, or the head of a linked list of chunks in
+/// a body that was stored before trees were introduced)
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub enum BodyIpld {
+    Tree(BodyTreeIpld),
+    Chunk(BodyChunkIpld),
+}
+
+impl BodyIpld {
+    /// Decode a body node from a DAG-CBOR block
+    pub fn from_block(block: &[u8]) -> Result<Self> {
+        let ipld = block_decode::<DagCborCodec, Ipld>(block)?;
+
+        Ok(match &ipld {
+            Ipld::Map(map) if map.contains_key("links") => BodyIpld::Tree(from_ipld(ipld)?),
+            _ => BodyIpld::Chunk(from_ipld(ipld)?),
+        })
+    }
+
+    /// Load the body node with the given [Cid]
+    pub async fn load<S: BlockStore>(cid: &Cid, store: &S) -> Result<Self> {
+        BodyIpld::from_block(&store.require_block(cid).await?)
+    }
+
+    /// The total number of bytes in the body; note that every chunk has to be
+    /// loaded to find the size of a body that is a linked list
+    pub async fn size<S: BlockStore>(&self, store: &S) -> Result<u64> {
+        match self {
+            BodyIpld::Tree(tree) => Ok(tree.size()),
+            BodyIpld::Chunk(chunk) => {
+                let mut size = chunk.bytes.len() as u64;
+                let mut next_cid = chunk.next;
+
+                while let Some(cid) = next_cid {
+                    let chunk = store.load::<DagCborCodec, BodyChunkIpld>(&cid).await?;
+
+                    size += chunk.bytes.len() as u64;
+                    next_cid = chunk.next;
+                }
+
+                Ok(size)
+            }
+        }
+    }
+
+    /// Load the chunk that holds the byte at the given offset into the body,
+    /// returning the offset of the start of the chunk along with the chunk,
+    /// or [None] if the offset is past the end of the body
+    pub async fn load_chunk_at<S: BlockStore>(
+        self,
+        offset: u64,
+        store: &S,
+    ) -> Result<Option<(u64, BodyChunkIpld)>> {
+        let mut node = self;
+        let mut node_offset = 0;
+
+        loop {
+            match node {
+                BodyIpld::Tree(tree) => {
+                    let relative_offset = offset - node_offset;
+                    let index = tree
+                        .cumulative_sizes
+                        .partition_point(|size| *size <= relative_offset);
+
+                    let cid = match tree.links.get(index) {
+                        Some(cid) => cid,
+                        None => return Ok(None),
+                    };
+
+                    if index > 0 {
+                        node_offset += tree.cumulative_sizes[index - 1];
+                    }
+
+                    node = BodyIpld::load(cid, store).await?;
+                }
+                BodyIpld::Chunk(mut chunk) => loop {
+                    let chunk_end = node_offset + chunk.bytes.len() as u64;
+
+                    if offset < chunk_end {
+                        return Ok(Some((node_offset, chunk)));
+                    }
+
+                    match chunk.next {
+                        Some(cid) => {
+                            node_offset = chunk_end;
+                            chunk = store.load::<DagCborCodec, _>(&cid).await?;
+                        }
+                        None => return Ok(None),
+                    }
+                },
+            }
+        }
+    }
+
+    /// Load the bytes of the body that are within the given range; the range
+    /// is truncated to the end of the body
+    pub async fn load_range<S: BlockStore>(self, range: Range<u64>, store: &S) -> Result<Vec<u8>> {
+        let mut bytes = Vec::new();
+        let mut position = range.start;
+
+        match self {
+            BodyIpld::Tree(tree) => {
+                while position < range.end {
+                    let (chunk_offset, chunk) = match BodyIpld::Tree(tree.clone())
+                        .load_chunk_at(position, store)
+                        .await?
+                    {
+                        Some(chunk) => chunk,
+                        None => break,
+                    };
+
+                    position = append_overlap(&mut bytes, chunk_offset, &chunk.bytes, &range);
+                }
+            }
+            // Linked lists are walked once, rather than from the start for
+            // every chunk in the range
+            BodyIpld::Chunk(chunk) => {
+                let mut chunk_offset = 0;
+                let mut chunk = Some(chunk);
+
+                while let Some(BodyChunkIpld {
+                    bytes: chunk_bytes,
+                    next,
+                }) = chunk.take()
+                {
+                    if position >= range.end {
+                        break;
+                    }
+
+                    if chunk_offset + chunk_bytes.len() as u64 > position {
+                        position = append_overlap(&mut bytes, chunk_offset, &chunk_bytes, &range);
+                    }
+
+                    chunk_offset += chunk_bytes.len() as u64;
+
+                    if let Some(cid) = next {
+                        chunk = Some(store.load::<DagCborCodec, _>(&cid).await?);
+                    }
+                }
+            }
+        }
+
+        Ok(bytes)
+    }
+
+    /// Load all of the bytes of the body
+    pub async fn load_all_bytes<S: BlockStore>(self, store: &S) -> Result<Vec<u8>> {
+        self.load_range(0..u64::MAX, store).await
+    }
+}
+
+/// Append the part of a chunk that overlaps with a range, returning the
+/// position in the body after the appended bytes
+fn append_overlap(bytes: &mut Vec<u8>, chunk_offset: u64, chunk: &[u8], range: &Range<u64>) -> u64 {
+    let start = range.start.saturating_sub(chunk_offset) as usize;
+    let end = (range.end - chunk_offset).min(chunk.len() as u64) as usize;
+
+    if start < end {
+        bytes.extend_from_slice(&chunk[start..end]);
+    }
+
+    chunk_offset + end as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use noosphere_storage::MemoryStore;
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    #[cfg(target_arch = "wasm32")]
+    wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_browser);
+
+    use super::*;
+
+    /// Enough varied bytes to be split into several chunks
+    fn make_bytes() -> Vec<u8> {
+        let mut state: u32 = 0x9e3779b9;
+
+        (0..(3 * 1024 * 1024))
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                state as u8
+            })
+            .collect()
+    }
+
+    fn ranges(length: u64) -> Vec<Range<u64>> {
+        vec![
+            0..length,
+            0..1,
+            length - 1..length,
+            1000..600_000,
+            length / 2..length / 2 + 1_500_000,
+            length - 10..length + 10,
+            length..length + 10,
+        ]
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[cfg_attr(not(target_arch = "wasm32"), tokio::test)]
+    async fn it_reads_ranges_of_a_body_tree() {
+        let mut store = MemoryStore::default();
+        let bytes = make_bytes();
+        let length = bytes.len() as u64;
+
+        // A narrow tree has several levels even with only a few chunks
+        let root = BodyTreeIpld::store_bytes_with_width(&bytes, 2, &mut store)
+            .await
+            .unwrap();
+
+        let body = BodyIpld::load(&root, &store).await.unwrap();
+
+        match &body {
+            BodyIpld::Tree(tree) => assert_eq!(tree.links.len(), 2),
+            _ => panic!("Expected the body to be stored as a tree"),
+        };
+
+        assert_eq!(body.size(&store).await.unwrap(), length);
+
+        for range in ranges(length) {
+            let expected = &bytes[range.start.min(length) as usize..range.end.min(length) as usize];
+
+            assert_eq!(
+                body.clone().load_range(range, &store).await.unwrap(),
+                expected
+            );
+        }
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[cfg_attr(not(target_arch = "wasm32"), tokio::test)]
+    async fn it_reads_ranges_of_a_linked_list_body() {
+        let mut store = MemoryStore::default();
+        let bytes = make_bytes();
+        let length = bytes.len() as u64;
+
+        let head = BodyChunkIpld::store_bytes(&bytes, &mut store)
+            .await
+            .unwrap();
+        let body = BodyIpld::load(&head, &store).await.unwrap();
+
+        assert!(matches!(body, BodyIpld::Chunk(_)));
+        assert_eq!(body.size(&store).await.unwrap(), length);
+
+        for range in ranges(length) {
+            let expected = &bytes[range.start.min(length) as usize..range.end.min(length) as usize];
+
+            assert_eq!(
+                body.clone().load_range(range, &store).await.unwrap(),
+                expected
+            );
+        }
+
+        let (chunk_offset, chunk) = body
+            .load_chunk_at(length - 1, &store)
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(chunk_offset + chunk.bytes.len() as u64, length);
+        assert_eq!(chunk.next, None);
+    }
+
+    /// The sizes of the chunks at the leaves of a body tree, in order
+    async fn leaf_sizes(root: &Cid, store: &MemoryStore) -> Vec<usize> {
+        let mut sizes = Vec::new();
+        let mut remaining = vec![*root];
+
+        while let Some(cid) = remaining.pop() {
+            match BodyIpld::load(&cid, store).await.unwrap() {
+                BodyIpld::Tree(tree) => remaining.extend(tree.links.into_iter().rev()),
+                BodyIpld::Chunk(chunk) => sizes.push(chunk.bytes.len()),
+            }
+        }
+
+        sizes
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[cfg_attr(not(target_arch = "wasm32"), tokio::test)]
+    async fn it_stores_the_same_tree_when_bytes_are_written_incrementally() {
+        let mut store = MemoryStore::default();
+        let bytes = make_bytes();
+
+        let expected_root = BodyTreeIpld::store_bytes_with_width(&bytes, 2, &mut store)
+            .await
+            .unwrap();
+
+        // Chunk boundaries match those found when all of the bytes are
+        // chunked at once
+        assert_eq!(
+            leaf_sizes(&expected_root, &store).await,
+            BodyChunkIpld::chunk_bytes(&bytes)
+                .iter()
+                .map(|chunk| chunk.len())
+                .collect::<Vec<usize>>()
+        );
+
+        for piece_size in [4_093, 100_003] {
+            let mut writer = BodyTreeWriter::with_width(&mut store, 2);
+
+            for piece in bytes.chunks(piece_size) {
+                writer.write(piece).await.unwrap();
+            }
+
+            assert_eq!(writer.finish().await.unwrap(), expected_root);
+        }
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[cfg_attr(not(target_arch = "wasm32"), tokio::test)]
+    async fn it_stores_small_bodies_as_a_single_chunk() {
+        let mut store = MemoryStore::default();
+
+        let root = BodyTreeIpld::store_bytes(b"Hello, tree", &mut store)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            BodyIpld::load(&root, &store).await.unwrap(),
+            BodyIpld::Chunk(BodyChunkIpld {
+                bytes: b"Hello, tree".to_vec(),
+                next: None
+            })
+        );
+    }
+}
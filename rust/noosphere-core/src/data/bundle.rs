@@ -7,14 +7,16 @@ use cid::Cid;
 use futures::{pin_mut, StreamExt};
 use libipld_cbor::DagCborCodec;
 use libipld_core::raw::RawCodec;
-use noosphere_storage::{block_deserialize, block_serialize, BlockStore};
+use noosphere_storage::{
+    block_deserialize, block_references, block_serialize, verify_cid, BlockStore,
+};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
 use crate::{
     data::{
-        AuthorityIpld, BodyChunkIpld, ChangelogIpld, ContentIpld, ContentType, DelegationIpld,
-        DelegationsIpld, Header, MapOperation, MemoIpld, RevocationIpld, RevocationsIpld,
-        SphereIpld, VersionedMapIpld, VersionedMapKey, VersionedMapValue,
+        AuthorityIpld, BodyChunkIpld, BodyIpld, ChangelogIpld, ContentIpld, ContentType,
+        DelegationIpld, DelegationsIpld, Header, MapOperation, MemoIpld, RevocationIpld,
+        RevocationsIpld, SphereIpld, VersionedMapIpld, VersionedMapKey, VersionedMapValue,
     },
     view::Timeslice,
 };
@@ -39,26 +41,35 @@ impl Bundle {
         self.0.contains_key(&cid.to_string())
     }
 
+    /// Store all of the blocks in this [Bundle] together (see
+    /// [BlockStore::put_blocks]), and then record their links
     pub async fn load_into<S: BlockStore>(&self, store: &mut S) -> Result<()> {
         debug!("Loading {} blocks into store...", self.0.len());
 
-        // TODO: Parrallelize this
-        for (cid_string, block_bytes) in self.0.iter() {
-            let cid = Cid::from_str(cid_string)?;
+        let blocks = self
+            .0
+            .iter()
+            .map(|(cid_string, block_bytes)| Ok((Cid::from_str(cid_string)?, block_bytes.clone())))
+            .collect::<Result<Vec<(Cid, Vec<u8>)>>>()?;
 
-            store.put_block(&cid, block_bytes).await?;
+        for (cid, block_bytes) in blocks.iter() {
+            if !verify_cid(cid, block_bytes)? {
+                return Err(anyhow!("Block in bundle does not match its CID {}", cid));
+            }
+        }
 
+        store.put_blocks(&blocks).await?;
+
+        for (cid, block_bytes) in blocks.iter() {
             match cid.codec() {
                 codec_id if codec_id == u64::from(DagCborCodec) => {
-                    store.put_links::<DagCborCodec>(&cid, block_bytes).await?;
+                    store.put_links::<DagCborCodec>(cid, block_bytes).await?;
                 }
                 codec_id if codec_id == u64::from(RawCodec) => {
-                    store.put_links::<RawCodec>(&cid, block_bytes).await?;
+                    store.put_links::<RawCodec>(cid, block_bytes).await?;
                 }
                 codec_id => warn!("Unrecognized codec {}; skipping...", codec_id),
             }
-
-            // TODO: Verify CID is correct, maybe?
         }
 
         Ok(())
@@ -100,6 +111,25 @@ impl Bundle {
         &self.0
     }
 
+    /// Add every block of an arbitrary IPLD DAG, following the links within
+    /// each block (see [block_references])
+    pub async fn extend_with_dag<S: BlockStore>(&mut self, root: &Cid, store: &S) -> Result<()> {
+        let mut remaining = vec![*root];
+
+        while let Some(cid) = remaining.pop() {
+            if self.contains(&cid) {
+                continue;
+            }
+
+            let block = store.require_block(&cid).await?;
+
+            remaining.extend(block_references(&cid, &block)?);
+            self.add(cid, block);
+        }
+
+        Ok(())
+    }
+
     pub async fn extend<CanBundle: TryBundle, S: BlockStore>(
         &mut self,
         cid: &Cid,
@@ -163,13 +193,18 @@ impl TryBundle for BodyChunkIpld {
         bundle: &mut Bundle,
         store: &S,
     ) -> Result<()> {
-        let mut next_cid = Some(*cid);
+        // A body may be either a tree of chunks or a linked list of chunks
+        let mut remaining_cids = vec![*cid];
 
-        while let Some(cid) = next_cid {
+        while let Some(cid) = remaining_cids.pop() {
             let bytes = store.require_block(&cid).await?;
-            let chunk = block_deserialize::<DagCborCodec, BodyChunkIpld>(&bytes)?;
+
+            match BodyIpld::from_block(&bytes)? {
+                BodyIpld::Tree(tree) => remaining_cids.extend(tree.links.into_iter().rev()),
+                BodyIpld::Chunk(chunk) => remaining_cids.extend(chunk.next),
+            };
+
             bundle.add(cid, bytes);
-            next_cid = chunk.next;
         }
 
         Ok(())
@@ -221,6 +256,9 @@ impl TryBundle for MemoIpld {
                     | ContentType::Cbor => {
                         bundle.extend::<BodyChunkIpld, _>(&self.body, store).await?;
                     }
+                    ContentType::DagCbor => {
+                        bundle.extend_with_dag(&self.body, store).await?;
+                    }
                     ContentType::Sphere => {
                         trace!("Bundling sphere revision {self_cid}...");
                         bundle.extend::<SphereIpld, _>(&self.body, store).await?;
@@ -344,8 +382,10 @@ impl TryBundle for SphereIpld {
         AuthorityIpld::extend_bundle_with_cid(&sphere.authority, bundle, store).await?;
         AddressBookIpld::extend_bundle_with_cid(&sphere.address_book, bundle, store).await?;
 
-        if let Some(_cid) = sphere.private {
-            todo!();
+        // Private content is bundled like public content; the bodies of its
+        // memos are sealed, so they are carried along as opaque body chunks
+        if let Some(cid) = sphere.private {
+            ContentIpld::extend_bundle_with_cid(&cid, bundle, store).await?;
         }
 
         Ok(())
@@ -431,9 +471,11 @@ mod tests {
     #[cfg(target_arch = "wasm32")]
     wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_browser);
 
+    use std::collections::BTreeMap;
+
     use crate::{
         authority::generate_ed25519_key,
-        data::{Bundle, ContentIpld, DelegationIpld, MemoIpld, TryBundle},
+        data::{Bundle, ContentIpld, ContentType, DelegationIpld, Header, MemoIpld, TryBundle},
         view::{Sphere, SphereMutation, Timeline},
     };
 
@@ -572,6 +614,44 @@ mod tests {
         assert!(bundle.contains(&body_cid));
     }
 
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[cfg_attr(not(target_arch = "wasm32"), tokio::test)]
+    async fn it_bundles_every_block_of_a_dag_cbor_memo_body() {
+        let mut store = MemoryStore::default();
+
+        let leaf_cid = store
+            .save::<RawCodec, _>(Ipld::Bytes(b"foobar".to_vec()))
+            .await
+            .unwrap();
+        let branch_cid = store
+            .save::<DagCborCodec, _>(Ipld::List(vec![Ipld::Link(leaf_cid)]))
+            .await
+            .unwrap();
+        let body_cid = store
+            .save::<DagCborCodec, _>(Ipld::Map(BTreeMap::from([
+                ("branch".to_string(), Ipld::Link(branch_cid)),
+                ("leaf".to_string(), Ipld::Link(leaf_cid)),
+            ])))
+            .await
+            .unwrap();
+
+        let memo = MemoIpld {
+            parent: None,
+            headers: vec![(
+                Header::ContentType.to_string(),
+                ContentType::DagCbor.to_string(),
+            )],
+            body: body_cid,
+        };
+
+        let bundle = memo.bundle(&store).await.unwrap();
+
+        assert_eq!(bundle.len(), 4);
+        assert!(bundle.contains(&body_cid));
+        assert!(bundle.contains(&branch_cid));
+        assert!(bundle.contains(&leaf_cid));
+    }
+
     #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
     #[cfg_attr(not(target_arch = "wasm32"), tokio::test)]
     async fn it_only_bundles_the_revision_delta() {
@@ -8,6 +8,9 @@ pub enum ContentType {
     Bytes,
     Cbor,
     Json,
+    /// The body of the memo is itself a DAG-CBOR block (possibly the root of
+    /// a larger DAG), rather than chunks of bytes
+    DagCbor,
     Unknown(String),
 }
 
@@ -20,6 +23,7 @@ impl Display for ContentType {
             ContentType::Bytes => "raw/bytes",
             ContentType::Cbor => "application/cbor",
             ContentType::Json => "application/json",
+            ContentType::DagCbor => "application/vnd.ipld.dag-cbor",
             ContentType::Unknown(header) => header.as_str(),
         };
 
@@ -38,6 +42,7 @@ impl FromStr for ContentType {
             "raw/bytes" => ContentType::Bytes,
             "application/json" => ContentType::Json,
             "application/cbor" => ContentType::Cbor,
+            "application/vnd.ipld.dag-cbor" => ContentType::DagCbor,
             _ => ContentType::Unknown(String::from(s)),
         })
     }
@@ -8,6 +8,8 @@ pub enum Header {
     Signature,
     Version,
     FileExtension,
+    Created,
+    Modified,
     Unknown(String),
 }
 
@@ -21,6 +23,8 @@ impl Display for Header {
             Header::Signature => "Signature",
             Header::Version => "Version",
             Header::FileExtension => "File-Extension",
+            Header::Created => "Created",
+            Header::Modified => "Modified",
             Header::Unknown(name) => name,
         };
 
@@ -40,6 +44,8 @@ impl FromStr for Header {
             "title" => Header::Title,
             "signature" => Header::Signature,
             "version" => Header::Version,
+            "created" => Header::Created,
+            "modified" => Header::Modified,
             _ => Header::Unknown(s.to_string()),
         })
     }
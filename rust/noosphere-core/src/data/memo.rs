@@ -80,7 +80,8 @@ impl MemoIpld {
 
     /// Loads a memo from the provided CID, initializes a copy of it, sets
     /// the copy's parent to the provided CID and cleans signature information
-    /// from the copy's headers; the new memo is returned.
+    /// from the copy's headers; the new memo is returned. All other headers
+    /// (notably [Header::Created]) are carried over to the new memo.
     pub async fn branch_from<S: BlockStore>(cid: &Cid, store: &S) -> Result<Self> {
         match store.load::<DagCborCodec, MemoIpld>(cid).await {
             Ok(mut memo) => {
@@ -1,6 +1,7 @@
 mod address;
 mod authority;
 mod body_chunk;
+mod body_tree;
 mod bundle;
 mod changelog;
 mod headers;
@@ -14,6 +15,7 @@ mod versioned_map;
 pub use address::*;
 pub use authority::*;
 pub use body_chunk::*;
+pub use body_tree::*;
 pub use bundle::*;
 pub use changelog::*;
 pub use headers::*;
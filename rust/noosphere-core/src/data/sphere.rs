@@ -107,9 +107,7 @@ mod tests {
 
         let capability: Capability<SphereReference, SphereAction> = Capability {
             with: With::Resource {
-                kind: Resource::Scoped(SphereReference {
-                    did: identity.to_string(),
-                }),
+                kind: Resource::Scoped(SphereReference::new(identity.to_string())),
             },
             can: SphereAction::Authorize,
         };
@@ -178,9 +176,7 @@ mod tests {
 
         let capability: Capability<SphereReference, SphereAction> = Capability {
             with: With::Resource {
-                kind: Resource::Scoped(SphereReference {
-                    did: identity.to_string(),
-                }),
+                kind: Resource::Scoped(SphereReference::new(identity.to_string())),
             },
             can: SphereAction::Authorize,
         };
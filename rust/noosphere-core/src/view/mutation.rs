@@ -51,6 +51,7 @@ impl<S: BlockStore> SphereRevision<S> {
 pub struct SphereMutation {
     did: Did,
     content: ContentMutation,
+    private_content: ContentMutation,
     identities: IdentitiesMutation,
     delegations: DelegationsMutation,
     revocations: RevocationsMutation,
@@ -61,6 +62,7 @@ impl SphereMutation {
         SphereMutation {
             did: did.into(),
             content: ContentMutation::new(did),
+            private_content: ContentMutation::new(did),
             identities: IdentitiesMutation::new(did),
             delegations: DelegationsMutation::new(did),
             revocations: RevocationsMutation::new(did),
@@ -78,6 +80,7 @@ impl SphereMutation {
     /// to set the author [Did] for a new [SphereMutation].
     pub fn reset(&mut self) {
         self.content = ContentMutation::new(&self.did);
+        self.private_content = ContentMutation::new(&self.did);
         self.identities = IdentitiesMutation::new(&self.did);
         self.delegations = DelegationsMutation::new(&self.did);
         self.revocations = RevocationsMutation::new(&self.did);
@@ -95,6 +98,14 @@ impl SphereMutation {
         &self.content
     }
 
+    pub fn private_content_mut(&mut self) -> &mut ContentMutation {
+        &mut self.private_content
+    }
+
+    pub fn private_content(&self) -> &ContentMutation {
+        &self.private_content
+    }
+
     pub fn identities_mut(&mut self) -> &mut IdentitiesMutation {
         &mut self.identities
     }
@@ -123,6 +134,7 @@ impl SphereMutation {
     /// mutation to a [Sphere]. Otherwise, false.
     pub fn is_empty(&self) -> bool {
         self.content.changes.len() == 0
+            && self.private_content.changes.len() == 0
             && self.identities.changes.len() == 0
             && self.delegations.changes.len() == 0
             && self.revocations.changes.len() == 0
@@ -135,6 +135,19 @@ impl<S: BlockStore> Sphere<S> {
         Ok(Content::at(&sphere.content, &self.store.clone()))
     }
 
+    /// Attempt to load the private (sealed) [Content] of this sphere. The
+    /// entries are memos whose bodies are encrypted, so they can be stored and
+    /// replicated like any other content without being readable by anyone who
+    /// does not hold the key that sealed them. If no private content has been
+    /// added to this sphere yet, this returns `None`.
+    pub async fn get_private_content(&self) -> Result<Option<Content<S>>> {
+        let sphere = self.to_body().await?;
+
+        Ok(sphere
+            .private
+            .map(|cid| Content::at(&cid, &self.store.clone())))
+    }
+
     /// Attempt to load the [Authority] of this sphere. If no authorizations or
     /// revocations have been added to this sphere yet, this initializes an
     /// empty [Authority] and returns it for the caller to populate.
@@ -189,6 +202,22 @@ impl<S: BlockStore> Sphere<S> {
             mutation.content_mut().apply_changelog(changelog)?;
         }
 
+        let parent_private_content = parent.get_private_content().await?;
+
+        if let Some(private_content) = self.get_private_content().await? {
+            if Some(private_content.cid()) != parent_private_content.as_ref().map(|map| map.cid()) {
+                let changelog = private_content.get_changelog().await?;
+
+                if changelog.is_empty() {
+                    return Err(anyhow!(
+                        "Private content changed but the changelog is empty"
+                    ));
+                }
+
+                mutation.private_content_mut().apply_changelog(changelog)?;
+            }
+        }
+
         let parent_address_book = parent.get_address_book().await?;
         let address_book = self.get_address_book().await?;
 
@@ -268,6 +297,15 @@ impl<S: BlockStore> Sphere<S> {
             false => sphere.content,
         };
 
+        let private_content_mutation = mutation.private_content();
+
+        if !private_content_mutation.changes().is_empty() {
+            sphere.private = Some(
+                Content::apply_with_cid(sphere.private.as_ref(), private_content_mutation, store)
+                    .await?,
+            );
+        }
+
         let identities_mutation = mutation.identities();
 
         if !identities_mutation.changes().is_empty() {
@@ -443,9 +481,7 @@ impl<S: BlockStore> Sphere<S> {
 
         let capability = Capability {
             with: With::Resource {
-                kind: Resource::Scoped(SphereReference {
-                    did: sphere_did.to_string(),
-                }),
+                kind: Resource::Scoped(SphereReference::new(sphere_did.to_string())),
             },
             can: SphereAction::Authorize,
         };
@@ -524,9 +560,7 @@ impl<S: BlockStore> Sphere<S> {
 
         let authorize_capability = Capability {
             with: With::Resource {
-                kind: Resource::Scoped(SphereReference {
-                    did: sphere_did.to_string(),
-                }),
+                kind: Resource::Scoped(SphereReference::new(sphere_did.to_string())),
             },
             can: SphereAction::Authorize,
         };
@@ -567,6 +601,9 @@ impl<S: BlockStore> Sphere<S> {
         mutation
             .delegations_mut()
             .set(&Link::new(delegation.jwt), &delegation);
+        mutation
+            .delegations_mut()
+            .remove(&Link::new(current_jwt_cid));
         mutation
             .revocations_mut()
             .set(&Link::new(current_jwt_cid), &revocation);
@@ -794,8 +831,11 @@ mod tests {
         let revocations = authority.get_revocations().await.unwrap();
 
         let new_delegation = delegations.get(&Link::new(new_jwt_cid)).await.unwrap();
+        let original_delegation = delegations.get(&Link::new(original_jwt_cid)).await.unwrap();
         let new_revocation = revocations.get(&Link::new(original_jwt_cid)).await.unwrap();
 
+        assert!(original_delegation.is_none());
+
         assert_eq!(
             new_delegation,
             Some(&DelegationIpld {
@@ -860,9 +900,9 @@ mod tests {
                 .for_audience(&next_owner_did)
                 .claiming_capability(&Capability {
                     with: With::Resource {
-                        kind: Resource::Scoped(SphereReference {
-                            did: sphere.get_identity().await.unwrap().to_string(),
-                        }),
+                        kind: Resource::Scoped(SphereReference::new(
+                            sphere.get_identity().await.unwrap().to_string(),
+                        )),
                     },
                     can: SphereAction::Publish,
                 })
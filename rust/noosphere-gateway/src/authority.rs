@@ -9,12 +9,23 @@ use axum::{
     TypedHeader,
 };
 use libipld_core::cid::Cid;
-use noosphere_core::authority::{SphereAction, SphereReference, SPHERE_SEMANTICS};
+use noosphere_core::{
+    authority::{
+        generate_capability, verify_proof_chain_unrevoked, SphereAction, SphereReference,
+        SPHERE_SEMANTICS,
+    },
+    view::Sphere,
+};
 use noosphere_sphere::SphereContext;
 use noosphere_storage::NativeStorage;
 
 use tokio::sync::Mutex;
-use ucan::{capability::Capability, chain::ProofChain, crypto::KeyMaterial, store::UcanJwtStore};
+use ucan::{
+    capability::{Capability, Resource, With},
+    chain::ProofChain,
+    crypto::KeyMaterial,
+    store::UcanJwtStore,
+};
 
 use super::GatewayScope;
 
@@ -22,7 +33,9 @@ use super::GatewayScope;
 /// embodies the authorization status of the request-maker as it is
 /// represented by a UCAN. Any request handler can use a GatewayAuthority
 /// to test if a required capability is satisfied by the authorization
-/// presented by the maker of the request.
+/// presented by the maker of the request. A GatewayAuthority is only ever
+/// constructed for a proof chain that contains no UCANs that have been
+/// revoked in the latest local version of the counterpart sphere.
 pub struct GatewayAuthority<K>
 where
     K: KeyMaterial + Clone + 'static,
@@ -56,6 +69,64 @@ where
 
         Err(StatusCode::UNAUTHORIZED)
     }
+
+    /// Like [GatewayAuthority::try_authorize], but for an action on the
+    /// content of the counterpart sphere that may be satisfied by a
+    /// delegation that is limited to some of that content. Yields `None` if
+    /// the action is authorized for all content; otherwise yields the slug
+    /// prefixes of the content that the action is authorized for.
+    pub fn try_authorize_content(
+        &self,
+        action: &SphereAction,
+    ) -> Result<Option<Vec<String>>, StatusCode> {
+        let sphere_capability = generate_capability(&self.scope.counterpart, action.clone());
+
+        if self.try_authorize(&sphere_capability).is_ok() {
+            return Ok(None);
+        }
+
+        let mut content_prefixes = Vec::new();
+
+        for capability_info in self.proof.reduce_capabilities(&SPHERE_SEMANTICS) {
+            if !capability_info
+                .originators
+                .contains(self.scope.counterpart.as_str())
+            {
+                continue;
+            }
+
+            if let With::Resource {
+                kind:
+                    Resource::Scoped(SphereReference {
+                        content_prefix: Some(content_prefix),
+                        ..
+                    }),
+            } = &capability_info.capability.with
+            {
+                let content_capability = Capability {
+                    with: With::Resource {
+                        kind: Resource::Scoped(SphereReference::content(
+                            self.scope.counterpart.as_str(),
+                            content_prefix,
+                        )),
+                    },
+                    can: action.clone(),
+                };
+
+                if capability_info.capability.enables(&content_capability) {
+                    content_prefixes.push(content_prefix.clone());
+                }
+            }
+        }
+
+        if content_prefixes.is_empty() {
+            return Err(StatusCode::UNAUTHORIZED);
+        }
+
+        debug!("Authorized for content under {:?}", content_prefixes);
+
+        Ok(Some(content_prefixes))
+    }
 }
 
 #[async_trait]
@@ -143,6 +214,25 @@ where
                     StatusCode::UNAUTHORIZED
                 })?;
 
+            let counterpart_version =
+                db.get_version(&gateway_scope.counterpart)
+                    .await
+                    .map_err(|error| {
+                        error!("{:?}", error);
+                        StatusCode::INTERNAL_SERVER_ERROR
+                    })?;
+
+            if let Some(counterpart_version) = counterpart_version {
+                let counterpart_sphere = Sphere::at(&counterpart_version, &db);
+
+                verify_proof_chain_unrevoked(&proof_chain, &counterpart_sphere, did_parser)
+                    .await
+                    .map_err(|error| {
+                        warn!("{:?}", error);
+                        StatusCode::UNAUTHORIZED
+                    })?;
+            }
+
             proof_chain
         };
 
@@ -1,11 +1,11 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use axum::http::{HeaderValue, Method};
 use axum::routing::{get, put};
-use axum::{Extension, Router, Server};
+use axum::{middleware, Extension, Router, Server};
 use noosphere_core::data::Did;
 use noosphere_ipfs::KuboClient;
 use noosphere_sphere::HasMutableSphereContext;
-use noosphere_storage::Storage;
+use noosphere_storage::{Storage, Store};
 use std::net::TcpListener;
 use tower_http::cors::{Any, CorsLayer};
 use tower_http::trace::TraceLayer;
@@ -15,10 +15,14 @@ use url::Url;
 use noosphere_api::route::Route as GatewayRoute;
 
 use crate::{
-    route::{did_route, fetch_route, identify_route, push_route, replicate_route},
+    route::{
+        approve_pairing_route, did_route, fetch_route, identify_route, pair_route,
+        pairing_status_route, publish_route, push_route, replicate_route, GatewayPairings,
+    },
+    tenant::{require_tenant, resolve_tenant, GatewayTenants},
     worker::{
-        start_ipfs_syndication, start_name_system, NameSystemConfiguration,
-        NameSystemConnectionType,
+        start_garbage_collection, start_ipfs_syndication, start_name_system,
+        NameSystemConfiguration, NameSystemConnectionType,
     },
 };
 
@@ -44,13 +48,69 @@ where
     C: HasMutableSphereContext<K, S> + 'static,
     K: KeyMaterial + Clone + 'static,
     S: Storage + 'static,
+    S::BlockStore: Store,
+    S::KeyValueStore: Store,
+{
+    start_multi_tenant_gateway(
+        listener,
+        vec![(gateway_scope, sphere_context)],
+        ipfs_api,
+        name_resolver_api,
+        cors_origin,
+    )
+    .await
+}
+
+/// Same as [start_gateway], but serves many counterpart spheres from a single
+/// process. Each tenant is a pair of a [GatewayScope] and the sphere context
+/// of the gateway sphere that reflects that counterpart, so every tenant keeps
+/// its own history and storage. Requests are routed to a tenant by the
+/// counterpart sphere that they are authorized for (see [resolve_tenant]).
+///
+/// All tenant spheres must be owned by the same gateway key, since that key is
+/// the identity that clients address their authorizations to.
+pub async fn start_multi_tenant_gateway<C, K, S>(
+    listener: TcpListener,
+    tenants: Vec<(GatewayScope, C)>,
+    ipfs_api: Url,
+    name_resolver_api: Url,
+    cors_origin: Option<Url>,
+) -> Result<()>
+where
+    C: HasMutableSphereContext<K, S> + 'static,
+    K: KeyMaterial + Clone + 'static,
+    S: Storage + 'static,
+    S::BlockStore: Store,
+    S::KeyValueStore: Store,
 {
     initialize_tracing(None);
 
-    let gateway_key_did = {
-        let sphere_context = sphere_context.sphere_context().await?;
-        sphere_context.author().identity().await?
-    };
+    let mut gateway_key_did: Option<Did> = None;
+
+    for (scope, sphere_context) in tenants.iter() {
+        let key_did = {
+            let sphere_context = sphere_context.sphere_context().await?;
+            sphere_context.author().identity().await?
+        };
+
+        match &gateway_key_did {
+            Some(gateway_key_did) if gateway_key_did != &key_did => {
+                return Err(anyhow!(
+                    "Gateway sphere {} is not managed by the gateway key {}",
+                    scope.identity,
+                    gateway_key_did
+                ));
+            }
+            Some(_) => (),
+            None => gateway_key_did = Some(key_did),
+        }
+    }
+
+    let gateway_key_did =
+        gateway_key_did.ok_or_else(|| anyhow!("A gateway needs at least one sphere to manage"))?;
+
+    let tenants = GatewayTenants::new(tenants);
+
     let mut cors = CorsLayer::new();
 
     if let Some(cors_origin) = cors_origin {
@@ -80,11 +140,11 @@ where
             connection_type: NameSystemConnectionType::Remote(name_resolver_api),
             ipfs_api,
         },
-        vec![sphere_context.clone()],
+        tenants.contexts(),
     );
+    let garbage_collection_task = start_garbage_collection::<C, K, S>(tenants.contexts());
 
-    let app = Router::new()
-        .route(&GatewayRoute::Did.to_string(), get(did_route))
+    let tenant_routes = Router::new()
         .route(
             &GatewayRoute::Replicate(None).to_string(),
             get(replicate_route::<C, K, S>),
@@ -94,12 +154,31 @@ where
             get(identify_route::<C, K, S>),
         )
         .route(&GatewayRoute::Push.to_string(), put(push_route::<C, K, S>))
+        .route(
+            &GatewayRoute::Publish.to_string(),
+            put(publish_route::<C, K, S>),
+        )
         .route(
             &GatewayRoute::Fetch.to_string(),
             get(fetch_route::<C, K, S>),
         )
-        .layer(Extension(sphere_context.clone()))
-        .layer(Extension(gateway_scope.clone()))
+        .route(
+            &GatewayRoute::Pairing(None).to_string(),
+            put(approve_pairing_route::<C, K, S>),
+        )
+        .route_layer(middleware::from_fn(require_tenant));
+
+    let app = Router::new()
+        .route(&GatewayRoute::Did.to_string(), get(did_route))
+        .route(&GatewayRoute::Pair.to_string(), put(pair_route::<C>))
+        .route(
+            &GatewayRoute::Pairing(None).to_string(),
+            get(pairing_status_route::<K>),
+        )
+        .merge(tenant_routes)
+        .layer(middleware::from_fn(resolve_tenant::<C>))
+        .layer(Extension(tenants.clone()))
+        .layer(Extension(GatewayPairings::default()))
         .layer(Extension(ipfs_client))
         .layer(Extension(gateway_key_did))
         .layer(Extension(syndication_tx))
@@ -107,17 +186,19 @@ where
         .layer(cors)
         .layer(TraceLayer::new_for_http());
 
-    info!(
-        r#"A geist is summoned to manage local sphere {}
+    for gateway_scope in tenants.scopes() {
+        info!(
+            r#"A geist is summoned to manage local sphere {}
 
 It has bound a gateway to {:?}
 It awaits updates from sphere {}..."#,
-        gateway_scope.identity,
-        listener
-            .local_addr()
-            .expect("Unexpected missing listener address"),
-        gateway_scope.counterpart
-    );
+            gateway_scope.identity,
+            listener
+                .local_addr()
+                .expect("Unexpected missing listener address"),
+            gateway_scope.counterpart
+        );
+    }
 
     Server::from_tcp(listener)?
         .serve(app.into_make_service())
@@ -125,6 +206,7 @@ It awaits updates from sphere {}..."#,
 
     syndication_task.abort();
     name_system_task.abort();
+    garbage_collection_task.abort();
 
     Ok(())
 }
@@ -20,5 +20,11 @@ mod route;
 #[cfg(not(target_arch = "wasm32"))]
 mod gateway;
 
+#[cfg(not(target_arch = "wasm32"))]
+mod tenant;
+
 #[cfg(not(target_arch = "wasm32"))]
 pub use gateway::*;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use tenant::*;
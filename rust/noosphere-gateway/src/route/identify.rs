@@ -23,9 +23,7 @@ where
 
     authority.try_authorize(&Capability {
         with: With::Resource {
-            kind: Resource::Scoped(SphereReference {
-                did: scope.counterpart.to_string(),
-            }),
+            kind: Resource::Scoped(SphereReference::new(scope.counterpart.to_string())),
         },
         can: SphereAction::Fetch,
     })?;
@@ -1,11 +1,15 @@
 mod did;
 mod fetch;
 mod identify;
+mod pair;
+mod publish;
 mod push;
 mod replicate;
 
 pub use did::*;
 pub use fetch::*;
 pub use identify::*;
+pub use pair::*;
+pub use publish::*;
 pub use push::*;
 pub use replicate::*;
@@ -0,0 +1,277 @@
+use std::{collections::BTreeMap, str::FromStr, sync::Arc};
+
+use anyhow::Result;
+use axum::{
+    extract::{Path, Query},
+    http::StatusCode,
+    Extension,
+};
+use cid::Cid;
+use noosphere_api::data::{
+    PairingApproval, PairingError, PairingRequest, PairingStatus, PairingStatusParameters,
+};
+use noosphere_core::{
+    authority::{SphereAction, SphereReference, SUPPORTED_KEYS},
+    data::Did,
+};
+use noosphere_sphere::HasMutableSphereContext;
+use noosphere_storage::Storage;
+use tokio::sync::{Mutex, MutexGuard};
+use ucan::{
+    capability::{Capability, Resource, With},
+    crypto::{did::DidParser, KeyMaterial},
+    store::UcanJwtStore,
+    Ucan,
+};
+
+use crate::{authority::GatewayAuthority, extractor::Cbor, tenant::GatewayTenants, GatewayScope};
+
+/// How long (in seconds) a pairing request is held by the gateway before it
+/// is forgotten, whether or not it has been approved
+pub const PAIRING_REQUEST_LIFETIME: u64 = 60 * 10;
+
+/// The most requests that may be pending for any one sphere at a time; since
+/// requests are held for [PAIRING_REQUEST_LIFETIME] seconds, this also limits
+/// how often a sphere may be asked to pair
+pub const MAX_PENDING_PAIRINGS_PER_SPHERE: usize = 8;
+
+/// The most requests that may be pending across all spheres at a time
+pub const MAX_PENDING_PAIRINGS: usize = 1024;
+
+/// The longest pairing code that is accepted
+pub const MAX_PAIRING_CODE_LENGTH: usize = 64;
+
+struct PendingPairing {
+    sphere: Did,
+    did: Did,
+    signature: String,
+    authorization: Option<Cid>,
+    expires_at: u64,
+}
+
+impl From<&PendingPairing> for PairingStatus {
+    fn from(pending: &PendingPairing) -> Self {
+        PairingStatus {
+            sphere: pending.sphere.clone(),
+            did: pending.did.clone(),
+            authorization: pending.authorization,
+        }
+    }
+}
+
+/// The requests from devices to be paired with the spheres that this gateway
+/// manages, keyed by their pairing code. Requests are only held in memory,
+/// only for [PAIRING_REQUEST_LIFETIME] seconds, and only up to
+/// [MAX_PENDING_PAIRINGS] at a time.
+#[derive(Clone, Default)]
+pub struct GatewayPairings {
+    pending: Arc<Mutex<BTreeMap<String, PendingPairing>>>,
+}
+
+impl GatewayPairings {
+    /// Lock the pending requests, forgetting any that have expired
+    async fn lock_unexpired(&self) -> MutexGuard<'_, BTreeMap<String, PendingPairing>> {
+        let now = ucan::time::now();
+        let mut pending = self.pending.lock().await;
+
+        pending.retain(|_, pairing| pairing.expires_at > now);
+        pending
+    }
+}
+
+/// Accepts a request from a device to be paired with a sphere. The request is
+/// not authorized (the device has no authorization yet), but it must be signed
+/// by the key that it asks to have authorized; it is only held until the owner
+/// of the sphere approves it, or until it expires.
+#[instrument(level = "debug", skip(tenants, pairings, request_body))]
+pub async fn pair_route<C>(
+    Extension(tenants): Extension<GatewayTenants<C>>,
+    Extension(pairings): Extension<GatewayPairings>,
+    Cbor(request_body): Cbor<PairingRequest>,
+) -> Result<(), StatusCode>
+where
+    C: Clone,
+{
+    debug!("Invoking pair route...");
+
+    if tenants.get(&request_body.sphere).is_none() {
+        return Err(PairingError::UnknownSphere.into());
+    }
+
+    if request_body.code.is_empty() || request_body.code.len() > MAX_PAIRING_CODE_LENGTH {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let mut did_parser = DidParser::new(SUPPORTED_KEYS);
+
+    if let Err(error) = request_body.verify(&mut did_parser).await {
+        warn!("Pairing request has an invalid signature: {}", error);
+        return Err(PairingError::InvalidSignature.into());
+    }
+
+    let mut pending = pairings.lock_unexpired().await;
+
+    if pending.contains_key(&request_body.code) {
+        return Err(PairingError::CodeInUse.into());
+    }
+
+    let pending_for_sphere = pending
+        .values()
+        .filter(|pairing| pairing.sphere == request_body.sphere)
+        .count();
+
+    if pending.len() >= MAX_PENDING_PAIRINGS
+        || pending_for_sphere >= MAX_PENDING_PAIRINGS_PER_SPHERE
+    {
+        warn!(
+            "Refusing to hold another pairing request for {}",
+            request_body.sphere
+        );
+        return Err(PairingError::TooManyRequests.into());
+    }
+
+    pending.insert(
+        request_body.code,
+        PendingPairing {
+            sphere: request_body.sphere,
+            did: request_body.did,
+            signature: request_body.signature,
+            authorization: None,
+            expires_at: ucan::time::now() + PAIRING_REQUEST_LIFETIME,
+        },
+    );
+
+    Ok(())
+}
+
+/// Reports the status of a pending pairing request, including the identity of
+/// the authorization that was granted once the request has been approved. The
+/// status is only reported to the device that made the request (which presents
+/// the signature from its request), or to a key that is authorized to approve
+/// it; to anyone else, the request appears not to exist.
+#[instrument(level = "debug", skip(authority, gateway_scope, pairings))]
+pub async fn pairing_status_route<K>(
+    authority: Option<GatewayAuthority<K>>,
+    gateway_scope: Option<Extension<GatewayScope>>,
+    Extension(pairings): Extension<GatewayPairings>,
+    Path(code): Path<String>,
+    Query(PairingStatusParameters { signature }): Query<PairingStatusParameters>,
+) -> Result<Cbor<PairingStatus>, StatusCode>
+where
+    K: KeyMaterial + Clone + 'static,
+{
+    debug!("Invoking pairing status route...");
+
+    let pending = pairings.lock_unexpired().await;
+    let pairing = pending.get(&code).ok_or(PairingError::UnknownRequest)?;
+
+    let is_requester = signature.as_ref() == Some(&pairing.signature);
+    let is_approver = match (authority, gateway_scope) {
+        (Some(authority), Some(Extension(gateway_scope))) => {
+            pairing.sphere == gateway_scope.counterpart
+                && authority
+                    .try_authorize(&Capability {
+                        with: With::Resource {
+                            kind: Resource::Scoped(SphereReference::new(
+                                gateway_scope.counterpart.to_string(),
+                            )),
+                        },
+                        can: SphereAction::Authorize,
+                    })
+                    .is_ok()
+        }
+        _ => false,
+    };
+
+    if !is_requester && !is_approver {
+        return Err(PairingError::UnknownRequest.into());
+    }
+
+    Ok(Cbor(pairing.into()))
+}
+
+/// Approves a pending pairing request on behalf of the owner of the
+/// counterpart sphere. The approval names an authorization that must already
+/// have been synced to the gateway, and that must be addressed to the key
+/// that requested to be paired.
+#[instrument(
+    level = "debug",
+    skip(authority, sphere_context, gateway_scope, pairings, request_body)
+)]
+pub async fn approve_pairing_route<C, K, S>(
+    authority: GatewayAuthority<K>,
+    Extension(sphere_context): Extension<C>,
+    Extension(gateway_scope): Extension<GatewayScope>,
+    Extension(pairings): Extension<GatewayPairings>,
+    Path(code): Path<String>,
+    Cbor(request_body): Cbor<PairingApproval>,
+) -> Result<Cbor<PairingStatus>, StatusCode>
+where
+    C: HasMutableSphereContext<K, S>,
+    K: KeyMaterial + Clone,
+    S: Storage + 'static,
+{
+    debug!("Invoking approve pairing route...");
+
+    authority.try_authorize(&Capability {
+        with: With::Resource {
+            kind: Resource::Scoped(SphereReference::new(gateway_scope.counterpart.to_string())),
+        },
+        can: SphereAction::Authorize,
+    })?;
+
+    let did = {
+        let pending = pairings.lock_unexpired().await;
+
+        match pending.get(&code) {
+            Some(pairing) if pairing.sphere == gateway_scope.counterpart => pairing.did.clone(),
+            _ => return Err(PairingError::UnknownRequest.into()),
+        }
+    };
+
+    verify_authorization(&sphere_context, &did, &request_body.authorization).await?;
+
+    let mut pending = pairings.lock_unexpired().await;
+    let pairing = pending.get_mut(&code).ok_or(PairingError::UnknownRequest)?;
+
+    pairing.authorization = Some(request_body.authorization);
+
+    Ok(Cbor(PairingStatus::from(&*pairing)))
+}
+
+/// Ensure that the authorization has been synced to the gateway, and that it
+/// is addressed to the key that requested to be paired
+async fn verify_authorization<C, K, S>(
+    sphere_context: &C,
+    did: &Did,
+    authorization: &Cid,
+) -> Result<(), PairingError>
+where
+    C: HasMutableSphereContext<K, S>,
+    K: KeyMaterial + Clone + 'static,
+    S: Storage + 'static,
+{
+    let db = sphere_context.sphere_context().await?.db().clone();
+
+    let jwt = match db.read_token(authorization).await? {
+        Some(jwt) => jwt,
+        None => {
+            warn!("Authorization {} has not been synced", authorization);
+            return Err(PairingError::InvalidAuthorization);
+        }
+    };
+
+    let ucan = Ucan::from_str(&jwt).map_err(|_| PairingError::InvalidAuthorization)?;
+
+    if ucan.audience() != did.as_str() {
+        warn!(
+            "Authorization {} is for {}, not {}",
+            authorization,
+            ucan.audience(),
+            did
+        );
+        return Err(PairingError::InvalidAuthorization);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,122 @@
+use anyhow::Result;
+
+use axum::{http::StatusCode, Extension};
+
+use libipld_cbor::DagCborCodec;
+use noosphere_api::data::{PublishBody, PublishError, PublishResponse};
+use noosphere_core::{
+    authority::{SphereAction, SphereReference},
+    data::{ContentType, LinkRecord, MemoIpld},
+    view::Sphere,
+};
+use noosphere_sphere::HasMutableSphereContext;
+use noosphere_storage::{BlockStore, Storage};
+use tokio::sync::mpsc::UnboundedSender;
+use ucan::capability::{Capability, Resource, With};
+use ucan::crypto::KeyMaterial;
+
+use crate::{authority::GatewayAuthority, extractor::Cbor, worker::NameSystemJob, GatewayScope};
+
+/// Accepts a signed link record that points the counterpart sphere's identity
+/// to some version of its history, and forwards it to the name system worker
+/// to be published. The version may be any revision of the counterpart sphere
+/// that the gateway already knows about, so an older version can be published
+/// (e.g., to roll back) without pushing new history.
+#[instrument(
+    level = "debug",
+    skip(authority, gateway_scope, sphere_context, name_system_tx, request_body)
+)]
+pub async fn publish_route<C, K, S>(
+    authority: GatewayAuthority<K>,
+    Extension(sphere_context): Extension<C>,
+    Extension(gateway_scope): Extension<GatewayScope>,
+    Extension(name_system_tx): Extension<UnboundedSender<NameSystemJob<C>>>,
+    Cbor(request_body): Cbor<PublishBody>,
+) -> Result<Cbor<PublishResponse>, StatusCode>
+where
+    C: HasMutableSphereContext<K, S>,
+    K: KeyMaterial + Clone,
+    S: Storage + 'static,
+{
+    debug!("Invoking publish route...");
+
+    if request_body.sphere != gateway_scope.counterpart {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    authority.try_authorize(&Capability {
+        with: With::Resource {
+            kind: Resource::Scoped(SphereReference::new(gateway_scope.counterpart.to_string())),
+        },
+        can: SphereAction::Publish,
+    })?;
+
+    let record = verify_record(&sphere_context, &gateway_scope, &request_body).await?;
+    let version = record.get_link().ok_or(PublishError::InvalidRecord)?;
+
+    if let Err(error) = name_system_tx.send(NameSystemJob::Publish {
+        context: sphere_context,
+        record,
+        temporary_validate_expiry: true,
+    }) {
+        error!("Failed to request name record publish: {}", error);
+        return Err(StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    Ok(Cbor(PublishResponse { version }))
+}
+
+/// Ensure that the submitted link record is authorized by the counterpart
+/// sphere, and that it links to a revision of the counterpart sphere that has
+/// previously been pushed to the gateway.
+async fn verify_record<C, K, S>(
+    sphere_context: &C,
+    gateway_scope: &GatewayScope,
+    request_body: &PublishBody,
+) -> Result<LinkRecord, PublishError>
+where
+    C: HasMutableSphereContext<K, S>,
+    K: KeyMaterial + Clone + 'static,
+    S: Storage + 'static,
+{
+    debug!("Verifying link record...");
+
+    let record =
+        LinkRecord::try_from(&request_body.name_record).map_err(|_| PublishError::InvalidRecord)?;
+
+    if record.sphere_identity() != gateway_scope.counterpart.as_str() {
+        warn!("Link record is for a different sphere!");
+        return Err(PublishError::InvalidRecord);
+    }
+
+    let version = record.get_link().ok_or(PublishError::InvalidRecord)?;
+    let db = sphere_context.sphere_context().await?.db().clone();
+
+    if let Err(error) = record.validate(&db).await {
+        warn!("Invalid link record: {}", error);
+        return Err(PublishError::InvalidRecord);
+    }
+
+    let memo = match db.load::<DagCborCodec, MemoIpld>(&version).await {
+        Ok(memo) => memo,
+        Err(_) => {
+            warn!("Link record refers to unknown version {}", version);
+            return Err(PublishError::UnknownVersion);
+        }
+    };
+
+    if memo.content_type() != Some(ContentType::Sphere) {
+        warn!("Link record refers to {} which is not a sphere", version);
+        return Err(PublishError::InvalidRecord);
+    }
+
+    if Sphere::from_memo(&memo, &db)?.get_identity().await? != gateway_scope.counterpart {
+        warn!(
+            "Link record refers to {} which is not a version of {}",
+            version, gateway_scope.counterpart
+        );
+        return Err(PublishError::InvalidRecord);
+    }
+
+    Ok(record)
+}
@@ -7,15 +7,14 @@ use axum::{http::StatusCode, Extension};
 use cid::Cid;
 use noosphere_api::data::{PushBody, PushError, PushResponse};
 use noosphere_core::{
-    authority::{SphereAction, SphereReference},
+    authority::{verify_sphere_scope, SphereAction},
     data::{Bundle, LinkRecord, MapOperation},
     view::Sphere,
 };
 use noosphere_sphere::{HasMutableSphereContext, SphereContentWrite, SphereCursor};
-use noosphere_storage::Storage;
+use noosphere_storage::{BlockStore, BufferedBlockStore, Storage};
 use tokio::sync::mpsc::UnboundedSender;
 use tokio_stream::StreamExt;
-use ucan::capability::{Capability, Resource, With};
 use ucan::crypto::KeyMaterial;
 
 use crate::{
@@ -58,18 +57,12 @@ where
         return Err(StatusCode::FORBIDDEN);
     }
 
-    authority.try_authorize(&Capability {
-        with: With::Resource {
-            kind: Resource::Scoped(SphereReference {
-                did: gateway_scope.counterpart.to_string(),
-            }),
-        },
-        can: SphereAction::Push,
-    })?;
+    let content_prefixes = authority.try_authorize_content(&SphereAction::Push)?;
 
     let gateway_push_routine = GatewayPushRoutine {
         sphere_context,
         gateway_scope,
+        content_prefixes,
         syndication_tx,
         name_system_tx,
         request_body,
@@ -88,6 +81,9 @@ where
 {
     sphere_context: C,
     gateway_scope: GatewayScope,
+    /// If the pusher is only authorized to push some of the content of the
+    /// sphere, these are the slug prefixes of that content
+    content_prefixes: Option<Vec<String>>,
     syndication_tx: UnboundedSender<SyndicationJob<C>>,
     name_system_tx: UnboundedSender<NameSystemJob<C>>,
     request_body: PushBody,
@@ -164,6 +160,39 @@ where
             _ => (),
         };
 
+        if let Some(content_prefixes) = &self.content_prefixes {
+            // The pushed blocks are only buffered while they are checked, so
+            // that nothing out of scope is ever stored
+            let mut store = BufferedBlockStore::new(db.clone());
+            self.request_body.blocks.load_into(&mut store).await?;
+            self.verify_scope(&store, content_prefixes).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Ensure that every revision in the pushed history only changes content
+    /// whose slug starts with one of the given prefixes (see
+    /// [verify_sphere_scope])
+    async fn verify_scope<St: BlockStore + 'static>(
+        &self,
+        store: &St,
+        content_prefixes: &[String],
+    ) -> Result<(), PushError> {
+        debug!("Verifying that pushed history is within {content_prefixes:?}...");
+
+        let PushBody { base, tip, .. } = &self.request_body;
+        let stream = Sphere::at(tip, store).into_history_stream(base.as_ref());
+
+        tokio::pin!(stream);
+
+        while let Some((cid, _)) = stream.try_next().await? {
+            if let Err(error) = verify_sphere_scope(&cid, store, content_prefixes).await {
+                warn!("{}", error);
+                return Err(PushError::OutOfScope);
+            }
+        }
+
         Ok(())
     }
 
@@ -343,3 +372,164 @@ where
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{marker::PhantomData, sync::Arc};
+
+    use anyhow::Result;
+    use cid::Cid;
+    use libipld_cbor::DagCborCodec;
+    use noosphere_api::data::{PushBody, PushError};
+    use noosphere_core::{
+        data::{
+            Bundle, ChangelogIpld, ContentIpld, ContentType, Link, MapOperation, MemoIpld,
+            SphereIpld,
+        },
+        view::{Sphere, SphereMutation},
+    };
+    use noosphere_sphere::{
+        helpers::{simulated_sphere_context, SimulationAccess},
+        HasMutableSphereContext, HasSphereContext, SphereContentWrite, SphereContext,
+    };
+    use noosphere_storage::{BlockStore, MemoryStorage, TrackingStorage};
+    use tokio::sync::{mpsc::unbounded_channel, Mutex};
+    use ucan_key_support::ed25519::Ed25519KeyMaterial;
+
+    use crate::GatewayScope;
+
+    use super::GatewayPushRoutine;
+
+    type TestContext =
+        Arc<Mutex<SphereContext<Ed25519KeyMaterial, TrackingStorage<MemoryStorage>>>>;
+
+    /// Make a revision on top of the given base that adds each of the given
+    /// slugs, and then forge its changelog so that it only lists the slugs
+    /// that are claimed to have changed
+    async fn make_revision(
+        sphere_context: &TestContext,
+        base: &Cid,
+        slugs: &[&str],
+        claimed_slugs: &[&str],
+    ) -> Result<Cid> {
+        let sphere_context = sphere_context.sphere_context().await?;
+        let author = sphere_context.author();
+        let author_did = author.identity().await?;
+        let mut db = sphere_context.db().clone();
+
+        let memo = MemoIpld::for_body(&mut db, b"Cats are great".to_vec()).await?;
+        let memo_link: Link<MemoIpld> = db.save::<DagCborCodec, _>(&memo).await?.into();
+        let mut mutation = SphereMutation::new(&author_did);
+
+        for slug in slugs {
+            mutation.content_mut().set(&slug.to_string(), &memo_link);
+        }
+
+        let mut revision = Sphere::at(base, &db).apply_mutation(&mutation).await?;
+
+        let mut sphere = db
+            .load::<DagCborCodec, SphereIpld>(&revision.memo.body)
+            .await?;
+        let mut content = db
+            .load::<DagCborCodec, ContentIpld>(&sphere.content)
+            .await?;
+
+        content.changelog = db
+            .save::<DagCborCodec, _>(&ChangelogIpld {
+                did: Some(author_did.to_string()),
+                changes: claimed_slugs
+                    .iter()
+                    .map(|slug| MapOperation::Add {
+                        key: slug.to_string(),
+                        value: memo_link.clone(),
+                    })
+                    .collect(),
+            })
+            .await?;
+        sphere.content = db.save::<DagCborCodec, _>(&content).await?.into();
+        revision.memo.body = db.save::<DagCborCodec, _>(&sphere).await?;
+
+        revision
+            .sign(&author.key, author.authorization.as_ref())
+            .await
+    }
+
+    async fn push_routine(
+        sphere_context: &TestContext,
+        base: &Cid,
+        tip: &Cid,
+    ) -> Result<GatewayPushRoutine<TestContext, Ed25519KeyMaterial, TrackingStorage<MemoryStorage>>>
+    {
+        let identity = sphere_context.identity().await?;
+        let (syndication_tx, _) = unbounded_channel();
+        let (name_system_tx, _) = unbounded_channel();
+
+        Ok(GatewayPushRoutine {
+            sphere_context: sphere_context.clone(),
+            gateway_scope: GatewayScope {
+                identity: identity.clone(),
+                counterpart: identity.clone(),
+            },
+            content_prefixes: Some(vec!["build-reports/".into()]),
+            syndication_tx,
+            name_system_tx,
+            request_body: PushBody {
+                sphere: identity,
+                base: Some(*base),
+                tip: *tip,
+                blocks: Bundle::default(),
+                name_record: None,
+            },
+            key_type: PhantomData,
+            storage_type: PhantomData,
+        })
+    }
+
+    #[tokio::test]
+    async fn it_refuses_a_push_whose_changelog_hides_an_out_of_scope_change() -> Result<()> {
+        let mut sphere_context =
+            simulated_sphere_context(SimulationAccess::ReadWrite, None).await?;
+
+        sphere_context
+            .write(
+                "build-reports/one",
+                &ContentType::Text.to_string(),
+                b"Passed".as_ref(),
+                None,
+            )
+            .await?;
+        let base = sphere_context.save(None).await?;
+
+        let in_scope_tip = make_revision(
+            &sphere_context,
+            &base,
+            &["build-reports/two"],
+            &["build-reports/two"],
+        )
+        .await?;
+
+        assert!(push_routine(&sphere_context, &base, &in_scope_tip)
+            .await?
+            .verify_history()
+            .await
+            .is_ok());
+
+        let forged_tip = make_revision(
+            &sphere_context,
+            &base,
+            &["build-reports/two", "secrets"],
+            &["build-reports/two"],
+        )
+        .await?;
+
+        assert!(matches!(
+            push_routine(&sphere_context, &base, &forged_tip)
+                .await?
+                .invoke()
+                .await,
+            Err(PushError::OutOfScope)
+        ));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,161 @@
+use std::{collections::BTreeMap, str::FromStr, sync::Arc};
+
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Extension,
+};
+use noosphere_core::{
+    authority::{SphereReference, SPHERE_SEMANTICS},
+    data::Did,
+};
+use ucan::{
+    capability::{CapabilitySemantics, Resource, With},
+    ucan::Ucan,
+};
+
+use crate::GatewayScope;
+
+/// A registry of the spheres that a gateway manages on behalf of its
+/// counterparts. Each tenant pairs a [GatewayScope] with the sphere context of
+/// the gateway's own sphere for that counterpart, so every tenant has its own
+/// history and its own storage. Tenants are looked up by the identity of their
+/// counterpart sphere.
+#[derive(Clone)]
+pub struct GatewayTenants<C>
+where
+    C: Clone,
+{
+    tenants: Arc<BTreeMap<Did, (GatewayScope, C)>>,
+}
+
+impl<C> GatewayTenants<C>
+where
+    C: Clone,
+{
+    pub fn new<I>(tenants: I) -> Self
+    where
+        I: IntoIterator<Item = (GatewayScope, C)>,
+    {
+        GatewayTenants {
+            tenants: Arc::new(
+                tenants
+                    .into_iter()
+                    .map(|(scope, context)| (scope.counterpart.clone(), (scope, context)))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Look up the tenant whose counterpart sphere has the given identity
+    pub fn get(&self, counterpart: &Did) -> Option<&(GatewayScope, C)> {
+        self.tenants.get(counterpart)
+    }
+
+    /// The [GatewayScope] of every tenant
+    pub fn scopes(&self) -> Vec<GatewayScope> {
+        self.tenants
+            .values()
+            .map(|(scope, _)| scope.clone())
+            .collect()
+    }
+
+    /// The sphere context of every tenant
+    pub fn contexts(&self) -> Vec<C> {
+        self.tenants
+            .values()
+            .map(|(_, context)| context.clone())
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.tenants.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tenants.is_empty()
+    }
+
+    /// Find the tenant that a request is meant for. If the request does not
+    /// name a counterpart sphere and there is only one tenant, that tenant is
+    /// assumed.
+    fn resolve(&self, counterpart: Option<&Did>) -> Option<&(GatewayScope, C)> {
+        match counterpart {
+            Some(counterpart) => self.get(counterpart),
+            None if self.tenants.len() == 1 => self.tenants.values().next(),
+            None => None,
+        }
+    }
+}
+
+/// Middleware that determines which tenant a request is for, and then makes
+/// that tenant's [GatewayScope] and sphere context available to route handlers
+/// (and to the `GatewayAuthority` extractor) as request extensions.
+/// Requests are routed by the sphere that is named by the capabilities claimed
+/// in their bearer UCAN; the UCAN itself is verified later by the route.
+pub async fn resolve_tenant<C>(
+    Extension(tenants): Extension<GatewayTenants<C>>,
+    mut request: Request<Body>,
+    next: Next<Body>,
+) -> Response
+where
+    C: Clone + Send + Sync + 'static,
+{
+    let counterpart = requested_counterpart(&request);
+
+    match tenants.resolve(counterpart.as_ref()) {
+        Some((scope, context)) => {
+            let extensions = request.extensions_mut();
+            extensions.insert(scope.clone());
+            extensions.insert(context.clone());
+        }
+        None => {
+            if let Some(counterpart) = counterpart {
+                warn!("Request for sphere {} is not served here", counterpart);
+                return StatusCode::FORBIDDEN.into_response();
+            }
+        }
+    };
+
+    next.run(request).await
+}
+
+/// Middleware for routes that can only be served on behalf of a tenant. It
+/// rejects requests that [resolve_tenant] could not route to a tenant, which
+/// happens when a gateway has more than one tenant and the request does not
+/// carry a bearer UCAN that names one of their spheres.
+pub async fn require_tenant(request: Request<Body>, next: Next<Body>) -> Response {
+    if request.extensions().get::<GatewayScope>().is_none() {
+        warn!("Request does not identify the sphere that it is for");
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+
+    next.run(request).await
+}
+
+/// Read the identity of the counterpart sphere that a request is scoped to
+/// from the capabilities of its bearer UCAN, if there is one
+fn requested_counterpart(request: &Request<Body>) -> Option<Did> {
+    let token = request
+        .headers()
+        .get(header::AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")?;
+
+    let ucan = Ucan::from_str(token.trim()).ok()?;
+
+    ucan.attenuation().iter().find_map(|capability| {
+        match SPHERE_SEMANTICS
+            .parse(&capability.with, &capability.can)?
+            .with
+        {
+            With::Resource {
+                kind: Resource::Scoped(SphereReference { did, .. }),
+            } => Some(Did(did)),
+            _ => None,
+        }
+    })
+}
@@ -0,0 +1,81 @@
+use std::{collections::BTreeSet, time::Duration};
+
+use anyhow::Result;
+use noosphere_sphere::HasMutableSphereContext;
+use noosphere_storage::{GarbageCollectionReport, Storage, Store};
+use tokio::task::JoinHandle;
+use ucan::crypto::KeyMaterial;
+
+/// How many seconds between garbage collection passes
+const PERIODIC_GARBAGE_COLLECTION_INTERVAL_SECONDS: u64 = 24 * 60 * 60;
+
+/// Start a Tokio task that periodically removes blocks from the gateway's
+/// storage that are no longer referenced by any locally tracked sphere. All
+/// sphere history is retained; only unreferenced blocks (for example, those
+/// left behind by a rejected push) are removed.
+pub fn start_garbage_collection<C, K, S>(local_spheres: Vec<C>) -> JoinHandle<Result<()>>
+where
+    C: HasMutableSphereContext<K, S> + 'static,
+    K: KeyMaterial + Clone + 'static,
+    S: Storage + 'static,
+    S::BlockStore: Store,
+    S::KeyValueStore: Store,
+{
+    tokio::task::spawn(periodic_garbage_collection_task(local_spheres))
+}
+
+/// Every PERIODIC_GARBAGE_COLLECTION_INTERVAL_SECONDS, collect garbage in the
+/// storage of each local sphere and report how much space was reclaimed.
+async fn periodic_garbage_collection_task<C, K, S>(mut local_spheres: Vec<C>) -> Result<()>
+where
+    C: HasMutableSphereContext<K, S>,
+    K: KeyMaterial + Clone + 'static,
+    S: Storage + 'static,
+    S::BlockStore: Store,
+    S::KeyValueStore: Store,
+{
+    loop {
+        tokio::time::sleep(Duration::from_secs(
+            PERIODIC_GARBAGE_COLLECTION_INTERVAL_SECONDS,
+        ))
+        .await;
+
+        for local_sphere in local_spheres.iter_mut() {
+            match garbage_collect_sphere(local_sphere).await {
+                Ok(report) => info!(
+                    "Garbage collection removed {} blocks and reclaimed {} bytes",
+                    report.blocks_removed, report.bytes_reclaimed
+                ),
+                Err(error) => error!("Could not collect garbage: {}", error),
+            }
+        }
+    }
+}
+
+async fn garbage_collect_sphere<C, K, S>(local_sphere: &mut C) -> Result<GarbageCollectionReport>
+where
+    C: HasMutableSphereContext<K, S>,
+    K: KeyMaterial + Clone + 'static,
+    S: Storage + 'static,
+    S::BlockStore: Store,
+    S::KeyValueStore: Store,
+{
+    let excluded = BTreeSet::new();
+
+    // Most of the work of marking referenced blocks is done without holding
+    // the lock on the sphere context, so that pushes are not held up
+    let db = local_sphere.sphere_context().await?.db().clone();
+    let marked = db
+        .mark_referenced_blocks(&excluded, BTreeSet::new())
+        .await?;
+
+    // A push holds the lock while it stores its blocks and records the new
+    // version, so marking again while holding the lock catches up on any
+    // pushes that completed in the meantime, and ensures that blocks from a
+    // push that is in progress cannot be swept before they are referenced
+    let mut sphere_context = local_sphere.sphere_context_mut().await?;
+    let db = sphere_context.db_mut();
+    let marked = db.mark_referenced_blocks(&excluded, marked).await?;
+
+    db.sweep_unmarked_blocks(&marked).await
+}
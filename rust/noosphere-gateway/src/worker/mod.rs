@@ -1,5 +1,7 @@
+mod garbage_collection;
 mod name_system;
 mod syndication;
 
+pub use garbage_collection::*;
 pub use name_system::*;
 pub use syndication::*;
@@ -4,8 +4,9 @@ use noosphere_core::data::{ContentType, Header, MemoIpld};
 /// Generate an HTML "envelope" for content described by the given [MemoIpld].
 /// Currently, only Subtext and Sphere content types are explicitly supported.
 /// This envelope is made up of two parts because the content inside will be
-/// streamed for most content types.
-pub fn html_document_envelope(memo: &MemoIpld) -> (String, String) {
+/// streamed for most content types. An optional footer (such as a list of
+/// backlinks) is placed after the content, at the end of the document body.
+pub fn html_document_envelope(memo: &MemoIpld, footer: Option<&str>) -> (String, String) {
     let content_type = memo.content_type();
 
     let title = if let Some(title) = memo.get_first_header(&Header::Title.to_string()) {
@@ -42,6 +43,11 @@ pub fn html_document_envelope(memo: &MemoIpld) -> (String, String) {
         _ => ("", ""),
     };
 
+    let footer = match footer {
+        Some(footer) => format!("\n{footer}"),
+        None => String::new(),
+    };
+
     (
         format!(
             r#"<!doctype html>
@@ -53,7 +59,7 @@ pub fn html_document_envelope(memo: &MemoIpld) -> (String, String) {
 "#
         ),
         format!(
-            r#"{body_close}
+            r#"{body_close}{footer}
 </body>
 </html>"#
         ),
@@ -2,8 +2,9 @@ use std::{collections::BTreeSet, io::Cursor, path::PathBuf, sync::Arc};
 
 use anyhow::{anyhow, Result};
 use cid::Cid;
-use noosphere_sphere::{HasSphereContext, SphereContentRead, SphereCursor};
+use noosphere_sphere::{HasSphereContext, SphereBacklinks, SphereContentRead, SphereCursor};
 use noosphere_storage::Storage;
+use subtext::{Peer, Slashlink};
 use tokio::sync::Mutex;
 use tokio_stream::StreamExt;
 use ucan::crypto::KeyMaterial;
@@ -44,6 +45,7 @@ where
         }
 
         let write_actions = Arc::new(Mutex::new(BTreeSet::<Cid>::new()));
+        let backlink_index = Arc::new(cursor.backlink_index().await?);
         let sphere = cursor.to_sphere().await?;
         let links = sphere.get_content().await?;
         let mut link_stream = links.stream().await?;
@@ -66,6 +68,7 @@ where
             tasks.push(W::spawn({
                 let slug = slug.clone();
                 let write_actions = write_actions.clone();
+                let backlink_index = backlink_index.clone();
                 let write_target = write_target.clone();
                 let latest_revision = latest_revision;
                 let cursor = cursor.clone();
@@ -89,10 +92,15 @@ where
                         .await?
                         .ok_or_else(|| anyhow!("No file found for {}", slug))?;
 
+                    let backlinks = backlink_index.sources(&Slashlink {
+                        peer: Peer::None,
+                        slug: Some(slug.clone()),
+                    });
+
                     let transform = StaticHtmlTransform::new(cursor.clone());
                     let reader = TransformStream(file_to_html_stream(
                         sphere_file,
-                        HtmlOutput::Document,
+                        HtmlOutput::DocumentWithBacklinks(backlinks),
                         transform,
                     ))
                     .into_reader();
@@ -108,9 +116,6 @@ where
                             .await?;
                     }
 
-                    // TODO(#56): Support backlinks somehow; probably as a dynamic
-                    // widget at the bottom of the HTML document
-
                     Ok(())
                 }
             }));
@@ -246,6 +251,64 @@ pub mod tests {
         assert_eq!(html, expected);
     }
 
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[cfg_attr(not(target_arch = "wasm32"), tokio::test)]
+    async fn it_lists_backlinks_at_the_end_of_each_document() {
+        let context = simulated_sphere_context(SimulationAccess::ReadWrite, None)
+            .await
+            .unwrap();
+        let mut cursor = SphereCursor::latest(context);
+
+        for (slug, body) in [
+            ("cats", "Cats are /animals"),
+            ("dogs", "Dogs are /animals too, unlike [[cats]]"),
+            ("animals", "Animals are multicellular"),
+        ] {
+            cursor
+                .write(
+                    slug,
+                    &ContentType::Subtext.to_string(),
+                    body.as_bytes(),
+                    None,
+                )
+                .await
+                .unwrap();
+        }
+
+        cursor.save(None).await.unwrap();
+
+        let write_target = MemoryWriteTarget::default();
+
+        sphere_into_html(cursor, &write_target).await.unwrap();
+
+        let read_latest = |slug: &'static str| {
+            let write_target = write_target.clone();
+            async move {
+                let path = write_target
+                    .resolve_symlink(&PathBuf::from(slug))
+                    .await
+                    .unwrap();
+                let bytes = write_target.read(&path.join("index.html")).await.unwrap();
+                String::from_utf8(bytes).unwrap()
+            }
+        };
+
+        let animals_html = read_latest("animals").await;
+
+        assert!(animals_html.contains(
+            r#"<aside class="backlinks"><h2 class="backlinks-header">Linked from</h2><ul class="backlinks-list"><li><a href="/cats" class="slashlink">/cats</a></li><li><a href="/dogs" class="slashlink">/dogs</a></li></ul></aside>
+</body>"#
+        ));
+
+        let cats_html = read_latest("cats").await;
+
+        assert!(cats_html.contains(r#"<li><a href="/dogs" class="slashlink">/dogs</a></li>"#));
+
+        let dogs_html = read_latest("dogs").await;
+
+        assert!(!dogs_html.contains("Linked from"));
+    }
+
     #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
     #[cfg_attr(not(target_arch = "wasm32"), tokio::test)]
     async fn it_symlinks_a_file_slug_to_the_latest_file_version() {
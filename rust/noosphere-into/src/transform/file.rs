@@ -1,3 +1,5 @@
+use std::collections::BTreeSet;
+
 use async_stream::stream;
 use futures::Stream;
 use noosphere_core::data::ContentType;
@@ -10,6 +12,9 @@ use crate::{subtext_to_html_document_stream, subtext_to_html_fragment_stream, Tr
 pub enum HtmlOutput {
     /// Output as a full HTML document
     Document,
+    /// Output as a full HTML document, ending with a "Linked from" section
+    /// that lists the given slugs
+    DocumentWithBacklinks(BTreeSet<String>),
     /// Output as just a body content fragment
     Fragment,
 }
@@ -32,7 +37,13 @@ where
             Some(ContentType::Subtext) => {
                 match output {
                     HtmlOutput::Document => {
-                        let stream = subtext_to_html_document_stream(transform, file);
+                        let stream = subtext_to_html_document_stream(transform, file, BTreeSet::new());
+                        for await part in stream {
+                            yield part;
+                        }
+                    },
+                    HtmlOutput::DocumentWithBacklinks(backlinks) => {
+                        let stream = subtext_to_html_document_stream(transform, file, backlinks);
                         for await part in stream {
                             yield part;
                         }
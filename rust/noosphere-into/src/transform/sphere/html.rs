@@ -44,7 +44,7 @@ where
             }
         };
 
-        let (html_prefix, html_suffix) = html_document_envelope(&memo);
+        let (html_prefix, html_suffix) = html_document_envelope(&memo, None);
 
         memo.replace_first_header(&Header::ContentType.to_string(), &ContentType::Subtext.to_string());
 
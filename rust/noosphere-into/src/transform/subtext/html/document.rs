@@ -1,21 +1,41 @@
-use crate::{html_document_envelope, subtext_to_html_fragment_stream, Transform};
+use std::collections::BTreeSet;
+
+use crate::{html_document_envelope, subtext_to_html_fragment_stream, Resolver, Transform};
+use anyhow::Result;
 use async_stream::stream;
 use futures::Stream;
+use horrorshow::html;
 use noosphere_sphere::SphereFile;
+use subtext::{Peer, Slashlink};
 use tokio::io::AsyncRead;
 
 /// Given a [Transform] and a [SphereFile], produce a stream that yields the
-/// file content as an HTML document
+/// file content as an HTML document. If any backlinks (the slugs of other
+/// content that links to this file) are given, they are listed in a "Linked
+/// from" section at the end of the document.
 pub fn subtext_to_html_document_stream<T, R>(
     transform: T,
     file: SphereFile<R>,
+    backlinks: BTreeSet<String>,
 ) -> impl Stream<Item = String>
 where
     T: Transform,
     R: AsyncRead + Unpin,
 {
     stream! {
-      let (html_prefix, html_suffix) = html_document_envelope(&file.memo);
+      let footer = if backlinks.is_empty() {
+        None
+      } else {
+        match backlinks_to_html(&backlinks, transform.clone()).await {
+          Ok(footer) => Some(footer),
+          Err(error) => {
+            warn!("Failed to transform backlinks: {:?}", error);
+            None
+          }
+        }
+      };
+
+      let (html_prefix, html_suffix) = html_document_envelope(&file.memo, footer.as_deref());
       let fragment_stream = subtext_to_html_fragment_stream(file, transform);
 
       yield html_prefix;
@@ -27,3 +47,37 @@ where
       yield html_suffix;
     }
 }
+
+/// Given a [Transform] and the slugs of the content that links to a
+/// document, produce a "Linked from" section for that document as an HTML
+/// string
+pub async fn backlinks_to_html<T>(backlinks: &BTreeSet<String>, transform: T) -> Result<String>
+where
+    T: Transform,
+{
+    let mut links = Vec::new();
+
+    for slug in backlinks {
+        let slashlink = Slashlink {
+            peer: Peer::None,
+            slug: Some(slug.to_owned()),
+        };
+        let href = transform.resolver().resolve(&slashlink).await?.to_string();
+
+        links.push((href, slashlink.to_string()));
+    }
+
+    Ok(html! {
+        aside(class="backlinks") {
+            h2(class="backlinks-header") : "Linked from";
+            ul(class="backlinks-list") {
+                @ for (href, text) in links.iter() {
+                    li {
+                        a(href=href.as_str(), class="slashlink") : text
+                    }
+                }
+            }
+        }
+    }
+    .to_string())
+}
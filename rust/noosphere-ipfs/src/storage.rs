@@ -111,6 +111,12 @@ where
         local_store.put_block(cid, block).await
     }
 
+    #[instrument(skip(self, blocks), level = "trace")]
+    async fn put_blocks(&mut self, blocks: &[(Cid, Vec<u8>)]) -> Result<()> {
+        let mut local_store = self.local_store.write().await;
+        local_store.put_blocks(blocks).await
+    }
+
     #[instrument(skip(self), level = "trace")]
     async fn get_block(&self, cid: &Cid) -> Result<Option<Vec<u8>>> {
         trace!("IpfsStore: Getting block {}...", cid);
@@ -279,6 +279,42 @@ no_default_peers = true
         Ok(())
     }
 
+    #[tokio::test]
+    async fn try_from_command_with_record_store_path() -> Result<()> {
+        let env = Env::new_with_config(
+            r#"
+key = "my-bootstrap-key-1"
+listening_address = 10000
+
+[dht_config]
+record_store_path = "/var/lib/orb-ns/records"
+"#,
+        )
+        .await?;
+
+        let _ = env.create_key("my-bootstrap-key-1").await?;
+        let config = RunnerNodeConfig::try_from_command(
+            CLICommand::Run {
+                api_address: None,
+                config: env.config_path.to_owned(),
+                key: None,
+                listening_address: None,
+                peers: None,
+                no_default_peers: false,
+                ipfs_api_url: None,
+            },
+            &env.key_storage,
+        )
+        .await?;
+
+        assert_eq!(
+            config.dht_config.record_store_path,
+            Some(PathBuf::from("/var/lib/orb-ns/records")),
+            "expected record store path"
+        );
+        Ok(())
+    }
+
     #[tokio::test]
     async fn try_from_command_validation() -> Result<()> {
         let env = Env::new_with_config(
@@ -2,6 +2,7 @@ use crate::{dht::DhtConfig, name_system::NameSystem, DhtClient, NameSystemKeyMat
 use anyhow::{anyhow, Result};
 use libp2p::{self, Multiaddr};
 use std::net::Ipv4Addr;
+use std::path::Path;
 use ucan::store::UcanJwtStore;
 
 #[cfg(doc)]
@@ -120,6 +121,13 @@ where
         self
     }
 
+    /// A directory where records stored by this node are persisted, so
+    /// that they survive a restart.
+    pub fn record_store_path(mut self, path: &Path) -> Self {
+        self.dht_config.record_store_path = Some(path.to_owned());
+        self
+    }
+
     /// How long, in seconds, stored records are replicated to
     /// peers. Should be significantly shorter than `publication_interval`.
     /// See [KademliaConfig::set_replication_interval].
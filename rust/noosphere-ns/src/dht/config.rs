@@ -1,4 +1,5 @@
 use serde::Deserialize;
+use std::path::PathBuf;
 
 #[cfg(doc)]
 use libp2p::kad::KademliaConfig;
@@ -35,6 +36,12 @@ pub struct DhtConfig {
     /// See [KademliaConfig::set_record_ttl] and [KademliaConfig::set_provider_record_ttl].
     #[serde(default = "default_record_ttl")]
     pub record_ttl: u32,
+    /// A directory where the records that this node stores on behalf of
+    /// the network are persisted, so that they are restored when the node
+    /// restarts (as long as they are still valid and have not outlived
+    /// `record_ttl`). If not set, records are only held in memory.
+    #[serde(default)]
+    pub record_store_path: Option<PathBuf>,
 }
 
 // We break up defaults into individual functions to support deserializing
@@ -79,6 +86,7 @@ impl Default for DhtConfig {
             query_timeout: default_query_timeout(),
             replication_interval: default_replication_interval(),
             record_ttl: default_record_ttl(),
+            record_store_path: None,
         }
     }
 }
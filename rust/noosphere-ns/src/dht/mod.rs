@@ -4,6 +4,7 @@ mod errors;
 mod node;
 mod processor;
 mod rpc;
+mod store;
 mod swarm;
 mod types;
 mod validator;
@@ -1,6 +1,7 @@
 use super::{
     errors::DhtError,
     rpc::{DhtMessage, DhtMessageProcessor, DhtRequest, DhtResponse},
+    store::{load_records, DHT_RECORD_STORE},
     swarm::{build_swarm, DHTEvent, DHTSwarmEvent, DhtBehavior},
     types::{DhtRecord, Peer},
     DhtConfig, Validator,
@@ -23,6 +24,7 @@ use libp2p::{
     },
     Multiaddr, PeerId,
 };
+use noosphere_storage::{KeyValueStore, NativeStorage, NativeStorageInit, Storage};
 use std::{collections::HashMap, time::Duration};
 use std::{fmt, num::NonZeroUsize};
 use tokio;
@@ -73,6 +75,12 @@ where
         processor: DhtMessageProcessor,
     ) -> Result<tokio::task::JoinHandle<Result<(), DhtError>>, DhtError> {
         let swarm = build_swarm(keypair, &peer_id, &config)?;
+        let storage = match &config.record_store_path {
+            Some(path) => Some(NativeStorage::new(NativeStorageInit::Path(
+                path.to_owned(),
+            ))?),
+            None => None,
+        };
 
         let mut node = DhtProcessor {
             peer_id,
@@ -86,7 +94,46 @@ where
             pending_listener_request: None,
         };
 
-        Ok(tokio::spawn(async move { node.process().await }))
+        Ok(tokio::spawn(async move {
+            if let Some(storage) = storage {
+                node.restore_records(storage).await?;
+            }
+            node.process().await
+        }))
+    }
+
+    /// Load the records that were persisted by a previous run of this node,
+    /// keeping those that are still valid, and then persist all subsequent
+    /// changes to records in the same storage.
+    async fn restore_records(&mut self, storage: NativeStorage) -> Result<(), DhtError> {
+        let mut store = storage.get_key_value_store(DHT_RECORD_STORE).await?;
+        let record_ttl = Duration::from_secs(self.config.record_ttl.into());
+        let records = load_records(&mut store, record_ttl).await?;
+        let mut restored = 0usize;
+
+        for record in records {
+            if !self.validate(&record.value).await {
+                warn!(
+                    "Discarding persisted record that is no longer valid: {:?}",
+                    record.key
+                );
+                store.unset_key(record.key.to_vec()).await?;
+                continue;
+            }
+
+            let key = record.key.clone();
+
+            match self.swarm.behaviour_mut().kad.store_mut().restore(record) {
+                Ok(_) => restored += 1,
+                Err(error) => warn!("Could not restore persisted record {:?}: {:?}", key, error),
+            };
+        }
+
+        debug!("Restored {} persisted DHT records", restored);
+
+        self.swarm.behaviour_mut().kad.store_mut().persist_to(store);
+
+        Ok(())
     }
 
     /// Begin processing requests and connections on the DHT network
@@ -0,0 +1,292 @@
+use anyhow::Result;
+use libp2p::{
+    kad::{
+        record::{
+            store::{self, MemoryStore, RecordStore},
+            Key, ProviderRecord,
+        },
+        Record,
+    },
+    PeerId,
+};
+use noosphere_storage::{KeyValueStore, NativeStore, Store};
+use serde::{Deserialize, Serialize};
+use std::{
+    borrow::Cow,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
+
+/// The name of the key/value store that DHT records are persisted in.
+pub const DHT_RECORD_STORE: &str = "dht_records";
+
+/// The form a [Record] takes when it is persisted. Expiration is recorded as
+/// a Unix timestamp (in seconds), since an [Instant] is only meaningful within
+/// the lifetime of a process.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PersistedRecord {
+    value: Vec<u8>,
+    publisher: Option<Vec<u8>>,
+    stored_at: u64,
+    expires: Option<u64>,
+}
+
+impl PersistedRecord {
+    fn from_record(record: &Record) -> Self {
+        PersistedRecord {
+            value: record.value.clone(),
+            publisher: record.publisher.map(|peer_id| peer_id.to_bytes()),
+            stored_at: now_timestamp(),
+            expires: record.expires.map(instant_to_timestamp),
+        }
+    }
+
+    /// Restore the [Record] that this was persisted from. Returns `None` if
+    /// the record expired, either explicitly or because it was stored longer
+    /// than `record_ttl` ago.
+    fn into_record(self, key: Key, record_ttl: Duration) -> Option<Record> {
+        let ttl_expires = self.stored_at.saturating_add(record_ttl.as_secs());
+        let expires = match self.expires {
+            Some(expires) => expires.min(ttl_expires),
+            None => ttl_expires,
+        };
+
+        let expires = timestamp_to_instant(expires)?;
+        let publisher = match self.publisher {
+            Some(bytes) => Some(PeerId::from_bytes(&bytes).ok()?),
+            None => None,
+        };
+
+        Some(Record {
+            key,
+            value: self.value,
+            publisher,
+            expires: Some(expires),
+        })
+    }
+}
+
+/// Changes to be written to the persistent store in the background, since
+/// [RecordStore] is a synchronous interface.
+enum StoreOperation {
+    Put(Key, PersistedRecord),
+    Remove(Key),
+}
+
+impl StoreOperation {
+    async fn apply(self, store: &mut NativeStore) -> Result<()> {
+        match self {
+            StoreOperation::Put(key, record) => store.set_key(key.to_vec(), record).await,
+            StoreOperation::Remove(key) => store.unset_key(key.to_vec()).await,
+        }
+    }
+}
+
+/// A [RecordStore] for the name system DHT. Records are served from memory,
+/// and once [DhtRecordStore::persist_to] is called, every value record that
+/// is stored or removed is also written through to a [NativeStore] so that
+/// they can be restored (see [load_records]) when the node restarts.
+///
+/// Provider records are only held in memory.
+pub struct DhtRecordStore {
+    memory: MemoryStore,
+    persistence: Option<UnboundedSender<StoreOperation>>,
+}
+
+impl DhtRecordStore {
+    pub fn new(local_peer_id: PeerId) -> Self {
+        DhtRecordStore {
+            memory: MemoryStore::new(local_peer_id),
+            persistence: None,
+        }
+    }
+
+    /// Add a record that was loaded from persistent storage, without writing
+    /// it back again.
+    pub fn restore(&mut self, record: Record) -> store::Result<()> {
+        self.memory.put(record)
+    }
+
+    /// Write through all subsequent changes to value records to the given
+    /// store. Writes happen in a background task, which ends when this
+    /// [DhtRecordStore] is dropped.
+    pub fn persist_to(&mut self, mut store: NativeStore) {
+        let (tx, mut rx) = unbounded_channel::<StoreOperation>();
+
+        tokio::spawn(async move {
+            while let Some(operation) = rx.recv().await {
+                if let Err(error) = operation.apply(&mut store).await {
+                    warn!("Failed to persist DHT record change: {}", error);
+                }
+            }
+        });
+
+        self.persistence = Some(tx);
+    }
+
+    fn persist(&self, operation: StoreOperation) {
+        if let Some(tx) = self.persistence.as_ref() {
+            if tx.send(operation).is_err() {
+                warn!("DHT record persistence has stopped");
+            }
+        }
+    }
+}
+
+impl RecordStore for DhtRecordStore {
+    type RecordsIter<'a> = <MemoryStore as RecordStore>::RecordsIter<'a>;
+    type ProvidedIter<'a> = <MemoryStore as RecordStore>::ProvidedIter<'a>;
+
+    fn get(&self, k: &Key) -> Option<Cow<'_, Record>> {
+        self.memory.get(k)
+    }
+
+    fn put(&mut self, r: Record) -> store::Result<()> {
+        let persisted = PersistedRecord::from_record(&r);
+        let key = r.key.clone();
+
+        self.memory.put(r)?;
+        self.persist(StoreOperation::Put(key, persisted));
+
+        Ok(())
+    }
+
+    fn remove(&mut self, k: &Key) {
+        self.memory.remove(k);
+        self.persist(StoreOperation::Remove(k.clone()));
+    }
+
+    fn records(&self) -> Self::RecordsIter<'_> {
+        self.memory.records()
+    }
+
+    fn add_provider(&mut self, record: ProviderRecord) -> store::Result<()> {
+        self.memory.add_provider(record)
+    }
+
+    fn providers(&self, key: &Key) -> Vec<ProviderRecord> {
+        self.memory.providers(key)
+    }
+
+    fn provided(&self) -> Self::ProvidedIter<'_> {
+        self.memory.provided()
+    }
+
+    fn remove_provider(&mut self, k: &Key, p: &PeerId) {
+        self.memory.remove_provider(k, p)
+    }
+}
+
+/// Read every record from a persistent store. Records that have expired
+/// (given `record_ttl`) or that can no longer be decoded are removed from the
+/// store and are not returned.
+pub async fn load_records(store: &mut NativeStore, record_ttl: Duration) -> Result<Vec<Record>> {
+    let mut records = Vec::new();
+
+    for key in store.keys().await? {
+        let persisted: Option<PersistedRecord> = match store.get_key(&key).await {
+            Ok(persisted) => persisted,
+            Err(error) => {
+                warn!("Could not decode persisted DHT record: {}", error);
+                None
+            }
+        };
+
+        match persisted
+            .and_then(|persisted| persisted.into_record(Key::from(key.clone()), record_ttl))
+        {
+            Some(record) => records.push(record),
+            None => store.unset_key(&key).await?,
+        };
+    }
+
+    Ok(records)
+}
+
+fn now_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+fn instant_to_timestamp(instant: Instant) -> u64 {
+    let now = Instant::now();
+    let timestamp = now_timestamp();
+
+    if instant >= now {
+        timestamp.saturating_add((instant - now).as_secs())
+    } else {
+        timestamp.saturating_sub((now - instant).as_secs())
+    }
+}
+
+/// Convert a Unix timestamp to an [Instant], or `None` if it is in the past.
+fn timestamp_to_instant(timestamp: u64) -> Option<Instant> {
+    let remaining = timestamp.checked_sub(now_timestamp())?;
+
+    match remaining {
+        0 => None,
+        remaining => Some(Instant::now() + Duration::from_secs(remaining)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libp2p::identity::Keypair;
+    use noosphere_storage::{NativeStorage, NativeStorageInit, Storage};
+
+    async fn make_store() -> Result<(NativeStore, tempfile::TempDir)> {
+        let directory = tempfile::TempDir::new()?;
+        let storage = NativeStorage::new(NativeStorageInit::Path(directory.path().into()))?;
+        Ok((
+            storage.get_key_value_store(DHT_RECORD_STORE).await?,
+            directory,
+        ))
+    }
+
+    #[tokio::test]
+    async fn it_restores_persisted_records_until_they_expire() -> Result<()> {
+        let (mut store, _directory) = make_store().await?;
+        let record_ttl = Duration::from_secs(60 * 60);
+        let publisher = PeerId::from(Keypair::generate_ed25519().public());
+
+        let fresh = Record {
+            key: Key::from(b"fresh".to_vec()),
+            value: b"fresh".to_vec(),
+            publisher: Some(publisher),
+            expires: Some(Instant::now() + Duration::from_secs(60)),
+        };
+
+        StoreOperation::Put(fresh.key.clone(), PersistedRecord::from_record(&fresh))
+            .apply(&mut store)
+            .await?;
+
+        let mut expired =
+            PersistedRecord::from_record(&Record::new(Key::from(b"expired".to_vec()), vec![]));
+        expired.expires = Some(now_timestamp() - 1);
+        StoreOperation::Put(Key::from(b"expired".to_vec()), expired)
+            .apply(&mut store)
+            .await?;
+
+        let mut stale =
+            PersistedRecord::from_record(&Record::new(Key::from(b"stale".to_vec()), vec![]));
+        stale.stored_at = now_timestamp() - record_ttl.as_secs() - 1;
+        StoreOperation::Put(Key::from(b"stale".to_vec()), stale)
+            .apply(&mut store)
+            .await?;
+
+        let records = load_records(&mut store, record_ttl).await?;
+
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].key, fresh.key);
+        assert_eq!(records[0].value, fresh.value);
+        assert_eq!(records[0].publisher, Some(publisher));
+        assert!(records[0].expires.is_some());
+
+        assert_eq!(store.keys().await?, vec![fresh.key.to_vec()]);
+
+        Ok(())
+    }
+}
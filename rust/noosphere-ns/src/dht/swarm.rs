@@ -1,5 +1,5 @@
 use crate::dht::errors::DhtError;
-use crate::dht::DhtConfig;
+use crate::dht::{store::DhtRecordStore, DhtConfig};
 use libp2p::{
     allow_block_list,
     core::muxing::StreamMuxerBox,
@@ -8,7 +8,7 @@ use libp2p::{
     dns,
     identify::{Behaviour as Identify, Config as IdentifyConfig, Event as IdentifyEvent},
     identity::Keypair,
-    kad::{self, Kademlia, KademliaConfig, KademliaEvent, KademliaStoreInserts},
+    kad::{Kademlia, KademliaConfig, KademliaEvent, KademliaStoreInserts},
     noise,
     swarm::{self, NetworkBehaviour, SwarmBuilder, SwarmEvent, THandlerErr},
     tcp, yamux, PeerId, Swarm, Transport,
@@ -49,7 +49,7 @@ impl From<Void> for DHTEvent {
 #[behaviour(out_event = "DHTEvent", event_process = false)]
 pub struct DhtBehavior {
     pub identify: Identify,
-    pub kad: Kademlia<kad::record::store::MemoryStore>,
+    pub kad: Kademlia<DhtRecordStore>,
     blocked_peers: allow_block_list::Behaviour<allow_block_list::BlockedPeers>,
 }
 
@@ -82,8 +82,7 @@ impl DhtBehavior {
                 config.publication_interval.into(),
             )));
 
-            // TODO(#99): Use SphereFS storage
-            let store = kad::record::store::MemoryStore::new(local_peer_id.to_owned());
+            let store = DhtRecordStore::new(local_peer_id.to_owned());
             Kademlia::with_config(local_peer_id.to_owned(), store, cfg)
         };
 
@@ -0,0 +1,479 @@
+//! Spheres can be exported as (and imported from) CAR archives, so that they
+//! can be backed up, moved to another device or seeded for tests without the
+//! involvement of a gateway.
+//!
+//! An archive has a single root: the version of the sphere that it was
+//! exported at. It contains every block that is needed to read that version,
+//! as well as the UCANs that prove the authority of whoever signed it.
+
+use std::{collections::BTreeSet, str::FromStr};
+
+use anyhow::{anyhow, Result};
+use cid::{
+    multihash::{Code, MultihashDigest},
+    Cid,
+};
+use libipld_cbor::DagCborCodec;
+use noosphere_car::{CarHeader, CarReader, CarWriter};
+use noosphere_core::{
+    authority::verify_sphere_cid,
+    data::{Header, MemoIpld},
+    view::Sphere,
+};
+use noosphere_storage::{
+    block_deserialize, BlockStore, MemoryStorage, MemoryStore, SphereDb, Storage,
+};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio_stream::StreamExt;
+use ucan::{crypto::KeyMaterial, store::UcanJwtStore, Ucan};
+
+use crate::SphereContext;
+
+/// How much of the history of a sphere to include in an archive
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveHistory {
+    /// Every revision of the sphere, back to the one it was created with
+    Full,
+    /// Only the latest revision of the sphere
+    Latest,
+}
+
+impl<K, S> SphereContext<K, S>
+where
+    K: KeyMaterial + Clone + 'static,
+    S: Storage + 'static,
+{
+    /// Write the latest version of this sphere (and, if requested, all of its
+    /// history) to the given writer as a CARv1 archive, returning the version
+    /// that was exported
+    pub async fn export_archive<W>(&self, history: ArchiveHistory, writer: W) -> Result<Cid>
+    where
+        W: AsyncWrite + Send + Unpin,
+    {
+        let db = self.db();
+        let version = db.require_version(self.identity()).await?;
+
+        debug!("Exporting sphere {} at {}", self.identity(), version);
+
+        let mut signed_versions = vec![version];
+
+        if history == ArchiveHistory::Full {
+            let history = Sphere::at(&version, db).into_history_stream(None);
+
+            tokio::pin!(history);
+
+            while let Some((past_version, _)) = history.try_next().await? {
+                if past_version != version {
+                    signed_versions.push(past_version);
+                }
+            }
+        }
+
+        let mut car_writer = CarWriter::new(CarHeader::new_v1(vec![version]), writer);
+        let mut written = BTreeSet::new();
+        let mut remaining = vec![version];
+
+        while let Some(cid) = remaining.pop() {
+            if written.contains(&cid) {
+                continue;
+            }
+
+            let block = match db.get_block(&cid).await? {
+                Some(block) => block,
+                None => continue,
+            };
+
+            if let Some(mut links) = db.get_block_links(&cid).await? {
+                // Any memo (the sphere's own or that of some content) refers
+                // to its previous revision as its parent, so parents must not
+                // be followed in order to leave out the history
+                if history == ArchiveHistory::Latest && cid.codec() == u64::from(DagCborCodec) {
+                    if let Ok(MemoIpld {
+                        parent: Some(parent),
+                        ..
+                    }) = block_deserialize::<DagCborCodec, MemoIpld>(&block)
+                    {
+                        links.retain(|link| link != &parent);
+                    }
+                }
+
+                remaining.append(&mut links);
+            }
+
+            car_writer.write(cid, block).await?;
+            written.insert(cid);
+        }
+
+        // The UCANs that authorize each signature are only referred to by the
+        // headers of the signed memos, so they are not reachable as links
+        for signed_version in signed_versions {
+            for cid in proof_chain_cids(db, &signed_version).await? {
+                if written.contains(&cid) {
+                    continue;
+                }
+
+                car_writer.write(cid, db.require_block(&cid).await?).await?;
+                written.insert(cid);
+            }
+        }
+
+        car_writer.finish().await?;
+
+        Ok(version)
+    }
+
+    /// Read a CAR archive of this sphere (such as one written by
+    /// [SphereContext::export_archive]) from the given reader, and load its
+    /// blocks into local storage. Every block must match its [Cid], and every
+    /// revision of the sphere in the archive must be signed by a key that was
+    /// authorized to do so; the archive is verified before any of its blocks
+    /// are stored.
+    ///
+    /// If the sphere has no local version yet, or if the archived version
+    /// descends from the local version, the archived version becomes the local
+    /// version. If the local version already descends from the archived
+    /// version, the local version is kept. Otherwise, the histories have
+    /// diverged and an error is returned (although the archived blocks will
+    /// have been stored).
+    pub async fn import_archive<R>(&mut self, reader: R) -> Result<Cid>
+    where
+        R: AsyncRead + Send + Unpin,
+    {
+        let mut car_reader = CarReader::new(reader).await?;
+        let version = *car_reader
+            .header()
+            .roots()
+            .first()
+            .ok_or_else(|| anyhow!("The archive does not have a root"))?;
+
+        debug!("Importing sphere {} at {}", self.identity(), version);
+
+        let mut archive_store = MemoryStore::default();
+
+        while let Some((cid, block)) = car_reader.next_block().await? {
+            verify_block(&cid, &block)?;
+            archive_store.put_block(&cid, &block).await?;
+        }
+
+        // The archive is verified on its own before any of its blocks are
+        // stored locally, so that nothing is written if it is not valid
+        let mut archive_db = SphereDb::new(&MemoryStorage::default()).await?;
+
+        archive_db.persist(&archive_store).await?;
+
+        let identity = Sphere::at(&version, &archive_db).get_identity().await?;
+
+        if &identity != self.identity() {
+            return Err(anyhow!(
+                "The archive is of sphere {}, not {}",
+                identity,
+                self.identity()
+            ));
+        }
+
+        let mut next_version = Some(version);
+
+        while let Some(archived_version) = next_version {
+            if archive_store.get_block(&archived_version).await?.is_none() {
+                break;
+            }
+
+            verify_sphere_cid(&archived_version, &archive_db, self.did_parser_mut()).await?;
+
+            next_version = archive_db
+                .load::<DagCborCodec, MemoIpld>(&archived_version)
+                .await?
+                .parent;
+        }
+
+        let mut db = self.db().clone();
+
+        db.persist(&archive_store).await?;
+
+        match db.get_version(&identity).await? {
+            Some(local_version) if local_version == version => (),
+            Some(local_version) if descends_from(&db, &version, &local_version).await? => {
+                db.set_version(&identity, &version).await?;
+            }
+            Some(local_version) if descends_from(&db, &local_version, &version).await? => {
+                info!(
+                    "The local version {} is already newer than the archived version",
+                    local_version
+                );
+            }
+            Some(local_version) => {
+                return Err(anyhow!(
+                    "The archived version {} does not descend from the local version {}",
+                    version,
+                    local_version
+                ));
+            }
+            None => db.set_version(&identity, &version).await?,
+        };
+
+        db.flush().await?;
+
+        Ok(version)
+    }
+}
+
+/// Ensure that a block hashes to the [Cid] that it is stored under
+fn verify_block(cid: &Cid, block: &[u8]) -> Result<()> {
+    let code = Code::try_from(cid.hash().code())?;
+
+    if &code.digest(block) != cid.hash() {
+        return Err(anyhow!("Block {} does not match its content", cid));
+    }
+
+    Ok(())
+}
+
+/// Returns true if `ancestor` can be found by following the parents of
+/// `version` (or is `version` itself) in locally available history
+async fn descends_from<S>(db: &SphereDb<S>, version: &Cid, ancestor: &Cid) -> Result<bool>
+where
+    S: Storage,
+{
+    let mut next_version = Some(*version);
+
+    while let Some(version) = next_version {
+        if &version == ancestor {
+            return Ok(true);
+        }
+
+        if db.get_block(&version).await?.is_none() {
+            break;
+        }
+
+        next_version = db.load::<DagCborCodec, MemoIpld>(&version).await?.parent;
+    }
+
+    Ok(false)
+}
+
+/// The [Cid]s of the UCAN that authorizes the signature of a sphere revision,
+/// and of all the UCANs that it is proven by
+async fn proof_chain_cids<S>(db: &SphereDb<S>, version: &Cid) -> Result<Vec<Cid>>
+where
+    S: Storage,
+{
+    let memo = db.load::<DagCborCodec, MemoIpld>(version).await?;
+    let mut remaining = memo
+        .get_header(&Header::Proof.to_string())
+        .iter()
+        .map(|proof| Cid::from_str(proof))
+        .collect::<Result<Vec<Cid>, _>>()?;
+    let mut cids = Vec::new();
+
+    while let Some(cid) = remaining.pop() {
+        if cids.contains(&cid) {
+            continue;
+        }
+
+        let ucan = Ucan::from_str(&db.require_token(&cid).await?)?;
+
+        for proof in ucan.proofs() {
+            remaining.push(Cid::from_str(proof)?);
+        }
+
+        cids.push(cid);
+    }
+
+    Ok(cids)
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use noosphere_core::data::ContentType;
+    use noosphere_storage::{BlockStore, MemoryStorage, SphereDb};
+    use std::sync::Arc;
+    use tokio::{io::AsyncReadExt, sync::Mutex};
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    #[cfg(target_arch = "wasm32")]
+    wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_browser);
+
+    use crate::{
+        helpers::{simulated_sphere_context, SimulationAccess},
+        ArchiveHistory, HasMutableSphereContext, HasSphereContext, SphereContentRead,
+        SphereContentWrite, SphereContext,
+    };
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[cfg_attr(not(target_arch = "wasm32"), tokio::test)]
+    async fn it_restores_a_sphere_from_an_exported_archive() -> Result<()> {
+        let mut sphere_context =
+            simulated_sphere_context(SimulationAccess::ReadWrite, None).await?;
+
+        let first_memo_version = sphere_context
+            .write("cats", &ContentType::Text.to_string(), "one".as_ref(), None)
+            .await?;
+        let first_version = sphere_context.save(None).await?;
+
+        sphere_context
+            .write("cats", &ContentType::Text.to_string(), "two".as_ref(), None)
+            .await?;
+        sphere_context.save(None).await?;
+
+        let version = sphere_context.version().await?;
+        let (identity, author) = {
+            let sphere_context = sphere_context.sphere_context().await?;
+            (
+                sphere_context.identity().clone(),
+                sphere_context.author().clone(),
+            )
+        };
+
+        for history in [ArchiveHistory::Full, ArchiveHistory::Latest] {
+            let mut archive = Vec::new();
+            sphere_context
+                .sphere_context()
+                .await?
+                .export_archive(history, &mut archive)
+                .await?;
+
+            let db = SphereDb::new(&MemoryStorage::default()).await?;
+            let mut restored_context =
+                SphereContext::new(identity.clone(), author.clone(), db, None).await?;
+
+            assert_eq!(
+                restored_context.import_archive(&archive[..]).await?,
+                version
+            );
+
+            let restored_context = Arc::new(Mutex::new(restored_context));
+
+            assert_eq!(restored_context.version().await?, version);
+
+            let mut contents = String::new();
+            restored_context
+                .read("cats")
+                .await?
+                .unwrap()
+                .contents
+                .read_to_string(&mut contents)
+                .await?;
+
+            assert_eq!(contents, "two");
+
+            let has_first_version = restored_context
+                .sphere_context()
+                .await?
+                .db()
+                .get_block(&first_version)
+                .await?
+                .is_some();
+
+            assert_eq!(has_first_version, history == ArchiveHistory::Full);
+
+            let has_first_memo_version = restored_context
+                .sphere_context()
+                .await?
+                .db()
+                .get_block(&first_memo_version)
+                .await?
+                .is_some();
+
+            assert_eq!(has_first_memo_version, history == ArchiveHistory::Full);
+        }
+
+        Ok(())
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[cfg_attr(not(target_arch = "wasm32"), tokio::test)]
+    async fn it_refuses_to_import_a_tampered_archive() -> Result<()> {
+        let mut sphere_context =
+            simulated_sphere_context(SimulationAccess::ReadWrite, None).await?;
+
+        sphere_context
+            .write(
+                "cats",
+                &ContentType::Text.to_string(),
+                "Cats are great".as_ref(),
+                None,
+            )
+            .await?;
+        sphere_context.save(None).await?;
+
+        let mut archive = Vec::new();
+        sphere_context
+            .sphere_context()
+            .await?
+            .export_archive(ArchiveHistory::Latest, &mut archive)
+            .await?;
+
+        // The bytes of a body chunk are encoded as a CBOR array of integers,
+        // each of which is prefixed by 0x18 (for values from 24 to 255)
+        let cbor_bytes = |text: &str| {
+            text.bytes()
+                .flat_map(|byte| [0x18, byte])
+                .collect::<Vec<u8>>()
+        };
+        let needle = cbor_bytes("Cats are great");
+        let position = archive
+            .windows(needle.len())
+            .position(|window| window == needle)
+            .unwrap();
+        let mut tampered_archive = archive.clone();
+        let replacement = cbor_bytes("Dogs");
+
+        tampered_archive[position..position + replacement.len()].copy_from_slice(&replacement);
+
+        let (identity, author) = {
+            let sphere_context = sphere_context.sphere_context().await?;
+            (
+                sphere_context.identity().clone(),
+                sphere_context.author().clone(),
+            )
+        };
+        let db = SphereDb::new(&MemoryStorage::default()).await?;
+        let mut restored_context = SphereContext::new(identity, author, db, None).await?;
+
+        assert!(restored_context
+            .import_archive(&tampered_archive[..])
+            .await
+            .is_err());
+
+        Ok(())
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[cfg_attr(not(target_arch = "wasm32"), tokio::test)]
+    async fn it_does_not_store_any_blocks_from_an_archive_of_another_sphere() -> Result<()> {
+        let mut other_sphere_context =
+            simulated_sphere_context(SimulationAccess::ReadWrite, None).await?;
+
+        other_sphere_context
+            .write(
+                "cats",
+                &ContentType::Text.to_string(),
+                "Cats are great".as_ref(),
+                None,
+            )
+            .await?;
+        let other_version = other_sphere_context.save(None).await?;
+
+        let mut archive = Vec::new();
+        other_sphere_context
+            .sphere_context()
+            .await?
+            .export_archive(ArchiveHistory::Full, &mut archive)
+            .await?;
+
+        let sphere_context = simulated_sphere_context(SimulationAccess::ReadWrite, None).await?;
+        let mut sphere_context = sphere_context.lock().await;
+
+        assert!(sphere_context.import_archive(&archive[..]).await.is_err());
+        assert!(sphere_context
+            .db()
+            .get_block(&other_version)
+            .await?
+            .is_none());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,16 @@
+//! Sphere authority is the record of the keys that have been authorized to
+//! work on a sphere (its delegations), and of the authorizations that have
+//! been taken away (its revocations). Every authorization is a UCAN that
+//! expires at some point; before it does, the owner of the sphere may renew it
+//! by issuing a replacement.
+
+mod read;
+mod write;
+
+pub use read::*;
+pub use write::*;
+
+/// If the authorization of the local key expires within this many seconds, a
+/// [crate::SphereContext] will look for a renewed authorization (and warn if it
+/// cannot find one)
+pub const AUTHORIZATION_RENEWAL_WINDOW: u64 = 60 * 60 * 24 * 7;
@@ -0,0 +1,92 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use cid::Cid;
+use noosphere_core::{
+    authority::{SphereAction, SphereReference, SPHERE_SEMANTICS},
+    data::{Did, Link},
+};
+use noosphere_storage::Storage;
+use tokio_stream::StreamExt;
+use ucan::{
+    capability::{Capability, CapabilitySemantics},
+    crypto::KeyMaterial,
+};
+
+use crate::HasSphereContext;
+
+/// A key that has been authorized to work on a sphere, as it is recorded in
+/// the delegations of the sphere
+#[derive(Debug, Clone)]
+pub struct SphereAuthorization {
+    /// The name that was given to the authorization
+    pub name: String,
+    /// The DID of the authorized key
+    pub did: Did,
+    /// The CID of the UCAN that authorizes the key
+    pub jwt: Cid,
+    /// The sphere capabilities that are granted to the key
+    pub capabilities: Vec<Capability<SphereReference, SphereAction>>,
+    /// The time that the authorization expires, in seconds since the UNIX
+    /// epoch
+    pub expires_at: u64,
+}
+
+impl SphereAuthorization {
+    /// Returns true if the authorization has expired as of the given time (in
+    /// seconds since the UNIX epoch)
+    pub fn is_expired_at(&self, time: u64) -> bool {
+        self.expires_at <= time
+    }
+}
+
+/// Anything that can read the authority of a sphere should implement
+/// [SphereAuthorityRead]. A blanket implementation is provided for anything
+/// that implements [HasSphereContext].
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+pub trait SphereAuthorityRead<K, S>
+where
+    K: KeyMaterial + Clone + 'static,
+    S: Storage + 'static,
+{
+    /// Get all of the authorizations that are currently recorded in the
+    /// delegations of the sphere, including any that have expired
+    async fn get_authorizations(&self) -> Result<Vec<SphereAuthorization>>;
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl<C, K, S> SphereAuthorityRead<K, S> for C
+where
+    C: HasSphereContext<K, S>,
+    K: KeyMaterial + Clone + 'static,
+    S: Storage + 'static,
+{
+    async fn get_authorizations(&self) -> Result<Vec<SphereAuthorization>> {
+        let sphere = self.to_sphere().await?;
+        let delegations = sphere.get_authority().await?.get_delegations().await?;
+        let delegation_stream = delegations.into_stream().await?;
+        let mut authorizations = Vec::new();
+
+        tokio::pin!(delegation_stream);
+
+        while let Some((Link { cid, .. }, delegation)) = delegation_stream.try_next().await? {
+            let ucan = delegation.resolve_ucan(sphere.store()).await?;
+            let capabilities = ucan
+                .attenuation()
+                .iter()
+                .filter_map(|capability| SPHERE_SEMANTICS.parse(&capability.with, &capability.can))
+                .collect();
+
+            authorizations.push(SphereAuthorization {
+                name: delegation.name.clone(),
+                did: Did(ucan.audience().to_string()),
+                jwt: cid,
+                capabilities,
+                expires_at: *ucan.expires_at(),
+            });
+        }
+
+        Ok(authorizations)
+    }
+}
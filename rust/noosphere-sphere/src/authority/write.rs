@@ -0,0 +1,377 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use cid::Cid;
+use noosphere_core::{
+    authority::{Authorization, SphereAction, SphereReference},
+    data::{DelegationIpld, Did, Link},
+};
+use noosphere_storage::Storage;
+use ucan::{builder::UcanBuilder, capability::Capability, crypto::KeyMaterial};
+
+use crate::{
+    internal::SphereContextInternal, HasMutableSphereContext, SphereAuthorityRead,
+    SphereAuthorization,
+};
+
+/// Anything that can authorize keys to work on a sphere should implement
+/// [SphereAuthorityWrite]. A blanket implementation is provided for anything
+/// that implements [HasMutableSphereContext].
+///
+/// Authorizations are recorded in the pending mutation of the sphere, so they
+/// take effect once the sphere is saved (and, for a gateway to recognize them,
+/// synced).
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+pub trait SphereAuthorityWrite<K, S>: SphereAuthorityRead<K, S>
+where
+    K: KeyMaterial + Clone + 'static,
+    S: Storage + 'static,
+{
+    /// Authorize the key with the given [Did] to exercise the given
+    /// capabilities, recording the authorization under the given name. The
+    /// authorization expires at the given time (in seconds since the UNIX
+    /// epoch), or else when the author's own authorization expires. The CID
+    /// of the authorization's UCAN is returned.
+    async fn authorize(
+        &mut self,
+        name: &str,
+        did: &Did,
+        capabilities: &[Capability<SphereReference, SphereAction>],
+        expires_at: Option<u64>,
+    ) -> Result<Cid>;
+
+    /// Renew the authorizations with the given names (or, if no names are
+    /// given, all of the authorizations that were not issued to the author),
+    /// replacing each one with an authorization that grants the same
+    /// capabilities to the same key but expires at the given time. The renewed
+    /// authorizations are returned.
+    ///
+    /// Note that the replaced authorizations are removed from the delegations
+    /// of the sphere but not revoked, so they remain valid until they expire.
+    /// Revoking them would lock out any key that has not yet synced and adopted
+    /// its renewal, since it must still present its old authorization to do so.
+    async fn renew_authorizations(
+        &mut self,
+        names: &[String],
+        expires_at: Option<u64>,
+    ) -> Result<Vec<SphereAuthorization>>;
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl<C, K, S> SphereAuthorityWrite<K, S> for C
+where
+    C: HasMutableSphereContext<K, S>,
+    K: KeyMaterial + Clone + 'static,
+    S: Storage + 'static,
+{
+    async fn authorize(
+        &mut self,
+        name: &str,
+        did: &Did,
+        capabilities: &[Capability<SphereReference, SphereAction>],
+        expires_at: Option<u64>,
+    ) -> Result<Cid> {
+        self.assert_write_access().await?;
+
+        let (delegation, _) = delegate(self, name, did, capabilities, expires_at).await?;
+
+        self.sphere_context_mut()
+            .await?
+            .mutation_mut()
+            .delegations_mut()
+            .set(&Link::new(delegation.jwt), &delegation);
+
+        Ok(delegation.jwt)
+    }
+
+    async fn renew_authorizations(
+        &mut self,
+        names: &[String],
+        expires_at: Option<u64>,
+    ) -> Result<Vec<SphereAuthorization>> {
+        self.assert_write_access().await?;
+
+        let author_did = self.sphere_context().await?.author().identity().await?;
+        let authorizations = self.get_authorizations().await?;
+
+        for name in names {
+            if !authorizations
+                .iter()
+                .any(|authorization| &authorization.name == name)
+            {
+                return Err(anyhow!("There is no authorization named {:?}", name));
+            }
+        }
+
+        let mut renewed = Vec::new();
+
+        for authorization in authorizations {
+            let should_renew = if names.is_empty() {
+                authorization.did != author_did
+            } else {
+                names.contains(&authorization.name)
+            };
+
+            if !should_renew {
+                continue;
+            }
+
+            let (delegation, expires_at) = delegate(
+                self,
+                &authorization.name,
+                &authorization.did,
+                &authorization.capabilities,
+                expires_at,
+            )
+            .await?;
+
+            {
+                let mut sphere_context = self.sphere_context_mut().await?;
+                let delegations = sphere_context.mutation_mut().delegations_mut();
+
+                delegations.remove(&Link::new(authorization.jwt));
+                delegations.set(&Link::new(delegation.jwt), &delegation);
+            }
+
+            renewed.push(SphereAuthorization {
+                jwt: delegation.jwt,
+                expires_at,
+                ..authorization
+            });
+        }
+
+        Ok(renewed)
+    }
+}
+
+/// Issue a UCAN from the author of the sphere to the given key, granting the
+/// given capabilities, and register it as a delegation with the given name.
+/// The UCAN never outlives the author's own authorization. The delegation is
+/// returned along with the time that the UCAN expires.
+async fn delegate<C, K, S>(
+    context: &C,
+    name: &str,
+    did: &Did,
+    capabilities: &[Capability<SphereReference, SphereAction>],
+    expires_at: Option<u64>,
+) -> Result<(DelegationIpld, u64)>
+where
+    C: HasMutableSphereContext<K, S>,
+    K: KeyMaterial + Clone + 'static,
+    S: Storage + 'static,
+{
+    let sphere_context = context.sphere_context().await?;
+    let key = &sphere_context.author().key;
+    let authorization: &Authorization = sphere_context.author().require_authorization()?;
+    let db = sphere_context.db();
+
+    let authorization_expiry = *authorization.resolve_ucan(db).await?.expires_at();
+    let expires_at = match expires_at {
+        Some(expires_at) if expires_at > authorization_expiry => {
+            warn!(
+                "An authorization cannot outlive the authorization that it is issued with; it will expire at {} instead",
+                authorization_expiry
+            );
+            authorization_expiry
+        }
+        Some(expires_at) => expires_at,
+        None => authorization_expiry,
+    };
+
+    let mut builder = UcanBuilder::default()
+        .issued_by(key)
+        .for_audience(did)
+        .with_expiration(expires_at);
+
+    for capability in capabilities {
+        builder = builder.claiming_capability(capability);
+    }
+
+    let mut signable = builder
+        .with_nonce()
+        // TODO(ucan-wg/rs-ucan#32): Clean this up when we can use a CID as an authorization
+        // .witnessed_by(&authorization)
+        .build()?;
+
+    signable
+        .proofs
+        .push(Cid::try_from(authorization)?.to_string());
+
+    let jwt = signable.sign().await?.encode()?;
+
+    Ok((DelegationIpld::register(name, &jwt, db).await?, expires_at))
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use cid::Cid;
+    use noosphere_core::{
+        authority::{
+            generate_capability, generate_ed25519_key, Author, Authorization, SphereAction,
+            SphereReference,
+        },
+        data::Did,
+    };
+    use ucan::{
+        capability::{Capability, Resource, With},
+        crypto::KeyMaterial,
+    };
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    #[cfg(target_arch = "wasm32")]
+    wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_browser);
+
+    use crate::{
+        helpers::{simulated_sphere_context, SimulationAccess},
+        HasMutableSphereContext, HasSphereContext, SphereAuthorityRead, SphereAuthorityWrite,
+        SphereContext,
+    };
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[cfg_attr(not(target_arch = "wasm32"), tokio::test)]
+    async fn it_renews_authorizations_that_the_authorized_key_then_adopts() -> Result<()> {
+        let mut sphere_context =
+            simulated_sphere_context(SimulationAccess::ReadWrite, None).await?;
+        let identity = sphere_context.identity().await?;
+
+        let device_key = generate_ed25519_key();
+        let device_did = Did(device_key.get_did().await?);
+        let now = ucan::time::now();
+
+        let authorization = sphere_context
+            .authorize(
+                "device",
+                &device_did,
+                &[generate_capability(&identity, SphereAction::Push)],
+                Some(now + 60),
+            )
+            .await?;
+        sphere_context.save(None).await?;
+
+        let device_authorization = sphere_context
+            .get_authorizations()
+            .await?
+            .into_iter()
+            .find(|authorization| authorization.name == "device")
+            .unwrap();
+
+        assert_eq!(device_authorization.jwt, authorization);
+        assert_eq!(device_authorization.did, device_did);
+        assert_eq!(device_authorization.expires_at, now + 60);
+
+        // Every authorization but the author's own is renewed
+        let renewed = sphere_context
+            .renew_authorizations(&[], Some(now + 60 * 60 * 24 * 30))
+            .await?;
+        sphere_context.save(None).await?;
+
+        assert_eq!(renewed.len(), 1);
+        assert_eq!(renewed[0].name, "device");
+        assert_eq!(renewed[0].capabilities, device_authorization.capabilities);
+        assert!(!sphere_context
+            .get_authorizations()
+            .await?
+            .iter()
+            .any(|authorization| authorization.jwt == device_authorization.jwt));
+
+        // The device finds the renewal once its own authorization is about to
+        // expire
+        let db = sphere_context.sphere_context().await?.db().clone();
+        let mut device_context = SphereContext::new(
+            identity,
+            Author {
+                key: device_key,
+                authorization: Some(Authorization::Cid(authorization)),
+            },
+            db,
+            None,
+        )
+        .await?;
+
+        device_context.check_authorization_expiry().await?;
+
+        assert_eq!(
+            Cid::try_from(device_context.author().require_authorization()?)?,
+            renewed[0].jwt
+        );
+
+        Ok(())
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[cfg_attr(not(target_arch = "wasm32"), tokio::test)]
+    async fn it_only_adopts_a_renewal_that_grants_the_same_capabilities() -> Result<()> {
+        let mut sphere_context =
+            simulated_sphere_context(SimulationAccess::ReadWrite, None).await?;
+        let identity = sphere_context.identity().await?;
+
+        let device_key = generate_ed25519_key();
+        let device_did = Did(device_key.get_did().await?);
+        let now = ucan::time::now();
+
+        let authorization = sphere_context
+            .authorize(
+                "device",
+                &device_did,
+                &[generate_capability(&identity, SphereAction::Push)],
+                Some(now + 60),
+            )
+            .await?;
+
+        // A narrower authorization for the same key that outlives the first
+        sphere_context
+            .authorize(
+                "device-reports",
+                &device_did,
+                &[Capability {
+                    with: With::Resource {
+                        kind: Resource::Scoped(SphereReference::content(
+                            identity.as_str(),
+                            "build-reports/",
+                        )),
+                    },
+                    can: SphereAction::Push,
+                }],
+                Some(now + 60 * 60 * 24 * 60),
+            )
+            .await?;
+        sphere_context.save(None).await?;
+
+        let db = sphere_context.sphere_context().await?.db().clone();
+        let mut device_context = SphereContext::new(
+            identity,
+            Author {
+                key: device_key,
+                authorization: Some(Authorization::Cid(authorization)),
+            },
+            db,
+            None,
+        )
+        .await?;
+
+        device_context.check_authorization_expiry().await?;
+
+        assert_eq!(
+            Cid::try_from(device_context.author().require_authorization()?)?,
+            authorization
+        );
+
+        let renewed = sphere_context
+            .renew_authorizations(&["device".into()], Some(now + 60 * 60 * 24 * 30))
+            .await?;
+        sphere_context.save(None).await?;
+
+        device_context.check_authorization_expiry().await?;
+
+        assert_eq!(
+            Cid::try_from(device_context.author().require_authorization()?)?,
+            renewed[0].jwt
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,603 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    str::FromStr,
+    sync::Arc,
+};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use cid::Cid;
+use noosphere_core::data::{ContentType, Did, MapOperation};
+use noosphere_storage::{KeyValueStore, Storage};
+use serde::{Deserialize, Serialize};
+use subtext::{block::Block, primitive::Entity, Peer, Slashlink};
+use tokio::io::AsyncRead;
+use tokio_stream::StreamExt;
+use ucan::crypto::KeyMaterial;
+
+use crate::{HasSphereContext, SphereContentRead, SphereCursor, SpherePetnameRead, SphereWalker};
+
+/// A [BacklinkIndex] records the links between the slugs of one version of a
+/// sphere and the [Slashlink]s that they refer to. Links are recorded as they
+/// are written in the Subtext of each slug (so `/foo` and `@bob/foo` are
+/// distinct targets); wikilinks are recorded as the equivalent slashlink.
+#[derive(Default, Clone, Debug, Serialize, Deserialize)]
+pub struct BacklinkIndex {
+    /// For each link target, the slugs that link to it
+    backlinks: BTreeMap<String, BTreeSet<String>>,
+    /// For each slug, the link targets that it links to
+    links: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl BacklinkIndex {
+    /// Get the slugs in this version of the sphere that link to the given
+    /// target
+    pub fn sources(&self, target: &Slashlink) -> BTreeSet<String> {
+        self.backlinks
+            .get(&target.to_string())
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Get the link targets that the given slug links to
+    pub fn targets(&self, slug: &str) -> BTreeSet<String> {
+        self.links.get(slug).cloned().unwrap_or_default()
+    }
+
+    fn insert(&mut self, slug: &str, targets: BTreeSet<String>) {
+        self.remove(slug);
+
+        if targets.is_empty() {
+            return;
+        }
+
+        for target in targets.iter() {
+            self.backlinks
+                .entry(target.clone())
+                .or_default()
+                .insert(slug.to_string());
+        }
+
+        self.links.insert(slug.to_string(), targets);
+    }
+
+    fn remove(&mut self, slug: &str) {
+        let targets = match self.links.remove(slug) {
+            Some(targets) => targets,
+            None => return,
+        };
+
+        for target in targets {
+            if let Some(sources) = self.backlinks.get_mut(&target) {
+                sources.remove(slug);
+
+                if sources.is_empty() {
+                    self.backlinks.remove(&target);
+                }
+            }
+        }
+    }
+}
+
+/// Anything that can look up the backlinks of a sphere should implement
+/// [SphereBacklinks]. A blanket implementation is provided for anything that
+/// implements [HasSphereContext].
+///
+/// The [BacklinkIndex] of each version of a sphere is stored locally once it
+/// has been built. Building the index for a version starts from the nearest
+/// earlier version that has one, and only re-parses the slugs that changed in
+/// between (storing the index of each of those versions along the way). If
+/// no earlier version has been indexed, or the history in between is not
+/// available locally (as is usually the case for a peer sphere), the version
+/// is indexed from its content alone.
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+pub trait SphereBacklinks<K, S>
+where
+    K: KeyMaterial + Clone + 'static,
+    S: Storage + 'static,
+{
+    /// Get the [BacklinkIndex] for the version of the sphere that this view
+    /// is pointing to, building it if necessary
+    async fn backlink_index(&self) -> Result<BacklinkIndex>;
+
+    /// Get the slugs that link to the given [Slashlink] (which is relative to
+    /// this sphere). Backlinks from this sphere are returned as slashlinks
+    /// without a peer. Then, the spheres in this sphere's address book are
+    /// visited at their resolved versions, and backlinks from them are
+    /// returned as slashlinks prefixed with the petname of the sphere they
+    /// were found in. Peers that cannot be visited or indexed are skipped.
+    ///
+    /// In another sphere, a link counts as a backlink if it refers to this
+    /// sphere by any petname that the other sphere has assigned to it, or (for
+    /// targets in that other sphere) if it is a local link.
+    async fn backlinks(&self, target: &Slashlink) -> Result<Vec<Slashlink>>;
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl<C, K, S> SphereBacklinks<K, S> for C
+where
+    C: HasSphereContext<K, S>,
+    K: KeyMaterial + Clone + 'static,
+    S: Storage + 'static,
+{
+    async fn backlink_index(&self) -> Result<BacklinkIndex> {
+        let version = self.version().await?;
+        let mut store = self.sphere_context().await?.db().to_backlinks_store();
+
+        if let Some(index) = store.get_key(&version.to_string()).await? {
+            return Ok(index);
+        }
+
+        let index = match update_backlink_index(self, &mut store).await {
+            Ok(Some(index)) => index,
+            result => {
+                if let Err(error) = result {
+                    warn!("Could not update the backlink index of {version}: {error}");
+                }
+
+                let index = build_backlink_index(self).await?;
+                store.set_key(&version.to_string(), &index).await?;
+                index
+            }
+        };
+
+        store.flush().await?;
+
+        Ok(index)
+    }
+
+    async fn backlinks(&self, target: &Slashlink) -> Result<Vec<Slashlink>> {
+        let mut backlinks: Vec<Slashlink> = self
+            .backlink_index()
+            .await?
+            .sources(target)
+            .into_iter()
+            .map(|slug| Slashlink {
+                peer: Peer::None,
+                slug: Some(slug),
+            })
+            .collect();
+
+        let identity = self.identity().await?;
+        let petnames = SphereWalker::from(self.clone()).list_petnames().await?;
+
+        for petname in petnames {
+            let peer_context = match self
+                .sphere_context()
+                .await?
+                .traverse_by_petname(&petname)
+                .await
+            {
+                Ok(Some(peer_context)) => Arc::new(peer_context),
+                Ok(None) => continue,
+                Err(error) => {
+                    warn!("Could not look for backlinks in \"{petname}\": {}", error);
+                    continue;
+                }
+            };
+
+            let peer_targets = match peer_targets(target, &identity, &petname, &peer_context).await
+            {
+                Ok(peer_targets) if !peer_targets.is_empty() => peer_targets,
+                Ok(_) => continue,
+                Err(error) => {
+                    warn!("Could not look for backlinks in \"{petname}\": {}", error);
+                    continue;
+                }
+            };
+
+            let peer_index = match peer_context.backlink_index().await {
+                Ok(peer_index) => peer_index,
+                Err(error) => {
+                    warn!("Could not index backlinks in \"{petname}\": {}", error);
+                    continue;
+                }
+            };
+            let mut sources = BTreeSet::new();
+
+            for peer_target in peer_targets {
+                sources.append(&mut peer_index.sources(&peer_target));
+            }
+
+            backlinks.extend(sources.into_iter().map(|slug| Slashlink {
+                peer: Peer::Name(vec![petname.clone()]),
+                slug: Some(slug),
+            }));
+        }
+
+        Ok(backlinks)
+    }
+}
+
+/// Build the [BacklinkIndex] of the version of the sphere that the given view
+/// is pointing to from the index of the nearest earlier version that has one,
+/// re-indexing the slugs that changed in each version in between and storing
+/// the index of each of those versions as it goes. Returns `None` if no
+/// earlier version has a stored index.
+async fn update_backlink_index<C, K, S>(
+    context: &C,
+    store: &mut S::KeyValueStore,
+) -> Result<Option<BacklinkIndex>>
+where
+    C: HasSphereContext<K, S>,
+    K: KeyMaterial + Clone + 'static,
+    S: Storage + 'static,
+{
+    let mut stored_index: Option<BacklinkIndex> = None;
+    let mut unindexed_versions: Vec<Cid> = Vec::new();
+
+    let history = context.to_sphere().await?.into_history_stream(None);
+
+    tokio::pin!(history);
+
+    while let Some((version, _)) = history.try_next().await? {
+        if let Some(index) = store.get_key(&version.to_string()).await? {
+            stored_index = Some(index);
+            break;
+        }
+
+        unindexed_versions.push(version);
+    }
+
+    let mut index = match stored_index {
+        Some(index) => index,
+        None => return Ok(None),
+    };
+
+    for version in unindexed_versions.into_iter().rev() {
+        let cursor = SphereCursor::mounted_at(context.clone(), &version);
+        let changelog = cursor
+            .to_sphere()
+            .await?
+            .get_content()
+            .await?
+            .load_changelog()
+            .await?;
+
+        for operation in changelog.changes {
+            let slug = match operation {
+                MapOperation::Add { key, .. } => key,
+                MapOperation::Remove { key } => key,
+            };
+
+            index_slug(&cursor, &mut index, &slug).await?;
+        }
+
+        store.set_key(&version.to_string(), &index).await?;
+    }
+
+    Ok(Some(index))
+}
+
+/// Build a [BacklinkIndex] from the content of the version of the sphere that
+/// the given view is pointing to, without reference to its history
+async fn build_backlink_index<C, K, S>(context: &C) -> Result<BacklinkIndex>
+where
+    C: HasSphereContext<K, S>,
+    K: KeyMaterial + Clone + 'static,
+    S: Storage + 'static,
+{
+    let mut index = BacklinkIndex::default();
+
+    for slug in SphereWalker::from(context.clone()).list_slugs().await? {
+        index_slug(context, &mut index, &slug).await?;
+    }
+
+    Ok(index)
+}
+
+/// Re-index the links of a single slug, as of the version of the sphere that
+/// the given view is pointing to
+async fn index_slug<C, K, S>(context: &C, index: &mut BacklinkIndex, slug: &str) -> Result<()>
+where
+    C: HasSphereContext<K, S>,
+    K: KeyMaterial + Clone + 'static,
+    S: Storage + 'static,
+{
+    match context.read(slug).await? {
+        Some(file) if file.memo.content_type() == Some(ContentType::Subtext) => {
+            index.insert(slug, subtext_links(file.contents).await)
+        }
+        _ => index.remove(slug),
+    };
+
+    Ok(())
+}
+
+/// Translate a target that is relative to the sphere with the given identity
+/// into the equivalent targets as they would be written in the sphere that it
+/// calls `petname`
+async fn peer_targets<C, K, S>(
+    target: &Slashlink,
+    identity: &Did,
+    petname: &str,
+    peer_context: &C,
+) -> Result<Vec<Slashlink>>
+where
+    C: HasSphereContext<K, S>,
+    K: KeyMaterial + Clone + 'static,
+    S: Storage + 'static,
+{
+    Ok(match &target.peer {
+        Peer::None => peer_context
+            .get_assigned_petnames(identity)
+            .await?
+            .into_iter()
+            .map(|name| Slashlink {
+                peer: Peer::Name(vec![name]),
+                slug: target.slug.clone(),
+            })
+            .collect(),
+        Peer::Name(petnames) if petnames.len() == 1 && petnames[0] == petname => {
+            vec![Slashlink {
+                peer: Peer::None,
+                slug: target.slug.clone(),
+            }]
+        }
+        _ => Vec::new(),
+    })
+}
+
+/// Collect every slashlink and wikilink in some Subtext, normalized to the
+/// string form of the [Slashlink] that it refers to
+async fn subtext_links<R>(contents: R) -> BTreeSet<String>
+where
+    R: AsyncRead + Unpin,
+{
+    let mut links = BTreeSet::new();
+    let block_stream = subtext::stream::<Block<Entity>, Entity, _>(contents).await;
+
+    tokio::pin!(block_stream);
+
+    while let Some(block) = block_stream.next().await {
+        let block = match block {
+            Ok(block) => block,
+            Err(_) => continue,
+        };
+
+        for entity in block.to_content_entities() {
+            let slashlink = match entity {
+                Entity::SlashLink(text) => Slashlink::from_str(text.as_ref()).ok(),
+                Entity::WikiLink(text) => subtext::util::to_slug(text.as_ref())
+                    .ok()
+                    .and_then(|slug| Slashlink::from_str(&format!("/{slug}")).ok()),
+                _ => None,
+            };
+
+            if let Some(slashlink) = slashlink {
+                links.insert(slashlink.to_string());
+            }
+        }
+    }
+
+    links
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use anyhow::Result;
+    use noosphere_core::{
+        authority::{generate_capability, SphereAction},
+        data::{ContentType, LinkRecord},
+    };
+    use noosphere_storage::{KeyValueStore, Storage};
+    use serde_json::json;
+    use subtext::Slashlink;
+    use ucan::{builder::UcanBuilder, crypto::KeyMaterial};
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    #[cfg(target_arch = "wasm32")]
+    wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_browser);
+
+    use crate::{
+        helpers::{simulated_sphere_context, SimulationAccess},
+        BacklinkIndex, HasMutableSphereContext, HasSphereContext, SphereBacklinks,
+        SphereContentWrite, SphereCursor, SpherePetnameWrite,
+    };
+
+    async fn make_link_record<C, K, S>(sphere_context: &C) -> Result<LinkRecord>
+    where
+        C: HasSphereContext<K, S>,
+        K: KeyMaterial + Clone + 'static,
+        S: Storage + 'static,
+    {
+        let version = sphere_context.version().await?;
+        let identity = sphere_context.identity().await?;
+        let context = sphere_context.sphere_context().await?;
+        let author = context.author();
+
+        Ok(LinkRecord::from(
+            UcanBuilder::default()
+                .issued_by(&author.key)
+                .for_audience(&identity)
+                .witnessed_by(
+                    &author
+                        .authorization
+                        .as_ref()
+                        .unwrap()
+                        .resolve_ucan(context.db())
+                        .await?,
+                )
+                .claiming_capability(&generate_capability(&identity, SphereAction::Publish))
+                .with_lifetime(120)
+                .with_fact(json!({
+                    "link": version.to_string()
+                }))
+                .build()?
+                .sign()
+                .await?,
+        ))
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[cfg_attr(not(target_arch = "wasm32"), tokio::test)]
+    async fn it_indexes_backlinks_at_each_version() -> Result<()> {
+        let mut sphere_context =
+            simulated_sphere_context(SimulationAccess::ReadWrite, None).await?;
+
+        for (slug, body) in [
+            ("cats", "Cats are /animals\n\nSee also [[Dogs]]"),
+            ("dogs", "Dogs are /animals too"),
+            ("animals", "Animals are multicellular"),
+        ] {
+            sphere_context
+                .write(
+                    slug,
+                    &ContentType::Subtext.to_string(),
+                    body.as_bytes(),
+                    None,
+                )
+                .await?;
+        }
+
+        let first_version = sphere_context.save(None).await?;
+
+        sphere_context.remove("cats").await?;
+        sphere_context.save(None).await?;
+
+        let index = sphere_context.backlink_index().await?;
+        let animals = Slashlink::from_str("/animals")?;
+
+        assert_eq!(
+            index.sources(&animals).into_iter().collect::<Vec<String>>(),
+            vec!["dogs".to_string()]
+        );
+        assert!(index.sources(&Slashlink::from_str("/dogs")?).is_empty());
+
+        let cursor = SphereCursor::mounted_at(sphere_context.clone(), &first_version);
+        let first_index = cursor.backlink_index().await?;
+
+        assert_eq!(first_index.sources(&animals).len(), 2);
+        assert_eq!(
+            first_index
+                .sources(&Slashlink::from_str("/dogs")?)
+                .into_iter()
+                .collect::<Vec<String>>(),
+            vec!["cats".to_string()]
+        );
+
+        let backlinks = sphere_context.backlinks(&animals).await?;
+
+        assert_eq!(backlinks.len(), 1);
+        assert_eq!(backlinks[0].to_string(), "/dogs");
+
+        Ok(())
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[cfg_attr(not(target_arch = "wasm32"), tokio::test)]
+    async fn it_stores_the_index_of_each_version_it_builds_on() -> Result<()> {
+        let mut sphere_context =
+            simulated_sphere_context(SimulationAccess::ReadWrite, None).await?;
+        let mut versions = Vec::new();
+
+        for (slug, body) in [
+            ("cats", "Cats are /animals"),
+            ("dogs", "Dogs are /animals too"),
+            ("birds", "Birds are /animals with feathers"),
+        ] {
+            sphere_context
+                .write(
+                    slug,
+                    &ContentType::Subtext.to_string(),
+                    body.as_bytes(),
+                    None,
+                )
+                .await?;
+            versions.push(sphere_context.save(None).await?);
+        }
+
+        SphereCursor::mounted_at(sphere_context.clone(), &versions[0])
+            .backlink_index()
+            .await?;
+
+        let index = sphere_context.backlink_index().await?;
+
+        assert_eq!(index.sources(&Slashlink::from_str("/animals")?).len(), 3);
+
+        let store = sphere_context
+            .sphere_context()
+            .await?
+            .db()
+            .to_backlinks_store();
+
+        for version in versions.iter() {
+            let stored_index: Option<BacklinkIndex> = store.get_key(&version.to_string()).await?;
+
+            assert!(stored_index.is_some());
+        }
+
+        let second_index: BacklinkIndex = store.get_key(&versions[1].to_string()).await?.unwrap();
+
+        assert_eq!(
+            second_index
+                .sources(&Slashlink::from_str("/animals")?)
+                .into_iter()
+                .collect::<Vec<String>>(),
+            vec!["cats".to_string(), "dogs".to_string()]
+        );
+
+        Ok(())
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[cfg_attr(not(target_arch = "wasm32"), tokio::test)]
+    async fn it_finds_backlinks_in_peer_spheres() -> Result<()> {
+        let mut sphere_context =
+            simulated_sphere_context(SimulationAccess::ReadWrite, None).await?;
+        let db = sphere_context.sphere_context().await?.db().clone();
+        let mut peer_context =
+            simulated_sphere_context(SimulationAccess::ReadWrite, Some(db)).await?;
+
+        // The peer has some history before it links to its ideas
+        peer_context
+            .write(
+                "ideas",
+                &ContentType::Subtext.to_string(),
+                "Cats are great".as_bytes(),
+                None,
+            )
+            .await?;
+        peer_context.save(None).await?;
+
+        peer_context
+            .write(
+                "notes",
+                &ContentType::Subtext.to_string(),
+                "See my /ideas".as_bytes(),
+                None,
+            )
+            .await?;
+        peer_context.save(None).await?;
+
+        let link_record = make_link_record(&peer_context).await?;
+
+        sphere_context.adopt_petname("bob", &link_record).await?;
+        sphere_context
+            .write(
+                "reading-list",
+                &ContentType::Subtext.to_string(),
+                "Check out /bob-ideas".as_bytes(),
+                None,
+            )
+            .await?;
+        sphere_context.save(None).await?;
+
+        let backlinks = sphere_context
+            .backlinks(&Slashlink::from_str("@bob/ideas")?)
+            .await?
+            .into_iter()
+            .map(|slashlink| slashlink.to_string())
+            .collect::<Vec<String>>();
+
+        assert_eq!(backlinks, vec!["@bob/notes".to_string()]);
+
+        Ok(())
+    }
+}
@@ -1,27 +1,306 @@
+use std::{
+    future::Future,
+    io::{Error, ErrorKind, SeekFrom},
+    ops::Range,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use anyhow::Result;
 use async_stream::try_stream;
 use bytes::Bytes;
 use cid::Cid;
 use libipld_cbor::DagCborCodec;
-use noosphere_core::data::BodyChunkIpld;
+use noosphere_core::data::{BodyChunkIpld, BodyIpld};
 use noosphere_storage::BlockStore;
+use tokio::io::{AsyncRead, AsyncSeek, ReadBuf};
 use tokio_stream::Stream;
 
-/// Helper to easily decode a linked list of `BodyChunkIpld` as a byte stream
+/// Helper to easily decode a memo body (either a tree of `BodyChunkIpld` or,
+/// for bodies that were stored before trees were introduced, a linked list of
+/// them) as a byte stream
 pub struct BodyChunkDecoder<'a, 'b, S: BlockStore>(pub &'a Cid, pub &'b S);
 
 impl<'a, 'b, S: BlockStore> BodyChunkDecoder<'a, 'b, S> {
     pub fn stream(self) -> impl Stream<Item = Result<Bytes, std::io::Error>> + Unpin {
-        let mut next = Some(*self.0);
+        let mut remaining_cids = vec![*self.0];
         let store = self.1.clone();
         Box::pin(try_stream! {
-            while let Some(cid) = next {
+            while let Some(cid) = remaining_cids.pop() {
                 debug!("Unpacking block {}...", cid);
-                let chunk = store.load::<DagCborCodec, BodyChunkIpld>(&cid).await.map_err(|error| {
+                let node = BodyIpld::load(&cid, &store).await.map_err(|error| {
                     std::io::Error::new(std::io::ErrorKind::UnexpectedEof, error.to_string())
                 })?;
-                yield Bytes::from(chunk.bytes);
-                next = chunk.next;
+
+                match node {
+                    BodyIpld::Tree(tree) => remaining_cids.extend(tree.links.into_iter().rev()),
+                    BodyIpld::Chunk(chunk) => {
+                        remaining_cids.extend(chunk.next);
+                        yield Bytes::from(chunk.bytes);
+                    }
+                }
             }
         })
     }
 }
+
+#[cfg(not(target_arch = "wasm32"))]
+type LoadChunkFuture =
+    Pin<Box<dyn Future<Output = Result<Option<(u64, BodyChunkIpld)>>> + Send + 'static>>;
+
+#[cfg(target_arch = "wasm32")]
+type LoadChunkFuture =
+    Pin<Box<dyn Future<Output = Result<Option<(u64, BodyChunkIpld)>>> + 'static>>;
+
+/// A reader for a memo body that implements [AsyncSeek] as well as
+/// [AsyncRead]. Only the chunks that hold the bytes being read are loaded;
+/// for a body that is stored as a tree, finding the chunk for any position
+/// only requires loading one block at each level of the tree. Bodies that
+/// are stored as a linked list of chunks can be read too, but seeking within
+/// them requires walking the list from the start.
+pub struct BodyReader<S>
+where
+    S: BlockStore + 'static,
+{
+    root: BodyIpld,
+    store: S,
+    size: u64,
+    position: u64,
+    /// The most recently loaded chunk, along with its offset in the body
+    chunk: Option<(u64, BodyChunkIpld)>,
+    pending_chunk: Option<LoadChunkFuture>,
+}
+
+impl<S> BodyReader<S>
+where
+    S: BlockStore + 'static,
+{
+    /// Open the body whose root has the given [Cid]
+    pub async fn open(body: &Cid, store: S) -> Result<Self> {
+        let root = BodyIpld::load(body, &store).await?;
+        let size = root.size(&store).await?;
+
+        Ok(BodyReader {
+            root,
+            store,
+            size,
+            position: 0,
+            chunk: None,
+            pending_chunk: None,
+        })
+    }
+
+    /// The total number of bytes in the body
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Read the bytes of the body that are within the given range, without
+    /// changing the position of the reader; the range is truncated to the end
+    /// of the body
+    pub fn read_range(&self, range: Range<u64>) -> impl Future<Output = Result<Vec<u8>>> {
+        let root = self.root.clone();
+        let store = self.store.clone();
+
+        async move { root.load_range(range, &store).await }
+    }
+
+    fn load_chunk(&self) -> LoadChunkFuture {
+        let position = self.position;
+        let store = self.store.clone();
+
+        // When reading a linked list in order, the next chunk can be loaded
+        // directly rather than by walking the list from the start
+        if let Some((
+            offset,
+            BodyChunkIpld {
+                bytes,
+                next: Some(next),
+            },
+        )) = &self.chunk
+        {
+            let next_offset = offset + bytes.len() as u64;
+
+            if position == next_offset {
+                let next = *next;
+
+                return Box::pin(async move {
+                    let chunk = store.load::<DagCborCodec, BodyChunkIpld>(&next).await?;
+                    Ok(Some((next_offset, chunk)))
+                });
+            }
+        }
+
+        let root = self.root.clone();
+
+        Box::pin(async move { root.load_chunk_at(position, &store).await })
+    }
+}
+
+impl<S> AsyncRead for BodyReader<S>
+where
+    S: BlockStore + Unpin + 'static,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        let reader = self.get_mut();
+
+        loop {
+            if reader.position >= reader.size || buf.remaining() == 0 {
+                return Poll::Ready(Ok(()));
+            }
+
+            if let Some((offset, chunk)) = &reader.chunk {
+                let end = offset + chunk.bytes.len() as u64;
+
+                if (*offset..end).contains(&reader.position) {
+                    let start = (reader.position - offset) as usize;
+                    let length = buf.remaining().min(chunk.bytes.len() - start);
+
+                    buf.put_slice(&chunk.bytes[start..start + length]);
+                    reader.position += length as u64;
+
+                    return Poll::Ready(Ok(()));
+                }
+            }
+
+            if reader.pending_chunk.is_none() {
+                reader.pending_chunk = Some(reader.load_chunk());
+            }
+
+            let result = match reader.pending_chunk.as_mut() {
+                Some(pending_chunk) => match pending_chunk.as_mut().poll(cx) {
+                    Poll::Ready(result) => result,
+                    Poll::Pending => return Poll::Pending,
+                },
+                None => continue,
+            };
+
+            reader.pending_chunk = None;
+
+            match result {
+                Ok(Some(chunk)) => reader.chunk = Some(chunk),
+                Ok(None) => return Poll::Ready(Ok(())),
+                Err(error) => {
+                    return Poll::Ready(Err(Error::new(
+                        ErrorKind::UnexpectedEof,
+                        error.to_string(),
+                    )))
+                }
+            }
+        }
+    }
+}
+
+impl<S> AsyncSeek for BodyReader<S>
+where
+    S: BlockStore + Unpin + 'static,
+{
+    fn start_seek(self: Pin<&mut Self>, position: SeekFrom) -> std::io::Result<()> {
+        let reader = self.get_mut();
+
+        let (base, delta) = match position {
+            SeekFrom::Start(position) => (position as i128, 0),
+            SeekFrom::End(delta) => (reader.size as i128, delta as i128),
+            SeekFrom::Current(delta) => (reader.position as i128, delta as i128),
+        };
+
+        reader.position = u64::try_from(base + delta).map_err(|_| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                "Cannot seek to a position before the start of the body",
+            )
+        })?;
+        reader.pending_chunk = None;
+
+        Ok(())
+    }
+
+    fn poll_complete(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<std::io::Result<u64>> {
+        Poll::Ready(Ok(self.position))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::SeekFrom;
+
+    use noosphere_core::data::{BodyChunkIpld, BodyTreeIpld};
+    use noosphere_storage::MemoryStore;
+    use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    #[cfg(target_arch = "wasm32")]
+    wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_browser);
+
+    use super::BodyReader;
+
+    fn make_bytes() -> Vec<u8> {
+        let mut state: u32 = 0x2545f491;
+
+        (0..(2 * 1024 * 1024))
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                state as u8
+            })
+            .collect()
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[cfg_attr(not(target_arch = "wasm32"), tokio::test)]
+    async fn it_can_seek_and_read_within_a_body() {
+        let mut store = MemoryStore::default();
+        let bytes = make_bytes();
+
+        let tree = BodyTreeIpld::store_bytes(&bytes, &mut store).await.unwrap();
+        let list = BodyChunkIpld::store_bytes(&bytes, &mut store)
+            .await
+            .unwrap();
+
+        for body in [tree, list] {
+            let mut reader = BodyReader::open(&body, store.clone()).await.unwrap();
+
+            assert_eq!(reader.size(), bytes.len() as u64);
+
+            let mut all_bytes = Vec::new();
+            reader.read_to_end(&mut all_bytes).await.unwrap();
+
+            assert_eq!(all_bytes, bytes);
+
+            let mut window = vec![0u8; 700_000];
+
+            reader.seek(SeekFrom::Start(1_000_000)).await.unwrap();
+            reader.read_exact(&mut window).await.unwrap();
+
+            assert_eq!(window, &bytes[1_000_000..1_700_000]);
+
+            reader.seek(SeekFrom::Current(-1_700_000)).await.unwrap();
+            reader.read_exact(&mut window).await.unwrap();
+
+            assert_eq!(window, &bytes[0..700_000]);
+
+            let position = reader.seek(SeekFrom::End(-10)).await.unwrap();
+            let mut tail = Vec::new();
+            reader.read_to_end(&mut tail).await.unwrap();
+
+            assert_eq!(position, bytes.len() as u64 - 10);
+            assert_eq!(tail, &bytes[bytes.len() - 10..]);
+            assert!(reader
+                .seek(SeekFrom::Current(-(bytes.len() as i64) - 1))
+                .await
+                .is_err());
+
+            assert_eq!(
+                reader.read_range(500..1_500_000).await.unwrap(),
+                &bytes[500..1_500_000]
+            );
+        }
+    }
+}
@@ -1,8 +1,12 @@
-use std::pin::Pin;
+use std::{
+    io::SeekFrom,
+    pin::Pin,
+    task::{Context, Poll},
+};
 
 use cid::Cid;
 use noosphere_core::data::{Did, MemoIpld};
-use tokio::io::AsyncRead;
+use tokio::io::{AsyncRead, AsyncSeek, ReadBuf};
 
 #[cfg(not(target_arch = "wasm32"))]
 pub trait AsyncFileBody: AsyncRead + Unpin + Send {}
@@ -39,3 +43,30 @@ where
         }
     }
 }
+
+impl<C> AsyncRead for SphereFile<C>
+where
+    C: AsyncRead + Unpin,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.get_mut().contents).poll_read(cx, buf)
+    }
+}
+
+/// A [SphereFile] can be seeked when its contents can (see [crate::BodyReader])
+impl<C> AsyncSeek for SphereFile<C>
+where
+    C: AsyncSeek + Unpin,
+{
+    fn start_seek(self: Pin<&mut Self>, position: SeekFrom) -> std::io::Result<()> {
+        Pin::new(&mut self.get_mut().contents).start_seek(position)
+    }
+
+    fn poll_complete(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<u64>> {
+        Pin::new(&mut self.get_mut().contents).poll_complete(cx)
+    }
+}
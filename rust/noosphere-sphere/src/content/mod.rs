@@ -4,10 +4,14 @@
 
 mod decoder;
 mod file;
+mod policy;
 mod read;
+mod registry;
 mod write;
 
 pub use decoder::*;
 pub use file::*;
+pub use policy::*;
 pub use read::*;
+pub use registry::*;
 pub use write::*;
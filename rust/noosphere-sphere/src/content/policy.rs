@@ -0,0 +1,201 @@
+use noosphere_core::data::{ContentType, Did, Header, MemoIpld};
+use serde::{Deserialize, Serialize};
+
+#[cfg(doc)]
+use crate::{SphereContentWrite, SphereContext};
+
+/// A [HeaderPolicy] describes the headers that are implicitly added to every
+/// memo that is written (or linked) to a sphere via [SphereContentWrite]. It
+/// is stored in the sphere's local metadata (see
+/// [SphereContext::configure_header_policy]), so that every client that
+/// writes to the sphere on this device stamps the same headers. Headers that
+/// are passed explicitly when writing always take precedence over the ones
+/// added by the policy. By default, no headers are added.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HeaderPolicy {
+    /// Set the `Author` header to the [Did] of the author who writes the memo
+    pub author: bool,
+    /// Set the `Created` header when a memo is first written, and the
+    /// `Modified` header every time that it is written; both are formatted
+    /// as RFC 3339 timestamps in UTC
+    pub timestamps: bool,
+    /// Set the `File-Extension` header to one that is implied by the memo's
+    /// content type, unless the memo already has one
+    pub file_extension: bool,
+    /// Application-defined headers that are set on every memo
+    pub headers: Vec<(String, String)>,
+}
+
+impl HeaderPolicy {
+    /// Add the headers described by this policy to the memo, which is being
+    /// written by the author with the given [Did] at the given time (in
+    /// seconds since the Unix epoch). A `Created` header that the memo has
+    /// carried over from its previous version is left as it is.
+    pub fn apply(&self, memo: &mut MemoIpld, author: &Did, content_type: &ContentType, now: u64) {
+        for (name, value) in &self.headers {
+            memo.replace_first_header(name, value);
+        }
+
+        if self.author {
+            memo.replace_first_header(&Header::Author.to_string(), author);
+        }
+
+        if self.timestamps {
+            let timestamp = format_timestamp(now);
+
+            if memo
+                .get_first_header(&Header::Created.to_string())
+                .is_none()
+            {
+                memo.replace_first_header(&Header::Created.to_string(), &timestamp);
+            }
+
+            memo.replace_first_header(&Header::Modified.to_string(), &timestamp);
+        }
+
+        if self.file_extension
+            && memo
+                .get_first_header(&Header::FileExtension.to_string())
+                .is_none()
+        {
+            if let Some(extension) = file_extension_for(content_type) {
+                memo.replace_first_header(&Header::FileExtension.to_string(), extension);
+            }
+        }
+    }
+}
+
+fn file_extension_for(content_type: &ContentType) -> Option<&'static str> {
+    match content_type {
+        ContentType::Text => Some("txt"),
+        ContentType::Subtext => Some("subtext"),
+        ContentType::Json => Some("json"),
+        ContentType::Cbor => Some("cbor"),
+        ContentType::DagCbor => Some("dagcbor"),
+        _ => None,
+    }
+}
+
+/// Format a number of seconds since the Unix epoch as an RFC 3339 timestamp
+/// in UTC (e.g., `2000-02-29T00:00:00Z`)
+fn format_timestamp(seconds: u64) -> String {
+    let days = seconds / 86400;
+    let seconds_of_day = seconds % 86400;
+
+    // Converts days since the epoch to a date in the proleptic Gregorian
+    // calendar; see http://howardhinnant.github.io/date_algorithms.html
+    let shifted_days = days + 719468;
+    let era = shifted_days / 146097;
+    let day_of_era = shifted_days - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        seconds_of_day / 3600,
+        seconds_of_day % 3600 / 60,
+        seconds_of_day % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use noosphere_core::{data::Header, tracing::initialize_tracing};
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    #[cfg(target_arch = "wasm32")]
+    wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_browser);
+
+    use super::format_timestamp;
+    use crate::{
+        helpers::{simulated_sphere_context, SimulationAccess},
+        HasMutableSphereContext, HeaderPolicy, SphereContentRead, SphereContentWrite,
+    };
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[cfg_attr(not(target_arch = "wasm32"), test)]
+    fn it_formats_timestamps_as_rfc_3339() {
+        assert_eq!(format_timestamp(0), "1970-01-01T00:00:00Z");
+        assert_eq!(format_timestamp(951782400), "2000-02-29T00:00:00Z");
+        assert_eq!(format_timestamp(1700000000), "2023-11-14T22:13:20Z");
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[cfg_attr(not(target_arch = "wasm32"), tokio::test)]
+    async fn it_stamps_headers_according_to_the_header_policy() {
+        initialize_tracing(None);
+
+        let mut sphere_context = simulated_sphere_context(SimulationAccess::ReadWrite, None)
+            .await
+            .unwrap();
+
+        let author_did = {
+            let mut sphere_context = sphere_context.lock().await;
+
+            sphere_context
+                .configure_header_policy(Some(&HeaderPolicy {
+                    author: true,
+                    timestamps: true,
+                    file_extension: true,
+                    headers: vec![("App".into(), "Subconscious".into())],
+                }))
+                .await
+                .unwrap();
+
+            sphere_context.author().identity().await.unwrap()
+        };
+
+        sphere_context
+            .write(
+                "cats",
+                "text/plain",
+                b"Cats are great".as_ref(),
+                Some(vec![(
+                    Header::Created.to_string(),
+                    "2000-01-01T00:00:00Z".into(),
+                )]),
+            )
+            .await
+            .unwrap();
+        sphere_context.save(None).await.unwrap();
+
+        sphere_context
+            .write("cats", "text/plain", b"Cats are the best".as_ref(), None)
+            .await
+            .unwrap();
+        sphere_context.save(None).await.unwrap();
+
+        let memo = sphere_context.read("cats").await.unwrap().unwrap().memo;
+
+        assert_eq!(
+            memo.get_first_header(&Header::Created.to_string()),
+            Some("2000-01-01T00:00:00Z".into())
+        );
+        assert!(memo
+            .get_first_header(&Header::Modified.to_string())
+            .is_some());
+        assert_eq!(
+            memo.get_first_header(&Header::Author.to_string()),
+            Some(author_did.to_string())
+        );
+        assert_eq!(
+            memo.get_first_header(&Header::FileExtension.to_string()),
+            Some("txt".into())
+        );
+        assert_eq!(memo.get_first_header("App"), Some("Subconscious".into()));
+    }
+}
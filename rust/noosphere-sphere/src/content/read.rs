@@ -1,12 +1,18 @@
-use anyhow::Result;
-use noosphere_storage::Storage;
+use std::ops::Range;
+
+use anyhow::{anyhow, Result};
+use libipld_cbor::DagCborCodec;
+use noosphere_core::data::ContentType;
+use noosphere_storage::{block_deserialize, BlockStoreSend, SphereDb, Storage};
+use serde::de::DeserializeOwned;
+use tokio::io::AsyncReadExt;
 
 use ucan::crypto::KeyMaterial;
 
 use crate::HasSphereContext;
 use async_trait::async_trait;
 
-use crate::{internal::SphereContextInternal, AsyncFileBody, SphereFile};
+use crate::{internal::SphereContextInternal, AsyncFileBody, BodyReader, SphereFile};
 
 /// Anything that can read content from a sphere should implement [SphereContentRead].
 /// A blanket implementation is provided for anything that implements [HasSphereContext].
@@ -23,6 +29,60 @@ where
     /// until contents is polled.
     async fn read(&self, slug: &str) -> Result<Option<SphereFile<Box<dyn AsyncFileBody>>>>;
 
+    /// Read a file that is associated with a given slug, like
+    /// [SphereContentRead::read], but with contents that can be read from any
+    /// position (see [BodyReader]).
+    async fn read_seekable(
+        &self,
+        slug: &str,
+    ) -> Result<Option<SphereFile<BodyReader<SphereDb<S>>>>>;
+
+    /// Read only the bytes within the given range of the file that is
+    /// associated with a given slug; the range is truncated to the end of the
+    /// file. Only the chunks of the file that hold those bytes are loaded.
+    async fn read_range(&self, slug: &str, range: Range<u64>) -> Result<Option<Vec<u8>>> {
+        let file = match self.read_seekable(slug).await? {
+            Some(file) => file,
+            None => return Ok(None),
+        };
+        let read = file.contents.read_range(range);
+
+        Ok(Some(read.await?))
+    }
+
+    /// Read the IPLD document that is associated with a given slug, decoding
+    /// it as `T`. Documents written with [SphereContentWrite::write_ipld] are
+    /// decoded from their DAG-CBOR body block; content that was written as
+    /// CBOR or JSON bytes can be decoded as well.
+    ///
+    /// [SphereContentWrite::write_ipld]: crate::SphereContentWrite::write_ipld
+    async fn read_ipld<T>(&self, slug: &str) -> Result<Option<T>>
+    where
+        T: DeserializeOwned + BlockStoreSend,
+    {
+        let mut file = match self.read(slug).await? {
+            Some(file) => file,
+            None => return Ok(None),
+        };
+
+        let mut bytes = Vec::new();
+        file.contents.read_to_end(&mut bytes).await?;
+
+        Ok(Some(match file.memo.content_type() {
+            Some(ContentType::DagCbor) | Some(ContentType::Cbor) => {
+                block_deserialize::<DagCborCodec, T>(&bytes)?
+            }
+            Some(ContentType::Json) => serde_json::from_slice(&bytes)?,
+            content_type => {
+                return Err(anyhow!(
+                    "Content at {} cannot be read as IPLD (content type is {:?})",
+                    slug,
+                    content_type
+                ))
+            }
+        }))
+    }
+
     /// Returns true if the content identitifed by slug exists in the sphere at
     /// the current revision.
     async fn exists(&self, slug: &str) -> Result<bool> {
@@ -50,4 +110,20 @@ where
             None => None,
         })
     }
+
+    async fn read_seekable(
+        &self,
+        slug: &str,
+    ) -> Result<Option<SphereFile<BodyReader<SphereDb<S>>>>> {
+        let revision = self.version().await?;
+        let sphere = self.to_sphere().await?;
+
+        let links = sphere.get_content().await?;
+        let hamt = links.get_hamt().await?;
+
+        Ok(match hamt.get(&slug.to_string()).await? {
+            Some(memo) => Some(self.get_seekable_file(&revision, memo.clone()).await?),
+            None => None,
+        })
+    }
 }
@@ -0,0 +1,381 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use libipld_cbor::DagCborCodec;
+use libipld_core::ipld::Ipld;
+use noosphere_core::data::ContentType;
+use noosphere_storage::{base64_encode, block_deserialize};
+use serde_json::{Map, Number, Value};
+use subtext::{block::Block, primitive::Entity};
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio_stream::StreamExt;
+
+use crate::{HasConditionalSendSync, SphereFile};
+
+/// A typed value that has been decoded from the contents of a [SphereFile]
+/// (see [ContentDecoderRegistry])
+#[derive(Debug)]
+pub enum DecodedContent {
+    /// UTF-8 text
+    Text(String),
+    /// The blocks of a Subtext document
+    Subtext(Vec<Block<Entity>>),
+    /// A JSON value
+    Json(Value),
+    /// An IPLD value, decoded from CBOR or DAG-CBOR
+    Ipld(Ipld),
+    /// Bytes that have no decoder for their content type
+    Bytes(Vec<u8>),
+}
+
+impl DecodedContent {
+    /// Get the text of [DecodedContent::Text], or else an error
+    pub fn into_text(self) -> Result<String> {
+        match self {
+            DecodedContent::Text(text) => Ok(text),
+            _ => Err(anyhow!("Content was not decoded as text")),
+        }
+    }
+
+    /// Convert the content to a JSON value. IPLD links and bytes are
+    /// represented in the style of DAG-JSON, as `{ "/": "<cid>" }` and
+    /// `{ "/": { "bytes": "<base64url>" } }` respectively.
+    pub fn into_json(self) -> Result<Value> {
+        match self {
+            DecodedContent::Json(value) => Ok(value),
+            DecodedContent::Ipld(ipld) => ipld_to_json(ipld),
+            DecodedContent::Text(text) => Ok(Value::String(text)),
+            _ => Err(anyhow!("Content cannot be represented as JSON")),
+        }
+    }
+}
+
+fn ipld_to_json(ipld: Ipld) -> Result<Value> {
+    Ok(match ipld {
+        Ipld::Null => Value::Null,
+        Ipld::Bool(value) => Value::Bool(value),
+        Ipld::Integer(value) => match i64::try_from(value) {
+            Ok(value) => Value::Number(Number::from(value)),
+            Err(_) => Value::Number(Number::from(u64::try_from(value)?)),
+        },
+        Ipld::Float(value) => Value::Number(
+            Number::from_f64(value).ok_or_else(|| anyhow!("Cannot represent {} as JSON", value))?,
+        ),
+        Ipld::String(value) => Value::String(value),
+        Ipld::Bytes(bytes) => {
+            let mut inner = Map::new();
+            inner.insert("bytes".into(), Value::String(base64_encode(&bytes)?));
+
+            let mut outer = Map::new();
+            outer.insert("/".into(), Value::Object(inner));
+
+            Value::Object(outer)
+        }
+        Ipld::List(values) => Value::Array(
+            values
+                .into_iter()
+                .map(ipld_to_json)
+                .collect::<Result<Vec<Value>>>()?,
+        ),
+        Ipld::Map(entries) => {
+            let mut map = Map::new();
+
+            for (key, value) in entries {
+                map.insert(key, ipld_to_json(value)?);
+            }
+
+            Value::Object(map)
+        }
+        Ipld::Link(cid) => {
+            let mut map = Map::new();
+            map.insert("/".into(), Value::String(cid.to_string()));
+
+            Value::Object(map)
+        }
+    })
+}
+
+/// A [ContentDecoder] turns the bytes of a [SphereFile] into a typed value.
+/// Applications can implement it to decode their own content types (see
+/// [ContentDecoderRegistry::register]).
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+pub trait ContentDecoder: HasConditionalSendSync {
+    async fn decode(&self, bytes: Vec<u8>) -> Result<DecodedContent>;
+}
+
+/// Decodes bytes as UTF-8 text
+pub struct TextDecoder;
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl ContentDecoder for TextDecoder {
+    async fn decode(&self, bytes: Vec<u8>) -> Result<DecodedContent> {
+        Ok(DecodedContent::Text(String::from_utf8(bytes)?))
+    }
+}
+
+/// Decodes bytes as a list of Subtext blocks
+pub struct SubtextDecoder;
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl ContentDecoder for SubtextDecoder {
+    async fn decode(&self, bytes: Vec<u8>) -> Result<DecodedContent> {
+        let block_stream = subtext::stream::<Block<Entity>, Entity, _>(bytes.as_slice()).await;
+        let mut blocks = Vec::new();
+
+        tokio::pin!(block_stream);
+
+        while let Some(block) = block_stream.next().await {
+            blocks.push(block.map_err(|error| anyhow!("{:?}", error))?);
+        }
+
+        Ok(DecodedContent::Subtext(blocks))
+    }
+}
+
+/// Decodes bytes as a JSON value
+pub struct JsonDecoder;
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl ContentDecoder for JsonDecoder {
+    async fn decode(&self, bytes: Vec<u8>) -> Result<DecodedContent> {
+        Ok(DecodedContent::Json(serde_json::from_slice(&bytes)?))
+    }
+}
+
+/// Decodes bytes as a DAG-CBOR encoded IPLD value; this also works for CBOR
+/// that does not contain any links
+pub struct IpldDecoder;
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl ContentDecoder for IpldDecoder {
+    async fn decode(&self, bytes: Vec<u8>) -> Result<DecodedContent> {
+        Ok(DecodedContent::Ipld(block_deserialize::<DagCborCodec, _>(
+            &bytes,
+        )?))
+    }
+}
+
+/// A set of [ContentDecoder]s, keyed by the [ContentType] that each one is
+/// able to decode. By default, a registry can decode text, Subtext, JSON, CBOR
+/// and DAG-CBOR content; other decoders may be added (or the defaults
+/// replaced) with [ContentDecoderRegistry::register]. Clones of a registry
+/// share the same decoders.
+#[derive(Clone)]
+pub struct ContentDecoderRegistry {
+    decoders: BTreeMap<ContentType, Arc<dyn ContentDecoder>>,
+}
+
+impl Default for ContentDecoderRegistry {
+    fn default() -> Self {
+        let mut registry = ContentDecoderRegistry::empty();
+
+        registry
+            .register(ContentType::Text, TextDecoder)
+            .register(ContentType::Subtext, SubtextDecoder)
+            .register(ContentType::Json, JsonDecoder)
+            .register(ContentType::Cbor, IpldDecoder)
+            .register(ContentType::DagCbor, IpldDecoder);
+
+        registry
+    }
+}
+
+impl ContentDecoderRegistry {
+    /// A registry with no decoders at all
+    pub fn empty() -> Self {
+        ContentDecoderRegistry {
+            decoders: BTreeMap::new(),
+        }
+    }
+
+    /// Use the given decoder for content of the given [ContentType], replacing
+    /// any decoder that was previously registered for it
+    pub fn register<D>(&mut self, content_type: ContentType, decoder: D) -> &mut Self
+    where
+        D: ContentDecoder + 'static,
+    {
+        self.decoders.insert(content_type, Arc::new(decoder));
+        self
+    }
+
+    /// Get the decoder for the given [ContentType], if there is one
+    pub fn get(&self, content_type: &ContentType) -> Option<Arc<dyn ContentDecoder>> {
+        self.decoders.get(content_type).cloned()
+    }
+
+    /// Read the contents of a [SphereFile] and decode them according to the
+    /// content type of the file. Content with no content type, or with no
+    /// registered decoder, is returned as [DecodedContent::Bytes]. Note that
+    /// the contents of the file are consumed.
+    pub async fn decode<R>(&self, file: &mut SphereFile<R>) -> Result<DecodedContent>
+    where
+        R: AsyncRead + Unpin,
+    {
+        match file.memo.content_type() {
+            Some(content_type) => self.decode_as(&content_type, file).await,
+            None => Ok(DecodedContent::Bytes(read_all(&mut file.contents).await?)),
+        }
+    }
+
+    /// Read the contents of a [SphereFile] and decode them as if they had the
+    /// given [ContentType], regardless of the content type of the file
+    pub async fn decode_as<R>(
+        &self,
+        content_type: &ContentType,
+        file: &mut SphereFile<R>,
+    ) -> Result<DecodedContent>
+    where
+        R: AsyncRead + Unpin,
+    {
+        let bytes = read_all(&mut file.contents).await?;
+
+        match self.get(content_type) {
+            Some(decoder) => decoder.decode(bytes).await,
+            None => Ok(DecodedContent::Bytes(bytes)),
+        }
+    }
+}
+
+async fn read_all<R>(contents: &mut R) -> Result<Vec<u8>>
+where
+    R: AsyncRead + Unpin,
+{
+    let mut bytes = Vec::new();
+    contents.read_to_end(&mut bytes).await?;
+
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use async_trait::async_trait;
+    use noosphere_core::{data::ContentType, tracing::initialize_tracing};
+    use serde_json::json;
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    #[cfg(target_arch = "wasm32")]
+    wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_browser);
+
+    use crate::{
+        helpers::{simulated_sphere_context, SimulationAccess},
+        ContentDecoder, ContentDecoderRegistry, DecodedContent, HasMutableSphereContext,
+        SphereContentRead, SphereContentWrite,
+    };
+
+    struct ShoutingDecoder;
+
+    #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+    #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+    impl ContentDecoder for ShoutingDecoder {
+        async fn decode(&self, bytes: Vec<u8>) -> Result<DecodedContent> {
+            Ok(DecodedContent::Text(
+                String::from_utf8(bytes)?.to_uppercase(),
+            ))
+        }
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[cfg_attr(not(target_arch = "wasm32"), tokio::test)]
+    async fn it_decodes_files_according_to_their_content_type() {
+        initialize_tracing(None);
+
+        let mut sphere_context = simulated_sphere_context(SimulationAccess::ReadWrite, None)
+            .await
+            .unwrap();
+
+        sphere_context
+            .write("text", "text/plain", b"Hello".as_ref(), None)
+            .await
+            .unwrap();
+        sphere_context
+            .write(
+                "json",
+                "application/json",
+                br#"{"count": 3}"#.as_ref(),
+                None,
+            )
+            .await
+            .unwrap();
+        sphere_context
+            .write_ipld("ipld", json!({ "names": ["foo", "bar"] }), None)
+            .await
+            .unwrap();
+        sphere_context
+            .write("bytes", "raw/bytes", [1u8, 2, 3].as_ref(), None)
+            .await
+            .unwrap();
+        sphere_context
+            .write("shout", "text/x-shout", b"Hello".as_ref(), None)
+            .await
+            .unwrap();
+        sphere_context.save(None).await.unwrap();
+
+        let mut registry = ContentDecoderRegistry::default();
+
+        let mut text = sphere_context.read("text").await.unwrap().unwrap();
+
+        assert_eq!(
+            registry
+                .decode(&mut text)
+                .await
+                .unwrap()
+                .into_text()
+                .unwrap(),
+            "Hello"
+        );
+
+        let mut json = sphere_context.read("json").await.unwrap().unwrap();
+
+        assert_eq!(
+            registry
+                .decode(&mut json)
+                .await
+                .unwrap()
+                .into_json()
+                .unwrap(),
+            json!({ "count": 3 })
+        );
+
+        let mut ipld = sphere_context.read("ipld").await.unwrap().unwrap();
+
+        assert_eq!(
+            registry
+                .decode(&mut ipld)
+                .await
+                .unwrap()
+                .into_json()
+                .unwrap(),
+            json!({ "names": ["foo", "bar"] })
+        );
+
+        let mut bytes = sphere_context.read("bytes").await.unwrap().unwrap();
+
+        assert!(matches!(
+            registry.decode(&mut bytes).await.unwrap(),
+            DecodedContent::Bytes(bytes) if bytes == vec![1u8, 2, 3]
+        ));
+
+        registry.register(ContentType::Unknown("text/x-shout".into()), ShoutingDecoder);
+
+        let mut shout = sphere_context.read("shout").await.unwrap().unwrap();
+
+        assert_eq!(
+            registry
+                .decode(&mut shout)
+                .await
+                .unwrap()
+                .into_text()
+                .unwrap(),
+            "HELLO"
+        );
+    }
+}
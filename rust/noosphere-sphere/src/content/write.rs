@@ -1,18 +1,21 @@
+use std::str::FromStr;
+
 use anyhow::{anyhow, Result};
 use cid::Cid;
 use libipld_cbor::DagCborCodec;
-use noosphere_core::data::{BodyChunkIpld, Header, MemoIpld};
-use noosphere_storage::{BlockStore, Storage};
+use noosphere_core::data::{BodyTreeWriter, ContentType, Header, MemoIpld, BODY_CHUNK_MAX_SIZE};
+use noosphere_storage::{BlockStore, BlockStoreSend, Storage};
+use serde::Serialize;
 
 use tokio::io::AsyncReadExt;
-use ucan::crypto::KeyMaterial;
+use ucan::{crypto::KeyMaterial, time::now};
 
 use crate::{internal::SphereContextInternal, HasMutableSphereContext, HasSphereContext};
 use async_trait::async_trait;
 
 use crate::{AsyncFileBody, SphereContentRead};
 
-fn validate_slug(slug: &str) -> Result<()> {
+pub(crate) fn validate_slug(slug: &str) -> Result<()> {
     if slug.is_empty() {
         Err(anyhow!("Slug must not be empty."))
     } else {
@@ -51,6 +54,9 @@ where
     /// sphere, you must call save. You can buffer multiple writes before
     /// saving.
     ///
+    /// The value is read and stored one chunk at a time, so it is never
+    /// buffered in memory as a whole.
+    ///
     /// The returned CID is a link to the memo for the newly added content.
     async fn write<F: AsyncFileBody>(
         &mut self,
@@ -60,6 +66,25 @@ where
         additional_headers: Option<Vec<(String, String)>>,
     ) -> Result<Cid>;
 
+    /// Write an IPLD document to a slug in the sphere. Unlike [write], the
+    /// document is stored as a native DAG-CBOR block rather than as chunks of
+    /// bytes, so any links within it are followed when the sphere is
+    /// replicated. Any blocks that the document links to must already be in
+    /// the sphere's storage. The document can be read back with
+    /// [SphereContentRead::read_ipld].
+    ///
+    /// The returned CID is a link to the memo for the newly added content.
+    ///
+    /// [write]: SphereContentWrite::write
+    async fn write_ipld<T>(
+        &mut self,
+        slug: &str,
+        value: T,
+        additional_headers: Option<Vec<(String, String)>>,
+    ) -> Result<Cid>
+    where
+        T: Serialize + BlockStoreSend;
+
     /// Unlinks a slug from the content space. Note that this does not remove
     /// the blocks that were previously associated with the content found at the
     /// given slug, because they will still be available at an earlier revision
@@ -80,7 +105,7 @@ where
     S: Storage + 'static,
 {
     async fn link_raw(&mut self, slug: &str, cid: &Cid) -> Result<()> {
-        self.assert_write_access().await?;
+        self.assert_content_write_access(slug).await?;
         validate_slug(slug)?;
 
         self.sphere_context_mut()
@@ -99,7 +124,7 @@ where
         body_cid: &Cid,
         additional_headers: Option<Vec<(String, String)>>,
     ) -> Result<Cid> {
-        self.assert_write_access().await?;
+        self.assert_content_write_access(slug).await?;
         validate_slug(slug)?;
 
         let memo_cid = {
@@ -121,13 +146,24 @@ where
                 },
             };
 
+            // NOTE: Headers from the sphere's header policy are applied first,
+            // so that any headers that are passed explicitly take precedence
+            let header_policy = sphere_context.header_policy().await?;
+            let author_did = sphere_context.author().identity().await?;
+
+            header_policy.apply(
+                &mut new_memo,
+                &author_did,
+                &ContentType::from_str(content_type)?,
+                now(),
+            );
+
             if let Some(headers) = additional_headers {
                 new_memo.replace_headers(headers)
             }
 
             new_memo.replace_first_header(&Header::ContentType.to_string(), content_type);
 
-            // TODO(#43): Configure default/implicit headers here
             sphere_context
                 .db_mut()
                 .save::<DagCborCodec, MemoIpld>(new_memo)
@@ -148,24 +184,65 @@ where
     ) -> Result<Cid> {
         debug!("Writing {}...", slug);
 
-        self.assert_write_access().await?;
+        self.assert_content_write_access(slug).await?;
         validate_slug(slug)?;
 
-        let mut bytes = Vec::new();
-        value.read_to_end(&mut bytes).await?;
+        let body_cid = {
+            // NOTE: The sphere context is not held while the value is read, in
+            // case the value is itself being read from the sphere
+            let mut db = self.sphere_context().await?.db().clone();
+            let mut writer = BodyTreeWriter::new(&mut db);
+            let mut buffer = vec![0u8; BODY_CHUNK_MAX_SIZE];
 
-        // TODO(#38): We imply here that the only content types we care about
-        // amount to byte streams, but in point of fact we can support anything
-        // that may be referenced by CID including arbitrary IPLD structures
-        let body_cid =
-            BodyChunkIpld::store_bytes(&bytes, self.sphere_context_mut().await?.db_mut()).await?;
+            loop {
+                let read_count = value.read(&mut buffer).await?;
+
+                if read_count == 0 {
+                    break;
+                }
+
+                writer.write(&buffer[..read_count]).await?;
+            }
+
+            writer.finish().await?
+        };
 
         self.link(slug, content_type, &body_cid, additional_headers)
             .await
     }
 
+    async fn write_ipld<T>(
+        &mut self,
+        slug: &str,
+        value: T,
+        additional_headers: Option<Vec<(String, String)>>,
+    ) -> Result<Cid>
+    where
+        T: Serialize + BlockStoreSend,
+    {
+        debug!("Writing IPLD to {}...", slug);
+
+        self.assert_content_write_access(slug).await?;
+        validate_slug(slug)?;
+
+        let body_cid = self
+            .sphere_context_mut()
+            .await?
+            .db_mut()
+            .save::<DagCborCodec, _>(value)
+            .await?;
+
+        self.link(
+            slug,
+            &ContentType::DagCbor.to_string(),
+            &body_cid,
+            additional_headers,
+        )
+        .await
+    }
+
     async fn remove(&mut self, slug: &str) -> Result<Option<Cid>> {
-        self.assert_write_access().await?;
+        self.assert_content_write_access(slug).await?;
 
         let current_file = self.read(slug).await?;
 
@@ -183,3 +260,102 @@ where
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        pin::Pin,
+        task::{Context, Poll},
+    };
+
+    use anyhow::Result;
+    use noosphere_core::data::{BodyTreeIpld, ContentType};
+    use noosphere_storage::MemoryStore;
+    use tokio::io::{AsyncRead, AsyncReadExt, ReadBuf};
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    #[cfg(target_arch = "wasm32")]
+    wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_browser);
+
+    use crate::{
+        helpers::{simulated_sphere_context, SimulationAccess},
+        HasMutableSphereContext, SphereContentRead, SphereContentWrite,
+    };
+
+    /// A reader that yields fewer bytes than were asked for on every read,
+    /// the way that network streams often do
+    struct ShortReader {
+        bytes: Vec<u8>,
+        position: usize,
+        read_sizes: Vec<usize>,
+        read_count: usize,
+    }
+
+    impl AsyncRead for ShortReader {
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+            buf: &mut ReadBuf<'_>,
+        ) -> Poll<std::io::Result<()>> {
+            let read_size = self.read_sizes[self.read_count % self.read_sizes.len()];
+            let end = (self.position + read_size.min(buf.remaining())).min(self.bytes.len());
+
+            buf.put_slice(&self.bytes[self.position..end]);
+            self.position = end;
+            self.read_count += 1;
+
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    fn make_bytes() -> Vec<u8> {
+        let mut state: u32 = 0x6a09e667;
+
+        (0..(1024 * 1024 + 17))
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                state as u8
+            })
+            .collect()
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[cfg_attr(not(target_arch = "wasm32"), tokio::test)]
+    async fn it_writes_the_same_body_when_the_content_is_read_in_short_pieces() -> Result<()> {
+        let mut sphere_context =
+            simulated_sphere_context(SimulationAccess::ReadWrite, None).await?;
+        let bytes = make_bytes();
+
+        let expected_body = BodyTreeIpld::store_bytes(&bytes, &mut MemoryStore::default()).await?;
+
+        sphere_context
+            .write(
+                "cats",
+                &ContentType::Bytes.to_string(),
+                ShortReader {
+                    bytes: bytes.clone(),
+                    position: 0,
+                    read_sizes: vec![1, 4_093, 65_537, 100_003],
+                    read_count: 0,
+                },
+                None,
+            )
+            .await?;
+        sphere_context.save(None).await?;
+
+        let mut file = sphere_context.read("cats").await?.unwrap();
+
+        assert_eq!(file.memo.body, expected_body);
+
+        let mut contents = Vec::new();
+        file.contents.read_to_end(&mut contents).await?;
+
+        assert_eq!(contents, bytes);
+
+        Ok(())
+    }
+}
@@ -7,16 +7,22 @@ use libipld_cbor::DagCborCodec;
 use noosphere_api::client::Client;
 
 use noosphere_core::{
-    authority::{Access, Author, SUPPORTED_KEYS},
-    data::{ContentType, Did, MemoIpld, SphereIpld},
+    authority::{Access, Author, Authorization, SUPPORTED_KEYS},
+    data::{ContentType, Did, Link, MemoIpld, SphereIpld},
     view::{Sphere, SphereMutation},
 };
 use noosphere_storage::{BlockStore, KeyValueStore, SphereDb, Storage};
 use tokio::sync::OnceCell;
-use ucan::crypto::{did::DidParser, KeyMaterial};
+use ucan::{
+    crypto::{did::DidParser, KeyMaterial},
+    Ucan,
+};
 use url::Url;
 
-use crate::metadata::GATEWAY_URL;
+use crate::{
+    metadata::{AUTHORIZATION, GATEWAY_URL, HEADER_POLICY},
+    HeaderPolicy, AUTHORIZATION_RENEWAL_WINDOW,
+};
 
 #[cfg(doc)]
 use crate::has::HasSphereContext;
@@ -307,6 +313,50 @@ where
         Ok(())
     }
 
+    /// The [HeaderPolicy] that describes the headers that are implicitly added
+    /// to memos written to the sphere; if none has been configured, the
+    /// default (which adds no headers) is returned.
+    pub async fn header_policy(&self) -> Result<HeaderPolicy> {
+        Ok(self.db.get_key(HEADER_POLICY).await?.unwrap_or_default())
+    }
+
+    /// Sets or unsets the [HeaderPolicy] that describes the headers that are
+    /// implicitly added to memos written to the sphere.
+    pub async fn configure_header_policy(&mut self, policy: Option<&HeaderPolicy>) -> Result<()> {
+        match policy {
+            Some(policy) => {
+                self.db.set_key(HEADER_POLICY, policy).await?;
+            }
+            None => {
+                self.db.unset_key(HEADER_POLICY).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Replaces the [Author] who is accessing the sphere, for example after
+    /// ownership of the sphere has been transferred to a different key. The
+    /// [Access] level and API [Client] are reset so that they are initialized
+    /// again for the new [Author]. This will fail if there are unsaved changes
+    /// in the pending mutation, since they were made by the previous [Author].
+    pub async fn configure_author(&mut self, author: Author<K>) -> Result<()> {
+        if !self.mutation.is_empty() {
+            return Err(anyhow!(
+                "Cannot change author while there are unsaved changes to the sphere"
+            ));
+        }
+
+        let author_did = author.identity().await?;
+
+        self.mutation = SphereMutation::new(&author_did);
+        self.author = author;
+        self.access = OnceCell::new();
+        self.client = OnceCell::new();
+
+        Ok(())
+    }
+
     /// Get the [SphereDb] instance that manages the current sphere's block
     /// space and persisted configuration.
     pub fn db(&self) -> &SphereDb<S> {
@@ -365,6 +415,106 @@ where
         Ok(client.clone())
     }
 
+    /// Check that the authorization of the configured [Author] is not about to
+    /// expire. If it expires within [AUTHORIZATION_RENEWAL_WINDOW], the latest
+    /// local version of the sphere is searched for a renewed authorization for
+    /// the author's key (one that grants the same capabilities), and the author
+    /// adopts it if one is found. Otherwise, a
+    /// warning is logged if the authorization is about to expire, and an error
+    /// is returned if it has already expired.
+    pub async fn check_authorization_expiry(&mut self) -> Result<()>
+    where
+        S: 'static,
+    {
+        if self.sphere_identity != self.origin_sphere_identity {
+            return Ok(());
+        }
+
+        let ucan = match &self.author.authorization {
+            Some(authorization) => match authorization.resolve_ucan(&self.db).await {
+                Ok(ucan) => ucan,
+                // The authorization is a blind proof, so we can't tell when it
+                // expires
+                Err(_) => return Ok(()),
+            },
+            None => return Ok(()),
+        };
+
+        let author_did = self.author.identity().await?;
+
+        if ucan.audience() != author_did.as_str() {
+            return Ok(());
+        }
+
+        let now = ucan::time::now();
+        let expires_at = *ucan.expires_at();
+
+        if expires_at > now + AUTHORIZATION_RENEWAL_WINDOW {
+            return Ok(());
+        }
+
+        let mut renewal: Option<(Cid, u64)> = None;
+
+        let delegations = self
+            .sphere()
+            .await?
+            .get_authority()
+            .await?
+            .get_delegations()
+            .await?;
+        let delegation_stream = delegations.into_stream().await?;
+
+        tokio::pin!(delegation_stream);
+
+        while let Some((Link { cid, .. }, delegation)) = delegation_stream.try_next().await? {
+            let candidate = delegation.resolve_ucan(&self.db).await?;
+            let candidate_expires_at = *candidate.expires_at();
+
+            // Only a renewal of the current authorization is adopted, not some
+            // other authorization that grants the same key different access
+            if candidate.audience() != author_did.as_str()
+                || !grants_same_capabilities(&candidate, &ucan)
+            {
+                continue;
+            }
+
+            let is_later = match renewal {
+                Some((_, renewal_expires_at)) => candidate_expires_at > renewal_expires_at,
+                None => candidate_expires_at > expires_at,
+            };
+
+            if is_later {
+                renewal = Some((cid, candidate_expires_at));
+            }
+        }
+
+        if let Some((renewed_authorization, _)) = renewal {
+            info!("Adopting renewed authorization {}", renewed_authorization);
+
+            self.db
+                .set_key(AUTHORIZATION, renewed_authorization)
+                .await?;
+            self.author.authorization = Some(Authorization::Cid(renewed_authorization));
+            self.access = OnceCell::new();
+            self.client = OnceCell::new();
+
+            return Ok(());
+        }
+
+        if expires_at <= now {
+            return Err(anyhow!(
+                "The authorization for this key has expired; the owner of the sphere must renew it"
+            ));
+        }
+
+        warn!(
+            "The authorization for this key expires in {} hours; the owner of the sphere should renew it",
+            (expires_at - now) / 3600
+        );
+
+        Ok(())
+    }
+
     // Reset access so that it is re-evaluated the next time it is measured
     // self.access.take();
     pub(crate) fn reset_access(&mut self) {
@@ -372,6 +522,19 @@ where
     }
 }
 
+/// Returns true if both UCANs claim exactly the same capabilities
+fn grants_same_capabilities(ucan: &Ucan, other: &Ucan) -> bool {
+    let capabilities = ucan.attenuation();
+    let other_capabilities = other.attenuation();
+
+    capabilities
+        .iter()
+        .all(|capability| other_capabilities.contains(capability))
+        && other_capabilities
+            .iter()
+            .all(|capability| capabilities.contains(capability))
+}
+
 #[cfg(test)]
 pub mod tests {
     use anyhow::Result;
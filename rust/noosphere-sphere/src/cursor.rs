@@ -128,7 +128,10 @@ where
         self.has_sphere_context.sphere_context_mut().await
     }
 
-    async fn save(&mut self, additional_headers: Option<Vec<(String, String)>>) -> Result<Cid> {
+    async fn save(&mut self, additional_headers: Option<Vec<(String, String)>>) -> Result<Cid>
+    where
+        S: 'static,
+    {
         let new_version = self.has_sphere_context.save(additional_headers).await?;
 
         if self.sphere_version.is_some() {
@@ -163,10 +166,20 @@ where
 
 #[cfg(test)]
 pub mod tests {
+    use std::sync::Arc;
+
     use anyhow::Result;
-    use noosphere_core::data::{ContentType, Header};
-    use noosphere_storage::UcanStore;
-    use tokio::io::AsyncReadExt;
+    use noosphere_core::{
+        authority::{
+            generate_capability, generate_ed25519_key, Author, Authorization, SphereAction,
+            SUPPORTED_KEYS,
+        },
+        data::{ContentType, Did, Header},
+        view::Sphere,
+    };
+    use noosphere_storage::{MemoryStorage, SphereDb, UcanStore};
+    use tokio::{io::AsyncReadExt, sync::Mutex};
+    use ucan::crypto::{did::DidParser, KeyMaterial};
 
     #[cfg(target_arch = "wasm32")]
     use wasm_bindgen_test::wasm_bindgen_test;
@@ -175,9 +188,11 @@ pub mod tests {
     wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_browser);
 
     use crate::helpers::{make_valid_link_record, simulated_sphere_context, SimulationAccess};
+    use crate::internal::SphereContextInternal;
     use crate::{
-        HasMutableSphereContext, HasSphereContext, SphereContentRead, SphereContentWrite,
-        SpherePetnameRead, SpherePetnameWrite,
+        HasMutableSphereContext, HasSphereContext, SphereAuthorityWrite, SphereContentRead,
+        SphereContentWrite, SphereContext, SpherePetnameRead, SpherePetnameWrite,
+        SpherePrivateContentRead, SpherePrivateContentWrite,
     };
 
     use super::SphereCursor;
@@ -249,7 +264,9 @@ pub mod tests {
                 .await
         };
 
-        assert_eq!(first_save_stats.flushes, initial_stats.flushes + 1);
+        // The blocks of each revision are flushed when they are committed,
+        // and again along with the new version of the sphere
+        assert_eq!(first_save_stats.flushes, initial_stats.flushes + 2);
 
         cursor.remove("cats").await.unwrap();
         cursor.save(None).await.unwrap();
@@ -264,7 +281,7 @@ pub mod tests {
                 .await
         };
 
-        assert_eq!(second_save_stats.flushes, first_save_stats.flushes + 1);
+        assert_eq!(second_save_stats.flushes, first_save_stats.flushes + 2);
     }
 
     #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
@@ -323,6 +340,216 @@ pub mod tests {
         assert_eq!("Cats are great", value.as_str());
     }
 
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[cfg_attr(not(target_arch = "wasm32"), tokio::test)]
+    async fn it_can_write_a_private_file_and_read_it_back() {
+        let sphere_context = simulated_sphere_context(SimulationAccess::ReadWrite, None)
+            .await
+            .unwrap();
+        let mut cursor = SphereCursor::latest(sphere_context);
+
+        cursor
+            .write_private(
+                "draft",
+                &ContentType::Subtext.to_string(),
+                b"Cats are secretly great".as_ref(),
+                None,
+            )
+            .await
+            .unwrap();
+
+        cursor.save(None).await.unwrap();
+
+        assert!(cursor.read("draft").await.unwrap().is_none());
+        assert!(cursor.private_exists("draft").await.unwrap());
+
+        let mut file = cursor.read_private("draft").await.unwrap().unwrap();
+
+        file.memo
+            .expect_header(
+                &Header::ContentType.to_string(),
+                &ContentType::Subtext.to_string(),
+            )
+            .unwrap();
+
+        let mut value = String::new();
+        file.contents.read_to_string(&mut value).await.unwrap();
+
+        assert_eq!("Cats are secretly great", value.as_str());
+
+        let mut sealed_file = cursor
+            .get_file(&file.sphere_version, file.memo_version.into())
+            .await
+            .unwrap();
+        let mut sealed_bytes = Vec::new();
+        sealed_file
+            .contents
+            .read_to_end(&mut sealed_bytes)
+            .await
+            .unwrap();
+
+        assert_ne!(sealed_bytes.as_slice(), b"Cats are secretly great".as_ref());
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[cfg_attr(not(target_arch = "wasm32"), tokio::test)]
+    async fn it_can_unlink_slugs_from_the_private_content_space() {
+        let sphere_context = simulated_sphere_context(SimulationAccess::ReadWrite, None)
+            .await
+            .unwrap();
+        let mut cursor = SphereCursor::latest(sphere_context);
+
+        cursor
+            .write_private(
+                "draft",
+                &ContentType::Subtext.to_string(),
+                b"Cats are secretly great".as_ref(),
+                None,
+            )
+            .await
+            .unwrap();
+
+        cursor.save(None).await.unwrap();
+
+        assert!(cursor.remove("draft").await.unwrap().is_none());
+        assert!(cursor.remove_private("draft").await.unwrap().is_some());
+
+        cursor.save(None).await.unwrap();
+
+        assert!(cursor.read_private("draft").await.unwrap().is_none());
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[cfg_attr(not(target_arch = "wasm32"), tokio::test)]
+    async fn it_can_read_private_content_from_a_second_device() -> Result<()> {
+        let mut sphere_context =
+            simulated_sphere_context(SimulationAccess::ReadWrite, None).await?;
+        let identity = sphere_context.identity().await?;
+        let device_key = generate_ed25519_key();
+        let device_ucan_cid = sphere_context
+            .authorize(
+                "device",
+                &Did(device_key.get_did().await?),
+                &[generate_capability(&identity, SphereAction::Push)],
+                None,
+            )
+            .await?;
+        sphere_context.save(None).await?;
+
+        sphere_context
+            .write_private(
+                "draft",
+                &ContentType::Subtext.to_string(),
+                b"Cats are secretly great".as_ref(),
+                None,
+            )
+            .await?;
+        sphere_context.save(None).await?;
+
+        sphere_context
+            .lock()
+            .await
+            .configure_author(Author {
+                key: device_key,
+                authorization: Some(Authorization::Cid(device_ucan_cid)),
+            })
+            .await?;
+
+        let mut value = String::new();
+
+        sphere_context
+            .read_private("draft")
+            .await?
+            .unwrap()
+            .contents
+            .read_to_string(&mut value)
+            .await?;
+
+        assert_eq!("Cats are secretly great", value.as_str());
+
+        Ok(())
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[cfg_attr(not(target_arch = "wasm32"), tokio::test)]
+    async fn it_can_reseal_private_content_after_ownership_is_transferred() -> Result<()> {
+        let mut db = SphereDb::new(&MemoryStorage::default()).await?;
+        let owner_key = generate_ed25519_key();
+        let (sphere, owner_authorization, mnemonic) =
+            Sphere::generate(&owner_key.get_did().await?, &mut db).await?;
+        let identity = sphere.get_identity().await?;
+
+        db.set_version(&identity, sphere.cid()).await?;
+
+        let mut sphere_context = Arc::new(Mutex::new(
+            SphereContext::new(
+                identity.clone(),
+                Author {
+                    key: owner_key.clone(),
+                    authorization: Some(owner_authorization.clone()),
+                },
+                db,
+                None,
+            )
+            .await?,
+        ));
+
+        sphere_context
+            .write_private(
+                "draft",
+                &ContentType::Subtext.to_string(),
+                b"Cats are secretly great".as_ref(),
+                Some(vec![("Title".into(), "Cats".into())]),
+            )
+            .await?;
+        sphere_context.save(None).await?;
+
+        {
+            let mut sphere_context = sphere_context.lock().await;
+            let next_owner_key = generate_ed25519_key();
+            let (sphere, next_owner_authorization) = sphere_context
+                .sphere()
+                .await?
+                .change_owner(
+                    &mnemonic,
+                    &next_owner_key.get_did().await?,
+                    &owner_authorization,
+                    &mut DidParser::new(SUPPORTED_KEYS),
+                )
+                .await?;
+
+            sphere_context
+                .db_mut()
+                .set_version(&identity, sphere.cid())
+                .await?;
+            sphere_context
+                .configure_author(Author {
+                    key: next_owner_key,
+                    authorization: Some(next_owner_authorization),
+                })
+                .await?;
+        }
+
+        assert!(sphere_context.read_private("draft").await.is_err());
+
+        sphere_context.reseal_private(&owner_key).await?;
+        sphere_context.save(None).await?;
+
+        let mut value = String::new();
+        let mut file = sphere_context.read_private("draft").await?.unwrap();
+
+        file.contents.read_to_string(&mut value).await?;
+
+        assert_eq!("Cats are secretly great", value.as_str());
+        file.memo.expect_header("Title", "Cats")?;
+        file.memo.expect_header(
+            &Header::ContentType.to_string(),
+            &ContentType::Subtext.to_string(),
+        )?;
+
+        Ok(())
+    }
+
     #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
     #[cfg_attr(not(target_arch = "wasm32"), tokio::test)]
     async fn it_can_overwrite_a_file_with_new_contents_and_preserve_history() {
@@ -0,0 +1,185 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::Result;
+use cid::Cid;
+use noosphere_core::view::Sphere;
+use noosphere_storage::{BlockStore, GarbageCollectionReport, SphereDb, Storage, Store};
+
+/// How much of the history of each sphere to keep when collecting garbage
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HistoryRetention {
+    /// Keep every revision of every sphere; only blocks that are not
+    /// referenced by any revision (for example, left over from an abandoned
+    /// sync) are removed
+    All,
+    /// Keep at most this many of the most recent revisions of each sphere
+    /// (always at least one)
+    Revisions(usize),
+}
+
+impl Default for HistoryRetention {
+    fn default() -> Self {
+        HistoryRetention::All
+    }
+}
+
+/// Remove blocks from the [SphereDb] that are no longer needed to read the
+/// retained history of the spheres it tracks, returning a report of what was
+/// reclaimed. See [SphereDb::collect_garbage] for details on how reachability
+/// is decided.
+///
+/// When only a window of history is retained, any revision of a sphere that is
+/// referenced by the latest revision of another locally tracked sphere (for
+/// example, the last version of a local sphere that a gateway's counterpart
+/// sphere knows about) is retained along with everything after it, so that
+/// syncing continues to work.
+pub async fn collect_garbage<S>(
+    db: &mut SphereDb<S>,
+    retention: HistoryRetention,
+) -> Result<GarbageCollectionReport>
+where
+    S: Storage + 'static,
+    S::BlockStore: Store,
+    S::KeyValueStore: Store,
+{
+    let excluded = match retention {
+        HistoryRetention::All => BTreeSet::new(),
+        HistoryRetention::Revisions(count) => expired_revisions(db, count.max(1)).await?,
+    };
+
+    debug!("Collecting garbage; {} revisions expired", excluded.len());
+
+    db.collect_garbage(excluded).await
+}
+
+/// Find all revisions of each sphere that fall outside of the retention window
+async fn expired_revisions<S>(db: &SphereDb<S>, count: usize) -> Result<BTreeSet<Cid>>
+where
+    S: Storage + 'static,
+    S::BlockStore: Store,
+    S::KeyValueStore: Store,
+{
+    let versions = db.get_all_versions().await?;
+    let mut protected: BTreeMap<&str, BTreeSet<Cid>> = BTreeMap::new();
+
+    for (identity, version) in versions.iter() {
+        let content = Sphere::at(version, db).get_content().await?;
+
+        for (other_identity, _) in versions.iter() {
+            if other_identity == identity {
+                continue;
+            }
+
+            if let Some(link) = content.get(other_identity).await? {
+                protected
+                    .entry(other_identity.as_str())
+                    .or_default()
+                    .insert(link.cid);
+            }
+        }
+    }
+
+    let mut expired = BTreeSet::new();
+
+    for (identity, version) in versions.iter() {
+        let mut protected = protected.remove(identity.as_str()).unwrap_or_default();
+        let mut revision = Some(*version);
+        let mut retained = 0usize;
+
+        while let Some(cid) = revision {
+            // History may already be incomplete (for example, if it was
+            // collected earlier), in which case there is nothing more to expire
+            if db.get_block(&cid).await?.is_none() {
+                break;
+            }
+
+            if retained < count || !protected.is_empty() {
+                protected.remove(&cid);
+                retained += 1;
+            } else {
+                expired.insert(cid);
+            }
+
+            revision = Sphere::at(&cid, db)
+                .get_parent()
+                .await?
+                .map(|parent| *parent.cid());
+        }
+    }
+
+    Ok(expired)
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use noosphere_core::{data::ContentType, view::Sphere};
+    use noosphere_storage::BlockStore;
+    use tokio::io::AsyncReadExt;
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    #[cfg(target_arch = "wasm32")]
+    wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_browser);
+
+    use crate::{
+        collect_garbage,
+        helpers::{simulated_sphere_context, SimulationAccess},
+        HasMutableSphereContext, HasSphereContext, HistoryRetention, SphereContentRead,
+        SphereContentWrite,
+    };
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[cfg_attr(not(target_arch = "wasm32"), tokio::test)]
+    async fn it_discards_history_outside_of_the_retention_window() -> Result<()> {
+        let mut sphere_context =
+            simulated_sphere_context(SimulationAccess::ReadWrite, None).await?;
+
+        let mut versions = Vec::new();
+
+        for value in ["one", "two", "three"] {
+            sphere_context
+                .write(
+                    "numbers",
+                    &ContentType::Subtext.to_string(),
+                    value.as_bytes(),
+                    None,
+                )
+                .await?;
+            versions.push(sphere_context.save(None).await?);
+        }
+
+        let mut db = sphere_context.sphere_context().await?.db().clone();
+
+        collect_garbage(&mut db, HistoryRetention::All).await?;
+
+        for version in versions.iter() {
+            assert!(db.get_block(version).await?.is_some());
+        }
+
+        let report = collect_garbage(&mut db, HistoryRetention::Revisions(2)).await?;
+
+        assert!(report.blocks_removed > 0);
+        assert!(report.bytes_reclaimed > 0);
+
+        assert!(db.get_block(&versions[0]).await?.is_none());
+        assert!(db.get_block(&versions[1]).await?.is_some());
+        assert!(db.get_block(&versions[2]).await?.is_some());
+
+        Sphere::at(&versions[1], &db).get_content().await?;
+
+        let mut contents = String::new();
+        sphere_context
+            .read("numbers")
+            .await?
+            .unwrap()
+            .contents
+            .read_to_string(&mut contents)
+            .await?;
+
+        assert_eq!(contents, "three");
+
+        Ok(())
+    }
+}
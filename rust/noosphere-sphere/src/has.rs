@@ -2,7 +2,7 @@ use anyhow::{anyhow, Result};
 use async_trait::async_trait;
 use cid::Cid;
 use noosphere_core::{data::Did, view::Sphere};
-use noosphere_storage::Storage;
+use noosphere_storage::{BufferedBlockStore, Storage};
 use std::{
     ops::{Deref, DerefMut},
     sync::Arc,
@@ -11,6 +11,7 @@ use tokio::sync::{Mutex, OwnedMutexGuard};
 use ucan::crypto::KeyMaterial;
 
 use super::SphereContext;
+use crate::search::update_existing_search_index;
 
 #[cfg(not(target_arch = "wasm32"))]
 pub trait HasConditionalSendSync: Send + Sync {}
@@ -104,10 +105,25 @@ where
     /// Commits a series of writes to the sphere and signs the new version. The
     /// new version [Cid] of the sphere is returned. This method must be invoked
     /// in order to update the local history of the sphere with any changes that
-    /// have been made.
-    async fn save(&mut self, additional_headers: Option<Vec<(String, String)>>) -> Result<Cid> {
-        let sphere = self.to_sphere().await?;
+    /// have been made. If the sphere has a search index, it is brought up to
+    /// date with the new version. Saving fails if the author's authorization
+    /// has expired (see [SphereContext::check_authorization_expiry]).
+    async fn save(&mut self, additional_headers: Option<Vec<(String, String)>>) -> Result<Cid>
+    where
+        S: 'static,
+    {
+        let version = self.version().await?;
         let mut sphere_context = self.sphere_context_mut().await?;
+
+        sphere_context.check_authorization_expiry().await?;
+
+        // The blocks of the new revision are buffered, and only written to
+        // storage (all together, along with their links) once it has been
+        // signed, so that a version is never recorded for a revision that is
+        // only partially stored
+        let mut block_store = BufferedBlockStore::new(sphere_context.db().clone());
+        let sphere = Sphere::at(&version, &block_store);
+
         let sphere_identity = sphere_context.identity().clone();
         let mut revision = sphere.apply_mutation(sphere_context.mutation()).await?;
 
@@ -124,6 +140,8 @@ where
             )
             .await?;
 
+        block_store.commit().await?;
+
         sphere_context
             .db_mut()
             .set_version(&sphere_identity, &new_sphere_version)
@@ -131,6 +149,12 @@ where
         sphere_context.db_mut().flush().await?;
         sphere_context.mutation_mut().reset();
 
+        drop(sphere_context);
+
+        if let Err(error) = update_existing_search_index(&*self).await {
+            warn!("Failed to update search index: {}", error);
+        }
+
         Ok(new_sphere_version)
     }
 }
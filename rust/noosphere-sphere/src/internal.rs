@@ -1,10 +1,10 @@
-use super::{BodyChunkDecoder, SphereFile};
-use crate::{AsyncFileBody, HasSphereContext};
+use super::{BodyChunkDecoder, BodyReader, SphereFile};
+use crate::{AsyncFileBody, HasSphereContext, SphereContext};
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
 use futures_util::TryStreamExt;
-use noosphere_storage::{BlockStore, Storage};
-use std::str::FromStr;
+use noosphere_storage::{BlockStore, SphereDb, Storage};
+use std::{io::Cursor, str::FromStr};
 use tokio_util::io::StreamReader;
 use ucan::crypto::KeyMaterial;
 
@@ -28,11 +28,23 @@ where
     /// authorization).
     async fn assert_write_access(&self) -> Result<()>;
 
+    /// Like [SphereContextInternal::assert_write_access], but only requires
+    /// write access to the content at the given slug (which the author may
+    /// have even if they were only delegated some of the content of the
+    /// sphere).
+    async fn assert_content_write_access(&self, slug: &str) -> Result<()>;
+
     async fn get_file(
         &self,
         sphere_revision: &Cid,
         memo_link: Link<MemoIpld>,
     ) -> Result<SphereFile<Box<dyn AsyncFileBody>>>;
+
+    async fn get_seekable_file(
+        &self,
+        sphere_revision: &Cid,
+        memo_link: Link<MemoIpld>,
+    ) -> Result<SphereFile<BodyReader<SphereDb<S>>>>;
 }
 
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
@@ -46,10 +58,32 @@ where
     async fn assert_write_access(&self) -> Result<()> {
         let sphere_context = self.sphere_context().await?;
         match sphere_context.access().await? {
+            Access::ReadWrite => Ok(()),
+            Access::ReadWriteContent(_) => Err(anyhow!(
+                "Cannot mutate sphere; author only has write access to some of its content"
+            )),
+            Access::ReadOnly => Err(anyhow!(
+                "Cannot mutate sphere; author only has read access to its contents"
+            )),
+        }
+    }
+
+    async fn assert_content_write_access(&self, slug: &str) -> Result<()> {
+        let sphere_context = self.sphere_context().await?;
+        let access = sphere_context.access().await?;
+
+        if access.can_write_slug(slug) {
+            return Ok(());
+        }
+
+        match access {
             Access::ReadOnly => Err(anyhow!(
                 "Cannot mutate sphere; author only has read access to its contents"
             )),
-            _ => Ok(()),
+            _ => Err(anyhow!(
+                "Cannot mutate sphere; author does not have write access to '{}'",
+                slug
+            )),
         }
     }
 
@@ -59,37 +93,24 @@ where
         memo_link: Link<MemoIpld>,
     ) -> Result<SphereFile<Box<dyn AsyncFileBody>>> {
         let sphere_context = self.sphere_context().await?;
-        let memo = memo_link.load_from(sphere_context.db()).await?;
-
-        // If we have a memo, but not the content it refers to, we should try to
-        // replicate from the gateway
-        if sphere_context.db().get_block(&memo.body).await?.is_none() {
-            let client = sphere_context.client().await.map_err(|error| {
-                warn!("Unable to initialize API client for replicating missing content");
-                error
-            })?;
-
-            // NOTE: This is kind of a hack, since we may be accessing a
-            // "read-only" context. Technically this should be acceptable
-            // because our mutation here is propagating immutable blocks
-            // into the local DB
-            let mut db = sphere_context.db().clone();
-            let stream = client.replicate(&memo_link).await?;
-
-            tokio::pin!(stream);
-            while let Some((cid, block)) = stream.try_next().await? {
-                db.put_block(&cid, &block).await?;
-            }
-        }
+        let memo = load_memo_and_replicate_body(&*sphere_context, &memo_link).await?;
 
         let content_type = match memo.get_first_header(&Header::ContentType.to_string()) {
             Some(content_type) => Some(ContentType::from_str(content_type.as_str())?),
             None => None,
         };
 
-        let stream = match content_type {
-            // TODO(#86): Content-type aware decoding of body bytes
-            Some(_) => BodyChunkDecoder(&memo.body, sphere_context.db()).stream(),
+        // NOTE: we have to box here because traits don't support `impl` types in return values
+        let contents: Box<dyn AsyncFileBody> = match content_type {
+            // The body is a lone DAG-CBOR block, which is read as it is
+            Some(ContentType::DagCbor) => Box::new(Cursor::new(
+                sphere_context.db().require_block(&memo.body).await?,
+            )),
+            // NOTE: Decoding of body bytes by content type is left to the
+            // caller (see `ContentDecoderRegistry`)
+            Some(_) => Box::new(StreamReader::new(
+                BodyChunkDecoder(&memo.body, sphere_context.db()).stream(),
+            )),
             None => return Err(anyhow!("No content type specified")),
         };
 
@@ -98,8 +119,70 @@ where
             sphere_version: *sphere_revision,
             memo_version: memo_link.into(),
             memo,
-            // NOTE: we have to box here because traits don't support `impl` types in return values
-            contents: Box::new(StreamReader::new(stream)),
+            contents,
         })
     }
+
+    async fn get_seekable_file(
+        &self,
+        sphere_revision: &Cid,
+        memo_link: Link<MemoIpld>,
+    ) -> Result<SphereFile<BodyReader<SphereDb<S>>>> {
+        let sphere_context = self.sphere_context().await?;
+        let memo = load_memo_and_replicate_body(&*sphere_context, &memo_link).await?;
+
+        match memo.content_type() {
+            Some(ContentType::DagCbor) => {
+                return Err(anyhow!(
+                    "Content is a DAG-CBOR document, and cannot be read as a seekable file"
+                ))
+            }
+            Some(_) => (),
+            None => return Err(anyhow!("No content type specified")),
+        };
+
+        let contents = BodyReader::open(&memo.body, sphere_context.db().clone()).await?;
+
+        Ok(SphereFile {
+            sphere_identity: sphere_context.identity().clone(),
+            sphere_version: *sphere_revision,
+            memo_version: memo_link.into(),
+            memo,
+            contents,
+        })
+    }
+}
+
+/// Load a memo, and if we have the memo but not the content it refers to, try
+/// to replicate the content from the gateway
+async fn load_memo_and_replicate_body<K, S>(
+    sphere_context: &SphereContext<K, S>,
+    memo_link: &Link<MemoIpld>,
+) -> Result<MemoIpld>
+where
+    K: KeyMaterial + Clone + 'static,
+    S: Storage + 'static,
+{
+    let memo = memo_link.load_from(sphere_context.db()).await?;
+
+    if sphere_context.db().get_block(&memo.body).await?.is_none() {
+        let client = sphere_context.client().await.map_err(|error| {
+            warn!("Unable to initialize API client for replicating missing content");
+            error
+        })?;
+
+        // NOTE: This is kind of a hack, since we may be accessing a
+        // "read-only" context. Technically this should be acceptable
+        // because our mutation here is propagating immutable blocks
+        // into the local DB
+        let mut db = sphere_context.db().clone();
+        let stream = client.replicate(memo_link).await?;
+
+        tokio::pin!(stream);
+        while let Some((cid, block)) = stream.try_next().await? {
+            db.put_block(&cid, &block).await?;
+        }
+    }
+
+    Ok(memo)
 }
@@ -61,9 +61,13 @@ use noosphere_core::authority::Author;
 #[cfg(doc)]
 use noosphere_storage::Storage;
 
+mod archive;
+mod authority;
+mod backlinks;
 mod content;
 mod context;
 mod cursor;
+mod gc;
 mod has;
 #[cfg(not(target_arch = "wasm32"))]
 mod replication;
@@ -74,15 +78,23 @@ pub mod helpers;
 mod internal;
 pub mod metadata;
 mod petname;
+mod private;
+mod search;
 mod sync;
 
+pub use archive::*;
+pub use authority::*;
+pub use backlinks::*;
 pub use content::*;
 pub use context::*;
 pub use cursor::*;
+pub use gc::*;
 pub use has::*;
 pub use metadata::*;
 pub use petname::*;
+pub use private::*;
 #[cfg(not(target_arch = "wasm32"))]
 pub use replication::*;
+pub use search::*;
 pub use sync::*;
 pub use walker::*;
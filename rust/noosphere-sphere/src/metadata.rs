@@ -3,7 +3,7 @@
 ///! storage layer, we are make a best effort to document them here.
 
 #[cfg(doc)]
-use crate::SphereContext;
+use crate::{HeaderPolicy, SphereContext};
 
 #[cfg(doc)]
 use noosphere_core::data::Did;
@@ -34,3 +34,7 @@ pub const GATEWAY_URL: &str = "gateway_url";
 /// The counterpart sphere [Did] that either tracks or is tracked by this
 /// sphere.
 pub const COUNTERPART: &str = "counterpart";
+
+/// The [HeaderPolicy] that describes the headers that are implicitly added to
+/// every memo that is written to the sphere from this [SphereContext].
+pub const HEADER_POLICY: &str = "header_policy";
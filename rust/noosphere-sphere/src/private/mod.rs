@@ -0,0 +1,50 @@
+//! Sphere private content is a sealed storage space that sits alongside the
+//! public content space of a sphere. The body of each private memo is
+//! encrypted with a random content key, and that key is wrapped for every key
+//! that is authorized to work on the sphere at the time that the memo is
+//! written (including the owner's key). The sealed bodies may therefore be
+//! saved, synced and replicated like any other sphere data (e.g., to keep
+//! drafts in the same sphere), but they may only be read back with the private
+//! half of one of those keys.
+//!
+//! Note that only memo bodies are sealed: slugs and memo headers (such as the
+//! content type) are stored in the clear. Also note that only Ed25519 keys are
+//! able to seal and unseal content.
+
+mod read;
+mod seal;
+mod write;
+
+pub use read::*;
+pub use write::*;
+
+use std::collections::BTreeSet;
+
+use anyhow::Result;
+use noosphere_core::data::Did;
+use noosphere_storage::Storage;
+use ucan::crypto::KeyMaterial;
+
+use crate::{HasSphereContext, SphereAuthorityRead};
+
+/// Get the DIDs of the keys that private content should be sealed for: the
+/// author's own key, and every key that currently holds an unexpired
+/// authorization in the delegations of the sphere
+pub(crate) async fn get_seal_recipients<C, K, S>(context: &C) -> Result<Vec<Did>>
+where
+    C: HasSphereContext<K, S>,
+    K: KeyMaterial + Clone + 'static,
+    S: Storage + 'static,
+{
+    let now = ucan::time::now();
+    let mut recipients =
+        BTreeSet::from([context.sphere_context().await?.author().identity().await?]);
+
+    for authorization in context.get_authorizations().await? {
+        if !authorization.is_expired_at(now) {
+            recipients.insert(authorization.did);
+        }
+    }
+
+    Ok(recipients.into_iter().collect())
+}
@@ -0,0 +1,106 @@
+use std::io::Cursor;
+
+use anyhow::Result;
+use noosphere_core::data::{Link, MemoIpld};
+use noosphere_storage::Storage;
+use tokio::io::AsyncReadExt;
+
+use ucan::crypto::KeyMaterial;
+
+use crate::HasSphereContext;
+use async_trait::async_trait;
+
+use crate::{internal::SphereContextInternal, AsyncFileBody, SphereFile};
+
+use super::seal::unseal;
+
+/// Anything that can read private content from a sphere should implement
+/// [SpherePrivateContentRead]. A blanket implementation is provided for
+/// anything that implements [HasSphereContext].
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+pub trait SpherePrivateContentRead<K, S>
+where
+    K: KeyMaterial + Clone + 'static,
+    S: Storage + 'static,
+{
+    /// Read a private file that is associated with a given slug at the revision
+    /// of the sphere that this view is pointing to. The body of the file is
+    /// unsealed with the private key of the configured author, so this will
+    /// fail if the file was not sealed for that key (for example, because the
+    /// key was authorized after the file was written; see
+    /// [SpherePrivateContentWrite::reseal_private]).
+    ///
+    /// [SpherePrivateContentWrite::reseal_private]: crate::SpherePrivateContentWrite::reseal_private
+    async fn read_private(&self, slug: &str) -> Result<Option<SphereFile<Box<dyn AsyncFileBody>>>>;
+
+    /// Returns true if private content identified by slug exists in the sphere
+    /// at the current revision. Note that this does not attempt to unseal the
+    /// content.
+    async fn private_exists(&self, slug: &str) -> Result<bool>;
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl<C, K, S> SpherePrivateContentRead<K, S> for C
+where
+    C: HasSphereContext<K, S>,
+    K: KeyMaterial + Clone + 'static,
+    S: Storage + 'static,
+{
+    async fn read_private(&self, slug: &str) -> Result<Option<SphereFile<Box<dyn AsyncFileBody>>>> {
+        let revision = self.version().await?;
+        let memo_link = match get_private_memo_link(self, slug).await? {
+            Some(memo_link) => memo_link,
+            None => return Ok(None),
+        };
+
+        let mut file = self.get_file(&revision, memo_link).await?;
+
+        let mut sealed_bytes = Vec::new();
+        file.contents.read_to_end(&mut sealed_bytes).await?;
+
+        let bytes = {
+            let sphere_context = self.sphere_context().await?;
+            let author = sphere_context.author();
+
+            unseal(&sealed_bytes, &author.key, &author.identity().await?)?
+        };
+
+        Ok(Some(SphereFile {
+            sphere_identity: file.sphere_identity,
+            sphere_version: file.sphere_version,
+            memo_version: file.memo_version,
+            memo: file.memo,
+            contents: Box::new(Cursor::new(bytes)),
+        }))
+    }
+
+    async fn private_exists(&self, slug: &str) -> Result<bool> {
+        Ok(get_private_memo_link(self, slug).await?.is_some())
+    }
+}
+
+/// Look up the link to the sealed memo for a slug in the private content of
+/// the sphere at the revision that the [HasSphereContext] refers to
+pub(crate) async fn get_private_memo_link<C, K, S>(
+    context: &C,
+    slug: &str,
+) -> Result<Option<Link<MemoIpld>>>
+where
+    C: HasSphereContext<K, S>,
+    K: KeyMaterial + Clone + 'static,
+    S: Storage + 'static,
+{
+    let sphere = context.to_sphere().await?;
+
+    Ok(match sphere.get_private_content().await? {
+        Some(private_content) => private_content
+            .get_hamt()
+            .await?
+            .get(&slug.to_string())
+            .await?
+            .cloned(),
+        None => None,
+    })
+}
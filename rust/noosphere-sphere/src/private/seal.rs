@@ -0,0 +1,266 @@
+use std::{any::Any, collections::BTreeMap};
+
+use anyhow::{anyhow, Result};
+use chacha20poly1305::{
+    aead::{Aead, NewAead},
+    Key, XChaCha20Poly1305, XNonce,
+};
+use curve25519_dalek::{
+    constants::X25519_BASEPOINT, edwards::CompressedEdwardsY, montgomery::MontgomeryPoint,
+    scalar::Scalar,
+};
+use hkdf::Hkdf;
+use libipld_cbor::DagCborCodec;
+use noosphere_core::data::Did;
+use noosphere_storage::{block_deserialize, block_serialize};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256, Sha512};
+use ucan::crypto::did::ED25519_MAGIC_BYTES;
+use ucan_key_support::ed25519::Ed25519KeyMaterial;
+
+const SEAL_KEY_INFO: &[u8] = b"noosphere/sealed-content/v1";
+const NONCE_LENGTH: usize = 24;
+
+/// The envelope that sealed content is stored in. The content is sealed with
+/// a random content key, and that key is in turn wrapped for each of the keys
+/// that are allowed to unseal the content. Only the holder of the private half
+/// of one of those keys is able to recover the content key.
+#[derive(Serialize, Deserialize)]
+struct SealedIpld {
+    /// An ephemeral X25519 public key that the content key was wrapped with
+    pub ephemeral_key: Vec<u8>,
+    /// The content key, wrapped for each recipient (keyed by DID)
+    pub keys: BTreeMap<String, Vec<u8>>,
+    /// The sealed content
+    pub content: Vec<u8>,
+}
+
+/// Seal some bytes so that they can only be unsealed with the private key of
+/// one of the given recipients. Recipients must be Ed25519 keys; any other
+/// kinds of keys are skipped, and it is an error if none remain.
+pub(crate) fn seal(bytes: &[u8], recipients: &[Did]) -> Result<Vec<u8>> {
+    let content_key = SealKey::generate();
+    let ephemeral_secret = Scalar::from_bits(clamp(random_bytes()));
+    let ephemeral_key = X25519_BASEPOINT * ephemeral_secret;
+    let mut keys = BTreeMap::new();
+
+    for recipient in recipients {
+        let recipient_key = match x25519_public_key(recipient) {
+            Ok(recipient_key) => recipient_key,
+            Err(error) => {
+                warn!("Not sealing content for {recipient}: {error}");
+                continue;
+            }
+        };
+
+        let wrapping_key = SealKey::agree(
+            recipient_key * ephemeral_secret,
+            &ephemeral_key,
+            &recipient_key,
+        )?;
+
+        keys.insert(
+            recipient.to_string(),
+            wrapping_key.seal(content_key.0.as_slice())?,
+        );
+    }
+
+    if keys.is_empty() {
+        return Err(anyhow!(
+            "Content can only be sealed for Ed25519 keys, and none were given"
+        ));
+    }
+
+    let (_, sealed_bytes) = block_serialize::<DagCborCodec, _>(SealedIpld {
+        ephemeral_key: ephemeral_key.to_bytes().to_vec(),
+        keys,
+        content: content_key.seal(bytes)?,
+    })?;
+
+    Ok(sealed_bytes)
+}
+
+/// Unseal bytes that were previously produced by [seal], using the private
+/// half of a key that the content was sealed for
+pub(crate) fn unseal<K: 'static>(sealed_bytes: &[u8], key: &K, did: &Did) -> Result<Vec<u8>> {
+    let sealed = block_deserialize::<DagCborCodec, SealedIpld>(sealed_bytes)?;
+    let wrapped_key = sealed
+        .keys
+        .get(did.as_str())
+        .ok_or_else(|| anyhow!("The content was not sealed for {did}"))?;
+
+    let secret = x25519_secret(key)?;
+    let public_key = X25519_BASEPOINT * secret;
+    let ephemeral_key = MontgomeryPoint(
+        sealed
+            .ephemeral_key
+            .as_slice()
+            .try_into()
+            .map_err(|_| anyhow!("Sealed content has a malformed ephemeral key"))?,
+    );
+
+    let content_key =
+        SealKey::agree(ephemeral_key * secret, &ephemeral_key, &public_key)?.unseal(wrapped_key)?;
+
+    SealKey(*Key::from_slice(&content_key)).unseal(&sealed.content)
+}
+
+/// A symmetric key that is used to seal and unseal content, or to wrap the
+/// content key for a recipient
+struct SealKey(Key);
+
+impl SealKey {
+    /// Generate a random [SealKey]
+    fn generate() -> Self {
+        SealKey(*Key::from_slice(&random_bytes()))
+    }
+
+    /// Derive the [SealKey] that wraps a content key for a recipient from the
+    /// result of an X25519 key agreement
+    fn agree(
+        shared_secret: MontgomeryPoint,
+        ephemeral_key: &MontgomeryPoint,
+        recipient_key: &MontgomeryPoint,
+    ) -> Result<Self> {
+        if shared_secret.to_bytes() == [0u8; 32] {
+            return Err(anyhow!("Unable to agree on a key to seal content with"));
+        }
+
+        let salt = [
+            ephemeral_key.as_bytes().as_slice(),
+            recipient_key.as_bytes(),
+        ]
+        .concat();
+        let mut key = Key::default();
+
+        Hkdf::<Sha256>::new(Some(&salt), shared_secret.as_bytes())
+            .expand(SEAL_KEY_INFO, key.as_mut_slice())
+            .map_err(|error| anyhow!("Unable to derive seal key: {}", error))?;
+
+        Ok(SealKey(key))
+    }
+
+    /// Encrypt some bytes, producing a random nonce followed by the ciphertext
+    fn seal(&self, bytes: &[u8]) -> Result<Vec<u8>> {
+        let mut nonce = [0u8; NONCE_LENGTH];
+        rand::thread_rng().fill_bytes(&mut nonce);
+
+        let ciphertext = XChaCha20Poly1305::new(&self.0)
+            .encrypt(XNonce::from_slice(&nonce), bytes)
+            .map_err(|_| anyhow!("Unable to seal content"))?;
+
+        Ok([nonce.as_slice(), ciphertext.as_slice()].concat())
+    }
+
+    /// Decrypt bytes that were previously produced by [SealKey::seal]
+    fn unseal(&self, sealed_bytes: &[u8]) -> Result<Vec<u8>> {
+        if sealed_bytes.len() < NONCE_LENGTH {
+            return Err(anyhow!("Sealed content is truncated"));
+        }
+
+        let (nonce, ciphertext) = sealed_bytes.split_at(NONCE_LENGTH);
+
+        XChaCha20Poly1305::new(&self.0)
+            .decrypt(XNonce::from_slice(nonce), ciphertext)
+            .map_err(|_| {
+                anyhow!("Unable to unseal content; it may have been sealed by a different key")
+            })
+    }
+}
+
+fn random_bytes() -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    bytes
+}
+
+fn clamp(mut bytes: [u8; 32]) -> [u8; 32] {
+    bytes[0] &= 248;
+    bytes[31] &= 127;
+    bytes[31] |= 64;
+    bytes
+}
+
+/// Convert the Ed25519 public key in a `did:key` to its X25519 equivalent
+fn x25519_public_key(did: &Did) -> Result<MontgomeryPoint> {
+    let encoded = did
+        .strip_prefix("did:key:z")
+        .ok_or_else(|| anyhow!("Expected a did:key"))?;
+    let bytes = bs58::decode(encoded).into_vec()?;
+    let public_key = bytes
+        .strip_prefix(ED25519_MAGIC_BYTES)
+        .ok_or_else(|| anyhow!("Expected an Ed25519 key"))?;
+
+    Ok(CompressedEdwardsY(
+        public_key
+            .try_into()
+            .map_err(|_| anyhow!("Invalid Ed25519 key"))?,
+    )
+    .decompress()
+    .ok_or_else(|| anyhow!("Invalid Ed25519 key"))?
+    .to_montgomery())
+}
+
+/// Derive the X25519 secret that corresponds to an Ed25519 private key, in the
+/// same way that the Ed25519 signing scalar is derived from the key's seed
+fn x25519_secret<K: 'static>(key: &K) -> Result<Scalar> {
+    let private_key = (key as &dyn Any)
+        .downcast_ref::<Ed25519KeyMaterial>()
+        .ok_or_else(|| anyhow!("Sealed content can only be unsealed with an Ed25519 key"))?
+        .1
+        .ok_or_else(|| anyhow!("No private key; cannot unseal content"))?;
+
+    let mut secret = [0u8; 32];
+    secret.copy_from_slice(&Sha512::digest(private_key.as_ref())[..32]);
+
+    Ok(Scalar::from_bits(clamp(secret)))
+}
+
+#[cfg(test)]
+mod tests {
+    use noosphere_core::{authority::generate_ed25519_key, data::Did};
+    use ucan::crypto::KeyMaterial;
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::{seal, unseal};
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[cfg_attr(not(target_arch = "wasm32"), tokio::test)]
+    async fn it_can_unseal_content_with_the_key_of_any_recipient() {
+        let first_key = generate_ed25519_key();
+        let first_did = Did(first_key.get_did().await.unwrap());
+        let second_key = generate_ed25519_key();
+        let second_did = Did(second_key.get_did().await.unwrap());
+
+        let sealed = seal(
+            b"Hello, sealed world",
+            &[first_did.clone(), second_did.clone()],
+        )
+        .unwrap();
+
+        assert!(!sealed
+            .windows(b"Hello, sealed world".len())
+            .any(|window| window == b"Hello, sealed world"));
+
+        for (key, did) in [(first_key, first_did), (second_key, second_did)] {
+            let unsealed = unseal(&sealed, &key, &did).unwrap();
+
+            assert_eq!(unsealed.as_slice(), b"Hello, sealed world");
+        }
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[cfg_attr(not(target_arch = "wasm32"), tokio::test)]
+    async fn it_cannot_unseal_content_with_the_key_of_someone_else() {
+        let recipient_did = Did(generate_ed25519_key().get_did().await.unwrap());
+        let other_key = generate_ed25519_key();
+        let other_did = Did(other_key.get_did().await.unwrap());
+
+        let sealed = seal(b"Hello, sealed world", &[recipient_did.clone()]).unwrap();
+
+        assert!(unseal(&sealed, &other_key, &other_did).is_err());
+        assert!(unseal(&sealed, &other_key, &recipient_did).is_err());
+    }
+}
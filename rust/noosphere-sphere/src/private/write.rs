@@ -0,0 +1,209 @@
+use anyhow::{anyhow, Result};
+use cid::Cid;
+use libipld_cbor::DagCborCodec;
+use noosphere_core::data::{BodyTreeIpld, Did, Header, MemoIpld};
+use noosphere_storage::{BlockStore, Storage};
+
+use tokio::io::AsyncReadExt;
+use tokio_stream::StreamExt;
+use ucan::crypto::KeyMaterial;
+
+use crate::{
+    content::validate_slug, internal::SphereContextInternal, HasMutableSphereContext,
+    HasSphereContext,
+};
+use async_trait::async_trait;
+
+use crate::AsyncFileBody;
+
+use super::{
+    get_private_memo_link, get_seal_recipients,
+    seal::{seal, unseal},
+    SpherePrivateContentRead,
+};
+
+/// Anything that can write private content to a sphere should implement
+/// [SpherePrivateContentWrite]. A blanket implementation is provided for
+/// anything that implements [HasMutableSphereContext].
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+pub trait SpherePrivateContentWrite<K, S>: SpherePrivateContentRead<K, S>
+where
+    K: KeyMaterial + Clone + 'static,
+    S: Storage + 'static,
+{
+    /// Seal some bytes and write them to a slug in the private content of the
+    /// sphere. The bytes are sealed for the author and for every other key that
+    /// currently holds an authorization for the sphere, so that any of those
+    /// keys may read them back. In order to commit the change to the sphere,
+    /// you must call save.
+    ///
+    /// The returned CID is a link to the memo for the newly added content.
+    async fn write_private<F: AsyncFileBody>(
+        &mut self,
+        slug: &str,
+        content_type: &str,
+        mut value: F,
+        additional_headers: Option<Vec<(String, String)>>,
+    ) -> Result<Cid>;
+
+    /// Unlinks a slug from the private content space. As with public content,
+    /// the sealed blocks remain available at earlier revisions of the sphere.
+    /// In order to commit the change, you must save.
+    ///
+    /// The returned value is the CID previously associated with the slug, if
+    /// any.
+    async fn remove_private(&mut self, slug: &str) -> Result<Option<Cid>>;
+
+    /// Re-seal all of the private content in the sphere for the keys that are
+    /// currently authorized to work on it, unsealing it with the given key
+    /// (which must be one of the keys that the content was sealed for). Content
+    /// is only sealed for the keys that are authorized when it is written, so
+    /// this must be done in order for a key that was authorized afterwards to
+    /// be able to read it (for example, after ownership of the sphere has been
+    /// transferred). The headers of each memo are preserved. In order to commit
+    /// the change, you must save.
+    ///
+    /// Note that this only affects the latest revision of the private content:
+    /// earlier revisions of the sphere still refer to the content as it was
+    /// originally sealed, and remain readable by the keys it was sealed for.
+    async fn reseal_private(&mut self, unsealing_key: &K) -> Result<()>;
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl<C, K, S> SpherePrivateContentWrite<K, S> for C
+where
+    C: HasSphereContext<K, S> + HasMutableSphereContext<K, S>,
+    K: KeyMaterial + Clone + 'static,
+    S: Storage + 'static,
+{
+    async fn write_private<F: AsyncFileBody>(
+        &mut self,
+        slug: &str,
+        content_type: &str,
+        mut value: F,
+        additional_headers: Option<Vec<(String, String)>>,
+    ) -> Result<Cid> {
+        debug!("Writing private {}...", slug);
+
+        self.assert_write_access().await?;
+        validate_slug(slug)?;
+
+        let mut bytes = Vec::new();
+        value.read_to_end(&mut bytes).await?;
+
+        let previous_memo_cid = get_private_memo_link(self, slug).await?;
+        let sealed_bytes = seal(&bytes, &get_seal_recipients(self).await?)?;
+
+        let memo_cid = {
+            let mut sphere_context = self.sphere_context_mut().await?;
+
+            let body_cid =
+                BodyTreeIpld::store_bytes(&sealed_bytes, sphere_context.db_mut()).await?;
+
+            let mut new_memo = match previous_memo_cid {
+                Some(cid) => {
+                    let mut memo = MemoIpld::branch_from(&cid, sphere_context.db()).await?;
+                    memo.body = body_cid;
+                    memo
+                }
+                None => MemoIpld {
+                    parent: None,
+                    headers: Vec::new(),
+                    body: body_cid,
+                },
+            };
+
+            if let Some(headers) = additional_headers {
+                new_memo.replace_headers(headers)
+            }
+
+            new_memo.replace_first_header(&Header::ContentType.to_string(), content_type);
+
+            let memo_cid = sphere_context
+                .db_mut()
+                .save::<DagCborCodec, MemoIpld>(new_memo)
+                .await?;
+
+            sphere_context
+                .mutation_mut()
+                .private_content_mut()
+                .set(&slug.into(), &memo_cid.into());
+
+            memo_cid
+        };
+
+        Ok(memo_cid)
+    }
+
+    async fn remove_private(&mut self, slug: &str) -> Result<Option<Cid>> {
+        self.assert_write_access().await?;
+
+        Ok(match get_private_memo_link(self, slug).await? {
+            Some(memo_link) => {
+                self.sphere_context_mut()
+                    .await?
+                    .mutation_mut()
+                    .private_content_mut()
+                    .remove(&String::from(slug));
+
+                Some(memo_link.into())
+            }
+            None => None,
+        })
+    }
+
+    async fn reseal_private(&mut self, unsealing_key: &K) -> Result<()> {
+        self.assert_write_access().await?;
+
+        let unsealing_did = Did(unsealing_key.get_did().await?);
+        let private_content = self.to_sphere().await?.get_private_content().await?;
+
+        let private_content = match private_content {
+            Some(private_content) => private_content,
+            None => return Ok(()),
+        };
+
+        let revision = self.version().await?;
+        let entries = private_content.into_stream().await?;
+
+        tokio::pin!(entries);
+
+        let mut resealed_files = Vec::new();
+
+        while let Some((slug, memo_link)) = entries.try_next().await? {
+            let mut file = self.get_file(&revision, memo_link).await?;
+            let mut sealed_bytes = Vec::new();
+
+            file.contents.read_to_end(&mut sealed_bytes).await?;
+
+            let content_type = file
+                .memo
+                .get_first_header(&Header::ContentType.to_string())
+                .ok_or_else(|| anyhow!("Private content at {slug} has no content type"))?;
+            let headers = file
+                .memo
+                .headers
+                .into_iter()
+                .filter(|(name, _)| {
+                    name.to_lowercase() != Header::ContentType.to_string().to_lowercase()
+                })
+                .collect();
+
+            resealed_files.push((
+                slug,
+                content_type,
+                headers,
+                unseal(&sealed_bytes, unsealing_key, &unsealing_did)?,
+            ));
+        }
+
+        for (slug, content_type, headers, bytes) in resealed_files {
+            self.write_private(&slug, &content_type, bytes.as_slice(), Some(headers))
+                .await?;
+        }
+
+        Ok(())
+    }
+}
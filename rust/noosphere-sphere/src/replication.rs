@@ -9,7 +9,8 @@ use noosphere_core::{
     data::{ContentType, MemoIpld, VersionedMapKey, VersionedMapValue},
     view::{Sphere, VersionedMap},
 };
-use noosphere_storage::{BlockStore, BlockStoreTap, UcanStore};
+use noosphere_storage::{block_references, BlockStore, BlockStoreTap, UcanStore};
+use std::collections::BTreeSet;
 use std::io::{Error as IoError, ErrorKind as IoErrorKind};
 use std::ops::Fn;
 use tokio::sync::mpsc::{channel, error::TryRecvError};
@@ -72,6 +73,7 @@ where
                 let authority = sphere.get_authority().await?;
                 let address_book = sphere.get_address_book().await?;
                 let content = sphere.get_content().await?;
+                let private_content = sphere.get_private_content().await?;
                 let identities = address_book.get_identities().await?;
                 let delegations = authority.get_delegations().await?;
                 let revocations = authority.get_revocations().await?;
@@ -84,6 +86,20 @@ where
                     store.get_block(&link.into()).await?;
                     Ok(())
                 }));
+                // Private content is replicated the same way as public content;
+                // its memo bodies are sealed, so they are opaque to us here
+                let private_content_task = tokio::spawn({
+                    let store = store.clone();
+                    async move {
+                        match private_content {
+                            Some(private_content) => walk_versioned_map_and(private_content, store, move |_, link, store| async move {
+                                store.get_block(&link.into()).await?;
+                                Ok(())
+                            }).await,
+                            None => Ok(())
+                        }
+                    }
+                });
                 let delegations_task = tokio::spawn(walk_versioned_map(delegations));
                 let revocations_task = tokio::spawn(walk_versioned_map(revocations));
 
@@ -100,18 +116,40 @@ where
                     yield block;
                 }
 
-                let (identities_result, content_result, delegations_result, revocations_result) = tokio::join!(
+                let (identities_result, content_result, private_content_result, delegations_result, revocations_result) = tokio::join!(
                     identities_task,
                     content_task,
+                    private_content_task,
                     delegations_task,
                     revocations_task
                 );
 
                 identities_result??;
                 content_result??;
+                private_content_result??;
                 delegations_result??;
                 revocations_result??;
             }
+            Some(ContentType::DagCbor) => {
+                // The body is an arbitrary IPLD DAG, so follow the links
+                // within each of its blocks
+                let mut visited = BTreeSet::new();
+                let mut remaining = vec![memo.body];
+
+                while let Some(cid) = remaining.pop() {
+                    if !visited.insert(cid) {
+                        continue;
+                    }
+
+                    let block = store.require_block(&cid).await?;
+
+                    remaining.extend(block_references(&cid, &block)?);
+
+                    while let Ok(block) = rx.try_recv() {
+                        yield block;
+                    }
+                }
+            }
             Some(_) => {
                 let stream = BodyChunkDecoder(&memo.body, &store).stream();
 
@@ -185,16 +223,21 @@ where
 
 #[cfg(test)]
 mod tests {
-    use std::collections::BTreeSet;
+    use std::{collections::BTreeSet, sync::Arc};
 
+    use cid::Cid;
     use libipld_cbor::DagCborCodec;
+    use libipld_core::ipld::Ipld;
     use noosphere_car::CarReader;
     use noosphere_core::{
+        authority::{generate_ed25519_key, Author},
         data::{BodyChunkIpld, ContentType, MemoIpld},
         tracing::initialize_tracing,
         view::Sphere,
     };
-    use noosphere_storage::{BlockStore, MemoryStore, UcanStore};
+    use noosphere_storage::{BlockStore, MemoryStorage, MemoryStore, SphereDb, UcanStore};
+    use serde::{Deserialize, Serialize};
+    use tokio::sync::Mutex;
     use tokio_stream::StreamExt;
     use tokio_util::io::StreamReader;
 
@@ -202,7 +245,8 @@ mod tests {
         block_stream, car_stream,
         helpers::{make_valid_link_record, simulated_sphere_context, SimulationAccess},
         walk_versioned_map, BodyChunkDecoder, HasMutableSphereContext, HasSphereContext,
-        SphereContentWrite, SpherePetnameWrite,
+        SphereContentRead, SphereContentWrite, SphereContext, SpherePetnameWrite,
+        SpherePrivateContentRead, SpherePrivateContentWrite,
     };
 
     #[cfg(target_arch = "wasm32")]
@@ -305,6 +349,119 @@ mod tests {
         walk_versioned_map(revocations).await.unwrap();
     }
 
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[cfg_attr(not(target_arch = "wasm32"), tokio::test)]
+    async fn it_streams_private_content_in_a_sphere_version() {
+        initialize_tracing(None);
+        let mut sphere_context = simulated_sphere_context(SimulationAccess::ReadWrite, None)
+            .await
+            .unwrap();
+
+        sphere_context
+            .write_private(
+                "draft",
+                &ContentType::Subtext.to_string(),
+                b"Not ready yet".as_ref(),
+                None,
+            )
+            .await
+            .unwrap();
+        sphere_context.save(None).await.unwrap();
+
+        let final_version = sphere_context.version().await.unwrap();
+
+        let mut other_store = MemoryStore::default();
+        let stream = block_stream(
+            sphere_context.sphere_context().await.unwrap().db().clone(),
+            final_version,
+        );
+
+        tokio::pin!(stream);
+
+        while let Some((cid, block)) = stream.try_next().await.unwrap() {
+            other_store.put_block(&cid, &block).await.unwrap();
+        }
+
+        let private_content = Sphere::at(&final_version, &other_store)
+            .get_private_content()
+            .await
+            .unwrap()
+            .unwrap();
+
+        let memo_link = private_content
+            .get(&"draft".to_string())
+            .await
+            .unwrap()
+            .cloned()
+            .unwrap();
+
+        assert!(other_store
+            .get_block(&memo_link.into())
+            .await
+            .unwrap()
+            .is_some());
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[cfg_attr(not(target_arch = "wasm32"), tokio::test)]
+    async fn it_cannot_unseal_private_content_with_only_the_replicated_blocks() {
+        initialize_tracing(None);
+        let mut sphere_context = simulated_sphere_context(SimulationAccess::ReadWrite, None)
+            .await
+            .unwrap();
+
+        sphere_context
+            .write_private(
+                "draft",
+                &ContentType::Subtext.to_string(),
+                b"Not ready yet".as_ref(),
+                None,
+            )
+            .await
+            .unwrap();
+        sphere_context.save(None).await.unwrap();
+
+        let identity = sphere_context.identity().await.unwrap();
+        let final_version = sphere_context.version().await.unwrap();
+
+        let mut replica_db = SphereDb::new(&MemoryStorage::default()).await.unwrap();
+        let stream = block_stream(
+            sphere_context.sphere_context().await.unwrap().db().clone(),
+            final_version,
+        );
+
+        tokio::pin!(stream);
+
+        while let Some((cid, block)) = stream.try_next().await.unwrap() {
+            assert!(!block
+                .windows(b"Not ready yet".len())
+                .any(|window| window == b"Not ready yet"));
+            replica_db.put_block(&cid, &block).await.unwrap();
+        }
+
+        replica_db
+            .set_version(&identity, &final_version)
+            .await
+            .unwrap();
+
+        let replica_context = Arc::new(Mutex::new(
+            SphereContext::new(
+                identity,
+                Author {
+                    key: generate_ed25519_key(),
+                    authorization: None,
+                },
+                replica_db,
+                None,
+            )
+            .await
+            .unwrap(),
+        ));
+
+        assert!(replica_context.private_exists("draft").await.unwrap());
+        assert!(replica_context.read_private("draft").await.is_err());
+    }
+
     #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
     #[cfg_attr(not(target_arch = "wasm32"), tokio::test)]
     async fn it_can_stream_all_blocks_in_some_sphere_content() {
@@ -375,6 +532,84 @@ mod tests {
         assert_eq!(buffer.as_slice(), b"foobarbaz");
     }
 
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[cfg_attr(not(target_arch = "wasm32"), tokio::test)]
+    async fn it_can_stream_all_blocks_in_some_ipld_content() {
+        initialize_tracing(None);
+
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct TaskList {
+            title: String,
+            tasks: Vec<Cid>,
+        }
+
+        let mut sphere_context = simulated_sphere_context(SimulationAccess::ReadWrite, None)
+            .await
+            .unwrap();
+        let mut db = sphere_context
+            .sphere_context()
+            .await
+            .unwrap()
+            .db_mut()
+            .clone();
+
+        let task_cid = db
+            .save::<DagCborCodec, _>(Ipld::String("Water the plants".into()))
+            .await
+            .unwrap();
+        let task_list = TaskList {
+            title: "Chores".into(),
+            tasks: vec![task_cid],
+        };
+
+        let content_cid = sphere_context
+            .write_ipld("chores", &task_list, None)
+            .await
+            .unwrap();
+        sphere_context.save(None).await.unwrap();
+
+        assert_eq!(
+            sphere_context
+                .read_ipld::<TaskList>("chores")
+                .await
+                .unwrap(),
+            Some(task_list)
+        );
+
+        let stream = block_stream(
+            sphere_context.sphere_context().await.unwrap().db().clone(),
+            content_cid,
+        );
+
+        let mut store = MemoryStore::default();
+
+        tokio::pin!(stream);
+
+        while let Some((cid, block)) = stream.try_next().await.unwrap() {
+            store.put_block(&cid, &block).await.unwrap();
+        }
+
+        let memo = store
+            .load::<DagCborCodec, MemoIpld>(&content_cid)
+            .await
+            .unwrap();
+
+        assert_eq!(memo.content_type(), Some(ContentType::DagCbor));
+
+        let replicated_task_list = store
+            .load::<DagCborCodec, TaskList>(&memo.body)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            store
+                .load::<DagCborCodec, Ipld>(&replicated_task_list.tasks[0])
+                .await
+                .unwrap(),
+            Ipld::String("Water the plants".into())
+        );
+    }
+
     #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
     #[cfg_attr(not(target_arch = "wasm32"), tokio::test)]
     async fn it_can_stream_all_blocks_in_a_sphere_version_as_a_car() {
@@ -0,0 +1,296 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::Result;
+use cid::Cid;
+use noosphere_core::data::Did;
+use noosphere_storage::KeyValueStore;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    tokenize::{edit_distance, max_edit_distance},
+    SearchResult,
+};
+
+/// Relative weight of a query term that matches an indexed term exactly
+const EXACT_MATCH_WEIGHT: f32 = 1.0;
+/// Relative weight of a query term that is a prefix of an indexed term
+const PREFIX_MATCH_WEIGHT: f32 = 0.8;
+/// Relative weight of a query term that is a near-miss of an indexed term
+const FUZZY_MATCH_WEIGHT: f32 = 0.6;
+
+/// Bookkeeping for the index of a single sphere
+#[derive(Default, Serialize, Deserialize)]
+struct IndexSummary {
+    /// The version of the sphere that the index was last brought up to date with
+    version: Option<Cid>,
+    /// The number of documents in the index
+    documents: u64,
+    /// Every term that appears in at least one document
+    terms: BTreeSet<String>,
+}
+
+/// The indexed form of a single slug
+#[derive(Serialize, Deserialize)]
+struct IndexedDocument {
+    /// The memo version that the terms were taken from
+    memo: Cid,
+    /// Each term in the document, and how many times it occurs
+    terms: BTreeMap<String, u32>,
+}
+
+/// A [SearchIndex] is an inverted index over the content of one sphere,
+/// persisted in a [KeyValueStore] that is shared by all local spheres (so all
+/// keys are prefixed with the sphere's identity). For every term the index
+/// records the slugs that contain it (its "postings"), and for every slug it
+/// records its terms so that they can be retracted when the slug changes.
+///
+/// Changes to postings are accumulated in memory and written when
+/// [SearchIndex::save] is called.
+pub(crate) struct SearchIndex<St>
+where
+    St: KeyValueStore,
+{
+    store: St,
+    identity: Did,
+    summary: IndexSummary,
+    postings: BTreeMap<String, BTreeMap<String, u32>>,
+    changed_postings: BTreeSet<String>,
+}
+
+impl<St> SearchIndex<St>
+where
+    St: KeyValueStore,
+{
+    /// Returns true if an index has ever been saved for the given sphere
+    pub async fn exists(store: &St, identity: &Did) -> Result<bool> {
+        Ok(store
+            .get_key::<_, IndexSummary>(summary_key(identity))
+            .await?
+            .is_some())
+    }
+
+    /// Open the index for the given sphere; the index is empty if it has never
+    /// been saved before
+    pub async fn open(store: St, identity: &Did) -> Result<Self> {
+        let summary = store
+            .get_key(summary_key(identity))
+            .await?
+            .unwrap_or_default();
+
+        Ok(SearchIndex {
+            store,
+            identity: identity.clone(),
+            summary,
+            postings: BTreeMap::new(),
+            changed_postings: BTreeSet::new(),
+        })
+    }
+
+    /// The version of the sphere that this index reflects, if any
+    pub fn version(&self) -> Option<&Cid> {
+        self.summary.version.as_ref()
+    }
+
+    pub fn set_version(&mut self, version: &Cid) {
+        self.summary.version = Some(*version);
+    }
+
+    /// Returns true if the slug is indexed as of the given memo version
+    pub async fn is_current(&self, slug: &str, memo: &Cid) -> Result<bool> {
+        Ok(matches!(
+            self.get_document(slug).await?,
+            Some(document) if &document.memo == memo
+        ))
+    }
+
+    /// Index (or re-index) a slug given the frequency of each term in its
+    /// content
+    pub async fn put_document(
+        &mut self,
+        slug: &str,
+        memo: &Cid,
+        terms: BTreeMap<String, u32>,
+    ) -> Result<()> {
+        self.remove_document(slug).await?;
+
+        for (term, frequency) in terms.iter() {
+            self.postings_mut(term)
+                .await?
+                .insert(slug.to_string(), *frequency);
+        }
+
+        self.store
+            .set_key(
+                document_key(&self.identity, slug),
+                IndexedDocument { memo: *memo, terms },
+            )
+            .await?;
+        self.summary.documents += 1;
+
+        Ok(())
+    }
+
+    /// Drop a slug from the index, if it is indexed
+    pub async fn remove_document(&mut self, slug: &str) -> Result<()> {
+        let document = match self.get_document(slug).await? {
+            Some(document) => document,
+            None => return Ok(()),
+        };
+
+        for term in document.terms.keys() {
+            self.postings_mut(term).await?.remove(slug);
+        }
+
+        self.store
+            .unset_key(document_key(&self.identity, slug))
+            .await?;
+        self.summary.documents = self.summary.documents.saturating_sub(1);
+
+        Ok(())
+    }
+
+    /// Write all pending changes to the store
+    pub async fn save(&mut self) -> Result<()> {
+        for term in std::mem::take(&mut self.changed_postings) {
+            let key = term_key(&self.identity, &term);
+
+            match self.postings.get(&term) {
+                Some(postings) if !postings.is_empty() => {
+                    self.store.set_key(key, postings).await?;
+                    self.summary.terms.insert(term);
+                }
+                _ => {
+                    self.store.unset_key(key).await?;
+                    self.summary.terms.remove(&term);
+                }
+            }
+        }
+
+        self.store
+            .set_key(summary_key(&self.identity), &self.summary)
+            .await?;
+        self.store.flush().await
+    }
+
+    /// Find the slugs that best match the given query terms. Each query term
+    /// matches indexed terms that are identical to it, that it is a prefix of,
+    /// or that are within a small edit distance of it. Slugs are scored by the
+    /// frequency and rarity of the terms they matched, and by the share of
+    /// query terms that they matched.
+    pub async fn query(
+        &mut self,
+        query_terms: &[String],
+        limit: usize,
+    ) -> Result<Vec<SearchResult>> {
+        let document_count = self.summary.documents.max(1) as f32;
+        let mut scores: BTreeMap<String, (f32, usize)> = BTreeMap::new();
+
+        for query_term in query_terms {
+            let mut best_for_query_term: BTreeMap<String, f32> = BTreeMap::new();
+
+            for (term, weight) in self.matching_terms(query_term) {
+                let postings = self.postings(&term).await?;
+                let rarity = (1.0 + document_count / postings.len().max(1) as f32).ln();
+
+                for (slug, frequency) in postings {
+                    let score = weight * rarity * (1.0 + (frequency as f32).ln());
+                    let best = best_for_query_term.entry(slug).or_default();
+
+                    if score > *best {
+                        *best = score;
+                    }
+                }
+            }
+
+            for (slug, score) in best_for_query_term {
+                let entry = scores.entry(slug).or_default();
+                entry.0 += score;
+                entry.1 += 1;
+            }
+        }
+
+        let mut results: Vec<SearchResult> = scores
+            .into_iter()
+            .map(|(slug, (score, matched))| SearchResult {
+                slug,
+                score: score * matched as f32 / query_terms.len().max(1) as f32,
+            })
+            .collect();
+
+        results.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| a.slug.cmp(&b.slug))
+        });
+        results.truncate(limit);
+
+        Ok(results)
+    }
+
+    /// Find the indexed terms that a query term matches, and how strongly
+    fn matching_terms(&self, query_term: &str) -> Vec<(String, f32)> {
+        let max_distance = max_edit_distance(query_term);
+
+        self.summary
+            .terms
+            .iter()
+            .filter_map(|term| {
+                if term == query_term {
+                    Some((term.clone(), EXACT_MATCH_WEIGHT))
+                } else if term.starts_with(query_term) {
+                    Some((term.clone(), PREFIX_MATCH_WEIGHT))
+                } else if max_distance > 0
+                    && edit_distance(query_term, term, max_distance) <= max_distance
+                {
+                    Some((term.clone(), FUZZY_MATCH_WEIGHT))
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    async fn get_document(&self, slug: &str) -> Result<Option<IndexedDocument>> {
+        self.store.get_key(document_key(&self.identity, slug)).await
+    }
+
+    /// Make sure the postings for a term are loaded into memory
+    async fn load_postings(&mut self, term: &str) -> Result<()> {
+        if !self.postings.contains_key(term) {
+            let postings = self
+                .store
+                .get_key(term_key(&self.identity, term))
+                .await?
+                .unwrap_or_default();
+            self.postings.insert(term.to_string(), postings);
+        }
+
+        Ok(())
+    }
+
+    async fn postings(&mut self, term: &str) -> Result<BTreeMap<String, u32>> {
+        self.load_postings(term).await?;
+
+        Ok(self.postings.get(term).cloned().unwrap_or_default())
+    }
+
+    async fn postings_mut(&mut self, term: &str) -> Result<&mut BTreeMap<String, u32>> {
+        self.load_postings(term).await?;
+        self.changed_postings.insert(term.to_string());
+
+        Ok(self.postings.entry(term.to_string()).or_default())
+    }
+}
+
+fn summary_key(identity: &Did) -> String {
+    format!("{identity}/summary")
+}
+
+fn document_key(identity: &Did, slug: &str) -> String {
+    format!("{identity}/document/{slug}")
+}
+
+fn term_key(identity: &Did, term: &str) -> String {
+    format!("{identity}/term/{term}")
+}
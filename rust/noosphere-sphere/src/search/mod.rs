@@ -0,0 +1,245 @@
+//! Sphere search is an incremental full-text index over the `text/plain` and
+//! `text/subtext` content of a sphere. The index is kept locally (it is never
+//! synced) in a dedicated [noosphere_storage::KeyValueStore] namespace of the
+//! [noosphere_storage::SphereDb].
+//!
+//! The index for a sphere is built the first time that the sphere is searched.
+//! From then on, it is brought up to date each time the sphere is saved, and
+//! before each search, by re-indexing only the slugs that have changed since
+//! the version of the sphere that was last indexed.
+
+mod index;
+mod tokenize;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use noosphere_core::data::ContentType;
+use noosphere_storage::Storage;
+use tokio::io::AsyncReadExt;
+use ucan::crypto::KeyMaterial;
+
+use crate::{HasSphereContext, SphereContentRead, SphereWalker};
+
+use index::SearchIndex;
+use tokenize::{term_frequencies, tokenize};
+
+/// A slug that matched a search query, along with its relevance score (higher
+/// is more relevant)
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchResult {
+    pub slug: String,
+    pub score: f32,
+}
+
+/// Anything that can search the content of a sphere should implement
+/// [SphereSearch]. A blanket implementation is provided for anything that
+/// implements [HasSphereContext].
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+pub trait SphereSearch<K, S>
+where
+    K: KeyMaterial + Clone + 'static,
+    S: Storage + 'static,
+{
+    /// Bring the search index for this sphere up to date with the version
+    /// that this view is pointing to, building the index if this sphere has
+    /// never been indexed before.
+    async fn update_search_index(&self) -> Result<()>;
+
+    /// Find the slugs whose content best matches the query, most relevant
+    /// first, returning at most `limit` results. Query terms match indexed
+    /// terms exactly, as a prefix or with a small number of typos.
+    async fn search(&self, query: &str, limit: usize) -> Result<Vec<SearchResult>>;
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl<C, K, S> SphereSearch<K, S> for C
+where
+    C: HasSphereContext<K, S>,
+    K: KeyMaterial + Clone + 'static,
+    S: Storage + 'static,
+{
+    async fn update_search_index(&self) -> Result<()> {
+        open_and_update_index(self).await?;
+        Ok(())
+    }
+
+    async fn search(&self, query: &str, limit: usize) -> Result<Vec<SearchResult>> {
+        let query_terms = tokenize(query);
+
+        if query_terms.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        open_and_update_index(self)
+            .await?
+            .query(&query_terms, limit)
+            .await
+    }
+}
+
+/// Update the search index for a sphere, but only if one has been built
+/// before (that is, if the sphere has ever been searched)
+pub(crate) async fn update_existing_search_index<C, K, S>(context: &C) -> Result<()>
+where
+    C: HasSphereContext<K, S>,
+    K: KeyMaterial + Clone + 'static,
+    S: Storage + 'static,
+{
+    let (identity, store) = {
+        let sphere_context = context.sphere_context().await?;
+        (
+            sphere_context.identity().clone(),
+            sphere_context.db().to_search_store(),
+        )
+    };
+
+    if SearchIndex::exists(&store, &identity).await? {
+        open_and_update_index(context).await?;
+    }
+
+    Ok(())
+}
+
+async fn open_and_update_index<C, K, S>(context: &C) -> Result<SearchIndex<S::KeyValueStore>>
+where
+    C: HasSphereContext<K, S>,
+    K: KeyMaterial + Clone + 'static,
+    S: Storage + 'static,
+{
+    let (identity, store) = {
+        let sphere_context = context.sphere_context().await?;
+        (
+            sphere_context.identity().clone(),
+            sphere_context.db().to_search_store(),
+        )
+    };
+
+    let mut index = SearchIndex::open(store, &identity).await?;
+    let version = context.version().await?;
+
+    if index.version() == Some(&version) {
+        return Ok(index);
+    }
+
+    let since = index.version().cloned();
+    let changed_slugs = SphereWalker::from(context.clone())
+        .content_changes(since.as_ref())
+        .await?;
+
+    debug!(
+        "Updating search index for {} ({} changed slugs)",
+        identity,
+        changed_slugs.len()
+    );
+
+    for slug in changed_slugs {
+        let file = match context.read(&slug).await {
+            Ok(file) => file,
+            Err(error) => {
+                warn!("Could not read '{}' to index it: {}", slug, error);
+                None
+            }
+        };
+
+        let mut file = match file {
+            Some(file) => file,
+            None => {
+                index.remove_document(&slug).await?;
+                continue;
+            }
+        };
+
+        if index.is_current(&slug, &file.memo_version).await? {
+            continue;
+        }
+
+        match file.memo.content_type() {
+            Some(ContentType::Text) | Some(ContentType::Subtext) => {
+                let mut bytes = Vec::new();
+                file.contents.read_to_end(&mut bytes).await?;
+
+                let terms = term_frequencies(&slug, &String::from_utf8_lossy(&bytes));
+                index.put_document(&slug, &file.memo_version, terms).await?;
+            }
+            _ => index.remove_document(&slug).await?,
+        };
+    }
+
+    index.set_version(&version);
+    index.save().await?;
+
+    Ok(index)
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use noosphere_core::data::ContentType;
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    #[cfg(target_arch = "wasm32")]
+    wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_browser);
+
+    use crate::{
+        helpers::{simulated_sphere_context, SimulationAccess},
+        HasMutableSphereContext, SphereContentWrite, SphereSearch,
+    };
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[cfg_attr(not(target_arch = "wasm32"), tokio::test)]
+    async fn it_finds_text_content_and_follows_changes() -> Result<()> {
+        let mut sphere_context =
+            simulated_sphere_context(SimulationAccess::ReadWrite, None).await?;
+
+        for (slug, content_type, body) in [
+            ("cats", ContentType::Subtext, "Cats are curious.\n/dogs"),
+            ("dogs", ContentType::Text, "Dogs are loyal, unlike cats."),
+            ("birds", ContentType::Subtext, "Birds migrate south."),
+            ("data", ContentType::Bytes, "cats cats cats"),
+        ] {
+            sphere_context
+                .write(slug, &content_type.to_string(), body.as_bytes(), None)
+                .await?;
+        }
+
+        sphere_context.save(None).await?;
+
+        let results = sphere_context.search("cats", 10).await?;
+        let slugs: Vec<&str> = results.iter().map(|result| result.slug.as_str()).collect();
+
+        assert_eq!(slugs.len(), 2);
+        assert_eq!(slugs[0], "cats");
+        assert!(slugs.contains(&"dogs"));
+
+        // Prefixes and typos still match
+        assert_eq!(sphere_context.search("migr", 10).await?[0].slug, "birds");
+        assert_eq!(sphere_context.search("curios", 10).await?[0].slug, "cats");
+
+        // Saving keeps the index up to date once it exists
+        sphere_context.remove("dogs").await?;
+        sphere_context
+            .write(
+                "birds",
+                &ContentType::Subtext.to_string(),
+                "Birds chase cats.".as_bytes(),
+                None,
+            )
+            .await?;
+        sphere_context.save(None).await?;
+
+        let results = sphere_context.search("cats", 10).await?;
+        let slugs: Vec<&str> = results.iter().map(|result| result.slug.as_str()).collect();
+
+        assert_eq!(slugs.len(), 2);
+        assert!(slugs.contains(&"cats"));
+        assert!(slugs.contains(&"birds"));
+        assert!(sphere_context.search("migrate", 10).await?.is_empty());
+        assert!(sphere_context.search("", 10).await?.is_empty());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,108 @@
+use std::collections::BTreeMap;
+
+/// Terms shorter than this (in characters) are not indexed
+const MINIMUM_TERM_LENGTH: usize = 2;
+
+/// Split text into lower-cased terms at every character that is not
+/// alphanumeric (so, for example, Subtext slashlinks like `/my-note` yield the
+/// terms `my` and `note`)
+pub fn tokenize(text: &str) -> Vec<String> {
+    text.split(|character: char| !character.is_alphanumeric())
+        .filter(|term| term.chars().count() >= MINIMUM_TERM_LENGTH)
+        .map(|term| term.to_lowercase())
+        .collect()
+}
+
+/// Count the occurrences of each term in a document. Terms in the slug are
+/// counted along with the terms in the body, so that a document can be found
+/// by its name.
+pub fn term_frequencies(slug: &str, body: &str) -> BTreeMap<String, u32> {
+    let mut frequencies = BTreeMap::new();
+
+    for term in tokenize(slug).into_iter().chain(tokenize(body)) {
+        *frequencies.entry(term).or_insert(0) += 1;
+    }
+
+    frequencies
+}
+
+/// The largest number of edits at which an indexed term is still considered a
+/// match for a query term; short terms must match exactly (or as a prefix)
+pub fn max_edit_distance(query_term: &str) -> usize {
+    match query_term.chars().count() {
+        0..=3 => 0,
+        4..=7 => 1,
+        _ => 2,
+    }
+}
+
+/// The Levenshtein distance between two terms, giving up (and returning a
+/// value greater than `limit`) as soon as it is clear that the distance
+/// exceeds `limit`
+pub fn edit_distance(a: &str, b: &str, limit: usize) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+
+    if a.len().abs_diff(b.len()) > limit {
+        return limit + 1;
+    }
+
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+
+    for (i, a_char) in a.iter().enumerate() {
+        current[0] = i + 1;
+
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != b_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+
+        if current.iter().min().copied().unwrap_or_default() > limit {
+            return limit + 1;
+        }
+
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{edit_distance, term_frequencies, tokenize};
+
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    #[cfg(target_arch = "wasm32")]
+    wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_browser);
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[cfg_attr(not(target_arch = "wasm32"), test)]
+    fn it_tokenizes_subtext_into_lowercase_terms() {
+        assert_eq!(
+            tokenize("# Cats\nSee /dog-facts and https://example.com!"),
+            vec!["cats", "see", "dog", "facts", "and", "https", "example", "com"]
+        );
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[cfg_attr(not(target_arch = "wasm32"), test)]
+    fn it_counts_terms_in_the_slug_and_the_body() {
+        let frequencies = term_frequencies("cat-facts", "Cats are not dogs. Cat!");
+
+        assert_eq!(frequencies.get("cat"), Some(&2));
+        assert_eq!(frequencies.get("facts"), Some(&1));
+        assert_eq!(frequencies.get("dogs"), Some(&1));
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[cfg_attr(not(target_arch = "wasm32"), test)]
+    fn it_measures_bounded_edit_distance() {
+        assert_eq!(edit_distance("kitten", "sitting", 3), 3);
+        assert_eq!(edit_distance("flower", "flowers", 1), 1);
+        assert_eq!(edit_distance("garden", "sphere", 2), 3);
+        assert_eq!(edit_distance("a", "abcdef", 2), 3);
+    }
+}
@@ -1,6 +1,7 @@
 use std::{collections::BTreeMap, marker::PhantomData};
 
 use anyhow::{anyhow, Result};
+use async_trait::async_trait;
 use cid::Cid;
 use noosphere_api::data::{FetchParameters, FetchResponse, PushBody, PushResponse};
 use noosphere_core::{
@@ -21,6 +22,8 @@ use crate::{
     metadata::COUNTERPART, HasMutableSphereContext, SpherePetnameRead, SpherePetnameWrite,
 };
 
+use super::{SyncReport, SyncStrategy};
+
 /// The default synchronization strategy is a git-like fetch->rebase->push flow.
 /// It depends on the corresponding history of a "counterpart" sphere that is
 /// owned by a gateway server. As revisions are pushed to the gateway server, it
@@ -56,7 +59,23 @@ where
     }
 }
 
-impl<C, K, S> GatewaySyncStrategy<C, K, S>
+/// The changes to the counterpart sphere that were fetched from a gateway
+pub(super) struct CounterpartChanges {
+    /// The latest version of the counterpart sphere
+    pub counterpart_sphere_tip: Cid,
+    /// The version of the local sphere that the counterpart sphere referred to
+    /// before the changes were fetched
+    pub local_sphere_old_base: Option<Cid>,
+    /// The version of the local sphere that the counterpart sphere refers to
+    /// after the changes were fetched
+    pub local_sphere_new_base: Option<Cid>,
+    /// Entries in the counterpart sphere's address book that were updated
+    pub updated_names: BTreeMap<String, IdentityIpld>,
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl<C, K, S> SyncStrategy<C, K, S> for GatewaySyncStrategy<C, K, S>
 where
     C: HasMutableSphereContext<K, S>,
     K: KeyMaterial + Clone + 'static,
@@ -64,10 +83,7 @@ where
 {
     /// Synchronize a local sphere's data with the data in a gateway, and rollback
     /// if there is an error.
-    pub async fn sync(&self, context: &mut C) -> Result<()>
-    where
-        C: HasMutableSphereContext<K, S>,
-    {
+    async fn sync(&self, context: &mut C) -> Result<SyncReport> {
         let (local_sphere_version, counterpart_sphere_identity, counterpart_sphere_version) =
             self.handshake(context).await?;
 
@@ -107,10 +123,20 @@ where
             .await?
         }
 
-        result
+        result.map(|_| SyncReport::default())
     }
+}
 
-    async fn handshake(&self, context: &mut C) -> Result<(Option<Cid>, Did, Option<Cid>)> {
+impl<C, K, S> GatewaySyncStrategy<C, K, S>
+where
+    C: HasMutableSphereContext<K, S>,
+    K: KeyMaterial + Clone + 'static,
+    S: Storage + 'static,
+{
+    pub(super) async fn handshake(
+        &self,
+        context: &mut C,
+    ) -> Result<(Option<Cid>, Did, Option<Cid>)> {
         let mut context = context.sphere_context_mut().await?;
         let client = context.client().await?;
         let counterpart_sphere_identity = client.session.sphere_identity.clone();
@@ -139,13 +165,57 @@ where
 
     /// Fetches the latest changes from a gateway and updates the local lineage
     /// using a conflict-free rebase strategy
-    async fn fetch_remote_changes(
+    pub(super) async fn fetch_remote_changes(
         &self,
         context: &mut C,
         local_sphere_tip: Option<&Cid>,
         counterpart_sphere_identity: &Did,
         counterpart_sphere_base: Option<&Cid>,
     ) -> Result<(Cid, Cid, BTreeMap<String, IdentityIpld>)> {
+        let changes = match self
+            .fetch_counterpart_changes(context, counterpart_sphere_base)
+            .await?
+        {
+            Some(changes) => changes,
+            None => {
+                let context = context.sphere_context().await?;
+                let local_sphere_tip = context.db().require_version(context.identity()).await?;
+                return Ok((
+                    local_sphere_tip,
+                    *counterpart_sphere_base
+                        .ok_or_else(|| anyhow!("Counterpart sphere history is missing!"))?,
+                    BTreeMap::new(),
+                ));
+            }
+        };
+
+        let local_sphere_tip = self
+            .rebase_local_changes(context, local_sphere_tip, &changes)
+            .await?;
+
+        self.set_versions(
+            context,
+            &local_sphere_tip,
+            counterpart_sphere_identity,
+            &changes.counterpart_sphere_tip,
+        )
+        .await?;
+
+        Ok((
+            local_sphere_tip,
+            changes.counterpart_sphere_tip,
+            changes.updated_names,
+        ))
+    }
+
+    /// Fetches the latest changes to the counterpart sphere from a gateway and
+    /// hydrates the received history. Returns `None` if the local copy of the
+    /// counterpart sphere is already up to date.
+    pub(super) async fn fetch_counterpart_changes(
+        &self,
+        context: &mut C,
+        counterpart_sphere_base: Option<&Cid>,
+    ) -> Result<Option<CounterpartChanges>> {
         let mut context = context.sphere_context_mut().await?;
         let local_sphere_identity = context.identity().clone();
         let client = context.client().await?;
@@ -160,13 +230,7 @@ where
             FetchResponse::NewChanges { tip, blocks } => (tip, blocks),
             FetchResponse::UpToDate => {
                 info!("Local history is already up to date...");
-                let local_sphere_tip = context.db().require_version(&local_sphere_identity).await?;
-                return Ok((
-                    local_sphere_tip,
-                    *counterpart_sphere_base
-                        .ok_or_else(|| anyhow!("Counterpart sphere history is missing!"))?,
-                    updated_names,
-                ));
+                return Ok(None);
             }
         };
 
@@ -208,56 +272,89 @@ where
             .await?
             .map(|link| link.cid);
 
-        let local_sphere_tip = match (
-            local_sphere_tip,
+        Ok(Some(CounterpartChanges {
+            counterpart_sphere_tip,
             local_sphere_old_base,
             local_sphere_new_base,
-        ) {
-            // History diverged, so rebase our local changes on the newly received branch
-            (Some(current_tip), Some(old_base), Some(new_base)) => {
-                info!("Syncing received local sphere revisions...");
-                Sphere::at(current_tip, context.db())
-                    .sync(
-                        &old_base,
-                        &new_base,
-                        &context.author().key,
-                        context.author().authorization.as_ref(),
-                    )
-                    .await?
-            }
-            // No diverged history, just new linear history based on our local tip
-            (None, old_base, Some(new_base)) => {
-                info!("Hydrating received local sphere revisions...");
-                Sphere::hydrate_range(old_base.as_ref(), &new_base, context.db_mut()).await?;
+            updated_names,
+        }))
+    }
 
-                new_base
-            }
-            // No new history at all
-            (Some(current_tip), _, _) => {
-                info!("Nothing to sync!");
-                *current_tip
-            }
-            // We should have local history but we don't!
-            _ => {
-                return Err(anyhow!("Missing local history for sphere after sync!"));
-            }
-        };
+    /// Replays local changes on top of the lineage of the local sphere that
+    /// was received from the counterpart sphere, returning the new local tip.
+    /// Where the same slug was changed on both sides, the local change wins.
+    pub(super) async fn rebase_local_changes(
+        &self,
+        context: &mut C,
+        local_sphere_tip: Option<&Cid>,
+        changes: &CounterpartChanges,
+    ) -> Result<Cid> {
+        let mut context = context.sphere_context_mut().await?;
+
+        Ok(
+            match (
+                local_sphere_tip,
+                changes.local_sphere_old_base,
+                changes.local_sphere_new_base,
+            ) {
+                // History diverged, so rebase our local changes on the newly received branch
+                (Some(current_tip), Some(old_base), Some(new_base)) => {
+                    info!("Syncing received local sphere revisions...");
+                    Sphere::at(current_tip, context.db())
+                        .sync(
+                            &old_base,
+                            &new_base,
+                            &context.author().key,
+                            context.author().authorization.as_ref(),
+                        )
+                        .await?
+                }
+                // No diverged history, just new linear history based on our local tip
+                (None, old_base, Some(new_base)) => {
+                    info!("Hydrating received local sphere revisions...");
+                    Sphere::hydrate_range(old_base.as_ref(), &new_base, context.db_mut()).await?;
+
+                    new_base
+                }
+                // No new history at all
+                (Some(current_tip), _, _) => {
+                    info!("Nothing to sync!");
+                    *current_tip
+                }
+                // We should have local history but we don't!
+                _ => {
+                    return Err(anyhow!("Missing local history for sphere after sync!"));
+                }
+            },
+        )
+    }
+
+    /// Record the new tips of the local and counterpart spheres
+    pub(super) async fn set_versions(
+        &self,
+        context: &mut C,
+        local_sphere_tip: &Cid,
+        counterpart_sphere_identity: &Did,
+        counterpart_sphere_tip: &Cid,
+    ) -> Result<()> {
+        let mut context = context.sphere_context_mut().await?;
+        let local_sphere_identity = context.identity().clone();
 
         context
             .db_mut()
-            .set_version(&local_sphere_identity, &local_sphere_tip)
+            .set_version(&local_sphere_identity, local_sphere_tip)
             .await?;
 
         debug!("Setting counterpart sphere version to {counterpart_sphere_tip}");
         context
             .db_mut()
-            .set_version(counterpart_sphere_identity, &counterpart_sphere_tip)
+            .set_version(counterpart_sphere_identity, counterpart_sphere_tip)
             .await?;
 
-        Ok((local_sphere_tip, counterpart_sphere_tip, updated_names))
+        Ok(())
     }
 
-    async fn adopt_names(
+    pub(super) async fn adopt_names(
         &self,
         context: &mut C,
         updated_names: BTreeMap<String, IdentityIpld>,
@@ -291,7 +388,7 @@ where
 
     /// Attempts to push the latest local lineage to the gateway, causing the
     /// gateway to update its own pointer to the tip of the local sphere's history
-    async fn push_local_changes(
+    pub(super) async fn push_local_changes(
         &self,
         context: &mut C,
         local_sphere_tip: &Cid,
@@ -333,9 +430,7 @@ where
             .witnessed_by(&authorization)
             .claiming_capability(&Capability {
                 with: With::Resource {
-                    kind: Resource::Scoped(SphereReference {
-                        did: local_sphere_identity.to_string(),
-                    }),
+                    kind: Resource::Scoped(SphereReference::new(local_sphere_identity.to_string())),
                 },
                 can: SphereAction::Publish,
             })
@@ -389,7 +484,7 @@ where
         Ok(())
     }
 
-    async fn rollback(
+    pub(super) async fn rollback(
         &self,
         context: &mut C,
         original_sphere_version: Option<&Cid>,
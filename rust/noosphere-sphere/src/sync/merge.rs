@@ -0,0 +1,332 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use cid::Cid;
+use noosphere_core::{
+    data::{Did, IdentityIpld, MapOperation},
+    view::Sphere,
+};
+use noosphere_storage::{SphereDb, Storage};
+use tokio_stream::StreamExt;
+use ucan::crypto::KeyMaterial;
+
+use crate::{HasMutableSphereContext, SphereContentWrite};
+
+use super::{
+    gateway::GatewaySyncStrategy, ConflictResolution, SyncConflict, SyncReport, SyncStrategy,
+};
+
+/// A hook that is invoked for each [SyncConflict] that is encountered by a
+/// [MergeSyncStrategy]. Returning `None` leaves the conflict unresolved.
+#[cfg(not(target_arch = "wasm32"))]
+pub type ConflictResolver = Box<dyn Fn(&SyncConflict) -> Option<ConflictResolution> + Send + Sync>;
+
+/// A hook that is invoked for each [SyncConflict] that is encountered by a
+/// [MergeSyncStrategy]. Returning `None` leaves the conflict unresolved.
+#[cfg(target_arch = "wasm32")]
+pub type ConflictResolver = Box<dyn Fn(&SyncConflict) -> Option<ConflictResolution>>;
+
+/// A [MergeSyncStrategy] follows the same fetch->rebase->push flow as the
+/// [GatewaySyncStrategy], but it does not allow local changes to silently
+/// overwrite changes in the counterpart's history. When local history has
+/// diverged from the counterpart's, a three-way comparison is made for every
+/// slug that was changed on both sides between the common ancestor, the local
+/// memo and the counterpart memo. Slugs that ended up with different memos are
+/// reported as conflicts, and each conflict is offered to the configured
+/// [ConflictResolver]. If any conflict is left unresolved, the sync is
+/// abandoned and the conflicts are returned in the [SyncReport].
+pub struct MergeSyncStrategy<C, K, S>
+where
+    C: HasMutableSphereContext<K, S>,
+    K: KeyMaterial + Clone + 'static,
+    S: Storage + 'static,
+{
+    gateway: GatewaySyncStrategy<C, K, S>,
+    resolver: Option<ConflictResolver>,
+}
+
+impl<C, K, S> Default for MergeSyncStrategy<C, K, S>
+where
+    C: HasMutableSphereContext<K, S>,
+    K: KeyMaterial + Clone + 'static,
+    S: Storage + 'static,
+{
+    fn default() -> Self {
+        Self {
+            gateway: Default::default(),
+            resolver: None,
+        }
+    }
+}
+
+impl<C, K, S> MergeSyncStrategy<C, K, S>
+where
+    C: HasMutableSphereContext<K, S>,
+    K: KeyMaterial + Clone + 'static,
+    S: Storage + 'static,
+{
+    /// Use the given hook to resolve conflicts as they are encountered
+    pub fn with_resolver(resolver: ConflictResolver) -> Self {
+        Self {
+            gateway: Default::default(),
+            resolver: Some(resolver),
+        }
+    }
+
+    /// Resolve every conflict that is encountered in the same way
+    pub fn resolving_with(resolution: ConflictResolution) -> Self {
+        Self::with_resolver(Box::new(move |_: &SyncConflict| Some(resolution)))
+    }
+
+    /// Fetch the latest changes from the gateway, and merge them with local
+    /// changes if the histories have diverged
+    async fn fetch_and_merge(
+        &self,
+        context: &mut C,
+        local_sphere_tip: Option<&Cid>,
+        counterpart_sphere_identity: &Did,
+        counterpart_sphere_base: Option<&Cid>,
+    ) -> Result<(Cid, Cid, BTreeMap<String, IdentityIpld>, SyncReport)> {
+        let changes = match self
+            .gateway
+            .fetch_counterpart_changes(context, counterpart_sphere_base)
+            .await?
+        {
+            Some(changes) => changes,
+            None => {
+                let context = context.sphere_context().await?;
+                let local_sphere_tip = context.db().require_version(context.identity()).await?;
+                return Ok((
+                    local_sphere_tip,
+                    *counterpart_sphere_base
+                        .ok_or_else(|| anyhow!("Counterpart sphere history is missing!"))?,
+                    BTreeMap::new(),
+                    SyncReport::default(),
+                ));
+            }
+        };
+
+        let report = match (
+            local_sphere_tip,
+            changes.local_sphere_old_base,
+            changes.local_sphere_new_base,
+        ) {
+            (Some(current_tip), Some(old_base), Some(new_base)) if old_base != new_base => {
+                let db = context.sphere_context().await?.db().clone();
+                self.find_conflicts(&db, current_tip, &old_base, &new_base)
+                    .await?
+            }
+            _ => SyncReport::default(),
+        };
+
+        if !report.is_complete() {
+            return Ok((
+                *local_sphere_tip.unwrap_or(&changes.counterpart_sphere_tip),
+                changes.counterpart_sphere_tip,
+                changes.updated_names,
+                report,
+            ));
+        }
+
+        let mut local_sphere_tip = self
+            .gateway
+            .rebase_local_changes(context, local_sphere_tip, &changes)
+            .await?;
+
+        self.gateway
+            .set_versions(
+                context,
+                &local_sphere_tip,
+                counterpart_sphere_identity,
+                &changes.counterpart_sphere_tip,
+            )
+            .await?;
+
+        if let Some(version) = self.apply_resolutions(context, &report).await? {
+            local_sphere_tip = version;
+        }
+
+        Ok((
+            local_sphere_tip,
+            changes.counterpart_sphere_tip,
+            changes.updated_names,
+            report,
+        ))
+    }
+
+    /// Compare the slugs that were changed both locally and in the
+    /// counterpart's history since the common ancestor, and offer any
+    /// conflicts to the configured [ConflictResolver]
+    async fn find_conflicts(
+        &self,
+        db: &SphereDb<S>,
+        local_sphere_tip: &Cid,
+        common_ancestor: &Cid,
+        counterpart_tip: &Cid,
+    ) -> Result<SyncReport> {
+        Sphere::hydrate_range(Some(common_ancestor), counterpart_tip, db).await?;
+
+        let local_slugs = changed_slugs(db, local_sphere_tip, common_ancestor).await?;
+        let counterpart_slugs = changed_slugs(db, counterpart_tip, common_ancestor).await?;
+
+        let ancestor_content = Sphere::at(common_ancestor, db).get_content().await?;
+        let local_content = Sphere::at(local_sphere_tip, db).get_content().await?;
+        let counterpart_content = Sphere::at(counterpart_tip, db).get_content().await?;
+
+        let mut report = SyncReport::default();
+
+        for slug in local_slugs.intersection(&counterpart_slugs) {
+            let local = local_content.get(slug).await?.map(|link| link.cid);
+            let counterpart = counterpart_content.get(slug).await?.map(|link| link.cid);
+
+            if local == counterpart {
+                continue;
+            }
+
+            let conflict = SyncConflict {
+                slug: slug.clone(),
+                ancestor: ancestor_content.get(slug).await?.map(|link| link.cid),
+                local,
+                counterpart,
+            };
+
+            match self
+                .resolver
+                .as_ref()
+                .and_then(|resolver| resolver(&conflict))
+            {
+                Some(resolution) => report.resolved.push((conflict, resolution)),
+                None => report.unresolved.push(conflict),
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Apply resolved conflicts on top of the rebased local history. Since
+    /// rebasing replays local changes, the local version of each conflicting
+    /// slug is already in place. Returns the new local tip if any changes were
+    /// saved.
+    async fn apply_resolutions(&self, context: &mut C, report: &SyncReport) -> Result<Option<Cid>> {
+        for (conflict, resolution) in report.resolved.iter() {
+            match resolution {
+                ConflictResolution::KeepLocal => continue,
+                ConflictResolution::KeepCounterpart => (),
+                ConflictResolution::KeepBoth => {
+                    if let (Some(conflict_slug), Some(local)) =
+                        (conflict.conflict_slug(), conflict.local.as_ref())
+                    {
+                        context.link_raw(&conflict_slug, local).await?;
+                    }
+                }
+            };
+
+            match &conflict.counterpart {
+                Some(counterpart) => context.link_raw(&conflict.slug, counterpart).await?,
+                None => {
+                    context.remove(&conflict.slug).await?;
+                }
+            };
+        }
+
+        Ok(if context.has_unsaved_changes().await? {
+            Some(context.save(None).await?)
+        } else {
+            None
+        })
+    }
+}
+
+/// Collect the slugs that were changed in the history of a sphere between the
+/// given version and an ancestor version (exclusive)
+async fn changed_slugs<S: Storage>(
+    db: &SphereDb<S>,
+    version: &Cid,
+    since: &Cid,
+) -> Result<BTreeSet<String>> {
+    let stream = Sphere::at(version, db).into_content_changelog_stream(Some(since));
+
+    tokio::pin!(stream);
+
+    let mut slugs = BTreeSet::new();
+
+    while let Some((_, changelog)) = stream.try_next().await? {
+        for operation in changelog.changes {
+            match operation {
+                MapOperation::Add { key, .. } | MapOperation::Remove { key } => {
+                    slugs.insert(key);
+                }
+            }
+        }
+    }
+
+    Ok(slugs)
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl<C, K, S> SyncStrategy<C, K, S> for MergeSyncStrategy<C, K, S>
+where
+    C: HasMutableSphereContext<K, S>,
+    K: KeyMaterial + Clone + 'static,
+    S: Storage + 'static,
+{
+    async fn sync(&self, context: &mut C) -> Result<SyncReport> {
+        let (local_sphere_version, counterpart_sphere_identity, counterpart_sphere_version) =
+            self.gateway.handshake(context).await?;
+
+        let result: Result<SyncReport> = async {
+            let (mut local_sphere_tip, counterpart_sphere_tip, updated_names, report) = self
+                .fetch_and_merge(
+                    context,
+                    local_sphere_version.as_ref(),
+                    &counterpart_sphere_identity,
+                    counterpart_sphere_version.as_ref(),
+                )
+                .await?;
+
+            if !report.is_complete() {
+                warn!(
+                    "Sync abandoned because of {} unresolved conflict(s)",
+                    report.unresolved.len()
+                );
+                return Ok(report);
+            }
+
+            if let Some(version) = self.gateway.adopt_names(context, updated_names).await? {
+                local_sphere_tip = version;
+            }
+
+            self.gateway
+                .push_local_changes(
+                    context,
+                    &local_sphere_tip,
+                    &counterpart_sphere_identity,
+                    &counterpart_sphere_tip,
+                )
+                .await?;
+
+            Ok(report)
+        }
+        .await;
+
+        // Rollback if there is an error or if there are unresolved conflicts,
+        // so that the local history is left as it was before the sync
+        match &result {
+            Ok(report) if report.is_complete() => (),
+            _ => {
+                self.gateway
+                    .rollback(
+                        context,
+                        local_sphere_version.as_ref(),
+                        &counterpart_sphere_identity,
+                        counterpart_sphere_version.as_ref(),
+                    )
+                    .await?
+            }
+        };
+
+        result
+    }
+}
@@ -1,13 +1,90 @@
 mod gateway;
+mod merge;
 
 use anyhow::Result;
 use async_trait::async_trait;
+use cid::Cid;
 use noosphere_storage::Storage;
 use ucan::crypto::KeyMaterial;
 
-use crate::HasMutableSphereContext;
+use crate::{HasConditionalSendSync, HasMutableSphereContext};
 
-use self::gateway::GatewaySyncStrategy;
+pub use self::gateway::GatewaySyncStrategy;
+pub use self::merge::*;
+
+/// A [SyncStrategy] embodies a way of reconciling the local history of a
+/// sphere with the history that is tracked by some counterpart (typically, a
+/// gateway), and then sharing the reconciled history with that counterpart.
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+pub trait SyncStrategy<C, K, S>: HasConditionalSendSync
+where
+    C: HasMutableSphereContext<K, S>,
+    K: KeyMaterial + Clone + 'static,
+    S: Storage + 'static,
+{
+    /// Synchronize the local sphere with its counterpart. The returned
+    /// [SyncReport] describes any conflicts that were encountered along the
+    /// way, and how they were resolved.
+    async fn sync(&self, context: &mut C) -> Result<SyncReport>;
+}
+
+/// A slug that was changed both locally and in the counterpart's history
+/// since the two last agreed, such that the changes cannot both be kept at
+/// that slug.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyncConflict {
+    /// The slug that was changed on both sides
+    pub slug: String,
+    /// The memo that the slug referred to in the common ancestor revision, if
+    /// any
+    pub ancestor: Option<Cid>,
+    /// The memo that the slug refers to in the local history, or `None` if it
+    /// was removed locally
+    pub local: Option<Cid>,
+    /// The memo that the slug refers to in the counterpart's history, or
+    /// `None` if it was removed there
+    pub counterpart: Option<Cid>,
+}
+
+impl SyncConflict {
+    /// The slug that the local version of the content is kept under when a
+    /// conflict is resolved with [ConflictResolution::KeepBoth]
+    pub fn conflict_slug(&self) -> Option<String> {
+        self.local
+            .as_ref()
+            .map(|local| format!("{}.conflict-{}", self.slug, local))
+    }
+}
+
+/// The ways in which a [SyncConflict] may be resolved
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictResolution {
+    /// Keep the local version of the slug, discarding the counterpart version
+    KeepLocal,
+    /// Keep the counterpart version of the slug, discarding the local version
+    KeepCounterpart,
+    /// Keep the counterpart version at the slug, and keep the local version
+    /// at a separate slug (see [SyncConflict::conflict_slug])
+    KeepBoth,
+}
+
+/// A structured report of the conflicts that were encountered while syncing
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SyncReport {
+    /// Conflicts that were resolved, and how they were resolved
+    pub resolved: Vec<(SyncConflict, ConflictResolution)>,
+    /// Conflicts that could not be resolved; if there are any, the sync was
+    /// abandoned and no local or counterpart history was changed
+    pub unresolved: Vec<SyncConflict>,
+}
+
+impl SyncReport {
+    /// Returns true if there were no conflicts left unresolved
+    pub fn is_complete(&self) -> bool {
+        self.unresolved.is_empty()
+    }
+}
 
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
@@ -21,7 +98,18 @@ where
     /// fetched to local storage. Then, the local changes will be replayed on
     /// top of those changes. Finally, the synchronized local history will be
     /// pushed up to the gateway.
+    ///
+    /// The authorization of the local key is checked before and after syncing
+    /// (see [crate::SphereContext::check_authorization_expiry]), so a renewed
+    /// authorization is adopted as soon as it has been synced.
     async fn sync(&mut self) -> Result<()>;
+
+    /// Same as [SphereSync::sync], but uses the given [SyncStrategy] to
+    /// reconcile local and remote changes.
+    async fn sync_with<St>(&mut self, strategy: &St) -> Result<SyncReport>
+    where
+        Self: HasMutableSphereContext<K, S> + Sized,
+        St: SyncStrategy<Self, K, S>;
 }
 
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
@@ -33,9 +121,28 @@ where
     S: Storage + 'static,
 {
     async fn sync(&mut self) -> Result<()> {
-        let sync_strategy = GatewaySyncStrategy::default();
-        sync_strategy.sync(self).await?;
-        self.sphere_context_mut().await?.reset_access();
+        self.sync_with(&GatewaySyncStrategy::default()).await?;
         Ok(())
     }
+
+    async fn sync_with<St>(&mut self, strategy: &St) -> Result<SyncReport>
+    where
+        Self: HasMutableSphereContext<K, S> + Sized,
+        St: SyncStrategy<Self, K, S>,
+    {
+        self.sphere_context_mut()
+            .await?
+            .check_authorization_expiry()
+            .await?;
+
+        let report = strategy.sync(self).await?;
+
+        let mut sphere_context = self.sphere_context_mut().await?;
+        sphere_context.reset_access();
+
+        // The sync may have brought in a renewal of the local authorization
+        sphere_context.check_authorization_expiry().await?;
+
+        Ok(report)
+    }
 }
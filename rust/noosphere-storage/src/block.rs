@@ -71,6 +71,23 @@ pub trait BlockStore: Clone + BlockStoreSendSync {
     /// Given the [Cid] of a block, retrieve the block bytes from storage.
     async fn get_block(&self, cid: &Cid) -> Result<Option<Vec<u8>>>;
 
+    /// Given a set of blocks and their [Cid]s, persist all of them in storage
+    /// and flush. Implementations should store the blocks atomically (so that
+    /// either all of them are stored or none of them are) wherever they are
+    /// able to; any [Store] does so by committing them as a single
+    /// [StoreBatch]. The default implementation stores the blocks one at a
+    /// time.
+    ///
+    /// [Store]: crate::Store
+    /// [StoreBatch]: crate::StoreBatch
+    async fn put_blocks(&mut self, blocks: &[(Cid, Vec<u8>)]) -> Result<()> {
+        for (cid, block) in blocks {
+            self.put_block(cid, block).await?;
+        }
+
+        self.flush().await
+    }
+
     /// Given some data structure that implements [Encode] for a given [Codec],
     /// encode it as a block and persist it to storage for later retrieval by
     /// [Cid].
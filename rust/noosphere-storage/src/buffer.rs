@@ -0,0 +1,133 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use cid::Cid;
+use libipld_cbor::DagCborCodec;
+use libipld_core::raw::RawCodec;
+
+use crate::{BlockStore, MemoryStore};
+
+#[cfg(doc)]
+use crate::SphereDb;
+
+/// Wraps any [BlockStore] so that blocks written to it are held in memory
+/// until [BufferedBlockStore::commit] is invoked, at which point they are all
+/// written to the wrapped store together (see [BlockStore::put_blocks]).
+/// Blocks that have been written but not yet committed can still be read
+/// through the wrapper (and through any of its clones, which share the same
+/// buffer).
+///
+/// This makes it possible to produce a DAG one block at a time, and only write
+/// it to storage once it is complete. The links of the blocks are recorded in
+/// the wrapped store (see [BlockStore::put_links]) when they are committed, so
+/// wrapping a [SphereDb] makes them available to link queries as usual.
+#[derive(Clone)]
+pub struct BufferedBlockStore<S>
+where
+    S: BlockStore,
+{
+    store: S,
+    buffer: MemoryStore,
+}
+
+impl<S> BufferedBlockStore<S>
+where
+    S: BlockStore,
+{
+    pub fn new(store: S) -> Self {
+        BufferedBlockStore {
+            store,
+            buffer: MemoryStore::default(),
+        }
+    }
+
+    /// Write all of the buffered blocks to the wrapped store together, and
+    /// empty the buffer; returns the number of blocks that were written
+    pub async fn commit(&mut self) -> Result<usize> {
+        let mut entries = self.buffer.entries.lock().await;
+        let mut blocks = Vec::with_capacity(entries.len());
+
+        for (key, block) in entries.iter() {
+            blocks.push((Cid::try_from(key.as_slice())?, block.clone()));
+        }
+
+        self.store.put_blocks(&blocks).await?;
+
+        for (cid, block) in blocks.iter() {
+            match cid.codec() {
+                codec_id if codec_id == u64::from(DagCborCodec) => {
+                    self.store.put_links::<DagCborCodec>(cid, block).await?;
+                }
+                codec_id if codec_id == u64::from(RawCodec) => {
+                    self.store.put_links::<RawCodec>(cid, block).await?;
+                }
+                codec_id => warn!("Unrecognized codec {}; skipping...", codec_id),
+            }
+        }
+
+        entries.clear();
+
+        Ok(blocks.len())
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl<S> BlockStore for BufferedBlockStore<S>
+where
+    S: BlockStore,
+{
+    async fn put_block(&mut self, cid: &Cid, block: &[u8]) -> Result<()> {
+        self.buffer.put_block(cid, block).await
+    }
+
+    async fn get_block(&self, cid: &Cid) -> Result<Option<Vec<u8>>> {
+        match self.buffer.get_block(cid).await? {
+            Some(block) => Ok(Some(block)),
+            None => self.store.get_block(cid).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use libipld_cbor::DagCborCodec;
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use crate::{BlockStore, BufferedBlockStore, MemoryStore};
+
+    #[cfg(target_arch = "wasm32")]
+    wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_browser);
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[cfg_attr(not(target_arch = "wasm32"), tokio::test)]
+    async fn it_only_writes_blocks_to_the_wrapped_store_when_committed() {
+        let store = MemoryStore::default();
+        let mut buffered_store = BufferedBlockStore::new(store.clone());
+
+        let cats = buffered_store
+            .save::<DagCborCodec, _>("Cats are great")
+            .await
+            .unwrap();
+        let list = buffered_store
+            .save::<DagCborCodec, _>(vec![cats])
+            .await
+            .unwrap();
+
+        assert!(buffered_store
+            .clone()
+            .get_block(&cats)
+            .await
+            .unwrap()
+            .is_some());
+        assert!(store.get_block(&cats).await.unwrap().is_none());
+
+        assert_eq!(buffered_store.commit().await.unwrap(), 2);
+
+        for cid in [cats, list] {
+            assert!(store.get_block(&cid).await.unwrap().is_some());
+        }
+
+        assert_eq!(buffered_store.commit().await.unwrap(), 0);
+    }
+}
@@ -0,0 +1,381 @@
+use anyhow::Result;
+use async_std::sync::Mutex;
+use async_trait::async_trait;
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+};
+
+use crate::{
+    store::{Store, StoreBatch, StoreBatchOperation},
+    Storage,
+};
+
+#[cfg(doc)]
+use crate::BlockStore;
+
+/// Statistics about the effectiveness of a [CachingBlockStore]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// The number of blocks that were read from the cache
+    pub hits: usize,
+    /// The number of blocks that were not in the cache, and had to be read
+    /// from the wrapped [Store]
+    pub misses: usize,
+    /// The number of blocks that were removed from the cache to make room for
+    /// others
+    pub evictions: usize,
+    /// The number of blocks currently in the cache
+    pub blocks_cached: usize,
+    /// The total size of the blocks currently in the cache
+    pub bytes_cached: usize,
+}
+
+/// The least-recently-used cache that backs a [CachingBlockStore]
+#[derive(Debug, Default)]
+struct BlockCache {
+    capacity: usize,
+    /// Incremented on every access, so that a lower tick means that a block
+    /// was used less recently
+    tick: u64,
+    blocks: HashMap<Vec<u8>, (Vec<u8>, u64)>,
+    recency: BTreeMap<u64, Vec<u8>>,
+    stats: CacheStats,
+}
+
+impl BlockCache {
+    fn new(capacity: usize) -> Self {
+        BlockCache {
+            capacity,
+            ..Default::default()
+        }
+    }
+
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+
+    fn get(&mut self, key: &[u8]) -> Option<Vec<u8>> {
+        let tick = self.next_tick();
+
+        match self.blocks.get_mut(key) {
+            Some((block, last_used)) => {
+                self.recency.remove(last_used);
+                self.recency.insert(tick, key.to_vec());
+                *last_used = tick;
+                self.stats.hits += 1;
+
+                Some(block.clone())
+            }
+            None => {
+                self.stats.misses += 1;
+                None
+            }
+        }
+    }
+
+    fn insert(&mut self, key: &[u8], block: &[u8]) {
+        // Blocks are content-addressed, so a block that is already cached
+        // never needs to be replaced; blocks that would not fit are skipped
+        if block.len() > self.capacity || self.blocks.contains_key(key) {
+            return;
+        }
+
+        while self.stats.bytes_cached + block.len() > self.capacity {
+            let oldest_tick = match self.recency.keys().next() {
+                Some(tick) => *tick,
+                None => break,
+            };
+            let evicted_key = match self.recency.remove(&oldest_tick) {
+                Some(key) => key,
+                None => break,
+            };
+
+            if let Some((evicted_block, _)) = self.blocks.remove(&evicted_key) {
+                self.stats.bytes_cached -= evicted_block.len();
+                self.stats.blocks_cached -= 1;
+                self.stats.evictions += 1;
+            }
+        }
+
+        let tick = self.next_tick();
+
+        self.blocks.insert(key.to_vec(), (block.to_vec(), tick));
+        self.recency.insert(tick, key.to_vec());
+        self.stats.bytes_cached += block.len();
+        self.stats.blocks_cached += 1;
+    }
+
+    /// Drop a block from the cache because it is no longer stored (or is no
+    /// longer stored with the same bytes)
+    fn remove(&mut self, key: &[u8]) {
+        if let Some((block, last_used)) = self.blocks.remove(key) {
+            self.recency.remove(&last_used);
+            self.stats.bytes_cached -= block.len();
+            self.stats.blocks_cached -= 1;
+        }
+    }
+}
+
+/// Wraps the [Store] that backs a [BlockStore] and keeps the most recently
+/// used blocks in memory, up to a total size of `capacity` bytes, so that
+/// repeatedly loading the same blocks (for example, when traversing a
+/// sphere's HAMTs) does not require a round trip to the wrapped [Store] each
+/// time. Blocks are cached as they are written as well as when they are read,
+/// and are dropped from the cache when they are removed (for example, by
+/// garbage collection). Clones of a [CachingBlockStore] share the same cache.
+///
+/// Note that blocks removed from the wrapped store by some other means (such
+/// as by another process) may continue to be read from the cache.
+#[derive(Clone)]
+pub struct CachingBlockStore<S>
+where
+    S: Store,
+{
+    store: S,
+    cache: Arc<Mutex<BlockCache>>,
+}
+
+impl<S> CachingBlockStore<S>
+where
+    S: Store,
+{
+    pub fn new(store: S, capacity: usize) -> Self {
+        CachingBlockStore {
+            store,
+            cache: Arc::new(Mutex::new(BlockCache::new(capacity))),
+        }
+    }
+
+    pub async fn to_stats(&self) -> CacheStats {
+        self.cache.lock().await.stats.clone()
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl<S> Store for CachingBlockStore<S>
+where
+    S: Store,
+{
+    async fn read(&self, key: &[u8]) -> Result<Option<Vec<u8>>> {
+        if let Some(block) = self.cache.lock().await.get(key) {
+            return Ok(Some(block));
+        }
+
+        let maybe_block = self.store.read(key).await?;
+
+        if let Some(block) = &maybe_block {
+            self.cache.lock().await.insert(key, block);
+        }
+
+        Ok(maybe_block)
+    }
+
+    async fn write(&mut self, key: &[u8], bytes: &[u8]) -> Result<Option<Vec<u8>>> {
+        let previous = self.store.write(key, bytes).await?;
+        let mut cache = self.cache.lock().await;
+
+        cache.remove(key);
+        cache.insert(key, bytes);
+
+        Ok(previous)
+    }
+
+    async fn remove(&mut self, key: &[u8]) -> Result<Option<Vec<u8>>> {
+        let removed = self.store.remove(key).await?;
+
+        self.cache.lock().await.remove(key);
+
+        Ok(removed)
+    }
+
+    async fn keys(&self) -> Result<Vec<Vec<u8>>> {
+        self.store.keys().await
+    }
+
+    async fn flush(&self) -> Result<()> {
+        self.store.flush().await
+    }
+
+    async fn commit_batch(&mut self, batch: StoreBatch) -> Result<()> {
+        let operations = batch.clone().into_operations();
+
+        self.store.commit_batch(batch).await?;
+
+        let mut cache = self.cache.lock().await;
+
+        for operation in operations {
+            match operation {
+                StoreBatchOperation::Write { key, bytes } => {
+                    cache.remove(&key);
+                    cache.insert(&key, &bytes);
+                }
+                StoreBatchOperation::Remove { key } => cache.remove(&key),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// An implementation of [Storage] that wraps another implementation of
+/// [Storage], and produces a [CachingBlockStore] wrapped [BlockStore] with
+/// the given capacity (in bytes). A capacity of zero disables caching. To
+/// avoid repeated round trips to a remote block source such as an IPFS
+/// gateway, wrap the local [Storage] that blocks are kept in once they are
+/// fetched.
+#[derive(Clone, Debug)]
+pub struct CachingStorage<S>
+where
+    S: Storage,
+{
+    storage: S,
+    capacity: usize,
+}
+
+impl<S> CachingStorage<S>
+where
+    S: Storage,
+{
+    pub fn new(storage: S, capacity: usize) -> Self {
+        CachingStorage { storage, capacity }
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl<S> Storage for CachingStorage<S>
+where
+    S: Storage,
+    S::BlockStore: Store,
+{
+    type BlockStore = CachingBlockStore<S::BlockStore>;
+
+    type KeyValueStore = S::KeyValueStore;
+
+    async fn get_block_store(&self, name: &str) -> Result<Self::BlockStore> {
+        Ok(CachingBlockStore::new(
+            self.storage.get_block_store(name).await?,
+            self.capacity,
+        ))
+    }
+
+    async fn get_key_value_store(&self, name: &str) -> Result<Self::KeyValueStore> {
+        self.storage.get_key_value_store(name).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use libipld_cbor::DagCborCodec;
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use crate::{
+        block_serialize, db::tests, BlockStore, CacheStats, CachingBlockStore, CachingStorage,
+        MemoryStorage, MemoryStore, Store, TrackingStore,
+    };
+
+    #[cfg(target_arch = "wasm32")]
+    wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_browser);
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[cfg_attr(not(target_arch = "wasm32"), tokio::test)]
+    async fn it_serves_repeated_reads_from_the_cache() {
+        let tracking_store = TrackingStore::wrap(MemoryStore::default());
+        let mut store = CachingBlockStore::new(tracking_store.clone(), 1024);
+
+        let cid = store
+            .save::<DagCborCodec, _>(vec![1u8, 2, 3])
+            .await
+            .unwrap();
+
+        for _ in 0..3 {
+            store.load::<DagCborCodec, Vec<u8>>(&cid).await.unwrap();
+        }
+
+        assert_eq!(tracking_store.to_stats().await.reads, 0);
+        assert_eq!(store.to_stats().await.hits, 3);
+
+        // Blocks that are only in the wrapped store are cached when read
+        let (other_cid, other_block) = block_serialize::<DagCborCodec, _>(vec![4u8, 5, 6]).unwrap();
+        tracking_store
+            .clone()
+            .write(&other_cid.to_bytes(), &other_block)
+            .await
+            .unwrap();
+
+        for _ in 0..3 {
+            store
+                .load::<DagCborCodec, Vec<u8>>(&other_cid)
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(tracking_store.to_stats().await.reads, 1);
+        assert_eq!(store.to_stats().await.misses, 1);
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[cfg_attr(not(target_arch = "wasm32"), tokio::test)]
+    async fn it_evicts_the_least_recently_used_blocks_when_full() {
+        let mut store = CachingBlockStore::new(MemoryStore::default(), 20);
+
+        let first = store.save::<DagCborCodec, _>(vec![0u8; 8]).await.unwrap();
+        let second = store.save::<DagCborCodec, _>(vec![1u8; 8]).await.unwrap();
+
+        // Reading the first block makes the second the least recently used
+        store.get_block(&first).await.unwrap();
+
+        let third = store.save::<DagCborCodec, _>(vec![2u8; 8]).await.unwrap();
+
+        let stats = store.to_stats().await;
+
+        assert_eq!(stats.evictions, 1);
+        assert_eq!(stats.blocks_cached, 2);
+        assert!(stats.bytes_cached <= 20);
+
+        store.get_block(&first).await.unwrap();
+        store.get_block(&third).await.unwrap();
+        store.get_block(&second).await.unwrap();
+
+        assert_eq!(
+            store.to_stats().await,
+            CacheStats {
+                hits: 3,
+                misses: 1,
+                evictions: 2,
+                blocks_cached: 2,
+                bytes_cached: stats.bytes_cached,
+            }
+        );
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[cfg_attr(not(target_arch = "wasm32"), tokio::test)]
+    async fn it_drops_removed_blocks_from_the_cache() {
+        let mut store = CachingBlockStore::new(MemoryStore::default(), 1024);
+
+        let cid = store
+            .save::<DagCborCodec, _>(vec![1u8, 2, 3])
+            .await
+            .unwrap();
+
+        assert!(store.get_block(&cid).await.unwrap().is_some());
+
+        store.remove(&cid.to_bytes()).await.unwrap();
+
+        assert!(store.get_block(&cid).await.unwrap().is_none());
+        assert_eq!(store.to_stats().await.blocks_cached, 0);
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[cfg_attr(not(target_arch = "wasm32"), tokio::test)]
+    async fn it_does_not_serve_blocks_that_were_collected_as_garbage() {
+        let storage = CachingStorage::new(MemoryStorage::default(), 1024);
+
+        tests::collects_blocks_that_are_not_reachable_from_a_version(&storage).await;
+    }
+}
@@ -9,11 +9,15 @@ use libipld_core::{
 };
 use serde::{de::DeserializeOwned, Serialize};
 use std::future::Future;
+use std::sync::{Arc, Mutex};
 use std::{collections::BTreeSet, fmt::Debug};
-use tokio_stream::Stream;
+use tokio_stream::{Stream, StreamExt};
 use ucan::store::{UcanStore, UcanStoreConditionalSend};
 
-use crate::{BlockStore, BlockStoreSend, KeyValueStore, MemoryStore, Storage};
+use crate::{
+    block_references, verify_cid, BlockStore, BlockStoreSend, KeyValueStore, MemoryStore, Storage,
+    Store,
+};
 
 use async_stream::try_stream;
 
@@ -33,9 +37,17 @@ pub const BLOCK_STORE: &str = "blocks";
 pub const LINK_STORE: &str = "links";
 pub const VERSION_STORE: &str = "versions";
 pub const METADATA_STORE: &str = "metadata";
-
-pub const SPHERE_DB_STORE_NAMES: &[&str] =
-    &[BLOCK_STORE, LINK_STORE, VERSION_STORE, METADATA_STORE];
+pub const SEARCH_STORE: &str = "search";
+pub const BACKLINKS_STORE: &str = "backlinks";
+
+pub const SPHERE_DB_STORE_NAMES: &[&str] = &[
+    BLOCK_STORE,
+    LINK_STORE,
+    VERSION_STORE,
+    METADATA_STORE,
+    SEARCH_STORE,
+    BACKLINKS_STORE,
+];
 
 /// A [SphereDb] is a high-level storage primitive for Noosphere's APIs. It
 /// takes a [Storage] and implements [BlockStore] and [KeyValueStore],
@@ -51,6 +63,8 @@ where
     link_store: S::KeyValueStore,
     version_store: S::KeyValueStore,
     metadata_store: S::KeyValueStore,
+    search_store: S::KeyValueStore,
+    backlinks_store: S::KeyValueStore,
 }
 
 impl<S> SphereDb<S>
@@ -63,29 +77,45 @@ where
             link_store: storage.get_key_value_store(LINK_STORE).await?,
             version_store: storage.get_key_value_store(VERSION_STORE).await?,
             metadata_store: storage.get_key_value_store(METADATA_STORE).await?,
+            search_store: storage.get_key_value_store(SEARCH_STORE).await?,
+            backlinks_store: storage.get_key_value_store(BACKLINKS_STORE).await?,
         })
     }
 
     /// Given a [MemoryStore], store copies of all the blocks found within in
-    /// the storage that backs this [SphereDb].
+    /// the storage that backs this [SphereDb]. The blocks are stored together
+    /// (see [BlockStore::put_blocks]), and then their links are recorded.
     pub async fn persist(&mut self, memory_store: &MemoryStore) -> Result<()> {
         let cids = memory_store.get_stored_cids().await;
+        let mut blocks = Vec::with_capacity(cids.len());
 
-        for cid in &cids {
-            let block = memory_store.require_block(cid).await?;
+        for cid in cids {
+            let block = memory_store.require_block(&cid).await?;
+            blocks.push((cid, block));
+        }
 
-            self.put_block(cid, &block).await?;
+        self.put_blocks(&blocks).await?;
 
-            match cid.codec() {
-                codec_id if codec_id == u64::from(DagCborCodec) => {
-                    self.put_links::<DagCborCodec>(cid, &block).await?;
-                }
-                codec_id if codec_id == u64::from(RawCodec) => {
-                    self.put_links::<RawCodec>(cid, &block).await?;
-                }
-                codec_id => warn!("Unrecognized codec {}; skipping...", codec_id),
+        for (cid, block) in blocks.iter() {
+            self.put_links_for_codec(cid, block).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Store the links of a block, using the codec that is indicated by its
+    /// [Cid] to find them
+    async fn put_links_for_codec(&mut self, cid: &Cid, block: &[u8]) -> Result<()> {
+        match cid.codec() {
+            codec_id if codec_id == u64::from(DagCborCodec) => {
+                self.put_links::<DagCborCodec>(cid, block).await?;
+            }
+            codec_id if codec_id == u64::from(RawCodec) => {
+                self.put_links::<RawCodec>(cid, block).await?;
             }
+            codec_id => warn!("Unrecognized codec {}; skipping...", codec_id),
         }
+
         Ok(())
     }
 
@@ -103,11 +133,20 @@ where
 
     /// Manually flush all pending writes to the underlying [Storage]
     pub async fn flush(&self) -> Result<()> {
-        let (block_store_result, link_store_result, version_store_result, metadata_store_result) = tokio::join!(
+        let (
+            block_store_result,
+            link_store_result,
+            version_store_result,
+            metadata_store_result,
+            search_store_result,
+            backlinks_store_result,
+        ) = tokio::join!(
             self.block_store.flush(),
             self.link_store.flush(),
             self.version_store.flush(),
-            self.metadata_store.flush()
+            self.metadata_store.flush(),
+            self.search_store.flush(),
+            self.backlinks_store.flush()
         );
 
         let results = vec![
@@ -115,6 +154,8 @@ where
             ("link", link_store_result),
             ("version", version_store_result),
             ("metadata", metadata_store_result),
+            ("search", search_store_result),
+            ("backlinks", backlinks_store_result),
         ];
 
         for (store_kind, result) in results {
@@ -208,6 +249,333 @@ where
     pub fn to_block_store(&self) -> S::BlockStore {
         self.block_store.clone()
     }
+
+    /// Get an owned copy of the [KeyValueStore] that is set aside for search
+    /// indexes over the content of local spheres
+    pub fn to_search_store(&self) -> S::KeyValueStore {
+        self.search_store.clone()
+    }
+
+    /// Get an owned copy of the [KeyValueStore] that is set aside for the
+    /// backlink index of each version of a sphere
+    pub fn to_backlinks_store(&self) -> S::KeyValueStore {
+        self.backlinks_store.clone()
+    }
+}
+
+/// A summary of the work done by [SphereDb::collect_garbage]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct GarbageCollectionReport {
+    /// The number of blocks that are still referenced and were kept
+    pub blocks_kept: usize,
+    /// The number of unreferenced blocks that were removed
+    pub blocks_removed: usize,
+    /// The number of link records that were removed
+    pub links_removed: usize,
+    /// The total number of bytes freed by removing blocks and link records
+    pub bytes_reclaimed: usize,
+}
+
+/// A summary of the problems found by [SphereDb::verify_integrity]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct IntegrityReport {
+    /// The number of reachable blocks whose bytes match their [Cid]
+    pub blocks_verified: usize,
+    /// Blocks that are referenced but are not in the block store
+    pub missing_blocks: BTreeSet<Cid>,
+    /// Blocks whose bytes do not hash to their [Cid]
+    pub corrupt_blocks: BTreeSet<Cid>,
+    /// Blocks whose [Cid] uses a hash function that is not supported, so
+    /// their bytes could not be checked
+    pub unverifiable_blocks: BTreeSet<Cid>,
+    /// Blocks whose recorded links differ from the links found in the block
+    pub mismatched_links: BTreeSet<Cid>,
+    /// Blocks that are stored but are not referenced by anything
+    pub orphaned_blocks: BTreeSet<Cid>,
+}
+
+impl IntegrityReport {
+    /// True if no missing or corrupt blocks, and no mismatched links, were
+    /// found; orphaned blocks are harmless (they can be removed by
+    /// [SphereDb::collect_garbage]), and unverifiable blocks are not known to
+    /// be wrong
+    pub fn is_ok(&self) -> bool {
+        self.missing_blocks.is_empty()
+            && self.corrupt_blocks.is_empty()
+            && self.mismatched_links.is_empty()
+    }
+}
+
+impl<S> SphereDb<S>
+where
+    S: Storage + 'static,
+    S::BlockStore: Store,
+    S::KeyValueStore: Store,
+{
+    /// Get the most recently recorded tip of every local sphere lineage,
+    /// along with the identity of the sphere it belongs to
+    pub async fn get_all_versions(&self) -> Result<Vec<(String, Cid)>> {
+        let mut versions = Vec::new();
+
+        for key in self.version_store.keys().await? {
+            let identity = String::from_utf8(key)?;
+
+            if let Some(version) = self.version_store.get_key(&identity).await? {
+                versions.push((identity, version));
+            }
+        }
+
+        Ok(versions)
+    }
+
+    /// Get the roots that all referenced blocks are reachable from: the tip
+    /// of every local sphere lineage, and any [Cid] that is stored as a
+    /// metadata value
+    async fn get_reference_roots(&self) -> Result<Vec<Cid>> {
+        let mut roots = self
+            .get_all_versions()
+            .await?
+            .into_iter()
+            .map(|(_, version)| version)
+            .collect::<Vec<Cid>>();
+
+        for key in self.metadata_store.keys().await? {
+            if let Ok(Some(cid)) = self.metadata_store.get_key::<_, Cid>(&key).await {
+                roots.push(cid);
+            }
+        }
+
+        Ok(roots)
+    }
+
+    /// Walk every block that is reachable from the same roots that
+    /// [SphereDb::collect_garbage] uses, re-hashing each block to verify that
+    /// it matches its [Cid] and checking that its recorded links match the
+    /// links that are decoded from it. Unlike [SphereDb::stream_blocks], the
+    /// walk follows the links decoded from each block (falling back to the
+    /// recorded links for blocks that are missing or corrupt), since the link
+    /// records are among the things being verified. Nothing is modified; see
+    /// [SphereDb::repair_integrity].
+    pub async fn verify_integrity(&self) -> Result<IntegrityReport> {
+        let mut report = IntegrityReport::default();
+        let mut visited = BTreeSet::new();
+        let mut remaining = self.get_reference_roots().await?;
+
+        while let Some(cid) = remaining.pop() {
+            if !visited.insert(cid) {
+                continue;
+            }
+
+            let block = match self.block_store.get_block(&cid).await? {
+                Some(block) => block,
+                None => {
+                    report.missing_blocks.insert(cid);
+                    remaining.extend(self.get_block_links(&cid).await?.unwrap_or_default());
+                    continue;
+                }
+            };
+
+            match verify_cid(&cid, &block) {
+                Ok(true) => report.blocks_verified += 1,
+                Ok(false) => {
+                    report.corrupt_blocks.insert(cid);
+                    remaining.extend(self.get_block_links(&cid).await?.unwrap_or_default());
+                    continue;
+                }
+                Err(error) => {
+                    warn!("Unable to verify block {}: {}", cid, error);
+                    report.unverifiable_blocks.insert(cid);
+                }
+            };
+
+            let links = block_references(&cid, &block)?
+                .into_iter()
+                .collect::<BTreeSet<Cid>>();
+
+            // A block that was stored without its links (for example, via
+            // [BlockStore::put_block]) has no link record, which is fine
+            if let Some(recorded_links) = self.get_block_links(&cid).await? {
+                if recorded_links.into_iter().collect::<BTreeSet<Cid>>() != links {
+                    report.mismatched_links.insert(cid);
+                }
+            }
+
+            remaining.extend(links);
+        }
+
+        for key in self.block_store.keys().await? {
+            if let Ok(cid) = Cid::try_from(key.as_slice()) {
+                if !visited.contains(&cid) {
+                    report.orphaned_blocks.insert(cid);
+                }
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Fix the problems in an [IntegrityReport] that can be fixed without
+    /// fetching blocks from elsewhere: corrupt blocks are removed (so that
+    /// they are reported as missing from now on, and may be replaced), and
+    /// mismatched links are recorded again from the blocks they belong to.
+    pub async fn repair_integrity(&mut self, report: &IntegrityReport) -> Result<()> {
+        for cid in report.corrupt_blocks.iter() {
+            self.block_store.remove(&cid.to_bytes()).await?;
+        }
+
+        for cid in report.mismatched_links.iter() {
+            if let Some(block) = self.block_store.get_block(cid).await? {
+                self.put_links_for_codec(cid, &block).await?;
+            }
+        }
+
+        self.flush().await
+    }
+
+    /// Remove all blocks (and their link records) that are no longer
+    /// referenced. A block is considered referenced if it can be reached by
+    /// following links from the tip of any local sphere lineage, or from any
+    /// [Cid] that is stored as a metadata value. Blocks are not followed past
+    /// any [Cid] in `excluded`, which makes it possible to discard old history
+    /// that would otherwise still be referenced.
+    ///
+    /// Note that blocks written while this runs may be removed if they are not
+    /// yet referenced by a recorded version, so callers should ensure that
+    /// nothing else is writing to the [SphereDb] at the same time. Callers
+    /// that cannot pause writers for that long can instead call
+    /// [SphereDb::mark_referenced_blocks] while writers are active, and then
+    /// call it again (to catch up on anything that was written in the
+    /// meantime) followed by [SphereDb::sweep_unmarked_blocks] while they are
+    /// paused.
+    pub async fn collect_garbage(
+        &mut self,
+        excluded: BTreeSet<Cid>,
+    ) -> Result<GarbageCollectionReport> {
+        let marked = self
+            .mark_referenced_blocks(&excluded, BTreeSet::new())
+            .await?;
+
+        self.sweep_unmarked_blocks(&marked).await
+    }
+
+    /// Add the [Cid] of every block that is referenced (in the sense used by
+    /// [SphereDb::collect_garbage]) to the set of `marked` blocks, and return
+    /// it. Blocks that are already marked are assumed to have been marked
+    /// along with everything they refer to, so they are not followed again;
+    /// this makes it cheap to mark again after a small number of writes.
+    pub async fn mark_referenced_blocks(
+        &self,
+        excluded: &BTreeSet<Cid>,
+        marked: BTreeSet<Cid>,
+    ) -> Result<BTreeSet<Cid>> {
+        let roots = self.get_reference_roots().await?;
+
+        let excluded = Arc::new(excluded.clone());
+        let marked = Arc::new(Mutex::new(marked));
+
+        for root in roots.iter() {
+            let predicate = {
+                let db = self.clone();
+                let excluded = excluded.clone();
+                let marked = marked.clone();
+
+                move |cid: &Cid| {
+                    let cid = *cid;
+                    let mut db = db.clone();
+                    let excluded = excluded.clone();
+                    let marked = marked.clone();
+
+                    async move {
+                        if excluded.contains(&cid) {
+                            return Ok(false);
+                        }
+
+                        match marked.lock() {
+                            Ok(mut marked) => {
+                                if !marked.insert(cid) {
+                                    return Ok(false);
+                                }
+                            }
+                            Err(error) => return Err(anyhow!("{}", error)),
+                        };
+
+                        // Blocks that were stored without their links (for
+                        // example, via [BlockStore::put_block]) would otherwise
+                        // hide their descendants from the traversal
+                        if db.get_block_links(&cid).await?.is_none() {
+                            if let Some(block) = db.get_block(&cid).await? {
+                                db.put_links_for_codec(&cid, &block).await?;
+                            }
+                        }
+
+                        Ok(true)
+                    }
+                }
+            };
+
+            let stream = self.query_links(root, predicate);
+
+            tokio::pin!(stream);
+
+            while stream.try_next().await?.is_some() {}
+        }
+
+        let marked = match marked.lock() {
+            Ok(marked) => marked.clone(),
+            Err(error) => return Err(anyhow!("{}", error)),
+        };
+
+        Ok(marked)
+    }
+
+    /// Remove every block (and link record) whose [Cid] is not in the set of
+    /// `marked` blocks; see [SphereDb::mark_referenced_blocks]
+    pub async fn sweep_unmarked_blocks(
+        &mut self,
+        marked: &BTreeSet<Cid>,
+    ) -> Result<GarbageCollectionReport> {
+        let mut report = GarbageCollectionReport::default();
+
+        for key in self.block_store.keys().await? {
+            let cid = match Cid::try_from(key.as_slice()) {
+                Ok(cid) => cid,
+                Err(_) => continue,
+            };
+
+            if marked.contains(&cid) {
+                report.blocks_kept += 1;
+                continue;
+            }
+
+            if let Some(block) = self.block_store.remove(&key).await? {
+                report.blocks_removed += 1;
+                report.bytes_reclaimed += block.len();
+            }
+        }
+
+        for key in self.link_store.keys().await? {
+            let cid = match std::str::from_utf8(&key)
+                .ok()
+                .and_then(|cid| Cid::try_from(cid).ok())
+            {
+                Some(cid) => cid,
+                None => continue,
+            };
+
+            if marked.contains(&cid) {
+                continue;
+            }
+
+            if let Some(links) = self.link_store.remove(&key).await? {
+                report.links_removed += 1;
+                report.bytes_reclaimed += links.len();
+            }
+        }
+
+        self.flush().await?;
+
+        Ok(report)
+    }
 }
 
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
@@ -235,6 +603,10 @@ where
         self.block_store.put_block(cid, block).await
     }
 
+    async fn put_blocks(&mut self, blocks: &[(Cid, Vec<u8>)]) -> Result<()> {
+        self.block_store.put_blocks(blocks).await
+    }
+
     async fn get_block(&self, cid: &cid::Cid) -> Result<Option<Vec<u8>>> {
         self.block_store.get_block(cid).await
     }
@@ -289,15 +661,20 @@ where
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
+    use std::collections::BTreeSet;
 
+    use cid::{multihash::Multihash, Cid};
     use libipld_cbor::DagCborCodec;
     use libipld_core::{ipld::Ipld, raw::RawCodec};
     use ucan::store::UcanJwtStore;
     #[cfg(target_arch = "wasm32")]
     use wasm_bindgen_test::{wasm_bindgen_test, wasm_bindgen_test_configure};
 
-    use crate::{block_encode, derive_cid, BlockStore, MemoryStorage, SphereDb};
+    use crate::{
+        block_encode, derive_cid, BlockStore, KeyValueStore, MemoryStorage, SphereDb, Storage,
+        Store,
+    };
 
     use tokio_stream::StreamExt;
 
@@ -306,9 +683,12 @@ mod tests {
 
     #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
     #[cfg_attr(not(target_arch = "wasm32"), tokio::test)]
-    pub async fn it_stores_links_when_a_block_is_saved() {
-        let storage_provider = MemoryStorage::default();
-        let mut db = SphereDb::new(&storage_provider).await.unwrap();
+    async fn it_stores_links_when_a_block_is_saved() {
+        stores_links_when_a_block_is_saved(&MemoryStorage::default()).await;
+    }
+
+    pub async fn stores_links_when_a_block_is_saved<S: Storage>(storage_provider: &S) {
+        let mut db = SphereDb::new(storage_provider).await.unwrap();
 
         let list1 = vec!["cats", "dogs", "pigeons"];
         let list2 = vec!["apples", "oranges", "starfruit"];
@@ -327,9 +707,12 @@ mod tests {
 
     #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
     #[cfg_attr(not(target_arch = "wasm32"), tokio::test)]
-    pub async fn it_can_stream_all_blocks_in_a_dag() {
-        let storage_provider = MemoryStorage::default();
-        let mut db = SphereDb::new(&storage_provider).await.unwrap();
+    async fn it_can_stream_all_blocks_in_a_dag() {
+        can_stream_all_blocks_in_a_dag(&MemoryStorage::default()).await;
+    }
+
+    pub async fn can_stream_all_blocks_in_a_dag<S: Storage>(storage_provider: &S) {
+        let mut db = SphereDb::new(storage_provider).await.unwrap();
 
         let list1 = vec!["cats", "dogs", "pigeons"];
         let list2 = vec!["apples", "oranges", "starfruit"];
@@ -362,9 +745,163 @@ mod tests {
 
     #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
     #[cfg_attr(not(target_arch = "wasm32"), tokio::test)]
-    pub async fn it_can_put_a_raw_block_and_read_it_as_a_token() {
-        let storage_provider = MemoryStorage::default();
-        let mut db = SphereDb::new(&storage_provider).await.unwrap();
+    async fn it_collects_blocks_that_are_not_reachable_from_a_version() {
+        collects_blocks_that_are_not_reachable_from_a_version(&MemoryStorage::default()).await;
+    }
+
+    pub async fn collects_blocks_that_are_not_reachable_from_a_version<S>(storage_provider: &S)
+    where
+        S: Storage + 'static,
+        S::BlockStore: Store,
+        S::KeyValueStore: Store,
+    {
+        let mut db = SphereDb::new(storage_provider).await.unwrap();
+
+        let list1 = vec!["cats", "dogs", "pigeons"];
+        let list2 = vec!["apples", "oranges", "starfruit"];
+        let orphan = vec!["lost", "and", "found"];
+
+        let cid1 = db.save::<DagCborCodec, _>(&list1).await.unwrap();
+        let cid2 = db.save::<DagCborCodec, _>(&list2).await.unwrap();
+        let orphan_cid = db.save::<DagCborCodec, _>(&orphan).await.unwrap();
+
+        let list3 = vec![cid1, cid2];
+
+        let cid3 = db.save::<DagCborCodec, _>(&list3).await.unwrap();
+
+        db.set_version("did:key:foo", &cid3).await.unwrap();
+
+        let report = db.collect_garbage(BTreeSet::new()).await.unwrap();
+
+        assert_eq!(report.blocks_kept, 3);
+        assert_eq!(report.blocks_removed, 1);
+        assert_eq!(report.links_removed, 1);
+        assert!(report.bytes_reclaimed > 0);
+
+        assert!(db.get_block(&orphan_cid).await.unwrap().is_none());
+        assert!(db.get_block_links(&orphan_cid).await.unwrap().is_none());
+
+        for cid in [cid1, cid2, cid3] {
+            assert!(db.get_block(&cid).await.unwrap().is_some());
+        }
+
+        let report = db.collect_garbage(BTreeSet::from([cid2])).await.unwrap();
+
+        assert_eq!(report.blocks_kept, 2);
+        assert_eq!(report.blocks_removed, 1);
+        assert!(db.get_block(&cid2).await.unwrap().is_none());
+        assert!(db.get_block(&cid1).await.unwrap().is_some());
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[cfg_attr(not(target_arch = "wasm32"), tokio::test)]
+    async fn it_verifies_and_repairs_the_integrity_of_stored_blocks() {
+        verifies_and_repairs_the_integrity_of_stored_blocks(&MemoryStorage::default()).await;
+    }
+
+    pub async fn verifies_and_repairs_the_integrity_of_stored_blocks<S>(storage_provider: &S)
+    where
+        S: Storage + 'static,
+        S::BlockStore: Store,
+        S::KeyValueStore: Store,
+    {
+        let mut db = SphereDb::new(storage_provider).await.unwrap();
+
+        let list1 = vec!["cats", "dogs", "pigeons"];
+        let list2 = vec!["apples", "oranges", "starfruit"];
+        let list3 = vec!["red", "green", "blue"];
+        let orphan = vec!["lost", "and", "found"];
+
+        let cid1 = db.save::<DagCborCodec, _>(&list1).await.unwrap();
+        let cid2 = db.save::<DagCborCodec, _>(&list2).await.unwrap();
+        let cid3 = db.save::<DagCborCodec, _>(&list3).await.unwrap();
+        let orphan_cid = db.save::<DagCborCodec, _>(&orphan).await.unwrap();
+
+        let root = db
+            .save::<DagCborCodec, _>(&vec![cid1, cid2, cid3])
+            .await
+            .unwrap();
+
+        db.set_version("did:key:foo", &root).await.unwrap();
+
+        let report = db.verify_integrity().await.unwrap();
+
+        assert!(report.is_ok());
+        assert_eq!(report.blocks_verified, 4);
+        assert_eq!(report.orphaned_blocks, BTreeSet::from([orphan_cid]));
+
+        let mut block_store = db.to_block_store();
+
+        block_store.remove(&cid1.to_bytes()).await.unwrap();
+        block_store
+            .write(&cid2.to_bytes(), b"not the original bytes")
+            .await
+            .unwrap();
+        db.link_store
+            .set_key(&root.to_string(), vec![cid1])
+            .await
+            .unwrap();
+
+        let report = db.verify_integrity().await.unwrap();
+
+        assert!(!report.is_ok());
+        assert_eq!(report.blocks_verified, 2);
+        assert_eq!(report.missing_blocks, BTreeSet::from([cid1]));
+        assert_eq!(report.corrupt_blocks, BTreeSet::from([cid2]));
+        assert_eq!(report.mismatched_links, BTreeSet::from([root]));
+
+        db.repair_integrity(&report).await.unwrap();
+
+        let report = db.verify_integrity().await.unwrap();
+
+        assert_eq!(report.missing_blocks, BTreeSet::from([cid1, cid2]));
+        assert!(report.corrupt_blocks.is_empty());
+        assert!(report.mismatched_links.is_empty());
+        assert_eq!(
+            db.get_block_links(&root).await.unwrap(),
+            Some(vec![cid1, cid2, cid3])
+        );
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[cfg_attr(not(target_arch = "wasm32"), tokio::test)]
+    async fn it_reports_blocks_that_cannot_be_verified() {
+        let mut db = SphereDb::new(&MemoryStorage::default()).await.unwrap();
+
+        let list1 = vec!["cats", "dogs", "pigeons"];
+
+        let cid1 = db.save::<DagCborCodec, _>(&list1).await.unwrap();
+
+        // A CID whose multihash uses a hash function that is not supported
+        let (_, block2) = block_encode::<DagCborCodec, _>(&Ipld::String("apples".into())).unwrap();
+        let cid2 = Cid::new_v1(
+            u64::from(DagCborCodec),
+            Multihash::wrap(0x300000, &[0; 32]).unwrap(),
+        );
+
+        db.put_block(&cid2, &block2).await.unwrap();
+
+        let root = db.save::<DagCborCodec, _>(&vec![cid1, cid2]).await.unwrap();
+
+        db.set_version("did:key:foo", &root).await.unwrap();
+
+        let report = db.verify_integrity().await.unwrap();
+
+        assert!(report.is_ok());
+        assert_eq!(report.blocks_verified, 2);
+        assert_eq!(report.unverifiable_blocks, BTreeSet::from([cid2]));
+        assert!(report.corrupt_blocks.is_empty());
+        assert!(report.orphaned_blocks.is_empty());
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[cfg_attr(not(target_arch = "wasm32"), tokio::test)]
+    async fn it_can_put_a_raw_block_and_read_it_as_a_token() {
+        can_put_a_raw_block_and_read_it_as_a_token(&MemoryStorage::default()).await;
+    }
+
+    pub async fn can_put_a_raw_block_and_read_it_as_a_token<S: Storage>(storage_provider: &S) {
+        let mut db = SphereDb::new(storage_provider).await.unwrap();
 
         let (cid, block) = block_encode::<RawCodec, _>(&Ipld::Bytes(b"foobar".to_vec())).unwrap();
 
@@ -3,9 +3,11 @@ use cid::{
     multihash::{Code, MultihashDigest},
     Cid,
 };
+use libipld_cbor::DagCborCodec;
 use libipld_core::{
     codec::{Codec, Decode, Encode},
     ipld::Ipld,
+    raw::RawCodec,
     serde::{from_ipld, to_ipld},
 };
 use serde::{de::DeserializeOwned, Serialize};
@@ -31,6 +33,32 @@ where
     Cid::new_v1(u64::from(C::default()), Code::Blake3_256.digest(block))
 }
 
+/// Returns true if the bytes of a block hash to the multihash of the given
+/// [Cid]. An error is returned if the [Cid] uses a hash function that is not
+/// supported.
+pub fn verify_cid(cid: &Cid, block: &[u8]) -> Result<bool> {
+    let code = Code::try_from(cid.hash().code())?;
+
+    Ok(&code.digest(block) == cid.hash())
+}
+
+/// Find the [Cid]s that a block links to, using the codec that is indicated
+/// by the [Cid] of the block. Blocks with codecs that are not recognized are
+/// presumed to have no links.
+pub fn block_references(cid: &Cid, block: &[u8]) -> Result<Vec<Cid>> {
+    let mut links = Vec::new();
+
+    match cid.codec() {
+        codec_id if codec_id == u64::from(DagCborCodec) => {
+            DagCborCodec.references::<Ipld, _>(block, &mut links)?;
+        }
+        codec_id if codec_id == u64::from(RawCodec) => (),
+        codec_id => warn!("Unrecognized codec {}; skipping...", codec_id),
+    }
+
+    Ok(links)
+}
+
 /// Encode any encodable type as a block using the specified codec
 pub fn block_encode<C, T>(encodable: &T) -> Result<(Cid, Vec<u8>)>
 where
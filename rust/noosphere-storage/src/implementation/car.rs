@@ -0,0 +1,194 @@
+use std::{path::Path, sync::Arc};
+
+use anyhow::{anyhow, Result};
+use async_std::sync::Mutex;
+use async_trait::async_trait;
+use cid::Cid;
+use noosphere_car::CarV2Reader;
+use tokio::fs::File;
+
+use crate::encoding::verify_cid;
+use crate::storage::Storage;
+use crate::store::Store;
+
+use super::{MemoryStorage, MemoryStore};
+
+/// A read-only [Storage] that serves blocks directly out of a CAR file (such
+/// as a sphere archive), so that the file can be mounted without first being
+/// imported. Blocks are found by the index of the file when it is a CARv2 file
+/// with an index; otherwise, the file is indexed when it is opened. Blocks
+/// are verified against their CID as they are read.
+///
+/// Every block store that is requested is backed by the same CAR file. Key
+/// value stores are kept in memory, and do not outlive the [CarStorage]; a
+/// caller that opens a sphere from the file will typically want to record its
+/// version there, using the [CarStorage::roots] of the file.
+#[derive(Clone, Debug)]
+pub struct CarStorage {
+    block_store: CarStore,
+    key_value_storage: MemoryStorage,
+    roots: Vec<Cid>,
+}
+
+impl CarStorage {
+    /// Open the CAR file at the given path
+    pub async fn open(path: &Path) -> Result<Self> {
+        let reader = CarV2Reader::open(File::open(path).await?).await?;
+        let roots = reader.header().roots().to_vec();
+
+        Ok(CarStorage {
+            block_store: CarStore {
+                reader: Arc::new(Mutex::new(reader)),
+            },
+            key_value_storage: MemoryStorage::default(),
+            roots,
+        })
+    }
+
+    /// The roots that are declared by the header of the CAR file
+    pub fn roots(&self) -> &[Cid] {
+        &self.roots
+    }
+}
+
+#[async_trait]
+impl Storage for CarStorage {
+    type BlockStore = CarStore;
+
+    type KeyValueStore = MemoryStore;
+
+    async fn get_block_store(&self, _name: &str) -> Result<Self::BlockStore> {
+        Ok(self.block_store.clone())
+    }
+
+    async fn get_key_value_store(&self, name: &str) -> Result<Self::KeyValueStore> {
+        self.key_value_storage.get_key_value_store(name).await
+    }
+}
+
+/// A read-only [Store] over the blocks of a CAR file; see [CarStorage]
+#[derive(Clone, Debug)]
+pub struct CarStore {
+    reader: Arc<Mutex<CarV2Reader<File>>>,
+}
+
+#[async_trait]
+impl Store for CarStore {
+    async fn read(&self, key: &[u8]) -> Result<Option<Vec<u8>>> {
+        let cid = match Cid::try_from(key) {
+            Ok(cid) => cid,
+            Err(_) => return Ok(None),
+        };
+
+        let block = match self.reader.lock().await.get_block(&cid).await? {
+            Some(block) => block,
+            None => return Ok(None),
+        };
+
+        if !verify_cid(&cid, &block)? {
+            return Err(anyhow!("Block in CAR file does not match its CID {}", cid));
+        }
+
+        Ok(Some(block))
+    }
+
+    async fn write(&mut self, _key: &[u8], _bytes: &[u8]) -> Result<Option<Vec<u8>>> {
+        Err(anyhow!(
+            "Cannot write to a CAR file that is mounted as storage"
+        ))
+    }
+
+    async fn remove(&mut self, _key: &[u8]) -> Result<Option<Vec<u8>>> {
+        Err(anyhow!(
+            "Cannot remove from a CAR file that is mounted as storage"
+        ))
+    }
+
+    async fn keys(&self) -> Result<Vec<Vec<u8>>> {
+        Ok(self
+            .reader
+            .lock()
+            .await
+            .cids()
+            .await?
+            .into_iter()
+            .map(|cid| cid.to_bytes())
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use libipld_cbor::DagCborCodec;
+    use noosphere_car::CarV2Writer;
+
+    use crate::{BlockStore, CarStorage, MemoryStore, Storage};
+
+    #[tokio::test]
+    async fn it_serves_blocks_from_a_car_file() -> Result<()> {
+        let mut memory_store = MemoryStore::default();
+        let cats = memory_store
+            .save::<DagCborCodec, _>("Cats are great".to_string())
+            .await?;
+        let dogs = memory_store
+            .save::<DagCborCodec, _>("Dogs are fine".to_string())
+            .await?;
+
+        let path = std::env::temp_dir().join(format!("{cats}.car"));
+        let mut writer = CarV2Writer::new(vec![cats], tokio::fs::File::create(&path).await?);
+
+        for cid in [cats, dogs] {
+            writer
+                .write(cid, memory_store.require_block(&cid).await?)
+                .await?;
+        }
+
+        writer.finish().await?;
+
+        let storage = CarStorage::open(&path).await?;
+        let mut block_store = storage.get_block_store("blocks").await?;
+
+        assert_eq!(storage.roots(), &[cats]);
+        assert_eq!(
+            block_store.load::<DagCborCodec, String>(&dogs).await?,
+            "Dogs are fine"
+        );
+        assert!(block_store
+            .put_block(&dogs, b"Dogs are great")
+            .await
+            .is_err());
+
+        std::fs::remove_file(path)?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn it_rejects_blocks_that_do_not_match_their_cid() -> Result<()> {
+        let mut memory_store = MemoryStore::default();
+        let cats = memory_store
+            .save::<DagCborCodec, _>("Cats are great".to_string())
+            .await?;
+        let dogs = memory_store
+            .save::<DagCborCodec, _>("Dogs are fine".to_string())
+            .await?;
+
+        let path = std::env::temp_dir().join(format!("{dogs}.car"));
+        let mut writer = CarV2Writer::new(vec![cats], tokio::fs::File::create(&path).await?);
+
+        writer
+            .write(cats, memory_store.require_block(&dogs).await?)
+            .await?;
+        writer.finish().await?;
+
+        let storage = CarStorage::open(&path).await?;
+        let block_store = storage.get_block_store("blocks").await?;
+
+        assert!(block_store.get_block(&cats).await.is_err());
+
+        std::fs::remove_file(path)?;
+
+        Ok(())
+    }
+}
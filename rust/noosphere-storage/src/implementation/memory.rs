@@ -5,7 +5,7 @@ use cid::Cid;
 use std::{collections::HashMap, sync::Arc};
 
 use crate::storage::Storage;
-use crate::store::Store;
+use crate::store::{Store, StoreBatch, StoreBatchOperation};
 
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
@@ -129,4 +129,26 @@ impl Store for MemoryStore {
         let mut dags = self.entries.lock().await;
         Ok(dags.remove(key))
     }
+
+    async fn keys(&self) -> Result<Vec<Vec<u8>>> {
+        let dags = self.entries.lock().await;
+        Ok(dags.keys().cloned().collect())
+    }
+
+    async fn commit_batch(&mut self, batch: StoreBatch) -> Result<()> {
+        let mut dags = self.entries.lock().await;
+
+        for operation in batch.into_operations() {
+            match operation {
+                StoreBatchOperation::Write { key, bytes } => {
+                    dags.insert(key, bytes);
+                }
+                StoreBatchOperation::Remove { key } => {
+                    dags.remove(&key);
+                }
+            }
+        }
+
+        Ok(())
+    }
 }
@@ -4,12 +4,22 @@ mod tracking;
 pub use memory::*;
 pub use tracking::*;
 
+#[cfg(not(target_arch = "wasm32"))]
+mod car;
 #[cfg(not(target_arch = "wasm32"))]
 mod native;
 
+#[cfg(not(target_arch = "wasm32"))]
+pub use car::*;
 #[cfg(not(target_arch = "wasm32"))]
 pub use native::*;
 
+#[cfg(all(not(target_arch = "wasm32"), feature = "sqlite"))]
+mod sqlite;
+
+#[cfg(all(not(target_arch = "wasm32"), feature = "sqlite"))]
+pub use sqlite::*;
+
 #[cfg(target_arch = "wasm32")]
 mod web;
 
@@ -1,11 +1,11 @@
 use std::path::PathBuf;
 
 use crate::storage::Storage;
-use crate::store::Store;
+use crate::store::{Store, StoreBatch, StoreBatchOperation};
 
 use anyhow::Result;
 use async_trait::async_trait;
-use sled::{Db, Tree};
+use sled::{Batch, Db, Tree};
 
 pub enum NativeStorageInit {
     Path(PathBuf),
@@ -83,9 +83,30 @@ impl Store for NativeStore {
             .map(|maybe_entry| maybe_entry.map(|entry| entry.to_vec()))?)
     }
 
+    async fn keys(&self) -> Result<Vec<Vec<u8>>> {
+        self.db.iter().keys().map(|key| Ok(key?.to_vec())).collect()
+    }
+
     /// Flushes pending writes if there are any
     async fn flush(&self) -> Result<()> {
         self.db.flush_async().await?;
         Ok(())
     }
+
+    /// Applies the batch atomically, and flushes so that it is durable
+    async fn commit_batch(&mut self, batch: StoreBatch) -> Result<()> {
+        let mut sled_batch = Batch::default();
+
+        for operation in batch.into_operations() {
+            match operation {
+                StoreBatchOperation::Write { key, bytes } => sled_batch.insert(key, bytes),
+                StoreBatchOperation::Remove { key } => sled_batch.remove(key),
+            }
+        }
+
+        self.db.apply_batch(sled_batch)?;
+        self.db.flush_async().await?;
+
+        Ok(())
+    }
 }
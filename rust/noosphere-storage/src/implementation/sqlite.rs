@@ -0,0 +1,268 @@
+use std::{
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::storage::Storage;
+use crate::store::{Store, StoreBatch, StoreBatchOperation};
+
+/// A [Storage] that keeps everything in a single SQLite database file, with
+/// one table for each named store. Unlike the files kept by [NativeStorage],
+/// the database can be inspected with standard SQLite tooling. The database is
+/// opened in WAL mode, so that other processes (such as that tooling) can read
+/// it while a write is in progress. Within a process, every store shares a
+/// single connection, so reads and writes are serialized.
+///
+/// [NativeStorage]: crate::NativeStorage
+#[derive(Clone, Debug)]
+pub struct SqliteStorage {
+    connection: Arc<Mutex<Connection>>,
+}
+
+impl SqliteStorage {
+    /// Open (or create) the SQLite database at the given path
+    pub fn new(path: &Path) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let connection = Connection::open(path)?;
+
+        let journal_mode: String =
+            connection.pragma_update_and_check(None, "journal_mode", "WAL", |row| row.get(0))?;
+
+        if !journal_mode.eq_ignore_ascii_case("wal") {
+            warn!("SQLite storage could not use WAL (using {journal_mode} instead)");
+        }
+
+        connection.pragma_update(None, "synchronous", "NORMAL")?;
+
+        Ok(SqliteStorage {
+            connection: Arc::new(Mutex::new(connection)),
+        })
+    }
+
+    async fn get_store(&self, name: &str) -> Result<SqliteStore> {
+        let store = SqliteStore::new(self.connection.clone(), name);
+
+        store
+            .with_connection(|connection, table| {
+                connection.execute(
+                    &format!(
+                        "CREATE TABLE IF NOT EXISTS {table} (key BLOB PRIMARY KEY, value BLOB NOT NULL) WITHOUT ROWID"
+                    ),
+                    [],
+                )?;
+                Ok(())
+            })
+            .await?;
+
+        Ok(store)
+    }
+}
+
+#[async_trait]
+impl Storage for SqliteStorage {
+    type BlockStore = SqliteStore;
+
+    type KeyValueStore = SqliteStore;
+
+    async fn get_block_store(&self, name: &str) -> Result<Self::BlockStore> {
+        self.get_store(name).await
+    }
+
+    async fn get_key_value_store(&self, name: &str) -> Result<Self::KeyValueStore> {
+        self.get_store(name).await
+    }
+}
+
+/// A [Store] that is backed by one table of a [SqliteStorage] database
+#[derive(Clone, Debug)]
+pub struct SqliteStore {
+    connection: Arc<Mutex<Connection>>,
+    /// The quoted name of the table that holds this store's entries
+    table: Arc<str>,
+}
+
+impl SqliteStore {
+    fn new(connection: Arc<Mutex<Connection>>, name: &str) -> Self {
+        SqliteStore {
+            connection,
+            table: format!("\"store_{}\"", name.replace('"', "\"\"")).into(),
+        }
+    }
+
+    /// Run a function that uses the database connection on a thread where
+    /// blocking is acceptable, so that async executors are never blocked by
+    /// SQLite (or by waiting for the connection)
+    async fn with_connection<F, T>(&self, operation: F) -> Result<T>
+    where
+        F: FnOnce(&mut Connection, &str) -> Result<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let connection = self.connection.clone();
+        let table = self.table.clone();
+
+        tokio::task::spawn_blocking(move || {
+            let mut connection = connection
+                .lock()
+                .map_err(|_| anyhow!("SQLite connection lock was poisoned"))?;
+
+            operation(&mut connection, &table)
+        })
+        .await?
+    }
+
+    fn read_entry(connection: &Connection, table: &str, key: &[u8]) -> Result<Option<Vec<u8>>> {
+        Ok(connection
+            .prepare_cached(&format!("SELECT value FROM {table} WHERE key = ?1"))?
+            .query_row(params![key], |row| row.get(0))
+            .optional()?)
+    }
+}
+
+#[async_trait]
+impl Store for SqliteStore {
+    async fn read(&self, key: &[u8]) -> Result<Option<Vec<u8>>> {
+        let key = key.to_vec();
+
+        self.with_connection(move |connection, table| {
+            SqliteStore::read_entry(connection, table, &key)
+        })
+        .await
+    }
+
+    async fn write(&mut self, key: &[u8], bytes: &[u8]) -> Result<Option<Vec<u8>>> {
+        let key = key.to_vec();
+        let bytes = bytes.to_vec();
+
+        self.with_connection(move |connection, table| {
+            let old_bytes = SqliteStore::read_entry(connection, table, &key)?;
+
+            connection
+                .prepare_cached(&format!(
+                    "INSERT OR REPLACE INTO {table} (key, value) VALUES (?1, ?2)"
+                ))?
+                .execute(params![key, bytes])?;
+
+            Ok(old_bytes)
+        })
+        .await
+    }
+
+    async fn remove(&mut self, key: &[u8]) -> Result<Option<Vec<u8>>> {
+        let key = key.to_vec();
+
+        self.with_connection(move |connection, table| {
+            let old_bytes = SqliteStore::read_entry(connection, table, &key)?;
+
+            if old_bytes.is_some() {
+                connection
+                    .prepare_cached(&format!("DELETE FROM {table} WHERE key = ?1"))?
+                    .execute(params![key])?;
+            }
+
+            Ok(old_bytes)
+        })
+        .await
+    }
+
+    async fn keys(&self) -> Result<Vec<Vec<u8>>> {
+        self.with_connection(|connection, table| {
+            let mut statement = connection.prepare_cached(&format!("SELECT key FROM {table}"))?;
+            let keys = statement
+                .query_map([], |row| row.get(0))?
+                .collect::<Result<Vec<Vec<u8>>, _>>()?;
+
+            Ok(keys)
+        })
+        .await
+    }
+
+    /// Applies the batch in a single transaction
+    async fn commit_batch(&mut self, batch: StoreBatch) -> Result<()> {
+        self.with_connection(move |connection, table| {
+            let transaction = connection.transaction()?;
+
+            for operation in batch.into_operations() {
+                match operation {
+                    StoreBatchOperation::Write { key, bytes } => transaction
+                        .prepare_cached(&format!(
+                            "INSERT OR REPLACE INTO {table} (key, value) VALUES (?1, ?2)"
+                        ))?
+                        .execute(params![key, bytes])?,
+                    StoreBatchOperation::Remove { key } => transaction
+                        .prepare_cached(&format!("DELETE FROM {table} WHERE key = ?1"))?
+                        .execute(params![key])?,
+                };
+            }
+
+            transaction.commit()?;
+
+            Ok(())
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use tempfile::TempDir;
+
+    use crate::{db::tests, SqliteStorage, Storage, Store};
+
+    /// Make a [SqliteStorage] in a temporary directory, which is removed (along
+    /// with the database) when the returned [TempDir] is dropped
+    fn make_disposable_storage() -> Result<(SqliteStorage, TempDir)> {
+        let temp_dir = TempDir::new()?;
+        let storage = SqliteStorage::new(&temp_dir.path().join("sphere.sqlite"))?;
+
+        Ok((storage, temp_dir))
+    }
+
+    #[tokio::test]
+    async fn it_keeps_each_named_store_in_its_own_table() -> Result<()> {
+        let (storage, _temp_dir) = make_disposable_storage()?;
+        let mut cats = storage.get_key_value_store("cats").await?;
+        let dogs = storage.get_key_value_store("dogs").await?;
+
+        assert_eq!(cats.write(b"name", b"Tabby").await?, None);
+        assert_eq!(
+            cats.write(b"name", b"Felix").await?,
+            Some(b"Tabby".to_vec())
+        );
+        assert_eq!(cats.read(b"name").await?, Some(b"Felix".to_vec()));
+        assert_eq!(dogs.read(b"name").await?, None);
+        assert_eq!(cats.keys().await?, vec![b"name".to_vec()]);
+        assert_eq!(cats.remove(b"name").await?, Some(b"Felix".to_vec()));
+        assert_eq!(cats.read(b"name").await?, None);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn it_passes_the_sphere_db_test_suite() -> Result<()> {
+        let (storage, _temp_dir) = make_disposable_storage()?;
+        tests::stores_links_when_a_block_is_saved(&storage).await;
+
+        let (storage, _temp_dir) = make_disposable_storage()?;
+        tests::can_stream_all_blocks_in_a_dag(&storage).await;
+
+        let (storage, _temp_dir) = make_disposable_storage()?;
+        tests::collects_blocks_that_are_not_reachable_from_a_version(&storage).await;
+
+        let (storage, _temp_dir) = make_disposable_storage()?;
+        tests::verifies_and_repairs_the_integrity_of_stored_blocks(&storage).await;
+
+        let (storage, _temp_dir) = make_disposable_storage()?;
+        tests::can_put_a_raw_block_and_read_it_as_a_token(&storage).await;
+
+        Ok(())
+    }
+}
@@ -3,7 +3,10 @@ use async_std::sync::Mutex;
 use async_trait::async_trait;
 use std::sync::Arc;
 
-use crate::{store::Store, MemoryStorage, MemoryStore, Storage};
+use crate::{
+    store::{Store, StoreBatch, StoreBatchOperation},
+    MemoryStorage, MemoryStore, Storage,
+};
 
 #[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct StoreStats {
@@ -68,11 +71,34 @@ impl<S: Store> Store for TrackingStore<S> {
         Ok(value)
     }
 
+    async fn keys(&self) -> Result<Vec<Vec<u8>>> {
+        let mut stats = self.stats.lock().await;
+        stats.reads += 1;
+        self.store.keys().await
+    }
+
     async fn flush(&self) -> Result<()> {
         let mut stats = self.stats.lock().await;
         stats.flushes += 1;
         Ok(())
     }
+
+    async fn commit_batch(&mut self, batch: StoreBatch) -> Result<()> {
+        let mut stats = self.stats.lock().await;
+
+        for operation in batch.clone().into_operations() {
+            match operation {
+                StoreBatchOperation::Write { bytes, .. } => {
+                    stats.writes += 1;
+                    stats.bytes_written += bytes.len();
+                }
+                StoreBatchOperation::Remove { .. } => stats.removes += 1,
+            }
+        }
+
+        stats.flushes += 1;
+        self.store.commit_batch(batch).await
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -1,15 +1,17 @@
-use crate::store::Store;
+use crate::store::{Store, StoreBatch, StoreBatchOperation};
 use crate::{db::SPHERE_DB_STORE_NAMES, storage::Storage};
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
-use js_sys::Uint8Array;
+use js_sys::{Array, Promise, Uint8Array};
 use rexie::{
     KeyRange, ObjectStore, Rexie, RexieBuilder, Store as IdbStore, Transaction, TransactionMode,
 };
 use std::{fmt::Debug, rc::Rc};
 use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{IdbDatabase, IdbFactory, IdbRequest, Window, WorkerGlobalScope};
 
-pub const INDEXEDDB_STORAGE_VERSION: u32 = 1;
+pub const INDEXEDDB_STORAGE_VERSION: u32 = 3;
 
 #[derive(Clone)]
 pub struct WebStorage {
@@ -99,6 +101,32 @@ impl WebStore {
         Ok(())
     }
 
+    fn indexed_db() -> Result<IdbFactory> {
+        let global = js_sys::global();
+
+        let factory = match global.dyn_ref::<Window>() {
+            Some(window) => window.indexed_db(),
+            None => global.unchecked_ref::<WorkerGlobalScope>().indexed_db(),
+        };
+
+        factory
+            .map_err(|error| anyhow!("{:?}", error))?
+            .ok_or_else(|| anyhow!("IndexedDB is not available"))
+    }
+
+    async fn wait_for_request(request: &IdbRequest) -> Result<JsValue> {
+        let promise = Promise::new(&mut |resolve, reject| {
+            request.set_onsuccess(Some(&resolve));
+            request.set_onerror(Some(&reject));
+        });
+
+        JsFuture::from(promise)
+            .await
+            .map_err(|error| anyhow!("{:?}", error))?;
+
+        request.result().map_err(|error| anyhow!("{:?}", error))
+    }
+
     fn bytes_to_typed_array(bytes: &[u8]) -> Result<JsValue> {
         let array = Uint8Array::new_with_length(bytes.len() as u32);
         array.copy_from(&bytes);
@@ -178,4 +206,74 @@ impl Store for WebStore {
 
         Ok(old_value)
     }
+
+    /// Applies the batch in a single IndexedDB transaction
+    async fn commit_batch(&mut self, batch: StoreBatch) -> Result<()> {
+        let (store, tx) = self.start_transaction(TransactionMode::ReadWrite)?;
+
+        for operation in batch.into_operations() {
+            match operation {
+                StoreBatchOperation::Write { key, bytes } => {
+                    let key = WebStore::bytes_to_typed_array(&key)?;
+                    let value = WebStore::bytes_to_typed_array(&bytes)?;
+
+                    store
+                        .put(&value, Some(&key))
+                        .await
+                        .map_err(|error| anyhow!("{:?}", error))?;
+                }
+                StoreBatchOperation::Remove { key } => {
+                    let key = WebStore::bytes_to_typed_array(&key)?;
+
+                    store
+                        .delete(&key)
+                        .await
+                        .map_err(|error| anyhow!("{:?}", error))?;
+                }
+            }
+        }
+
+        WebStore::finish_transaction(tx).await?;
+
+        Ok(())
+    }
+
+    async fn keys(&self) -> Result<Vec<Vec<u8>>> {
+        // NOTE: `rexie` can only list keys along with their values, so the
+        // underlying object store is opened directly in order to use
+        // `getAllKeys` (which does not load any values)
+        let factory = WebStore::indexed_db()?;
+        let open_request = factory
+            .open(&self.db.name())
+            .map_err(|error| anyhow!("{:?}", error))?;
+        let db: IdbDatabase = WebStore::wait_for_request(&open_request)
+            .await?
+            .dyn_into()
+            .map_err(|error| anyhow!("{:?}", error))?;
+
+        let keys = async {
+            let store = db
+                .transaction_with_str(&self.store_name)
+                .and_then(|tx| tx.object_store(&self.store_name))
+                .map_err(|error| anyhow!("{:?}", error))?;
+            let keys_request = store
+                .get_all_keys()
+                .map_err(|error| anyhow!("{:?}", error))?;
+
+            WebStore::wait_for_request(&keys_request).await
+        }
+        .await;
+
+        db.close();
+
+        Array::from(&keys?)
+            .iter()
+            .map(|key| {
+                Ok(key
+                    .dyn_into::<Uint8Array>()
+                    .map_err(|error| anyhow!("{:?}", error))?
+                    .to_vec())
+            })
+            .collect()
+    }
 }
@@ -6,6 +6,8 @@
 extern crate tracing;
 
 mod block;
+mod buffer;
+mod cache;
 mod implementation;
 mod key_value;
 
@@ -19,6 +21,8 @@ mod ucan;
 
 pub use crate::ucan::*;
 pub use block::*;
+pub use buffer::*;
+pub use cache::*;
 pub use db::*;
 pub use encoding::*;
 pub use implementation::*;
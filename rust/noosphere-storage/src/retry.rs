@@ -50,6 +50,10 @@ where
         self.store.put_block(cid, block).await
     }
 
+    async fn put_blocks(&mut self, blocks: &[(Cid, Vec<u8>)]) -> Result<()> {
+        self.store.put_blocks(blocks).await
+    }
+
     async fn get_block(&self, cid: &Cid) -> Result<Option<Vec<u8>>> {
         let mut retry_count = 0;
         loop {
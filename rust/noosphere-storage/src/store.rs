@@ -1,6 +1,6 @@
 use std::io::Cursor;
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use async_trait::async_trait;
 use cid::Cid;
 use libipld_cbor::DagCborCodec;
@@ -28,6 +28,49 @@ pub trait StoreConditionalSendSync {}
 #[cfg(target_arch = "wasm32")]
 impl<S> StoreConditionalSendSync for S {}
 
+/// A single operation in a [StoreBatch]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StoreBatchOperation {
+    Write { key: Vec<u8>, bytes: Vec<u8> },
+    Remove { key: Vec<u8> },
+}
+
+/// A series of writes and removals that are applied to a [Store] together, by
+/// [Store::commit_batch]. Nothing is applied until the batch is committed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StoreBatch {
+    operations: Vec<StoreBatchOperation>,
+}
+
+impl StoreBatch {
+    /// Queue a write of some bytes against a given key
+    pub fn write(&mut self, key: &[u8], bytes: &[u8]) {
+        self.operations.push(StoreBatchOperation::Write {
+            key: key.to_vec(),
+            bytes: bytes.to_vec(),
+        });
+    }
+
+    /// Queue the removal of the value stored against a given key
+    pub fn remove(&mut self, key: &[u8]) {
+        self.operations
+            .push(StoreBatchOperation::Remove { key: key.to_vec() });
+    }
+
+    pub fn len(&self) -> usize {
+        self.operations.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.operations.is_empty()
+    }
+
+    /// The queued operations, in the order that they were queued
+    pub fn into_operations(self) -> Vec<StoreBatchOperation> {
+        self.operations
+    }
+}
+
 /// A primitive interface for storage backends. A storage backend does not
 /// necessarily need to implement this trait to be used in Noosphere, but if it
 /// does it automatically benefits from trait implementations for [BlockStore]
@@ -46,10 +89,44 @@ pub trait Store: Clone + StoreConditionalSendSync {
     /// Remove a value given a key, returning the removed value if any
     async fn remove(&mut self, key: &[u8]) -> Result<Option<Vec<u8>>>;
 
+    /// Get all of the keys that currently have values stored against them.
+    /// Backends that cannot list their keys may leave this unimplemented, in
+    /// which case it returns an error (and garbage collection is not
+    /// available for them).
+    async fn keys(&self) -> Result<Vec<Vec<u8>>> {
+        Err(anyhow!("Listing keys is not supported by this store"))
+    }
+
     /// Flushes pending writes if there are any
     async fn flush(&self) -> Result<()> {
         Ok(())
     }
+
+    /// Begin a [StoreBatch] of writes and removals, to be applied together by
+    /// [Store::commit_batch]
+    fn begin_batch(&self) -> StoreBatch {
+        StoreBatch::default()
+    }
+
+    /// Apply every operation in a [StoreBatch] in order, and flush. Backends
+    /// should override this to apply the batch atomically (so that either all
+    /// of its operations take effect or none of them do) wherever they are
+    /// able to. The default implementation applies the operations one at a
+    /// time, so a failure part way through leaves the earlier ones applied.
+    async fn commit_batch(&mut self, batch: StoreBatch) -> Result<()> {
+        for operation in batch.into_operations() {
+            match operation {
+                StoreBatchOperation::Write { key, bytes } => {
+                    self.write(&key, &bytes).await?;
+                }
+                StoreBatchOperation::Remove { key } => {
+                    self.remove(&key).await?;
+                }
+            }
+        }
+
+        Store::flush(self).await
+    }
 }
 
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
@@ -67,6 +144,16 @@ where
         self.read(&cid.to_bytes()).await
     }
 
+    async fn put_blocks(&mut self, blocks: &[(Cid, Vec<u8>)]) -> Result<()> {
+        let mut batch = self.begin_batch();
+
+        for (cid, block) in blocks {
+            batch.write(&cid.to_bytes(), block);
+        }
+
+        self.commit_batch(batch).await
+    }
+
     async fn flush(&self) -> Result<()> {
         Store::flush(self).await
     }
@@ -0,0 +1,89 @@
+use anyhow::anyhow;
+use noosphere_core::authority::{generate_capability, SphereAction};
+use noosphere_sphere::{HasSphereContext, SphereAuthorityWrite};
+use safer_ffi::{char_p::InvalidNulTerminator, prelude::*};
+
+use crate::ffi::{NsError, TryOrInitialize};
+
+use super::{NsNoosphere, NsSphere};
+
+/// Interpret a lifetime given in seconds, where 0 means that no lifetime was
+/// given, as an expiry time in seconds since the UNIX epoch
+fn lifetime_to_expiry(lifetime: u64) -> Option<u64> {
+    match lifetime {
+        0 => None,
+        lifetime => Some(ucan::time::now() + lifetime),
+    }
+}
+
+#[ffi_export]
+/// @memberof ns_sphere_t
+///
+/// Authorize the key with the given DID to work on a sphere, recording the
+/// authorization under the given name. The key is given full access to the
+/// sphere.
+///
+/// The authorization expires after the given lifetime (in seconds). If the
+/// lifetime is 0, it expires when the authorization of the key that issues it
+/// does. The returned value is the identity of the authorization, as a UTF-8,
+/// base64-encoded CIDv1 string; the authorized key uses it to join the sphere.
+///
+/// Make sure to save and then sync after authorizing a key, so that the
+/// gateway recognizes the authorization.
+pub fn ns_sphere_authority_authorize(
+    noosphere: &NsNoosphere,
+    sphere: &mut NsSphere,
+    name: char_p::Ref<'_>,
+    did: char_p::Ref<'_>,
+    lifetime: u64,
+    error_out: Option<Out<'_, repr_c::Box<NsError>>>,
+) -> Option<char_p::Box> {
+    error_out.try_or_initialize(|| {
+        let authorization = noosphere.async_runtime().block_on(async {
+            let identity = sphere.inner().identity().await?;
+
+            sphere
+                .inner_mut()
+                .authorize(
+                    name.to_str(),
+                    &did.to_str().into(),
+                    &[generate_capability(&identity, SphereAction::Authorize)],
+                    lifetime_to_expiry(lifetime),
+                )
+                .await
+        })?;
+
+        Ok(authorization
+            .to_string()
+            .try_into()
+            .map_err(|error: InvalidNulTerminator<String>| anyhow!(error))?)
+    })
+}
+
+#[ffi_export]
+/// @memberof ns_sphere_t
+///
+/// Renew every authorization that the key that is working on a sphere has
+/// issued, so that each one expires after the given lifetime (in seconds). If
+/// the lifetime is 0, each one expires when the authorization of the key that
+/// renews it does.
+///
+/// Make sure to save and then sync after renewing authorizations, so that the
+/// gateway and the authorized keys recognize the renewals.
+pub fn ns_sphere_authority_renew(
+    noosphere: &NsNoosphere,
+    sphere: &mut NsSphere,
+    lifetime: u64,
+    error_out: Option<Out<'_, repr_c::Box<NsError>>>,
+) {
+    error_out.try_or_initialize(|| {
+        noosphere.async_runtime().block_on(async {
+            sphere
+                .inner_mut()
+                .renew_authorizations(&[], lifetime_to_expiry(lifetime))
+                .await?;
+
+            Ok(())
+        })
+    });
+}
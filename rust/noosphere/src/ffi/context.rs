@@ -4,7 +4,7 @@
 use anyhow::anyhow;
 use cid::Cid;
 use itertools::Itertools;
-use noosphere_core::data::Did;
+use noosphere_core::data::{ContentType, Did};
 use safer_ffi::{char_p::InvalidNulTerminator, prelude::*};
 use std::{os::raw::c_void, pin::Pin, str::FromStr, sync::Arc};
 use subtext::{Peer, Slashlink};
@@ -17,8 +17,9 @@ use crate::{
 };
 
 use noosphere_sphere::{
-    AsyncFileBody, HasMutableSphereContext, HasSphereContext, SphereContentRead,
-    SphereContentWrite, SphereContext, SphereCursor, SphereFile, SphereWalker,
+    AsyncFileBody, ContentDecoderRegistry, HasMutableSphereContext, HasSphereContext,
+    SphereContentRead, SphereContentWrite, SphereContext, SphereCursor, SphereFile, SphereSearch,
+    SphereWalker,
 };
 
 #[derive_ReprC(rename = "ns_sphere")]
@@ -436,6 +437,57 @@ pub fn ns_sphere_content_write(
     });
 }
 
+#[ffi_export]
+/// @memberof ns_sphere_t
+///
+/// Write the contents of the file at `path` to a ns_sphere_t instance, keyed
+/// by `slug`, assigning its content-type header to the specified value.
+///
+/// This behaves like ns_sphere_content_write(), except that the file is read
+/// and stored incrementally, so that it never has to be loaded into memory as
+/// a whole. This is the preferred way to write large files.
+///
+/// Note that you must invoke ns_sphere_save() to commit one or more writes
+/// to the sphere.
+pub fn ns_sphere_content_write_file(
+    noosphere: &NsNoosphere,
+    sphere: &mut NsSphere,
+    slug: char_p::Ref<'_>,
+    content_type: char_p::Ref<'_>,
+    path: char_p::Ref<'_>,
+    additional_headers: Option<&NsHeaders>,
+    error_out: Option<Out<'_, repr_c::Box<NsError>>>,
+) {
+    error_out.try_or_initialize(|| {
+        noosphere.async_runtime().block_on(async {
+            let slug = slug.to_str();
+            let mut cursor = SphereCursor::latest(sphere.inner_mut().clone());
+
+            info!(
+                "Writing file {} to sphere {} slug {}...",
+                path.to_str(),
+                cursor.identity().await?,
+                slug
+            );
+
+            let file = tokio::fs::File::open(path.to_str())
+                .await
+                .map_err(|error| anyhow!("Could not open {}: {}", path.to_str(), error))?;
+
+            cursor
+                .write(
+                    slug,
+                    content_type.to_str(),
+                    file,
+                    additional_headers.map(|headers| headers.inner().clone()),
+                )
+                .await?;
+
+            Ok(())
+        })
+    });
+}
+
 #[ffi_export]
 /// @memberof ns_sphere_t
 ///
@@ -572,6 +624,47 @@ pub fn ns_sphere_content_changes(
     .into()
 }
 
+#[ffi_export]
+/// @memberof ns_sphere_t
+/// Search the text and Subtext content of a given sphere, and get an array of
+/// the slugs that best match the query (most relevant first). At most `limit`
+/// slugs will be returned.
+///
+/// The search index for the sphere is built the first time it is searched;
+/// after that it is kept up to date incrementally as the sphere changes.
+pub fn ns_sphere_search(
+    noosphere: &NsNoosphere,
+    sphere: &NsSphere,
+    query: char_p::Ref<'_>,
+    limit: usize,
+    error_out: Option<Out<'_, repr_c::Box<NsError>>>,
+) -> c_slice::Box<char_p::Box> {
+    let possible_output = error_out.try_or_initialize(|| {
+        noosphere.async_runtime().block_on(async {
+            let results = sphere.inner().search(query.to_str(), limit).await?;
+            let mut matching_slugs: Vec<char_p::Box> = Vec::new();
+
+            for result in results.into_iter() {
+                matching_slugs.push(
+                    result
+                        .slug
+                        .try_into()
+                        .map_err(|error: InvalidNulTerminator<String>| anyhow!(error))?,
+                );
+            }
+
+            Ok(matching_slugs)
+        })
+    });
+
+    match possible_output {
+        Some(slugs) => slugs,
+        None => Vec::new(),
+    }
+    .into_boxed_slice()
+    .into()
+}
+
 #[ffi_export]
 /// @memberof ns_sphere_file_t
 ///
@@ -635,6 +728,56 @@ pub fn ns_sphere_file_contents_read(
     });
 }
 
+#[ffi_export]
+/// @memberof ns_sphere_file_t
+///
+/// Trade in an ns_sphere_file_t for its contents, decoded as UTF-8 text.
+/// Any content may be read as text as long as it is valid UTF-8; otherwise,
+/// an error is returned. As with ns_sphere_file_contents_read, the contents
+/// can only be read from a ns_sphere_file_t one time.
+///
+/// The callback arguments are (in order):
+///
+///  1. The context argument provided in the original call to
+///     ns_sphere_file_contents_read_as_text
+///  2. An owned pointer to an ns_error_t if there was an error, otherwise NULL
+///  3. An owned pointer to a null terminated UTF-8 string if the call was
+///     successful, otherwise NULL
+///
+#[allow(clippy::type_complexity)]
+pub fn ns_sphere_file_contents_read_as_text(
+    noosphere: &NsNoosphere,
+    mut sphere_file: repr_c::Box<NsSphereFile>,
+    context: Option<repr_c::Box<c_void>>,
+    callback: extern "C" fn(
+        Option<repr_c::Box<c_void>>,
+        Option<repr_c::Box<NsError>>,
+        Option<char_p::Box>,
+    ),
+) {
+    let async_runtime = noosphere.async_runtime();
+
+    noosphere.async_runtime().spawn(async move {
+        let result: Result<char_p::Box, anyhow::Error> = async {
+            let text = ContentDecoderRegistry::default()
+                .decode_as(&ContentType::Text, sphere_file.inner_mut())
+                .await?
+                .into_text()?;
+
+            text.try_into()
+                .map_err(|error: InvalidNulTerminator<String>| anyhow!(error))
+        }
+        .await;
+
+        match result {
+            Ok(text) => async_runtime.spawn_blocking(move || callback(context, None, Some(text))),
+            Err(error) => async_runtime.spawn_blocking(move || {
+                callback(context, Some(NoosphereError::from(error).into()), None)
+            }),
+        };
+    });
+}
+
 #[ffi_export]
 /// @memberof ns_sphere_file_t
 ///
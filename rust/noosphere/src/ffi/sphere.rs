@@ -3,7 +3,7 @@ use std::ffi::c_void;
 use anyhow::anyhow;
 use cid::Cid;
 use noosphere_core::{authority::Authorization, data::Did};
-use noosphere_sphere::{HasSphereContext, SphereSync};
+use noosphere_sphere::{ConflictResolution, HasSphereContext, MergeSyncStrategy, SphereSync};
 use safer_ffi::char_p::InvalidNulTerminator;
 use safer_ffi::prelude::*;
 
@@ -216,6 +216,107 @@ pub fn ns_sphere_sync(
     });
 }
 
+#[derive_ReprC(rename = "ns_sync_resolution")]
+#[repr(u32)]
+/// Constant values that describe how ns_sphere_sync_merge should handle slugs
+/// that were changed both locally and by the gateway since the last sync.
+pub enum NsSyncResolution {
+    /// Do not resolve conflicts; if any are found, the sync is abandoned
+    Report = 0,
+    /// Keep the local version of each conflicting slug
+    KeepLocal = 1,
+    /// Keep the gateway's version of each conflicting slug
+    KeepCounterpart = 2,
+    /// Keep the gateway's version at each conflicting slug, and keep the local
+    /// version at a slug like "<slug>.conflict-<cid>"
+    KeepBoth = 3,
+}
+
+#[ffi_export]
+/// @memberof ns_sphere_t
+///
+/// Sync a sphere with a gateway, merging local changes with changes made
+/// elsewhere instead of overwriting them.
+///
+/// This works like ns_sphere_sync, but it also checks for slugs that were
+/// changed both locally and by the gateway since the last sync. The given
+/// ns_sync_resolution_t decides what happens to those slugs. If it is
+/// NS_SYNC_RESOLUTION_REPORT and conflicts are found, nothing is changed. The
+/// callback then receives an error that lists the conflicting slugs.
+///
+/// The callback arguments are (in order):
+///
+///  1. The context argument provided in the original call to
+///     ns_sphere_sync_merge
+///  2. An owned pointer to an ns_error_t if there was an error, otherwise NULL
+///  3. An owned pointer to a null terminated UTF-8 string if the call was
+///     successful, otherwise NULL
+///
+pub fn ns_sphere_sync_merge(
+    noosphere: &NsNoosphere,
+    sphere: &NsSphere,
+    resolution: NsSyncResolution,
+    context: Option<repr_c::Box<c_void>>,
+    callback: extern "C" fn(
+        Option<repr_c::Box<c_void>>,
+        Option<repr_c::Box<NsError>>,
+        Option<char_p::Box>,
+    ),
+) {
+    let async_runtime = noosphere.async_runtime();
+    let mut sphere_channel = sphere.to_channel();
+
+    noosphere.async_runtime().spawn(async move {
+        let result: Result<char_p::Box, anyhow::Error> = async {
+            let strategy = match resolution {
+                NsSyncResolution::Report => MergeSyncStrategy::default(),
+                NsSyncResolution::KeepLocal => {
+                    MergeSyncStrategy::resolving_with(ConflictResolution::KeepLocal)
+                }
+                NsSyncResolution::KeepCounterpart => {
+                    MergeSyncStrategy::resolving_with(ConflictResolution::KeepCounterpart)
+                }
+                NsSyncResolution::KeepBoth => {
+                    MergeSyncStrategy::resolving_with(ConflictResolution::KeepBoth)
+                }
+            };
+
+            let report = sphere_channel.mutable().sync_with(&strategy).await?;
+
+            if !report.is_complete() {
+                return Err(anyhow!(
+                    "Sync abandoned because of conflicting changes to: {}",
+                    report
+                        .unresolved
+                        .iter()
+                        .map(|conflict| conflict.slug.as_str())
+                        .collect::<Vec<&str>>()
+                        .join(", ")
+                ));
+            }
+
+            sphere_channel
+                .immutable()
+                .to_sphere()
+                .await?
+                .cid()
+                .to_string()
+                .try_into()
+                .map_err(|error: InvalidNulTerminator<String>| anyhow!(error))
+        }
+        .await;
+
+        match result {
+            Ok(cid_string) => {
+                async_runtime.spawn_blocking(move || callback(context, None, Some(cid_string)))
+            }
+            Err(error) => async_runtime.spawn_blocking(move || {
+                callback(context, Some(NoosphereError::from(error).into()), None)
+            }),
+        };
+    });
+}
+
 #[ffi_export]
 /// @memberof ns_sphere_t
 ///
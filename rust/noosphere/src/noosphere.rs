@@ -13,7 +13,9 @@ use crate::{
 };
 
 /// An enum describing different storage stragies that may be interesting
-/// depending on the environment and implementation of Noosphere
+/// depending on the environment and implementation of Noosphere. On native
+/// platforms, sphere data is stored with sled unless SQLite storage is
+/// configured.
 #[derive(Clone)]
 pub enum NoosphereStorage {
     /// Scoped storage implies that the given path is a root and that spheres
@@ -25,6 +27,11 @@ pub enum NoosphereStorage {
     /// path. Note that this is typically only appropriate when dealing with a
     /// single sphere.
     Unscoped { path: PathBuf },
+
+    /// Like [NoosphereStorage::Scoped], but the data of each sphere is kept in
+    /// a SQLite database file. This is only supported on native platforms when
+    /// the `sqlite-storage` feature is enabled.
+    Sqlite { path: PathBuf },
 }
 
 /// This enum exists so that we can incrementally layer on support for secure
@@ -103,6 +110,20 @@ impl NoosphereContext {
         match &self.configuration.storage {
             NoosphereStorage::Scoped { path } => path,
             NoosphereStorage::Unscoped { path } => path,
+            NoosphereStorage::Sqlite { path } => path,
+        }
+    }
+
+    /// A [SphereContextBuilder] that keeps sphere data in the configured
+    /// storage
+    fn sphere_context_builder(&self) -> SphereContextBuilder {
+        let builder = SphereContextBuilder::default()
+            .at_storage_path(self.sphere_storage_path())
+            .using_scoped_storage_layout();
+
+        match &self.configuration.storage {
+            NoosphereStorage::Sqlite { .. } => builder.using_sqlite_storage(),
+            _ => builder,
         }
     }
 
@@ -140,10 +161,9 @@ impl NoosphereContext {
     /// Create a sphere, generating an authorization for the specified owner key
     /// to administer the sphere over time
     pub async fn create_sphere(&self, owner_key_name: &str) -> Result<SphereReceipt> {
-        let artifacts = SphereContextBuilder::default()
+        let artifacts = self
+            .sphere_context_builder()
             .create_sphere()
-            .at_storage_path(self.sphere_storage_path())
-            .using_scoped_storage_layout()
             .reading_keys_from(self.key_storage().await?)
             .using_key(owner_key_name)
             .syncing_to(self.gateway_api())
@@ -178,10 +198,9 @@ impl NoosphereContext {
         local_key_name: &str,
         authorization: Option<&Authorization>,
     ) -> Result<()> {
-        let artifacts = SphereContextBuilder::default()
+        let artifacts = self
+            .sphere_context_builder()
             .join_sphere(sphere_identity)
-            .at_storage_path(self.sphere_storage_path())
-            .using_scoped_storage_layout()
             .reading_keys_from(self.key_storage().await?)
             .using_key(local_key_name)
             .authorized_by(authorization)
@@ -213,10 +232,9 @@ impl NoosphereContext {
         let mut contexts = self.sphere_channels.lock().await;
 
         if !contexts.contains_key(sphere_identity) {
-            let artifacts = SphereContextBuilder::default()
+            let artifacts = self
+                .sphere_context_builder()
                 .open_sphere(Some(sphere_identity))
-                .at_storage_path(self.sphere_storage_path())
-                .using_scoped_storage_layout()
                 .reading_keys_from(self.key_storage().await?)
                 .syncing_to(self.gateway_api())
                 .reading_ipfs_from(self.ipfs_gateway_url())
@@ -8,9 +8,14 @@
     target_vendor = "apple"
 ))]
 mod inner {
-    use noosphere_storage::NativeStorage;
     use ucan_key_support::ed25519::Ed25519KeyMaterial;
 
+    #[cfg(not(feature = "sqlite-storage"))]
+    use noosphere_storage::NativeStorage as LocalStorage;
+
+    #[cfg(feature = "sqlite-storage")]
+    use crate::storage::LocalStorage;
+
     use crate::key::InsecureKeyStorage;
 
     // NOTE: This is going to change when we transition to secure key storage
@@ -18,14 +23,16 @@ mod inner {
     pub type PlatformKeyMaterial = Ed25519KeyMaterial;
     pub type PlatformKeyStorage = InsecureKeyStorage;
 
+    use super::CachedStorage;
+
     #[cfg(not(feature = "ipfs-storage"))]
-    pub type PlatformStorage = NativeStorage;
+    pub type PlatformStorage = CachedStorage<LocalStorage>;
 
     #[cfg(feature = "ipfs-storage")]
     use noosphere_ipfs::{IpfsStorage, KuboClient};
 
     #[cfg(feature = "ipfs-storage")]
-    pub type PlatformStorage = IpfsStorage<NativeStorage, KuboClient>;
+    pub type PlatformStorage = IpfsStorage<CachedStorage<LocalStorage>, KuboClient>;
 
     #[cfg(test)]
     use anyhow::Result;
@@ -61,12 +68,14 @@ mod inner {
 
     use noosphere_storage::WebStorage;
 
+    use super::CachedStorage;
+
     #[cfg(feature = "ipfs-storage")]
     pub type PlatformStorage =
-        noosphere_ipfs::IpfsStorage<WebStorage, noosphere_ipfs::GatewayClient>;
+        noosphere_ipfs::IpfsStorage<CachedStorage<WebStorage>, noosphere_ipfs::GatewayClient>;
 
     #[cfg(not(feature = "ipfs-storage"))]
-    pub type PlatformStorage = WebStorage;
+    pub type PlatformStorage = CachedStorage<WebStorage>;
 
     #[cfg(test)]
     use anyhow::Result;
@@ -104,22 +113,29 @@ mod inner {
     ))
 ))]
 mod inner {
-    use noosphere_storage::NativeStorage;
     use ucan_key_support::ed25519::Ed25519KeyMaterial;
 
+    #[cfg(not(feature = "sqlite-storage"))]
+    use noosphere_storage::NativeStorage as LocalStorage;
+
+    #[cfg(feature = "sqlite-storage")]
+    use crate::storage::LocalStorage;
+
     use crate::key::InsecureKeyStorage;
 
     pub type PlatformKeyMaterial = Ed25519KeyMaterial;
     pub type PlatformKeyStorage = InsecureKeyStorage;
 
+    use super::CachedStorage;
+
     #[cfg(not(feature = "ipfs-storage"))]
-    pub type PlatformStorage = NativeStorage;
+    pub type PlatformStorage = CachedStorage<LocalStorage>;
 
     #[cfg(feature = "ipfs-storage")]
     use noosphere_ipfs::{IpfsStorage, KuboClient};
 
     #[cfg(feature = "ipfs-storage")]
-    pub type PlatformStorage = IpfsStorage<NativeStorage, KuboClient>;
+    pub type PlatformStorage = IpfsStorage<CachedStorage<LocalStorage>, KuboClient>;
 
     #[cfg(test)]
     use anyhow::Result;
@@ -146,6 +162,16 @@ mod inner {
 use std::sync::Arc;
 
 pub use inner::*;
+
+// With the `block-cache` feature, recently used blocks are kept in memory in
+// front of local storage (so that blocks fetched from IPFS are cached as
+// well); the size of the cache is configured by the [SphereContextBuilder]
+#[cfg(not(feature = "block-cache"))]
+type CachedStorage<S> = S;
+
+#[cfg(feature = "block-cache")]
+type CachedStorage<S> = noosphere_storage::CachingStorage<S>;
+
 use noosphere_sphere::SphereContext;
 use tokio::sync::Mutex;
 
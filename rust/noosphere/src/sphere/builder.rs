@@ -12,6 +12,12 @@ use noosphere_core::{
 #[cfg(all(target_arch = "wasm32", feature = "ipfs-storage"))]
 use noosphere_ipfs::{GatewayClient, IpfsStorage};
 
+#[cfg(feature = "block-cache")]
+use noosphere_storage::CachingStorage;
+
+#[cfg(all(not(target_arch = "wasm32"), feature = "sqlite-storage"))]
+use crate::storage::LocalStorage;
+
 use noosphere_storage::{KeyValueStore, MemoryStore, SphereDb};
 use ucan::crypto::KeyMaterial;
 use url::Url;
@@ -79,8 +85,10 @@ impl From<SphereContextBuilderArtifacts> for SphereContext<PlatformKeyMaterial,
 pub struct SphereContextBuilder {
     initialization: SphereInitialization,
     scoped_storage_layout: bool,
+    sqlite_storage: bool,
     gateway_api: Option<Url>,
     ipfs_gateway_url: Option<Url>,
+    block_cache_capacity: Option<usize>,
     storage_path: Option<PathBuf>,
     authorization: Option<Authorization>,
     key_storage: Option<PlatformKeyStorage>,
@@ -122,12 +130,30 @@ impl SphereContextBuilder {
         self
     }
 
+    /// Keep up to the given number of bytes of recently used blocks in memory,
+    /// so that repeatedly reading the same sphere data (e.g., to list its
+    /// content) is faster; by default, blocks are not cached. This is only
+    /// supported when the `block-cache` feature is enabled, and otherwise
+    /// building the [SphereContext] fails if a capacity is given
+    pub fn caching_blocks_up_to(mut self, capacity: Option<usize>) -> Self {
+        self.block_cache_capacity = capacity;
+        self
+    }
+
     /// When initializing sphere data, scope the namespace by the sphere's DID
     pub fn using_scoped_storage_layout(mut self) -> Self {
         self.scoped_storage_layout = true;
         self
     }
 
+    /// Keep sphere data in a SQLite database rather than with sled; this is
+    /// only supported on native platforms when the `sqlite-storage` feature
+    /// is enabled, and otherwise building the [SphereContext] fails
+    pub fn using_sqlite_storage(mut self) -> Self {
+        self.sqlite_storage = true;
+        self
+    }
+
     /// Specify the local namespace in storage where sphere data should be
     /// initialized
     pub fn at_storage_path(mut self, path: &Path) -> Self {
@@ -194,8 +220,10 @@ impl SphereContextBuilder {
                 let mut db = generate_db(
                     storage_path,
                     self.scoped_storage_layout,
+                    self.sqlite_storage,
                     Some(sphere_did.clone()),
                     self.ipfs_gateway_url,
+                    self.block_cache_capacity,
                 )
                 .await?;
 
@@ -241,8 +269,10 @@ impl SphereContextBuilder {
                 let mut db = generate_db(
                     storage_path,
                     self.scoped_storage_layout,
+                    self.sqlite_storage,
                     Some(sphere_identity.clone()),
                     self.ipfs_gateway_url,
+                    self.block_cache_capacity,
                 )
                 .await?;
 
@@ -281,8 +311,10 @@ impl SphereContextBuilder {
                 let db = generate_db(
                     storage_path,
                     self.scoped_storage_layout,
+                    self.sqlite_storage,
                     sphere_identity,
                     self.ipfs_gateway_url,
+                    self.block_cache_capacity,
                 )
                 .await?;
 
@@ -317,8 +349,10 @@ impl Default for SphereContextBuilder {
         Self {
             initialization: SphereInitialization::Create,
             scoped_storage_layout: false,
+            sqlite_storage: false,
             gateway_api: None,
             ipfs_gateway_url: None,
+            block_cache_capacity: None,
             storage_path: None,
             authorization: None,
             key_storage: None as Option<PlatformKeyStorage>,
@@ -331,8 +365,10 @@ impl Default for SphereContextBuilder {
 async fn generate_db(
     storage_path: PathBuf,
     scoped_storage_layout: bool,
+    sqlite_storage: bool,
     sphere_identity: Option<Did>,
     ipfs_gateway_url: Option<Url>,
+    block_cache_capacity: Option<usize>,
 ) -> Result<SphereDb<PlatformStorage>> {
     let storage_layout = match scoped_storage_layout {
         true => StorageLayout::Scoped(
@@ -342,13 +378,34 @@ async fn generate_db(
         false => StorageLayout::Unscoped(storage_path),
     };
 
-    #[cfg(not(all(target_arch = "wasm32", feature = "ipfs-storage")))]
+    #[cfg(all(not(target_arch = "wasm32"), feature = "sqlite-storage"))]
+    let storage = match sqlite_storage {
+        true => LocalStorage::Sqlite(storage_layout.to_sqlite_storage().await?),
+        false => LocalStorage::Native(storage_layout.to_storage().await?),
+    };
+
+    #[cfg(not(all(not(target_arch = "wasm32"), feature = "sqlite-storage")))]
+    if sqlite_storage {
+        return Err(anyhow!(
+            "Storing sphere data in SQLite requires the `sqlite-storage` feature"
+        ));
+    }
+
+    #[cfg(not(all(not(target_arch = "wasm32"), feature = "sqlite-storage")))]
     let storage = storage_layout.to_storage().await?;
+
+    #[cfg(feature = "block-cache")]
+    let storage = CachingStorage::new(storage, block_cache_capacity.unwrap_or_default());
+
+    #[cfg(not(feature = "block-cache"))]
+    if block_cache_capacity.is_some() {
+        return Err(anyhow!(
+            "Caching blocks in memory requires the `block-cache` feature"
+        ));
+    }
+
     #[cfg(all(target_arch = "wasm32", feature = "ipfs-storage"))]
-    let storage = IpfsStorage::new(
-        storage_layout.to_storage().await?,
-        ipfs_gateway_url.map(|url| GatewayClient::new(url)),
-    );
+    let storage = IpfsStorage::new(storage, ipfs_gateway_url.map(|url| GatewayClient::new(url)));
 
     SphereDb::new(&storage).await
 }
@@ -444,6 +501,28 @@ mod tests {
         drop(temporary_directories);
     }
 
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[cfg_attr(not(target_arch = "wasm32"), tokio::test)]
+    async fn it_only_caches_blocks_when_the_block_cache_feature_is_enabled() {
+        let (storage_path, key_storage, temporary_directories) =
+            make_temporary_platform_primitives().await.unwrap();
+
+        key_storage.create_key("foo").await.unwrap();
+
+        let result = SphereContextBuilder::default()
+            .create_sphere()
+            .at_storage_path(&storage_path)
+            .reading_keys_from(key_storage)
+            .using_key("foo")
+            .caching_blocks_up_to(Some(1024 * 1024))
+            .build()
+            .await;
+
+        assert_eq!(result.is_ok(), cfg!(feature = "block-cache"));
+
+        drop(temporary_directories);
+    }
+
     #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
     #[cfg_attr(not(target_arch = "wasm32"), tokio::test)]
     async fn it_can_initialize_a_sphere_to_sync_from_elsewhere() {
@@ -6,7 +6,7 @@ use std::{
 use anyhow::Result;
 use noosphere_core::data::Did;
 
-#[cfg(doc)]
+#[cfg(any(doc, all(not(target_arch = "wasm32"), feature = "sqlite-storage")))]
 use noosphere_storage::Storage;
 
 /// [StorageLayout] represents the namespace that should be used depending on
@@ -51,6 +51,119 @@ impl StorageLayout {
     }
 }
 
+/// The name of the database file that is kept at the path of a
+/// [StorageLayout] when sphere data is stored in SQLite
+#[cfg(all(not(target_arch = "wasm32"), feature = "sqlite-storage"))]
+pub const SQLITE_DATABASE_FILE: &str = "sphere.sqlite";
+
+#[cfg(all(not(target_arch = "wasm32"), feature = "sqlite-storage"))]
+use async_trait::async_trait;
+
+#[cfg(all(not(target_arch = "wasm32"), feature = "sqlite-storage"))]
+use noosphere_storage::{NativeStore, SqliteStorage, SqliteStore, Store, StoreBatch};
+
+#[cfg(all(not(target_arch = "wasm32"), feature = "sqlite-storage"))]
+impl StorageLayout {
+    pub async fn to_sqlite_storage(&self) -> Result<SqliteStorage> {
+        SqliteStorage::new(&PathBuf::from(self).join(SQLITE_DATABASE_FILE))
+    }
+}
+
+/// With the `sqlite-storage` feature, sphere data on native platforms may be
+/// kept either with sled or in a SQLite database; which one is used for a
+/// given sphere is decided when its [StorageLayout] is opened (see
+/// [crate::NoosphereStorage::Sqlite])
+#[cfg(all(not(target_arch = "wasm32"), feature = "sqlite-storage"))]
+#[derive(Clone, Debug)]
+pub enum LocalStorage {
+    Native(NativeStorage),
+    Sqlite(SqliteStorage),
+}
+
+#[cfg(all(not(target_arch = "wasm32"), feature = "sqlite-storage"))]
+#[async_trait]
+impl Storage for LocalStorage {
+    type BlockStore = LocalStore;
+
+    type KeyValueStore = LocalStore;
+
+    async fn get_block_store(&self, name: &str) -> Result<Self::BlockStore> {
+        Ok(match self {
+            LocalStorage::Native(storage) => {
+                LocalStore::Native(storage.get_block_store(name).await?)
+            }
+            LocalStorage::Sqlite(storage) => {
+                LocalStore::Sqlite(storage.get_block_store(name).await?)
+            }
+        })
+    }
+
+    async fn get_key_value_store(&self, name: &str) -> Result<Self::KeyValueStore> {
+        Ok(match self {
+            LocalStorage::Native(storage) => {
+                LocalStore::Native(storage.get_key_value_store(name).await?)
+            }
+            LocalStorage::Sqlite(storage) => {
+                LocalStore::Sqlite(storage.get_key_value_store(name).await?)
+            }
+        })
+    }
+}
+
+/// A [Store] of a [LocalStorage]
+#[cfg(all(not(target_arch = "wasm32"), feature = "sqlite-storage"))]
+#[derive(Clone)]
+pub enum LocalStore {
+    Native(NativeStore),
+    Sqlite(SqliteStore),
+}
+
+#[cfg(all(not(target_arch = "wasm32"), feature = "sqlite-storage"))]
+#[async_trait]
+impl Store for LocalStore {
+    async fn read(&self, key: &[u8]) -> Result<Option<Vec<u8>>> {
+        match self {
+            LocalStore::Native(store) => store.read(key).await,
+            LocalStore::Sqlite(store) => store.read(key).await,
+        }
+    }
+
+    async fn write(&mut self, key: &[u8], bytes: &[u8]) -> Result<Option<Vec<u8>>> {
+        match self {
+            LocalStore::Native(store) => store.write(key, bytes).await,
+            LocalStore::Sqlite(store) => store.write(key, bytes).await,
+        }
+    }
+
+    async fn remove(&mut self, key: &[u8]) -> Result<Option<Vec<u8>>> {
+        match self {
+            LocalStore::Native(store) => store.remove(key).await,
+            LocalStore::Sqlite(store) => store.remove(key).await,
+        }
+    }
+
+    async fn keys(&self) -> Result<Vec<Vec<u8>>> {
+        match self {
+            LocalStore::Native(store) => store.keys().await,
+            LocalStore::Sqlite(store) => store.keys().await,
+        }
+    }
+
+    async fn flush(&self) -> Result<()> {
+        match self {
+            LocalStore::Native(store) => Store::flush(store).await,
+            LocalStore::Sqlite(store) => Store::flush(store).await,
+        }
+    }
+
+    async fn commit_batch(&mut self, batch: StoreBatch) -> Result<()> {
+        match self {
+            LocalStore::Native(store) => store.commit_batch(batch).await,
+            LocalStore::Sqlite(store) => store.commit_batch(batch).await,
+        }
+    }
+}
+
 #[cfg(target_arch = "wasm32")]
 use noosphere_storage::WebStorage;
 
@@ -8,9 +8,10 @@ use subtext::Slashlink;
 use tokio_stream::StreamExt;
 
 use anyhow::{anyhow, Result};
-use js_sys::{Function, Promise, Uint8Array};
+use js_sys::{Function, Promise, Uint8Array, JSON};
 use noosphere_sphere::{
-    AsyncFileBody, HasSphereContext, SphereContext, SphereCursor, SphereFile as SphereFileImpl,
+    AsyncFileBody, ContentDecoderRegistry, HasSphereContext, SphereContext, SphereCursor,
+    SphereFile as SphereFileImpl,
 };
 use tokio::{
     io::{AsyncRead, AsyncReadExt},
@@ -92,6 +93,25 @@ impl SphereFile {
         Ok(contents)
     }
 
+    #[wasm_bindgen(js_name = "intoJson")]
+    /// Asynchronously read the contents of the file, decoding it according to
+    /// its content type and returning it as a JSON-compatible value. JSON,
+    /// CBOR and DAG-CBOR content is supported (links within DAG-CBOR are
+    /// represented as `{ "/": "<cid>" }`), as is plain text, which becomes a
+    /// string.
+    ///
+    /// Note that after this method is called, the SphereFile will be freed and
+    /// is no longer usable.
+    pub async fn into_json(mut self) -> Result<JsValue, String> {
+        let value = ContentDecoderRegistry::default()
+            .decode(&mut self.inner)
+            .await
+            .and_then(|content| content.into_json())
+            .map_err(|error| format!("{:?}", error))?;
+
+        JSON::parse(&value.to_string()).map_err(|error| format!("{:?}", error))
+    }
+
     #[wasm_bindgen(js_name = "intoHtml")]
     /// Consume this SphereFile and return its contents formatted as HTML. A
     /// resolver function must be provided in order to convert slashlinks to
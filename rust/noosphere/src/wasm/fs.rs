@@ -1,17 +1,21 @@
-use std::sync::Arc;
+use std::{pin::Pin, sync::Arc};
 
 use crate::{
     platform::{PlatformKeyMaterial, PlatformStorage},
     wasm::SphereFile,
 };
-use js_sys::{Array, Function};
+use async_stream::try_stream;
+use bytes::Bytes;
+use js_sys::{Array, AsyncIterator, Function, Reflect, Uint8Array};
 use noosphere_sphere::{
     HasMutableSphereContext, SphereContentRead, SphereContentWrite, SphereContext, SphereCursor,
     SphereWalker,
 };
 use tokio::sync::Mutex;
-use tokio_stream::StreamExt;
+use tokio_stream::{Stream, StreamExt};
+use tokio_util::io::StreamReader;
 use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::JsFuture;
 
 #[wasm_bindgen]
 /// A `SphereFs` is a view over the data of a `Sphere` that enables the user to
@@ -90,6 +94,59 @@ impl SphereFs {
         Ok(version.to_string())
     }
 
+    #[wasm_bindgen(js_name = "writeStream")]
+    /// Write content to the sphere's namespace from an async iterator of
+    /// `Uint8Array` chunks (such as the one returned by
+    /// `ReadableStream.prototype.values()`). The content is stored as it is
+    /// read, so unlike `write` it never has to be held in memory as a whole;
+    /// this is the preferred way to write large files.
+    pub async fn write_stream(
+        &mut self,
+        slug: String,
+        content_type: String,
+        chunks: AsyncIterator,
+        additional_headers: Option<Array>,
+    ) -> Result<String, String> {
+        let additional_headers = self.convert_headers_representation(additional_headers);
+
+        let to_io_error =
+            |error: JsValue| std::io::Error::new(std::io::ErrorKind::Other, format!("{:?}", error));
+
+        let stream: Pin<Box<dyn Stream<Item = Result<Bytes, std::io::Error>>>> =
+            Box::pin(try_stream! {
+                loop {
+                    let result = JsFuture::from(chunks.next().map_err(to_io_error)?)
+                        .await
+                        .map_err(to_io_error)?;
+
+                    if Reflect::get(&result, &JsValue::from_str("done"))
+                        .map_err(to_io_error)?
+                        .is_truthy()
+                    {
+                        break;
+                    }
+
+                    let value = Reflect::get(&result, &JsValue::from_str("value"))
+                        .map_err(to_io_error)?;
+
+                    yield Bytes::from(Uint8Array::new(&value).to_vec());
+                }
+            });
+
+        let version = self
+            .inner
+            .write(
+                &slug,
+                &content_type,
+                StreamReader::new(stream),
+                additional_headers,
+            )
+            .await
+            .map_err(|error| format!("{:?}", error))?;
+
+        Ok(version.to_string())
+    }
+
     #[wasm_bindgen]
     /// Save the current state of the sphere. Note that `save` is not invoked
     /// automatically; in order to persist any number of writes to a given
@@ -237,3 +237,83 @@ async fn key_names_must_not_be_empty() {
     assert!(noosphere.create_key("foo").await.is_ok());
     assert!(noosphere.create_key("").await.is_err());
 }
+
+#[cfg(not(target_arch = "wasm32"))]
+fn sqlite_configuration() -> (
+    NoosphereContextConfiguration,
+    (tempfile::TempDir, tempfile::TempDir),
+) {
+    let (mut configuration, temporary_directories) = platform_configuration();
+
+    configuration.storage = NoosphereStorage::Sqlite {
+        path: temporary_directories.1.path().to_path_buf(),
+    };
+
+    (configuration, temporary_directories)
+}
+
+#[cfg(all(not(target_arch = "wasm32"), feature = "sqlite-storage"))]
+#[tokio::test]
+async fn sphere_data_can_be_stored_in_sqlite() {
+    initialize_tracing(None);
+
+    let (configuration, _temporary_directories) = sqlite_configuration();
+    let key_name = "foobarbaz";
+
+    let sphere_identity = {
+        let noosphere = NoosphereContext::new(configuration.clone()).unwrap();
+
+        noosphere.create_key(key_name).await.unwrap();
+
+        let SphereReceipt {
+            identity: sphere_identity,
+            ..
+        } = noosphere.create_sphere(key_name).await.unwrap();
+
+        let mut sphere_channel = noosphere
+            .get_sphere_channel(&sphere_identity)
+            .await
+            .unwrap();
+
+        sphere_channel
+            .mutable()
+            .write("foo", "text/plain", b"bar".as_ref(), None)
+            .await
+            .unwrap();
+
+        sphere_channel.mutable().save(None).await.unwrap();
+
+        sphere_identity
+    };
+
+    let noosphere = NoosphereContext::new(configuration).unwrap();
+
+    let sphere_channel = noosphere
+        .get_sphere_channel(&sphere_identity)
+        .await
+        .unwrap();
+
+    let mut file = sphere_channel
+        .immutable()
+        .read("foo")
+        .await
+        .unwrap()
+        .unwrap();
+
+    let mut contents = String::new();
+    file.contents.read_to_string(&mut contents).await.unwrap();
+
+    assert_eq!(contents, "bar");
+}
+
+#[cfg(all(not(target_arch = "wasm32"), not(feature = "sqlite-storage")))]
+#[tokio::test]
+async fn sqlite_storage_requires_the_sqlite_storage_feature() {
+    let (configuration, _temporary_directories) = sqlite_configuration();
+
+    let noosphere = NoosphereContext::new(configuration).unwrap();
+
+    noosphere.create_key("foo").await.unwrap();
+
+    assert!(noosphere.create_sphere("foo").await.is_err());
+}